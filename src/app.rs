@@ -1,32 +1,63 @@
 #![warn(clippy::all, rust_2018_idioms)]
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")] // hide console window on Windows in release
 
-use std::any::Any;
 use std::collections::HashMap;
-use std::time::SystemTime;
-use std::vec;
+use std::sync::mpsc;
+use std::time::Duration;
 
-use egui::load::SizedTexture;
-use generic_camera::GenCam;
-use image::{open, DynamicImage, ImageReader};
-use refimage::{DynamicImageData, GenericImage};
+use image::DynamicImage;
 
 use eframe::egui;
 use eframe::egui::load::Bytes;
-use eframe::egui::{Margin, Visuals};
+use eframe::egui::Visuals;
 use egui::{menu, ImageSource};
-use egui::{Frame, Id, Image, Widget};
-use egui_dock::{DockArea, DockState, NodeIndex, Style, SurfaceIndex};
 use std::io::Cursor;
 
 use core::str;
 use std::io::prelude::*;
 use std::net::TcpStream;
 
+use chrono::{DateTime, Local, Utc};
 use circular_buffer::CircularBuffer;
 use refimage::GenericImageOwned;
-use std::path::Path;
 
+use crate::base::Site;
+use crate::comparison::FixComparison;
+use crate::geoid::GeoidGrid;
+use crate::gnss::SatelliteId;
+use crate::integrity::Severity;
+use crate::map::MapSource;
+use crate::multipath::Combination;
+use crate::receiver::GnssReceiver;
+use crate::stability::StabilityPoint;
+use crate::waypoints::{Navigator, Waypoint};
+
+mod base_window;
+mod cno_window;
+mod comparison_window;
+mod gps_data_window;
+mod integrity_window;
+mod map_window;
+mod multipath_window;
+mod position_window;
+mod receiver_info_window;
+mod rtk_window;
+mod sbas_window;
+mod spectrum_window;
+mod spp_window;
+mod time_window;
+mod timing_window;
+mod ttff_window;
+mod velocity_window;
+mod visibility_window;
+mod waypoints_window;
+mod widgets;
+
+use gps_data_window::{GPSSatData, SatTableSettings};
+use map_window::MapSettings;
+use position_window::PositionSettings;
+
+#[allow(dead_code)] // Not wired up to a dock yet.
 #[derive(Debug, Clone)]
 enum GUITabKind {
     // DeviceManager,
@@ -35,7 +66,7 @@ enum GUITabKind {
 }
 
 #[derive(Debug, Clone)]
-enum DialogType {
+pub enum DialogType {
     Debug,
     Info,
     Warn,
@@ -67,6 +98,7 @@ pub struct GenCamGUI {
     dark_mode: bool,
 
     comms_stream: Option<TcpStream>,
+    #[allow(dead_code)] // Reserved for the camera server connection, which does not use it yet.
     comms_buffer: [u8; 4096],
     server_connection: bool,
     connected_cameras: HashMap<String, CamData>,
@@ -79,21 +111,92 @@ pub struct GenCamGUI {
     msg_list: CircularBuffer<150, String>,
 
     sat_data: Vec<GPSSatData>,
-    satellites: i32, // FOR TESTING PURPOSES ONLY!
+    sat_table: SatTableSettings,
+
+    /// Receivers connected side by side, e.g. on an antenna splitter.
+    receivers: Vec<GnssReceiver>,
+    /// The receiver the windows show.
+    active_receiver: usize,
+    /// Reference and compared receiver of the comparison window.
+    comparison_pair: [usize; 2],
+    comparison: FixComparison,
+    comparison_started: DateTime<Utc>,
+    notifications: Vec<Notification>,
+    show_time_window: bool,
+    show_timing_window: bool,
+    show_integrity_window: bool,
+    show_spectrum_window: bool,
+    show_receiver_info_window: bool,
+    show_ttff_window: bool,
+    show_base_window: bool,
+    show_rtk_window: bool,
+    show_position_window: bool,
+    show_waypoints_window: bool,
+    show_map_window: bool,
+    show_velocity_window: bool,
+    show_spp_window: bool,
+    show_visibility_window: bool,
+    show_cno_window: bool,
+    show_multipath_window: bool,
+    show_sbas_window: bool,
+    show_comparison_window: bool,
+
+    stability: Vec<StabilityPoint>,
+    stability_csv_path: String,
+    ttff_bin_width: f64,
+    ttff_csv_path: String,
+    cno_csv_path: String,
+
+    /// Named base station positions, kept across sessions.
+    sites: Vec<Site>,
+    /// Coordinates being entered for fixed base mode.
+    manual_site: Site,
+
+    position_settings: PositionSettings,
+    geoid: Option<GeoidGrid>,
+    /// Grid being loaded in the background, and the file it comes from.
+    geoid_loading: Option<(String, mpsc::Receiver<std::io::Result<GeoidGrid>>)>,
+
+    /// Waypoints and geofences, kept across sessions.
+    navigator: Navigator,
+    /// Waypoint being entered by hand.
+    manual_waypoint: Waypoint,
+    gpx_path: String,
+    /// RINEX navigation file the local solution takes its ephemerides from.
+    rinex_nav_path: String,
+    /// Elevation in degrees above which predicted satellites are expected to be tracked.
+    visibility_mask: f64,
+    multipath_combination: Combination,
+    /// Satellite the multipath plot shows, or all of them.
+    multipath_satellite: Option<SatelliteId>,
+    multipath_by_elevation: bool,
+    fence_name: String,
+    fence_radius: f64,
+    /// Waypoints picked as polygon vertices, in order.
+    fence_vertices: Vec<usize>,
+
+    map_settings: MapSettings,
+    map: Option<MapSource>,
+    /// Textures of the tiles loaded so far; `None` for tiles the map does not have.
+    map_tiles: HashMap<(u8, u32, u32), Option<egui::TextureHandle>>,
+    /// Texture of a georeferenced image map.
+    map_image: Option<egui::TextureHandle>,
 }
 
-pub struct GPSSatData {
-    sat_num: i32,
-    constellation: String,
-    country: String,
-    azimuth: f32,
-    elevation: f32,
-    data1: String,
-    data2: String,
-    data3: String,
-    data4: String,
+/// A transient message shown in the corner of the screen.
+struct Notification {
+    kind: DialogType,
+    message: String,
+    expires: DateTime<Utc>,
 }
 
+// Storage keys for state kept across sessions.
+const SITES_KEY: &str = "base_sites";
+const POSITION_KEY: &str = "position_settings";
+const WAYPOINTS_KEY: &str = "waypoints";
+const MAP_KEY: &str = "map_settings";
+const SAT_TABLE_KEY: &str = "satellite_table";
+
 pub trait Modal {
     fn dialog(&mut self, dialog_type: DialogType, message: &str);
     fn show_dialog(&mut self, ctx: &egui::Context);
@@ -135,7 +238,67 @@ impl Default for GenCamGUI {
             msg_list: CircularBuffer::new(),
 
             sat_data: Vec::new(),
-            satellites: 0,
+            sat_table: SatTableSettings::default(),
+
+            receivers: vec![GnssReceiver::new("127.0.0.1:50043")],
+            active_receiver: 0,
+            comparison_pair: [0, 1],
+            comparison: FixComparison::default(),
+            comparison_started: Utc::now(),
+            notifications: Vec::new(),
+            show_time_window: true,
+            show_timing_window: false,
+            show_integrity_window: false,
+            show_spectrum_window: false,
+            show_receiver_info_window: false,
+            show_ttff_window: false,
+            show_base_window: false,
+            show_rtk_window: false,
+            show_position_window: true,
+            show_waypoints_window: false,
+            show_map_window: false,
+            show_velocity_window: false,
+            show_spp_window: false,
+            show_visibility_window: false,
+            show_cno_window: false,
+            show_multipath_window: false,
+            show_sbas_window: false,
+            show_comparison_window: false,
+
+            stability: Vec::new(),
+            stability_csv_path: "clock_stability.csv".into(),
+            ttff_bin_width: 5.0,
+            ttff_csv_path: "ttff_report.csv".into(),
+            cno_csv_path: "cno_sky_map.csv".into(),
+
+            sites: Vec::new(),
+            manual_site: Site::default(),
+
+            position_settings: PositionSettings::default(),
+            geoid: None,
+            geoid_loading: None,
+
+            navigator: Navigator::default(),
+            manual_waypoint: Waypoint {
+                name: String::new(),
+                latitude: 0.0,
+                longitude: 0.0,
+                height: None,
+            },
+            gpx_path: String::new(),
+            rinex_nav_path: String::new(),
+            visibility_mask: 10.0,
+            multipath_combination: Combination::Mp1,
+            multipath_satellite: None,
+            multipath_by_elevation: false,
+            fence_name: String::new(),
+            fence_radius: 100.0,
+            fence_vertices: Vec::new(),
+
+            map_settings: MapSettings::default(),
+            map: None,
+            map_tiles: HashMap::new(),
+            map_image: None,
         }
     }
 }
@@ -220,6 +383,26 @@ impl Modal for GenCamGUI {
 }
 
 impl GenCamGUI {
+    /// Creates the app, restoring saved state from the previous session.
+    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
+        let mut app = Self::default();
+        if let Some(storage) = cc.storage {
+            app.sites = eframe::get_value(storage, SITES_KEY).unwrap_or_default();
+            app.position_settings = eframe::get_value(storage, POSITION_KEY).unwrap_or_default();
+            app.navigator = eframe::get_value(storage, WAYPOINTS_KEY).unwrap_or_default();
+            app.map_settings = eframe::get_value(storage, MAP_KEY).unwrap_or_default();
+            app.sat_table = eframe::get_value(storage, SAT_TABLE_KEY).unwrap_or_default();
+        }
+        if !app.position_settings.geoid_path.is_empty() {
+            app.load_geoid();
+        }
+        if !app.map_settings.path.is_empty() {
+            app.load_map();
+        }
+        app
+    }
+
+    #[allow(dead_code)] // The camera control tab is not shown yet.
     fn connect_to_server(&mut self) -> std::io::Result<()> {
         println!("Attempting connection to server...");
         let mut stream = TcpStream::connect("127.0.0.1:50042")?;
@@ -238,6 +421,7 @@ impl GenCamGUI {
         Ok(())
     }
 
+    #[allow(dead_code)]
     fn receive_test_image(&mut self) -> std::io::Result<()> {
         let mut stream = self.comms_stream.as_ref().unwrap();
         let mut buffer = [0; 4096];
@@ -274,6 +458,7 @@ impl GenCamGUI {
 
     // Camera Control tab UI.
     // BOOKMARK (UI): This is where the camera control tab UI is defined.
+    #[allow(dead_code)]
     fn tab_camera_controls(&mut self, ui: &mut egui::Ui, utid: &str) {
         let winsize = ui.ctx().input(|i: &egui::InputState| i.screen_rect());
         let win_width = winsize.width();
@@ -366,7 +551,12 @@ impl GenCamGUI {
                             .clicked()
                         {
                             // Acquire image.
-                            self.receive_test_image();
+                            if let Err(e) = self.receive_test_image() {
+                                self.dialog(
+                                    DialogType::Error,
+                                    &format!("Failed to receive image: {}", e),
+                                );
+                            }
                         }
                     });
                 });
@@ -455,7 +645,9 @@ impl GenCamGUI {
         egui::TopBottomPanel::top("top_panel")
             .resizable(false)
             .show(ctx, |ui| {
-                ui.set_enabled(!self.modal_active);
+                if self.modal_active {
+                    ui.disable();
+                }
 
                 ui.horizontal(|ui| {
                     menu::bar(ui, |ui| {
@@ -469,21 +661,48 @@ impl GenCamGUI {
                                 // …
                             }
                         });
-                        ui.menu_button("View", |ui| match self.dark_mode {
-                            true => {
-                                if ui.button("Switch to Light Mode").clicked() {
-                                    ctx.set_visuals(Visuals::light());
-                                    // ctx.set_visuals_of(egui::Theme::Light, Visuals::light());
-                                    self.dark_mode = false;
+                        ui.menu_button("View", |ui| {
+                            match self.dark_mode {
+                                true => {
+                                    if ui.button("Switch to Light Mode").clicked() {
+                                        ctx.set_visuals(Visuals::light());
+                                        // ctx.set_visuals_of(egui::Theme::Light, Visuals::light());
+                                        self.dark_mode = false;
+                                    }
                                 }
-                            }
-                            false => {
-                                if ui.button("Switch to Dark Mode").clicked() {
-                                    ctx.set_visuals(Visuals::dark());
-                                    // ctx.set_visuals_of(egui::Theme::Dark, Visuals::dark());
-                                    self.dark_mode = true;
+                                false => {
+                                    if ui.button("Switch to Dark Mode").clicked() {
+                                        ctx.set_visuals(Visuals::dark());
+                                        // ctx.set_visuals_of(egui::Theme::Dark, Visuals::dark());
+                                        self.dark_mode = true;
+                                    }
                                 }
                             }
+                            ui.separator();
+                            ui.checkbox(&mut self.show_time_window, "Time Scales");
+                            ui.checkbox(&mut self.show_timing_window, "Timing");
+                            ui.checkbox(
+                                &mut self.show_integrity_window,
+                                "Interference & Integrity",
+                            );
+                            ui.checkbox(&mut self.show_spectrum_window, "RF Spectrum");
+                            ui.checkbox(
+                                &mut self.show_receiver_info_window,
+                                "Receiver Information",
+                            );
+                            ui.checkbox(&mut self.show_ttff_window, "Time to First Fix");
+                            ui.checkbox(&mut self.show_base_window, "Base Station");
+                            ui.checkbox(&mut self.show_rtk_window, "RTK");
+                            ui.checkbox(&mut self.show_position_window, "Position");
+                            ui.checkbox(&mut self.show_waypoints_window, "Waypoints & Geofences");
+                            ui.checkbox(&mut self.show_map_window, "Map");
+                            ui.checkbox(&mut self.show_velocity_window, "Velocity");
+                            ui.checkbox(&mut self.show_spp_window, "Single Point Positioning");
+                            ui.checkbox(&mut self.show_visibility_window, "Visibility");
+                            ui.checkbox(&mut self.show_cno_window, "C/N0 Sky Map");
+                            ui.checkbox(&mut self.show_multipath_window, "Multipath");
+                            ui.checkbox(&mut self.show_sbas_window, "SBAS");
+                            ui.checkbox(&mut self.show_comparison_window, "Receiver Comparison");
                         });
                         ui.menu_button("About", |ui| {
                             if ui.button("Open").clicked() {
@@ -515,7 +734,9 @@ impl GenCamGUI {
             .width_range((w_view / (8.0 / w_scale))..=(w_view / (4.0 / w_scale)))
             .default_width(ctx.available_rect().width() / (6.0 / w_scale))
             .show(ctx, |ui| {
-                ui.set_enabled(!self.modal_active);
+                if self.modal_active {
+                    ui.disable();
+                }
                 ui.label("Window Controls");
                 ui.separator(); // Placeholder to enable dragging (expands to fill).
 
@@ -524,19 +745,24 @@ impl GenCamGUI {
                     (w_view / (8.0 / w_scale))..=(w_view / (4.0 / w_scale))
                 ));
                 ui.label("Left Panel");
+
+                ui.separator();
+                self.ui_receiver_controls(ui);
             });
-        }
-        
-        fn ui_right_panel(&mut self, ctx: &egui::Context, w_view: f32) {
-            // Left Panel
-            let w_scale = 1.0;
-            
-            egui::SidePanel::right("right_panel")
+    }
+
+    fn ui_right_panel(&mut self, ctx: &egui::Context, w_view: f32) {
+        // Left Panel
+        let w_scale = 1.0;
+
+        egui::SidePanel::right("right_panel")
             .resizable(true)
             .width_range(w_view / (8.0 / w_scale)..=w_view / (2.0 / w_scale))
             .default_width(ctx.available_rect().width() / (6.0 / w_scale))
             .show(ctx, |ui| {
-                ui.set_enabled(!self.modal_active);
+                if self.modal_active {
+                    ui.disable();
+                }
                 ui.label("Communication Log");
                 ui.separator(); // Placeholder to enable dragging (expands to fill).
 
@@ -548,7 +774,7 @@ impl GenCamGUI {
                 egui::ScrollArea::both()
                     .stick_to_bottom(true)
                     .show(ui, |ui| {
-                
+
                         ui.label(format!(
                             "{:?}",
                             (w_view / (8.0 / w_scale))..=(w_view / (4.0 / w_scale))
@@ -561,69 +787,233 @@ impl GenCamGUI {
             });
     }
 
-    fn ui_gps_data_window(&mut self, ctx: &egui::Context) {
-        egui::Window::new("GNSS Satellite Data").show(ctx, |ui| {
-            egui::ScrollArea::vertical()
-                .max_height(400.0)
-                .show(ui, |ui| {
-                    ui.label("Helloo...");
-                    ui.add(egui::Slider::new(&mut self.satellites, 0..=99).text("Satellites"));
-                    if ui.button("Receive Info Packet").clicked() {
-                        self.sat_data.clear();
-                        for i in 0..self.satellites {
-                            self.sat_data.push(GPSSatData {
-                                sat_num: i,
-                                constellation: "GNSS".to_string(),
-                                country: "None".to_string(),
-                                azimuth: 67.7,
-                                elevation: 88.0,
-                                data1: "val".to_string(),
-                                data2: "val".to_string(),
-                                data3: "val".to_string(),
-                                data4: "val".to_string(),
-                            });
+    fn ui_receiver_controls(&mut self, ui: &mut egui::Ui) {
+        ui.label("GNSS Receivers");
+        let count = self.receivers.len();
+        let mut connect = None;
+        let mut remove = None;
+        for (i, receiver) in self.receivers.iter_mut().enumerate() {
+            ui.push_id(i, |ui| {
+                ui.horizontal(|ui| {
+                    ui.radio_value(&mut self.active_receiver, i, "")
+                        .on_hover_text("Show this receiver in the windows.");
+                    ui.add_enabled(
+                        !receiver.is_connected(),
+                        egui::TextEdit::singleline(&mut receiver.address),
+                    );
+                });
+                ui.horizontal(|ui| {
+                    if receiver.is_connected() {
+                        if ui.button("Disconnect").clicked() {
+                            receiver.disconnect();
                         }
+                    } else if ui
+                        .button("Connect")
+                        .on_hover_text("Connect to a raw UBX/NMEA stream over TCP.")
+                        .clicked()
+                    {
+                        connect = Some(i);
+                    }
+                    if count > 1 && ui.button("Remove").clicked() {
+                        remove = Some(i);
                     }
+                });
+            });
+        }
+        if ui
+            .button("Add receiver")
+            .on_hover_text("Connect another receiver, e.g. on the same antenna splitter.")
+            .clicked()
+        {
+            // Next port on the same host as the last receiver.
+            let last = &self.receivers[count - 1].address;
+            let address = match last.rsplit_once(':') {
+                Some((host, port)) => match port.parse::<u16>() {
+                    Ok(port) => format!("{}:{}", host, port.wrapping_add(1)),
+                    Err(_) => last.clone(),
+                },
+                None => last.clone(),
+            };
+            self.receivers.push(GnssReceiver::new(&address));
+        }
 
-                    egui::Grid::new("some_unique_id").show(ui, |ui| {
-                        ui.label("Sat#");
-                        ui.label("Constellation");
-                        ui.label("Country");
-                        ui.label("Az");
-                        ui.label("El");
-                        ui.label("Data 1");
-                        ui.label("Data 2");
-                        ui.label("Data 3");
-                        ui.label("Data 4");
-                        ui.end_row();
-
-                        for i in 0..self.sat_data.len() {
-                            ui.label(self.sat_data[i].sat_num.to_string());
-                            ui.label(self.sat_data[i].constellation.to_string());
-                            ui.label(self.sat_data[i].country.to_string());
-                            ui.label(self.sat_data[i].azimuth.to_string());
-                            ui.label(self.sat_data[i].elevation.to_string());
-                            ui.label(self.sat_data[i].data1.to_string());
-                            ui.label(self.sat_data[i].data2.to_string());
-                            ui.label(self.sat_data[i].data3.to_string());
-                            ui.label(self.sat_data[i].data4.to_string());
-                            ui.end_row();
-                        }
+        if let Some(i) = connect {
+            if let Err(e) = self.receivers[i].connect() {
+                self.dialog(
+                    DialogType::Error,
+                    &format!(
+                        "Failed to connect to the receiver at {}: {}",
+                        self.receivers[i].address, e
+                    ),
+                );
+            }
+        }
+        if let Some(i) = remove {
+            self.receivers.remove(i);
+            if self.active_receiver > i || self.active_receiver == self.receivers.len() {
+                self.active_receiver -= 1;
+            }
+            self.comparison_pair = [0, 1.min(self.receivers.len() - 1)];
+            self.reset_comparison();
+        }
+    }
 
-                        ui.horizontal(|ui| {
-                            ui.label("Same");
-                            ui.label("cell");
-                        });
-                        ui.label("Third row, second column");
-                        ui.end_row();
-                    });
-                });
+    /// Reads and decodes any pending data of every receiver. Should be called once per frame.
+    fn poll_receiver(&mut self, ctx: &egui::Context) {
+        let many = self.receivers.len() > 1;
+        let mut polled = false;
+        for i in 0..self.receivers.len() {
+            let receiver = &mut self.receivers[i];
+            if !receiver.is_connected() {
+                continue;
+            }
+            polled = true;
+            // Name the receiver in messages when there is more than one.
+            let source = match many {
+                true => format!(" ({})", receiver.address),
+                false => String::new(),
+            };
+            let result = receiver.poll();
+            let events = receiver.integrity.take_new();
+            if let Err(e) = result {
+                self.dialog(
+                    DialogType::Error,
+                    &format!("Lost connection to the receiver{}: {}", source, e),
+                );
+            }
+            for event in events {
+                let kind = match event.severity {
+                    Severity::Info => DialogType::Info,
+                    Severity::Warning => DialogType::Warn,
+                    Severity::Critical => DialogType::Error,
+                };
+                self.notify(kind, &format!("{}{}", event.message, source));
+            }
+        }
+        if !polled {
+            return;
+        }
+
+        let position = self.receivers[self.active_receiver]
+            .state
+            .fix
+            .as_ref()
+            .filter(|f| f.fix_type.has_position())
+            .map(|f| (f.latitude, f.longitude, f.h_acc));
+        if let Some((latitude, longitude, h_acc)) = position {
+            for crossing in self
+                .navigator
+                .update(latitude, longitude, h_acc, Utc::now())
+            {
+                self.notify(DialogType::Warn, &crossing.message());
+            }
+        }
+
+        let [reference, other] = self.comparison_pair;
+        if reference != other && other < self.receivers.len() {
+            let time = (Utc::now() - self.comparison_started).num_milliseconds() as f64 / 1e3;
+            self.comparison.add(
+                time,
+                self.receivers[reference].state.fix.as_ref(),
+                self.receivers[other].state.fix.as_ref(),
+            );
+        }
+
+        // Keep polling even when there is no user input.
+        ctx.request_repaint_after(Duration::from_millis(100));
+    }
+
+    fn reset_comparison(&mut self) {
+        self.comparison.clear();
+        self.comparison_started = Utc::now();
+    }
+
+    /// Shows a transient notification and records it in the communication log.
+    fn notify(&mut self, kind: DialogType, message: &str) {
+        self.msg_list.push_back(format!(
+            "[{}] [{}] {}",
+            Local::now().format("%H:%M:%S"),
+            kind.as_str(),
+            message
+        ));
+        self.notifications.push(Notification {
+            kind,
+            message: message.to_owned(),
+            expires: Utc::now() + chrono::Duration::seconds(8),
         });
     }
 
+    fn ui_notifications(&mut self, ctx: &egui::Context) {
+        let now = Utc::now();
+        self.notifications.retain(|n| n.expires > now);
+        if self.notifications.is_empty() {
+            return;
+        }
+
+        egui::Area::new(egui::Id::new("notifications"))
+            .anchor(egui::Align2::RIGHT_BOTTOM, [-10.0, -40.0])
+            .show(ctx, |ui| {
+                for notification in &self.notifications {
+                    egui::Frame::popup(ui.style()).show(ui, |ui| {
+                        let color = match notification.kind {
+                            DialogType::Warn => ui.visuals().warn_fg_color,
+                            DialogType::Error => ui.visuals().error_fg_color,
+                            _ => ui.visuals().text_color(),
+                        };
+                        ui.colored_label(
+                            color,
+                            format!("[{}] {}", notification.kind.as_str(), notification.message),
+                        );
+                    });
+                }
+            });
+
+        // Repaint so expired notifications disappear.
+        ctx.request_repaint_after(Duration::from_millis(500));
+    }
+
+    // Device List tab UI, the landing page listing cameras and the GNSS receiver.
+    fn tab_device_list(&mut self, ui: &mut egui::Ui) {
+        ui.heading("Cameras");
+        if self.connected_cameras.is_empty() {
+            ui.label("No cameras connected.");
+        }
+        for (utid, camera) in &self.connected_cameras {
+            ui.label(format!("{} ({})", camera.name, utid));
+        }
+
+        ui.separator();
+        ui.heading("GNSS Receivers");
+        let mut details = None;
+        for (i, receiver) in self.receivers.iter().enumerate() {
+            if !receiver.is_connected() {
+                ui.label(format!("{}: not connected.", receiver.address));
+                continue;
+            }
+            let state = &receiver.state;
+            ui.label(format!("{}: {}", receiver.address, state.info.summary()));
+            ui.label(match &state.fix {
+                Some(fix) => format!(
+                    "Fix {}, {} satellites used",
+                    fix.fix_type.name(),
+                    fix.num_sv
+                ),
+                None => "No fix".to_string(),
+            });
+            if ui.push_id(i, |ui| ui.link("Details...")).inner.clicked() {
+                details = Some(i);
+            }
+        }
+        if let Some(i) = details {
+            self.active_receiver = i;
+            self.show_receiver_info_window = true;
+        }
+    }
+
     fn ui_central_panel(&mut self, ctx: &egui::Context) {
         egui::CentralPanel::default().show(ctx, |ui| {
-            ui.label("Test.");
+            self.tab_device_list(ui);
+            ui.separator();
             ui.label(format!("Avail   {:?}", ctx.available_rect()));
             ui.label(format!("Used    {:?}", ctx.used_rect()));
             ui.label(format!("Screen  {:?}", ctx.screen_rect()));
@@ -635,7 +1025,9 @@ impl GenCamGUI {
         egui::TopBottomPanel::bottom("bottom_panel")
             .resizable(false)
             .show(ctx, |ui| {
-                ui.set_enabled(!self.modal_active);
+                if self.modal_active {
+                    ui.disable();
+                }
 
                 ui.horizontal(|ui| {
                     ui.label("Bottom Status Panel");
@@ -645,7 +1037,7 @@ impl GenCamGUI {
 }
 
 impl eframe::App for GenCamGUI {
-    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         ctx.set_pixels_per_point(1.5);
 
         //////////////////////////////////////////////////////////////
@@ -662,6 +1054,9 @@ impl eframe::App for GenCamGUI {
         //////////////////////////////////////////////////////////////
         //////////////////////////////////////////////////////////////
 
+        self.poll_receiver(ctx);
+        self.poll_geoid(ctx);
+
         let w_view = ctx.screen_rect().width();
 
         self.ui_developer_controls(ctx);
@@ -672,5 +1067,68 @@ impl eframe::App for GenCamGUI {
         self.ui_central_panel(ctx);
 
         self.ui_gps_data_window(ctx);
+        if self.show_time_window {
+            self.ui_time_window(ctx);
+        }
+        if self.show_timing_window {
+            self.ui_timing_window(ctx);
+        }
+        if self.show_integrity_window {
+            self.ui_integrity_window(ctx);
+        }
+        if self.show_spectrum_window {
+            self.ui_spectrum_window(ctx);
+        }
+        if self.show_receiver_info_window {
+            self.ui_receiver_info_window(ctx);
+        }
+        if self.show_ttff_window {
+            self.ui_ttff_window(ctx);
+        }
+        if self.show_base_window {
+            self.ui_base_window(ctx);
+        }
+        if self.show_rtk_window {
+            self.ui_rtk_window(ctx);
+        }
+        if self.show_position_window {
+            self.ui_position_window(ctx);
+        }
+        if self.show_waypoints_window {
+            self.ui_waypoints_window(ctx);
+        }
+        if self.show_map_window {
+            self.ui_map_window(ctx);
+        }
+        if self.show_velocity_window {
+            self.ui_velocity_window(ctx);
+        }
+        if self.show_spp_window {
+            self.ui_spp_window(ctx);
+        }
+        if self.show_visibility_window {
+            self.ui_visibility_window(ctx);
+        }
+        if self.show_cno_window {
+            self.ui_cno_window(ctx);
+        }
+        if self.show_multipath_window {
+            self.ui_multipath_window(ctx);
+        }
+        if self.show_sbas_window {
+            self.ui_sbas_window(ctx);
+        }
+        if self.show_comparison_window {
+            self.ui_comparison_window(ctx);
+        }
+        self.ui_notifications(ctx);
+    }
+
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, SITES_KEY, &self.sites);
+        eframe::set_value(storage, POSITION_KEY, &self.position_settings);
+        eframe::set_value(storage, WAYPOINTS_KEY, &self.navigator);
+        eframe::set_value(storage, MAP_KEY, &self.map_settings);
+        eframe::set_value(storage, SAT_TABLE_KEY, &self.sat_table);
     }
 }
@@ -0,0 +1,204 @@
+use eframe::egui;
+
+use crate::base::BaseMode;
+use crate::geodesy;
+
+use super::GenCamGUI;
+
+impl GenCamGUI {
+    pub(super) fn ui_base_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_base_window;
+        egui::Window::new("Base Station")
+            .open(&mut open)
+            .vscroll(true)
+            .show(ctx, |ui| {
+                let receiver = &mut self.receivers[self.active_receiver];
+                let connected = receiver.is_connected();
+                let (state, base) = (&receiver.state, &mut receiver.base);
+
+                ui.label(match &base.mode {
+                    BaseMode::Disabled => "Mode: disabled".to_string(),
+                    BaseMode::SurveyIn { .. } => "Mode: survey-in".to_string(),
+                    BaseMode::Fixed(site) => format!("Mode: fixed at {}", site.name),
+                });
+
+                ui.collapsing("Survey-in", |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Minimum duration:");
+                        ui.add(
+                            egui::DragValue::new(&mut base.survey.min_duration)
+                                .range(1..=86_400)
+                                .suffix(" s"),
+                        );
+                        ui.label("Accuracy:");
+                        ui.add(
+                            egui::DragValue::new(&mut base.survey.accuracy_limit)
+                                .range(0.01..=100.0)
+                                .speed(0.01)
+                                .suffix(" m"),
+                        );
+                    });
+                    if ui
+                        .add_enabled(connected, egui::Button::new("Start survey-in"))
+                        .on_hover_text(
+                            "Send CFG-TMODE3. The receiver is switched to fixed mode at the mean position once both targets are met.",
+                        )
+                        .clicked()
+                    {
+                        base.start_survey();
+                    }
+
+                    match &state.survey {
+                        Some(svin) => {
+                            let (lat, lon, height) = geodesy::ecef_to_geodetic(svin.mean);
+                            ui.label(format!(
+                                "{}: {} s, {} observations",
+                                match (svin.active, svin.valid) {
+                                    (true, _) => "In progress",
+                                    (false, true) => "Completed",
+                                    (false, false) => "Not running",
+                                },
+                                svin.duration_s,
+                                svin.observations
+                            ));
+                            ui.add(
+                                egui::ProgressBar::new(
+                                    svin.duration_s as f32 / base.survey.min_duration.max(1) as f32,
+                                )
+                                .text("Duration"),
+                            );
+                            ui.add(
+                                egui::ProgressBar::new(
+                                    (base.survey.accuracy_limit / svin.mean_acc.max(1e-4)).min(1.0)
+                                        as f32,
+                                )
+                                .text(format!("Accuracy {:.3} m", svin.mean_acc)),
+                            );
+                            if svin.observations > 0 {
+                                ui.label(format!(
+                                    "Mean position: {:.8}°, {:.8}°, {:.3} m",
+                                    lat, lon, height
+                                ));
+                            }
+                        }
+                        None => {
+                            ui.label("No NAV-SVIN received.");
+                        }
+                    }
+
+                    if let Some(site) = &mut base.surveyed {
+                        ui.horizontal(|ui| {
+                            ui.label("Surveyed position name:");
+                            ui.text_edit_singleline(&mut site.name);
+                            if ui.button("Save to sites").clicked() {
+                                self.sites.push(site.clone());
+                            }
+                        });
+                    }
+                });
+
+                ui.collapsing("Fixed position", |ui| {
+                    let site = &mut self.manual_site;
+                    egui::Grid::new("base_manual_grid")
+                        .num_columns(2)
+                        .show(ui, |ui| {
+                            ui.label("Name");
+                            ui.text_edit_singleline(&mut site.name);
+                            ui.end_row();
+                            ui.label("Latitude");
+                            ui.add(
+                                egui::DragValue::new(&mut site.latitude)
+                                    .range(-90.0..=90.0)
+                                    .speed(1e-6)
+                                    .max_decimals(9)
+                                    .suffix("°"),
+                            );
+                            ui.end_row();
+                            ui.label("Longitude");
+                            ui.add(
+                                egui::DragValue::new(&mut site.longitude)
+                                    .range(-180.0..=180.0)
+                                    .speed(1e-6)
+                                    .max_decimals(9)
+                                    .suffix("°"),
+                            );
+                            ui.end_row();
+                            ui.label("Ellipsoidal height");
+                            ui.add(
+                                egui::DragValue::new(&mut site.height)
+                                    .speed(0.01)
+                                    .max_decimals(4)
+                                    .suffix(" m"),
+                            );
+                            ui.end_row();
+                            ui.label("Accuracy");
+                            ui.add(
+                                egui::DragValue::new(&mut site.accuracy)
+                                    .range(0.0001..=100.0)
+                                    .speed(0.001)
+                                    .suffix(" m"),
+                            );
+                            ui.end_row();
+                        });
+                    ui.horizontal(|ui| {
+                        if ui
+                            .add_enabled(connected, egui::Button::new("Set fixed mode"))
+                            .clicked()
+                        {
+                            base.set_fixed(site);
+                        }
+                        if ui
+                            .add_enabled(!site.name.is_empty(), egui::Button::new("Save to sites"))
+                            .clicked()
+                        {
+                            self.sites.push(site.clone());
+                        }
+                    });
+                });
+
+                if ui
+                    .add_enabled(connected, egui::Button::new("Disable base mode"))
+                    .clicked()
+                {
+                    base.disable();
+                }
+
+                ui.separator();
+                ui.strong("Sites");
+                if self.sites.is_empty() {
+                    ui.label("No saved sites.");
+                }
+                let mut remove = None;
+                egui::Grid::new("base_sites_grid")
+                    .num_columns(4)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for (i, site) in self.sites.iter().enumerate() {
+                            ui.label(&site.name);
+                            ui.label(format!(
+                                "{:.8}°, {:.8}°, {:.3} m (±{:.3} m)",
+                                site.latitude, site.longitude, site.height, site.accuracy
+                            ));
+                            if ui
+                                .add_enabled(connected, egui::Button::new("Use"))
+                                .on_hover_text("Put the receiver into fixed mode at this site.")
+                                .clicked()
+                            {
+                                base.set_fixed(site);
+                            }
+                            if ui.button("Edit").clicked() {
+                                self.manual_site = site.clone();
+                            }
+                            if ui.button("Delete").clicked() {
+                                remove = Some(i);
+                            }
+                            ui.end_row();
+                        }
+                    });
+                if let Some(i) = remove {
+                    self.sites.remove(i);
+                }
+            });
+        self.show_base_window = open;
+    }
+}
@@ -0,0 +1,135 @@
+use eframe::egui;
+use egui_plot::{Line, Plot, PlotPoints};
+
+use crate::cno_map;
+use crate::visibility;
+
+use super::widgets::sky_plot;
+use super::{DialogType, GenCamGUI, Modal};
+
+impl GenCamGUI {
+    pub(super) fn ui_cno_window(&mut self, ctx: &egui::Context) {
+        /// C/N0 range of the heat-map colours, in dB-Hz.
+        const LOW: f64 = 20.0;
+        const HIGH: f64 = 50.0;
+        let heat = |cno: f64| {
+            let f = ((cno - LOW) / (HIGH - LOW)).clamp(0.0, 1.0) as f32;
+            egui::Color32::from(egui::ecolor::Hsva::new(0.66 * (1.0 - f), 0.9, 0.9, 0.7))
+        };
+
+        let mut open = self.show_cno_window;
+        let mut clear = false;
+        let mut export = false;
+        egui::Window::new("C/N0 Sky Map")
+            .open(&mut open)
+            .vscroll(true)
+            .show(ctx, |ui| {
+                let map = &self.receivers[self.active_receiver].state.cno_map;
+                ui.horizontal(|ui| {
+                    ui.label(format!("{} epochs sampled", map.epochs));
+                    clear = ui.button("Clear").clicked();
+                });
+                let cells = map.cells();
+                let (response, _, axes) = sky_plot(ui, |painter, axes| {
+                    for cell in &cells {
+                        axes.fill_cell(
+                            painter,
+                            [cell.azimuth, cell.azimuth + visibility::AZIMUTH_STEP],
+                            [cell.elevation, cell.elevation + visibility::ELEVATION_STEP],
+                            heat(cell.mean),
+                        );
+                    }
+                });
+                if let Some((azimuth, elevation)) =
+                    response.hover_pos().and_then(|pos| axes.sky_at(pos))
+                {
+                    response.on_hover_text(match map.cell_at(azimuth, elevation) {
+                        Some(c) => format!(
+                            "Az {:.0}–{:.0}°, el {:.0}–{:.0}°\n{:.1} ± {:.1} dB-Hz, {} samples",
+                            c.azimuth,
+                            c.azimuth + visibility::AZIMUTH_STEP,
+                            c.elevation,
+                            c.elevation + visibility::ELEVATION_STEP,
+                            c.mean,
+                            c.std_dev,
+                            c.samples
+                        ),
+                        None => format!("Az {:.0}°, el {:.0}°: no samples", azimuth, elevation),
+                    });
+                }
+                ui.label(format!(
+                    "Cells show the mean C/N0 from blue, {} dB-Hz or less, to red, {} dB-Hz or \
+                     more.",
+                    LOW, HIGH
+                ));
+
+                ui.separator();
+                ui.label("C/N0 against elevation, with a quadratic fit per constellation");
+                Plot::new("cno_elevation")
+                    .height(220.0)
+                    .include_x(0.0)
+                    .include_x(90.0)
+                    .x_axis_label("Elevation (°)")
+                    .y_axis_label("C/N0 (dB-Hz)")
+                    .legend(egui_plot::Legend::default())
+                    .show(ui, |plot_ui| {
+                        for (i, (constellation, samples)) in map.samples.iter().enumerate() {
+                            let color = egui::Color32::from(egui::ecolor::Hsva::new(
+                                (i as f32 * 0.618) % 1.0,
+                                0.85,
+                                0.6,
+                                1.0,
+                            ));
+                            let points: PlotPoints = samples.iter().copied().collect();
+                            plot_ui.points(
+                                egui_plot::Points::new(points)
+                                    .radius(1.5)
+                                    .color(color.gamma_multiply(0.5))
+                                    .name(constellation.name()),
+                            );
+                            if let Some(fit) = map.fit(*constellation) {
+                                let curve: PlotPoints = (0..=50)
+                                    .map(|k| {
+                                        let e = fit.low + (fit.high - fit.low) * k as f64 / 50.0;
+                                        [e, cno_map::evaluate_curve(&fit.coefficients, e)]
+                                    })
+                                    .collect();
+                                plot_ui.line(
+                                    Line::new(curve)
+                                        .color(color)
+                                        .width(2.0)
+                                        .name(constellation.name()),
+                                );
+                            }
+                        }
+                    });
+
+                ui.horizontal(|ui| {
+                    ui.label("CSV file:");
+                    ui.text_edit_singleline(&mut self.cno_csv_path);
+                    if ui
+                        .add_enabled(!cells.is_empty(), egui::Button::new("Save"))
+                        .clicked()
+                    {
+                        export = true;
+                    }
+                });
+            });
+
+        self.show_cno_window = open;
+        if clear {
+            self.receivers[self.active_receiver].state.cno_map.clear();
+        }
+        if export {
+            if let Err(e) = std::fs::write(
+                &self.cno_csv_path,
+                self.receivers[self.active_receiver].state.cno_map.to_csv(),
+            ) {
+                self.dialog(
+                    DialogType::Error,
+                    &format!("Failed to write {}: {}", self.cno_csv_path, e),
+                );
+            }
+        }
+    }
+}
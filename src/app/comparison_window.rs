@@ -0,0 +1,175 @@
+use eframe::egui;
+use egui_plot::{Line, Plot, PlotPoints};
+
+use crate::comparison;
+
+use super::GenCamGUI;
+
+impl GenCamGUI {
+    pub(super) fn ui_comparison_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_comparison_window;
+        let mut reset = false;
+        egui::Window::new("Receiver Comparison")
+            .open(&mut open)
+            .vscroll(true)
+            .show(ctx, |ui| {
+                if self.receivers.len() < 2 {
+                    ui.label("Add a second receiver in the left panel to compare.");
+                    return;
+                }
+                let names: Vec<String> = self
+                    .receivers
+                    .iter()
+                    .enumerate()
+                    .map(|(i, r)| format!("{}: {}", i + 1, r.address))
+                    .collect();
+                let previous = self.comparison_pair;
+                egui::Grid::new("comparison_pair").num_columns(2).show(ui, |ui| {
+                    for (label, slot) in ["Reference", "Compared"].iter().zip(0..2) {
+                        ui.label(*label);
+                        let pair = &mut self.comparison_pair;
+                        let selected = names.get(pair[slot]).cloned().unwrap_or_default();
+                        egui::ComboBox::from_id_source(("comparison_receiver", slot))
+                            .selected_text(selected)
+                            .show_ui(ui, |ui| {
+                                for (i, name) in names.iter().enumerate() {
+                                    ui.selectable_value(&mut pair[slot], i, name);
+                                }
+                            });
+                        ui.end_row();
+                    }
+                });
+                reset = self.comparison_pair != previous;
+                let [a, b] = self.comparison_pair;
+                if a == b || b >= self.receivers.len() {
+                    ui.label("Pick two different receivers.");
+                    return;
+                }
+                let (reference, other) = (&self.receivers[a].state, &self.receivers[b].state);
+                let satellites = comparison::compare_satellites(&reference.satellites, &other.satellites);
+                let list = |pick: fn(&comparison::SatelliteComparison) -> bool| {
+                    let ids: Vec<String> = satellites
+                        .iter()
+                        .filter(|s| pick(s))
+                        .map(|s| s.id.to_string())
+                        .collect();
+                    match ids.is_empty() {
+                        true => "-".to_string(),
+                        false => ids.join(" "),
+                    }
+                };
+
+                ui.separator();
+                ui.strong("Tracked satellites");
+                egui::Grid::new("comparison_sets").num_columns(2).show(ui, |ui| {
+                    ui.label("Both");
+                    ui.label(format!(
+                        "{}",
+                        satellites.iter().filter(|s| s.difference().is_some()).count()
+                    ));
+                    ui.end_row();
+                    ui.label("Reference only");
+                    ui.label(list(|s| s.cno[1].is_none()));
+                    ui.end_row();
+                    ui.label("Compared only");
+                    ui.label(list(|s| s.cno[0].is_none()));
+                    ui.end_row();
+                });
+
+                ui.separator();
+                ui.strong("C/N0, compared minus reference");
+                for (constellation, (mean, n)) in comparison::mean_cno_difference(&satellites) {
+                    ui.label(format!(
+                        "{}: {:+.1} dB mean over {} satellites",
+                        constellation.name(),
+                        mean,
+                        n
+                    ));
+                }
+                let common: Vec<&comparison::SatelliteComparison> = satellites
+                    .iter()
+                    .filter(|s| s.difference().is_some())
+                    .collect();
+                let bars: Vec<egui_plot::Bar> = common
+                    .iter()
+                    .enumerate()
+                    .map(|(i, s)| {
+                        egui_plot::Bar::new(i as f64, s.difference().unwrap_or(0) as f64)
+                            .name(s.id.to_string())
+                            .width(0.7)
+                    })
+                    .collect();
+                let labels: Vec<String> = common.iter().map(|s| s.id.to_string()).collect();
+                Plot::new("comparison_cno")
+                    .height(160.0)
+                    .y_axis_label("ΔC/N0 (dB)")
+                    .x_axis_formatter(move |mark, _| {
+                        let i = mark.value.round();
+                        match (i - mark.value).abs() < 1e-6 && i >= 0.0 {
+                            true => labels.get(i as usize).cloned().unwrap_or_default(),
+                            false => String::new(),
+                        }
+                    })
+                    .show(ui, |plot_ui| {
+                        plot_ui.bar_chart(egui_plot::BarChart::new(bars));
+                    });
+                ui.collapsing("Per satellite", |ui| {
+                    egui::Grid::new("comparison_cno_grid")
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for title in ["Satellite", "Reference", "Compared", "Difference"] {
+                                ui.strong(title);
+                            }
+                            ui.end_row();
+                            let cno = |v: Option<u8>| v.map_or("-".to_string(), |v| format!("{} dB-Hz", v));
+                            for s in &satellites {
+                                ui.label(s.id.to_string());
+                                ui.label(cno(s.cno[0]));
+                                ui.label(cno(s.cno[1]));
+                                ui.label(s.difference().map_or("-".to_string(), |d| format!("{:+} dB", d)));
+                                ui.end_row();
+                            }
+                        });
+                });
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.strong("Position, compared relative to reference");
+                    if ui.button("Clear").clicked() {
+                        reset = true;
+                    }
+                });
+                match self.comparison.statistics() {
+                    Some(([east, north, up], rms)) => ui.label(format!(
+                        "Mean E {:+.3} m, N {:+.3} m, U {:+.3} m; horizontal RMS {:.3} m over {} samples",
+                        east,
+                        north,
+                        up,
+                        rms,
+                        self.comparison.samples.len()
+                    )),
+                    None => ui.label("Waiting for both receivers to have a fix with a height."),
+                };
+                Plot::new("comparison_position")
+                    .height(200.0)
+                    .legend(egui_plot::Legend::default())
+                    .x_axis_label("Time (s)")
+                    .y_axis_label("Difference (m)")
+                    .show(ui, |plot_ui| {
+                        for (axis, name) in ["East", "North", "Up"].iter().enumerate() {
+                            let points: Vec<[f64; 2]> = self
+                                .comparison
+                                .samples
+                                .iter()
+                                .map(|s| [s.time, s.enu[axis]])
+                                .collect();
+                            plot_ui.line(Line::new(PlotPoints::from(points)).name(*name));
+                        }
+                    });
+            });
+        self.show_comparison_window = open;
+        if reset {
+            self.reset_comparison();
+        }
+    }
+}
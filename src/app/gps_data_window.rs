@@ -0,0 +1,385 @@
+use chrono::Utc;
+use eframe::egui;
+use egui_extras::{Column, TableBuilder};
+use serde::{Deserialize, Serialize};
+
+use crate::gnss::{Constellation, Satellite, SatelliteId};
+use crate::receiver::ReceiverState;
+
+use super::GenCamGUI;
+
+pub struct GPSSatData {
+    id: SatelliteId,
+    azimuth: Option<f32>,
+    elevation: Option<f32>,
+    /// dB-Hz, zero when not tracked.
+    cno: u8,
+    used: bool,
+    /// Excluded by RAIM from the local solution.
+    excluded: bool,
+    ephemeris: String,
+    /// Minutes since the reference time of the latest ephemeris.
+    ephemeris_age: Option<f64>,
+}
+
+impl GPSSatData {
+    /// A table row for a satellite; `now` is the current time in seconds since the GPS epoch,
+    /// for the age of its ephemeris.
+    fn from_satellite(sat: &Satellite, state: &ReceiverState, now: f64) -> Self {
+        let excluded = state.raim.as_ref().is_some_and(|r| r.is_excluded(sat.id));
+        let nav = &state.navigation;
+        let latest = nav.latest(sat.id);
+        let ephemeris_age = latest.map(|eph| (now - eph.toe) / 60.0);
+        let ephemeris = match latest {
+            Some(eph) => format!(
+                "{:.0} min, IOD {}, {}",
+                (now - eph.toe) / 60.0,
+                eph.iode,
+                if eph.health == 0 {
+                    "healthy"
+                } else {
+                    "unhealthy"
+                }
+            ),
+            None if nav.almanacs.contains_key(&sat.id) => "Almanac only".to_string(),
+            None => "-".to_string(),
+        };
+        Self {
+            id: sat.id,
+            azimuth: sat.azimuth,
+            elevation: sat.elevation,
+            cno: sat.cno,
+            used: sat.used,
+            excluded,
+            ephemeris,
+            ephemeris_age,
+        }
+    }
+
+    fn text(&self, column: SatColumn) -> String {
+        let angle = |a: Option<f32>| a.map_or("-".to_string(), |a| format!("{:.0}", a));
+        match column {
+            SatColumn::Satellite => self.id.prn.to_string(),
+            SatColumn::Constellation => self.id.constellation.name().to_string(),
+            SatColumn::Country => self.id.constellation.country().to_string(),
+            SatColumn::Azimuth => angle(self.azimuth),
+            SatColumn::Elevation => angle(self.elevation),
+            SatColumn::Cno => match self.cno {
+                0 => "-".to_string(),
+                cno => cno.to_string(),
+            },
+            SatColumn::Used => if self.used { "Yes" } else { "No" }.to_string(),
+            SatColumn::Raim => if self.excluded { "Excluded" } else { "" }.to_string(),
+            SatColumn::Ephemeris => self.ephemeris.clone(),
+        }
+    }
+
+    /// Orders two rows by a column, unknown values last in either direction.
+    fn compare(&self, other: &Self, column: SatColumn, descending: bool) -> std::cmp::Ordering {
+        let known = |order: std::cmp::Ordering| match descending {
+            true => order.reverse(),
+            false => order,
+        };
+        let optional = |a: Option<f64>, b: Option<f64>| match (a, b) {
+            (Some(a), Some(b)) => known(a.total_cmp(&b)),
+            (a, b) => a.is_none().cmp(&b.is_none()),
+        };
+        match column {
+            SatColumn::Satellite => known(
+                (self.id.constellation, self.id.prn).cmp(&(other.id.constellation, other.id.prn)),
+            ),
+            SatColumn::Constellation => known(self.id.cmp(&other.id)),
+            SatColumn::Country => known(
+                self.id
+                    .constellation
+                    .country()
+                    .cmp(other.id.constellation.country()),
+            ),
+            SatColumn::Azimuth => {
+                optional(self.azimuth.map(f64::from), other.azimuth.map(f64::from))
+            }
+            SatColumn::Elevation => optional(
+                self.elevation.map(f64::from),
+                other.elevation.map(f64::from),
+            ),
+            SatColumn::Cno => known(self.cno.cmp(&other.cno)),
+            SatColumn::Used => known(self.used.cmp(&other.used)),
+            SatColumn::Raim => known(self.excluded.cmp(&other.excluded)),
+            SatColumn::Ephemeris => optional(self.ephemeris_age, other.ephemeris_age),
+        }
+    }
+}
+
+/// Columns of the satellite table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+enum SatColumn {
+    Satellite,
+    Constellation,
+    Country,
+    Azimuth,
+    Elevation,
+    Cno,
+    Used,
+    Raim,
+    Ephemeris,
+}
+
+impl SatColumn {
+    const ALL: [SatColumn; 9] = [
+        SatColumn::Satellite,
+        SatColumn::Constellation,
+        SatColumn::Country,
+        SatColumn::Azimuth,
+        SatColumn::Elevation,
+        SatColumn::Cno,
+        SatColumn::Used,
+        SatColumn::Raim,
+        SatColumn::Ephemeris,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            SatColumn::Satellite => "Sat#",
+            SatColumn::Constellation => "Constellation",
+            SatColumn::Country => "Country",
+            SatColumn::Azimuth => "Az",
+            SatColumn::Elevation => "El",
+            SatColumn::Cno => "C/N0",
+            SatColumn::Used => "Used",
+            SatColumn::Raim => "RAIM",
+            SatColumn::Ephemeris => "Ephemeris",
+        }
+    }
+}
+
+/// Which satellites the table lists by their use in the fix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+enum UsedFilter {
+    All,
+    Used,
+    NotUsed,
+}
+
+impl UsedFilter {
+    const ALL: [UsedFilter; 3] = [UsedFilter::All, UsedFilter::Used, UsedFilter::NotUsed];
+
+    fn name(&self) -> &'static str {
+        match self {
+            UsedFilter::All => "All",
+            UsedFilter::Used => "Used in fix",
+            UsedFilter::NotUsed => "Not used",
+        }
+    }
+}
+
+/// Sorting, filters and columns of the satellite table, kept across sessions.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub(super) struct SatTableSettings {
+    sort: SatColumn,
+    descending: bool,
+    hidden_columns: Vec<SatColumn>,
+    hidden_constellations: Vec<Constellation>,
+    used: UsedFilter,
+    /// Satellites below this elevation in degrees are hidden. Satellites without an elevation
+    /// are shown while the mask is zero and hidden once it is raised.
+    elevation_mask: f64,
+}
+
+impl Default for SatTableSettings {
+    fn default() -> Self {
+        Self {
+            sort: SatColumn::Constellation,
+            descending: false,
+            hidden_columns: Vec::new(),
+            hidden_constellations: Vec::new(),
+            used: UsedFilter::All,
+            elevation_mask: 0.0,
+        }
+    }
+}
+
+impl SatTableSettings {
+    fn shows(&self, row: &GPSSatData) -> bool {
+        let elevation = match row.elevation {
+            Some(e) => e as f64 >= self.elevation_mask,
+            None => self.elevation_mask <= 0.0,
+        };
+        let used = match self.used {
+            UsedFilter::All => true,
+            UsedFilter::Used => row.used,
+            UsedFilter::NotUsed => !row.used,
+        };
+        elevation && used && !self.hidden_constellations.contains(&row.id.constellation)
+    }
+}
+
+impl GenCamGUI {
+    pub(super) fn ui_gps_data_window(&mut self, ctx: &egui::Context) {
+        let receiver = &self.receivers[self.active_receiver];
+        let state = &receiver.state;
+        let now = state.now(Utc::now()).as_gps_secs_f64();
+        // A disconnected receiver keeps its last state, which is no longer being tracked.
+        let tracked = match receiver.is_connected() {
+            true => &state.satellites[..],
+            false => &[],
+        };
+        self.sat_data = tracked
+            .iter()
+            .map(|sat| GPSSatData::from_satellite(sat, state, now))
+            .collect();
+
+        let settings = &mut self.sat_table;
+        egui::Window::new("GNSS Satellite Data").show(ctx, |ui| {
+            ui.horizontal_wrapped(|ui| {
+                for constellation in Constellation::ALL {
+                    let mut shown = !settings.hidden_constellations.contains(&constellation);
+                    if ui.checkbox(&mut shown, constellation.name()).changed() {
+                        settings
+                            .hidden_constellations
+                            .retain(|c| *c != constellation);
+                        if !shown {
+                            settings.hidden_constellations.push(constellation);
+                        }
+                    }
+                }
+            });
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_id_source("sat_table_used")
+                    .selected_text(settings.used.name())
+                    .show_ui(ui, |ui| {
+                        for filter in UsedFilter::ALL {
+                            ui.selectable_value(&mut settings.used, filter, filter.name());
+                        }
+                    });
+                ui.label("Elevation mask");
+                ui.add(
+                    egui::DragValue::new(&mut settings.elevation_mask)
+                        .range(0.0..=90.0)
+                        .speed(1.0)
+                        .suffix("°"),
+                );
+                ui.menu_button("Columns", |ui| {
+                    for column in SatColumn::ALL {
+                        let mut shown = !settings.hidden_columns.contains(&column);
+                        if ui.checkbox(&mut shown, column.name()).changed() {
+                            settings.hidden_columns.retain(|c| *c != column);
+                            if !shown {
+                                settings.hidden_columns.push(column);
+                            }
+                        }
+                    }
+                });
+            });
+
+            let columns: Vec<SatColumn> = SatColumn::ALL
+                .into_iter()
+                .filter(|c| !settings.hidden_columns.contains(c))
+                .collect();
+            let mut rows: Vec<&GPSSatData> =
+                self.sat_data.iter().filter(|r| settings.shows(r)).collect();
+            rows.sort_by(|a, b| {
+                a.compare(b, settings.sort, settings.descending)
+                    .then(a.id.cmp(&b.id))
+            });
+            ui.label(format!(
+                "{} of {} satellites",
+                rows.len(),
+                self.sat_data.len()
+            ));
+
+            let mut table = TableBuilder::new(ui)
+                .striped(true)
+                .resizable(true)
+                .max_scroll_height(400.0)
+                .cell_layout(egui::Layout::left_to_right(egui::Align::Center));
+            for column in &columns {
+                table = table.column(match column {
+                    SatColumn::Ephemeris => Column::remainder().at_least(80.0),
+                    _ => Column::auto().at_least(30.0),
+                });
+            }
+            table
+                .header(20.0, |mut header| {
+                    for column in &columns {
+                        header.col(|ui| {
+                            let sorted = settings.sort == *column;
+                            let arrow = match (sorted, settings.descending) {
+                                (false, _) => "",
+                                (true, false) => " ⏶",
+                                (true, true) => " ⏷",
+                            };
+                            if ui
+                                .selectable_label(sorted, format!("{}{}", column.name(), arrow))
+                                .on_hover_text("Sort by this column.")
+                                .clicked()
+                            {
+                                settings.descending = sorted && !settings.descending;
+                                settings.sort = *column;
+                            }
+                        });
+                    }
+                })
+                .body(|body| {
+                    body.rows(18.0, rows.len(), |mut row| {
+                        let sat = rows[row.index()];
+                        for column in &columns {
+                            row.col(|ui| {
+                                ui.label(sat.text(*column));
+                            });
+                        }
+                    });
+                });
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(constellation: Constellation, prn: u8, elevation: Option<f32>) -> GPSSatData {
+        GPSSatData {
+            id: SatelliteId::new(constellation, prn),
+            azimuth: None,
+            elevation,
+            cno: 40,
+            used: true,
+            excluded: false,
+            ephemeris: "-".to_string(),
+            ephemeris_age: None,
+        }
+    }
+
+    #[test]
+    fn sorts_and_masks_satellite_rows() {
+        let rows = [
+            row(Constellation::Galileo, 3, Some(20.0)),
+            row(Constellation::Gps, 5, None),
+            row(Constellation::Gps, 3, Some(5.0)),
+        ];
+        let mut sorted: Vec<&GPSSatData> = rows.iter().collect();
+        sorted.sort_by(|a, b| a.compare(b, SatColumn::Satellite, false));
+        let ids: Vec<_> = sorted.iter().map(|r| r.id).collect();
+        assert_eq!(
+            ids,
+            [
+                SatelliteId::new(Constellation::Gps, 3),
+                SatelliteId::new(Constellation::Gps, 5),
+                SatelliteId::new(Constellation::Galileo, 3),
+            ]
+        );
+        sorted.sort_by(|a, b| a.compare(b, SatColumn::Elevation, true));
+        assert_eq!(sorted[2].elevation, None);
+
+        let mut settings = SatTableSettings::default();
+        assert!(rows.iter().all(|r| settings.shows(r)));
+        settings.elevation_mask = 10.0;
+        let shown: Vec<_> = rows
+            .iter()
+            .filter(|r| settings.shows(r))
+            .map(|r| r.id)
+            .collect();
+        assert_eq!(shown, [SatelliteId::new(Constellation::Galileo, 3)]);
+    }
+}
@@ -0,0 +1,200 @@
+use chrono::Local;
+use eframe::egui;
+use egui_plot::{Line, Plot, PlotPoints};
+
+use crate::ubx;
+
+use super::GenCamGUI;
+
+impl GenCamGUI {
+    pub(super) fn ui_integrity_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_integrity_window;
+        egui::Window::new("Interference & Integrity")
+            .open(&mut open)
+            .vscroll(true)
+            .show(ctx, |ui| {
+                let receiver = &mut self.receivers[self.active_receiver];
+                let (state, integrity) = (&receiver.state, &mut receiver.integrity);
+
+                ui.collapsing("RF (MON-RF)", |ui| match &state.rf {
+                    Some(rf) => {
+                        egui::Grid::new("rf_blocks_grid")
+                            .striped(true)
+                            .show(ui, |ui| {
+                                ui.label("Block");
+                                ui.label("Jamming");
+                                ui.label("AGC");
+                                ui.label("CW jamming");
+                                ui.label("Noise/ms");
+                                ui.label("Antenna");
+                                ui.end_row();
+
+                                for block in &rf.blocks {
+                                    ui.label(block.block_id.to_string());
+                                    let color = match block.jamming {
+                                        ubx::JammingState::Warning => ui.visuals().warn_fg_color,
+                                        ubx::JammingState::Critical => ui.visuals().error_fg_color,
+                                        _ => ui.visuals().text_color(),
+                                    };
+                                    ui.colored_label(color, block.jamming.name());
+                                    ui.label(format!("{:.1}%", block.agc_percent()));
+                                    ui.label(format!("{}/255", block.jam_ind));
+                                    ui.label(block.noise_per_ms.to_string());
+                                    ui.label(format!(
+                                        "{}, power {}",
+                                        ubx::antenna_status_name(block.ant_status),
+                                        ubx::antenna_power_name(block.ant_power)
+                                    ));
+                                    ui.end_row();
+                                }
+                            });
+
+                        Plot::new("agc_plot")
+                            .height(150.0)
+                            .legend(egui_plot::Legend::default())
+                            .x_axis_label("Time (s)")
+                            .y_axis_label("AGC (%)")
+                            .show(ui, |plot_ui| {
+                                for (block, history) in &state.agc_history {
+                                    let points: Vec<[f64; 2]> = history.iter().copied().collect();
+                                    plot_ui.line(
+                                        Line::new(PlotPoints::from(points))
+                                            .name(format!("Block {}", block)),
+                                    );
+                                }
+                            });
+                    }
+                    None => {
+                        ui.label("No MON-RF received.");
+                    }
+                });
+
+                ui.collapsing("Spoofing (NAV-STATUS)", |ui| match &state.nav_status {
+                    Some(status) => {
+                        ui.label(status.spoofing().name());
+                    }
+                    None => {
+                        ui.label("No NAV-STATUS received.");
+                    }
+                });
+
+                ui.collapsing("RAIM (local solution)", |ui| match &state.raim {
+                    Some(raim) => {
+                        let metres =
+                            |v: Option<f64>| v.map_or("-".to_string(), |v| format!("{:.1} m", v));
+                        ui.label(match (raim.threshold, raim.fault) {
+                            (None, _) => "Unavailable: no redundant satellites".to_owned(),
+                            (Some(t), fault) => format!(
+                                "Chi-square {:.1}, threshold {:.1}: {}",
+                                raim.chi_square,
+                                t,
+                                if fault { "fault" } else { "pass" }
+                            ),
+                        });
+                        ui.label(format!(
+                            "HPL {} (limit {:.0} m), VPL {} (limit {:.0} m)",
+                            metres(raim.hpl),
+                            integrity.thresholds.horizontal_alert_limit,
+                            metres(raim.vpl),
+                            integrity.thresholds.vertical_alert_limit
+                        ));
+                        if !raim.excluded.is_empty() {
+                            let excluded: Vec<String> =
+                                raim.excluded.iter().map(|(id, _)| id.to_string()).collect();
+                            ui.label(format!("Excluded: {}", excluded.join(", ")));
+                        }
+                    }
+                    None => {
+                        ui.label("No local solution; needs RXM-RAWX and ephemerides.");
+                    }
+                });
+
+                ui.collapsing("Heuristics", |ui| {
+                    ui.label(match integrity.cno_spread {
+                        Some(spread) => format!("C/N0 spread: {:.1} dB-Hz", spread),
+                        None => "C/N0 spread: not enough satellites".to_owned(),
+                    });
+
+                    let thresholds = &mut integrity.thresholds;
+                    egui::Grid::new("integrity_thresholds_grid").show(ui, |ui| {
+                        ui.label("Minimum satellites for C/N0 check");
+                        ui.add(egui::DragValue::new(&mut thresholds.min_satellites).range(2..=64));
+                        ui.end_row();
+
+                        ui.label("Minimum C/N0 spread");
+                        ui.add(
+                            egui::DragValue::new(&mut thresholds.min_cno_spread)
+                                .speed(0.1)
+                                .suffix(" dB-Hz"),
+                        );
+                        ui.end_row();
+
+                        ui.label("Maximum implied speed");
+                        ui.add(
+                            egui::DragValue::new(&mut thresholds.max_speed)
+                                .speed(1.0)
+                                .suffix(" m/s"),
+                        );
+                        ui.end_row();
+
+                        ui.label("Maximum time jump");
+                        ui.add(
+                            egui::DragValue::new(&mut thresholds.max_time_jump)
+                                .speed(0.01)
+                                .suffix(" s"),
+                        );
+                        ui.end_row();
+
+                        ui.label("Maximum clock drift step");
+                        ui.add(
+                            egui::DragValue::new(&mut thresholds.max_drift_step)
+                                .speed(1.0)
+                                .suffix(" ns/s"),
+                        );
+                        ui.end_row();
+
+                        ui.label("Horizontal alert limit");
+                        ui.add(
+                            egui::DragValue::new(&mut thresholds.horizontal_alert_limit)
+                                .speed(1.0)
+                                .suffix(" m"),
+                        );
+                        ui.end_row();
+
+                        ui.label("Vertical alert limit");
+                        ui.add(
+                            egui::DragValue::new(&mut thresholds.vertical_alert_limit)
+                                .speed(1.0)
+                                .suffix(" m"),
+                        );
+                        ui.end_row();
+                    });
+                });
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.label(format!("Event log ({})", integrity.events.len()));
+                    if ui.button("Clear").clicked() {
+                        integrity.clear();
+                    }
+                });
+                egui::Grid::new("integrity_events_grid")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for event in integrity.events.iter().rev() {
+                            ui.label(
+                                event
+                                    .time
+                                    .with_timezone(&Local)
+                                    .format("%Y-%m-%d %H:%M:%S")
+                                    .to_string(),
+                            );
+                            ui.label(event.severity.as_str());
+                            ui.label(&event.message);
+                            ui.end_row();
+                        }
+                    });
+            });
+        self.show_integrity_window = open;
+    }
+}
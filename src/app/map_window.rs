@@ -0,0 +1,283 @@
+use std::collections::hash_map::Entry;
+
+use eframe::egui;
+use egui_plot::{Line, Plot, PlotPoints};
+use serde::{Deserialize, Serialize};
+
+use crate::gnss::ELLIPSE_95;
+use crate::map::{self, MapSource, MapSourceKind};
+use crate::waypoints::FenceShape;
+
+use super::{DialogType, GenCamGUI, Modal};
+
+/// Where the offline map comes from, kept across sessions.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub(super) struct MapSettings {
+    kind: MapSourceKind,
+    /// MBTiles file, tile directory or image, loaded at start-up when set.
+    pub(super) path: String,
+    /// Keep the current position in the centre of the map.
+    follow: bool,
+}
+
+impl Default for MapSettings {
+    fn default() -> Self {
+        Self {
+            kind: MapSourceKind::MbTiles,
+            path: String::new(),
+            follow: true,
+        }
+    }
+}
+
+/// Uploads an image as a texture.
+fn rgba_texture(ctx: &egui::Context, name: &str, image: &image::RgbaImage) -> egui::TextureHandle {
+    let size = [image.width() as usize, image.height() as usize];
+    ctx.load_texture(
+        name,
+        egui::ColorImage::from_rgba_unmultiplied(size, image.as_raw()),
+        egui::TextureOptions::LINEAR,
+    )
+}
+
+/// A map image covering the given south-west and north-east corners in Web Mercator metres.
+fn map_image(texture: &egui::TextureHandle, sw: [f64; 2], ne: [f64; 2]) -> egui_plot::PlotImage {
+    egui_plot::PlotImage::new(
+        texture.id(),
+        egui_plot::PlotPoint::new((sw[0] + ne[0]) / 2.0, (sw[1] + ne[1]) / 2.0),
+        [(ne[0] - sw[0]) as f32, (ne[1] - sw[1]) as f32],
+    )
+}
+
+/// Tiles decoded per frame, so panning to a new area does not stall the UI.
+const MAX_TILE_LOADS_PER_FRAME: usize = 8;
+
+/// Tile textures kept before the cache is emptied.
+const MAX_CACHED_TILES: usize = 512;
+
+impl GenCamGUI {
+    pub(super) fn load_map(&mut self) {
+        let settings = &self.map_settings;
+        self.map_tiles.clear();
+        self.map_image = None;
+        match MapSource::load(settings.kind, std::path::Path::new(&settings.path)) {
+            Ok(source) => self.map = Some(source),
+            Err(e) => {
+                self.map = None;
+                let message = format!("Failed to load the map {}: {}", settings.path, e);
+                self.dialog(DialogType::Error, &message);
+            }
+        }
+    }
+
+    pub(super) fn ui_map_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_map_window;
+        let mut load = false;
+        egui::Window::new("Map")
+            .open(&mut open)
+            .default_size([600.0, 500.0])
+            .show(ctx, |ui| {
+                let settings = &mut self.map_settings;
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_id_source("map_source_kind")
+                        .selected_text(settings.kind.name())
+                        .show_ui(ui, |ui| {
+                            for kind in MapSourceKind::ALL {
+                                ui.selectable_value(&mut settings.kind, kind, kind.name());
+                            }
+                        });
+                    ui.text_edit_singleline(&mut settings.path);
+                    load = ui.button("Load").clicked();
+                });
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut settings.follow, "Follow position");
+                    ui.label(match &self.map {
+                        Some(map) => format!("Map: {}", map.name()),
+                        None => "No map loaded.".to_string(),
+                    });
+                });
+
+                let state = &self.receivers[self.active_receiver].state;
+                let fix = state.fix.as_ref().filter(|f| f.fix_type.has_position());
+                let position = fix.map(|f| map::mercator(f.latitude, f.longitude));
+                let degrees = |p: [f64; 2]| {
+                    let (lat, lon) = map::inverse_mercator(p);
+                    format!("{:.6}°, {:.6}°", lat, lon)
+                };
+
+                Plot::new("map_plot")
+                    .data_aspect(1.0)
+                    .show_grid(false)
+                    .legend(egui_plot::Legend::default())
+                    .x_axis_formatter(|mark, _| {
+                        format!("{:.4}°", map::inverse_mercator([mark.value, 0.0]).1)
+                    })
+                    .y_axis_formatter(|mark, _| {
+                        format!("{:.4}°", map::inverse_mercator([0.0, mark.value]).0)
+                    })
+                    .label_formatter(move |name, point| match name {
+                        "" => degrees([point.x, point.y]),
+                        name => format!("{}\n{}", name, degrees([point.x, point.y])),
+                    })
+                    .show(ui, |plot_ui| {
+                        if plot_ui.response().dragged() {
+                            settings.follow = false;
+                        }
+                        if let (true, Some([x, y])) = (settings.follow, position) {
+                            let bounds = plot_ui.plot_bounds();
+                            // Start from a few hundred metres across rather than the bounds
+                            // of a single point.
+                            let scale = (500.0 / bounds.width()).max(1.0);
+                            let (w, h) = (bounds.width() * scale, bounds.height() * scale);
+                            plot_ui.set_plot_bounds(egui_plot::PlotBounds::from_min_max(
+                                [x - w / 2.0, y - h / 2.0],
+                                [x + w / 2.0, y + h / 2.0],
+                            ));
+                        }
+
+                        match &mut self.map {
+                            Some(MapSource::Image(image)) => {
+                                let texture = self.map_image.get_or_insert_with(|| {
+                                    rgba_texture(ctx, "map_image", &image.image)
+                                });
+                                let (sw, ne) = image.bounds;
+                                plot_ui.image(map_image(texture, sw, ne));
+                            }
+                            Some(source) => {
+                                if self.map_tiles.len() > MAX_CACHED_TILES {
+                                    self.map_tiles.clear();
+                                }
+                                let bounds = plot_ui.plot_bounds();
+                                let pixels = plot_ui.response().rect.width().max(1.0) as f64;
+                                let zoom = map::zoom_for(bounds.width() / pixels)
+                                    .min(source.max_zoom().unwrap_or(0));
+                                let (min, max) = (bounds.min(), bounds.max());
+                                let (x0, y0) = map::tile_at(zoom, [min[0], max[1]]);
+                                let (x1, y1) = map::tile_at(zoom, [max[0], min[1]]);
+                                let mut loads = 0;
+                                for x in x0..=x1 {
+                                    for y in y0..=y1 {
+                                        let texture = match self.map_tiles.entry((zoom, x, y)) {
+                                            Entry::Occupied(entry) => entry.into_mut(),
+                                            Entry::Vacant(_)
+                                                if loads == MAX_TILE_LOADS_PER_FRAME =>
+                                            {
+                                                continue;
+                                            }
+                                            Entry::Vacant(entry) => {
+                                                loads += 1;
+                                                // Tiles that fail to decode are left blank.
+                                                let tile = source.tile(zoom, x, y).ok().flatten();
+                                                entry.insert(tile.map(|tile| {
+                                                    rgba_texture(
+                                                        ctx,
+                                                        &format!("map_tile_{}_{}_{}", zoom, x, y),
+                                                        &tile,
+                                                    )
+                                                }))
+                                            }
+                                        };
+                                        if let Some(texture) = texture {
+                                            let (sw, ne) = map::tile_bounds(zoom, x, y);
+                                            plot_ui.image(map_image(texture, sw, ne));
+                                        }
+                                    }
+                                }
+                                if loads == MAX_TILE_LOADS_PER_FRAME {
+                                    ctx.request_repaint();
+                                }
+                            }
+                            None => {}
+                        }
+
+                        for fence in &self.navigator.geofences {
+                            let outline = match &fence.shape {
+                                FenceShape::Circle {
+                                    latitude,
+                                    longitude,
+                                    radius,
+                                } => map::circle(*latitude, *longitude, *radius),
+                                FenceShape::Polygon(vertices) => vertices
+                                    .iter()
+                                    .map(|&(lat, lon)| map::mercator(lat, lon))
+                                    .collect(),
+                            };
+                            let color = match fence.inside {
+                                Some(true) => egui::Color32::RED,
+                                _ => egui::Color32::from_rgb(255, 165, 0),
+                            };
+                            plot_ui.polygon(
+                                egui_plot::Polygon::new(PlotPoints::from(outline))
+                                    .name(&fence.name)
+                                    .stroke(egui::Stroke::new(2.0, color)),
+                            );
+                        }
+
+                        let waypoints: Vec<[f64; 2]> = self
+                            .navigator
+                            .waypoints
+                            .iter()
+                            .map(|w| map::mercator(w.latitude, w.longitude))
+                            .collect();
+                        for (waypoint, &[x, y]) in self.navigator.waypoints.iter().zip(&waypoints) {
+                            plot_ui.text(
+                                egui_plot::Text::new(
+                                    egui_plot::PlotPoint::new(x, y),
+                                    format!("  {}", waypoint.name),
+                                )
+                                .anchor(egui::Align2::LEFT_CENTER),
+                            );
+                        }
+                        plot_ui.points(
+                            egui_plot::Points::new(PlotPoints::from(waypoints))
+                                .radius(4.0)
+                                .shape(egui_plot::MarkerShape::Diamond)
+                                .name("Waypoints"),
+                        );
+
+                        let track: Vec<[f64; 2]> = state
+                            .track
+                            .iter()
+                            .map(|p| map::mercator(p[0], p[1]))
+                            .collect();
+                        plot_ui.line(Line::new(PlotPoints::from(track)).name("Track"));
+
+                        if let (Some(fix), Some(p)) = (fix, position) {
+                            if let Some(h_acc) = fix.h_acc {
+                                plot_ui.polygon(
+                                    egui_plot::Polygon::new(PlotPoints::from(map::circle(
+                                        fix.latitude,
+                                        fix.longitude,
+                                        h_acc,
+                                    )))
+                                    .name("Accuracy"),
+                                );
+                            }
+                            if let Some(error) = &state.position_error {
+                                plot_ui.polygon(
+                                    egui_plot::Polygon::new(PlotPoints::from(map::ellipse(
+                                        fix.latitude,
+                                        fix.longitude,
+                                        &error.ellipse,
+                                        ELLIPSE_95,
+                                    )))
+                                    .name("Error ellipse (95%)"),
+                                );
+                            }
+                            plot_ui.points(
+                                egui_plot::Points::new(vec![p])
+                                    .radius(5.0)
+                                    .filled(true)
+                                    .name("Position"),
+                            );
+                        }
+                    });
+            });
+        self.show_map_window = open;
+
+        if load {
+            self.load_map();
+        }
+    }
+}
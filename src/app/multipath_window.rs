@@ -0,0 +1,199 @@
+use eframe::egui;
+use egui_plot::{Plot, PlotPoints};
+
+use crate::gnss::SatelliteId;
+use crate::multipath::{self, Combination};
+use crate::time_scales::SECONDS_PER_WEEK;
+
+use super::GenCamGUI;
+
+impl GenCamGUI {
+    pub(super) fn ui_multipath_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_multipath_window;
+        let mut clear = false;
+        egui::Window::new("Multipath")
+            .open(&mut open)
+            .vscroll(true)
+            .show(ctx, |ui| {
+                let monitor = &self.receivers[self.active_receiver].state.multipath;
+                ui.horizontal(|ui| {
+                    ui.label(format!(
+                        "{} epochs, {} phase observations, {} cycle slips, o/slps {}",
+                        monitor.epochs,
+                        monitor.observations,
+                        monitor.slip_count,
+                        monitor
+                            .observations_per_slip()
+                            .map_or("-".to_string(), |r| format!("{:.0}", r))
+                    ));
+                    clear = ui.button("Clear").clicked();
+                });
+                if monitor.epochs == 0 {
+                    ui.label("No UBX-RXM-RAWX with carrier phase received.");
+                    return;
+                }
+
+                let satellites: Vec<SatelliteId> = monitor.signals.keys().copied().collect();
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_id_source("multipath_combination")
+                        .selected_text(self.multipath_combination.name())
+                        .show_ui(ui, |ui| {
+                            for combination in Combination::ALL {
+                                ui.selectable_value(
+                                    &mut self.multipath_combination,
+                                    combination,
+                                    combination.name(),
+                                );
+                            }
+                        });
+                    egui::ComboBox::from_id_source("multipath_satellite")
+                        .selected_text(
+                            self.multipath_satellite
+                                .map_or("All satellites".to_string(), |id| id.to_string()),
+                        )
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(
+                                &mut self.multipath_satellite,
+                                None,
+                                "All satellites",
+                            );
+                            for id in &satellites {
+                                ui.selectable_value(
+                                    &mut self.multipath_satellite,
+                                    Some(*id),
+                                    id.to_string(),
+                                );
+                            }
+                        });
+                    ui.radio_value(&mut self.multipath_by_elevation, false, "Time");
+                    ui.radio_value(&mut self.multipath_by_elevation, true, "Elevation");
+                });
+
+                let combination = self.multipath_combination;
+                let selected = self.multipath_satellite;
+                let by_elevation = self.multipath_by_elevation;
+                let shown = |id: &SatelliteId| selected.map_or(true, |s| s == *id);
+                let start = monitor.start.unwrap_or_default();
+                let x = |time: f64, elevation: Option<f64>| match by_elevation {
+                    true => elevation,
+                    false => Some((time - start) / 60.0),
+                };
+                Plot::new("multipath_plot")
+                    .height(240.0)
+                    .legend(egui_plot::Legend::default())
+                    .x_axis_label(match by_elevation {
+                        true => "Elevation (°)",
+                        false => "Time since the first epoch (min)",
+                    })
+                    .y_axis_label(format!("{} (m)", combination.name()))
+                    .show(ui, |plot_ui| {
+                        for ((id, c), series) in &monitor.series {
+                            if *c != combination || !shown(id) {
+                                continue;
+                            }
+                            let points: Vec<[f64; 2]> = series
+                                .levelled()
+                                .iter()
+                                .filter_map(|s| Some([x(s.time, s.elevation)?, s.value]))
+                                .collect();
+                            plot_ui.points(
+                                egui_plot::Points::new(PlotPoints::from(points))
+                                    .radius(1.5)
+                                    .name(id.to_string()),
+                            );
+                        }
+                        let slips: Vec<[f64; 2]> = monitor
+                            .slips
+                            .iter()
+                            .filter(|s| shown(&s.id))
+                            .filter_map(|s| Some([x(s.time, s.elevation)?, 0.0]))
+                            .collect();
+                        plot_ui.points(
+                            egui_plot::Points::new(PlotPoints::from(slips))
+                                .shape(egui_plot::MarkerShape::Cross)
+                                .radius(5.0)
+                                .color(egui::Color32::RED)
+                                .name("Cycle slips"),
+                        );
+                    });
+                ui.label(
+                    "Each arc between slips and gaps has its mean removed; code minus carrier \
+                     also keeps twice the ionospheric change.",
+                );
+
+                ui.collapsing("Satellites", |ui| {
+                    egui::Grid::new("multipath_satellites")
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for title in ["Satellite", "Signals", "Epochs", "MP1", "MP2", "Slips"] {
+                                ui.strong(title);
+                            }
+                            ui.end_row();
+                            for (id, pair) in &monitor.signals {
+                                let name = |sig_id: u8| {
+                                    multipath::signal(id.constellation, sig_id, 7)
+                                        .map_or("?", |(name, _)| name)
+                                };
+                                let rms = |c: Combination| {
+                                    monitor
+                                        .series
+                                        .get(&(*id, c))
+                                        .and_then(|s| s.rms())
+                                        .map_or("-".to_string(), |r| format!("{:.2} m", r))
+                                };
+                                ui.label(id.to_string());
+                                ui.label(match pair.second {
+                                    Some(second) => {
+                                        format!("{} + {}", name(pair.first), name(second))
+                                    }
+                                    None => name(pair.first).to_string(),
+                                });
+                                ui.label(
+                                    monitor
+                                        .series
+                                        .get(&(*id, Combination::CodeMinusCarrier))
+                                        .map_or(0, |s| s.len())
+                                        .to_string(),
+                                );
+                                ui.label(rms(Combination::Mp1));
+                                ui.label(rms(Combination::Mp2));
+                                ui.label(monitor.slip_count_of(*id).to_string());
+                                ui.end_row();
+                            }
+                        });
+                });
+
+                ui.collapsing("Cycle slips", |ui| {
+                    egui::Grid::new("multipath_slips")
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for title in ["TOW", "Satellite", "Signal", "Elevation", "Cause"] {
+                                ui.strong(title);
+                            }
+                            ui.end_row();
+                            for slip in monitor.slips.iter().rev().take(50) {
+                                ui.label(format!(
+                                    "{:.1} s",
+                                    slip.time.rem_euclid(SECONDS_PER_WEEK as f64)
+                                ));
+                                ui.label(slip.id.to_string());
+                                ui.label(
+                                    multipath::signal(slip.id.constellation, slip.sig_id, 7)
+                                        .map_or("?", |(name, _)| name),
+                                );
+                                ui.label(
+                                    slip.elevation
+                                        .map_or("-".to_string(), |e| format!("{:.0}°", e)),
+                                );
+                                ui.label(slip.cause.name());
+                                ui.end_row();
+                            }
+                        });
+                });
+            });
+        self.show_multipath_window = open;
+        if clear {
+            self.receivers[self.active_receiver].state.multipath.clear();
+        }
+    }
+}
@@ -0,0 +1,575 @@
+use std::io::prelude::*;
+use std::sync::mpsc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use eframe::egui;
+use egui_plot::{Plot, PlotPoints};
+use serde::{Deserialize, Serialize};
+
+use crate::datum::{self, Datum, Helmert};
+use crate::geodesy::{self, CoordinateFormat};
+use crate::geoid::GeoidGrid;
+use crate::gnss::{ErrorEllipse, SpeedUnit, ELLIPSE_95};
+
+use super::{DialogType, GenCamGUI, Modal};
+
+/// How the current fix is displayed, kept across sessions.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub(super) struct PositionSettings {
+    formats: Vec<CoordinateFormat>,
+    /// UTM zone to use instead of the standard zone of the position.
+    utm_zone: Option<u8>,
+    /// Origin of the local east/north/up coordinates: latitude and longitude in degrees,
+    /// ellipsoidal height in metres.
+    enu_reference: Option<(f64, f64, f64)>,
+    /// Geoid grid file, loaded at start-up when set.
+    pub(super) geoid_path: String,
+    /// Frame coordinates are shown and exported in.
+    datum: Datum,
+    /// Parameters from WGS84 used by `Datum::Custom`.
+    custom_datum: Helmert,
+    /// CSV file positions are appended to.
+    csv_path: String,
+    pub(super) speed_unit: SpeedUnit,
+}
+
+impl Default for PositionSettings {
+    fn default() -> Self {
+        Self {
+            formats: vec![CoordinateFormat::Dd, CoordinateFormat::Utm],
+            utm_zone: None,
+            enu_reference: None,
+            geoid_path: String::new(),
+            datum: Datum::Wgs84,
+            custom_datum: Helmert::default(),
+            csv_path: "positions.csv".into(),
+            speed_unit: SpeedUnit::KilometresPerHour,
+        }
+    }
+}
+
+/// Observed spread of (east, north) positions in metres: their mean, standard deviations and
+/// error ellipse. `None` for fewer than two positions.
+fn spread(points: &[[f64; 2]]) -> Option<([f64; 2], f64, f64, ErrorEllipse)> {
+    if points.len() < 2 {
+        return None;
+    }
+    let n = points.len() as f64;
+    let mean = [0, 1].map(|i| points.iter().map(|p| p[i]).sum::<f64>() / n);
+    let (mut ee, mut nn, mut en) = (0.0, 0.0, 0.0);
+    for [e, n] in points.iter().map(|p| [p[0] - mean[0], p[1] - mean[1]]) {
+        ee += e * e;
+        nn += n * n;
+        en += e * n;
+    }
+    let [ee, nn, en] = [ee, nn, en].map(|v| v / (n - 1.0));
+    Some((
+        mean,
+        ee.sqrt(),
+        nn.sqrt(),
+        ErrorEllipse::from_covariance(ee, nn, en),
+    ))
+}
+
+/// Difference (m) between the geoid model and the receiver's own separation worth pointing out.
+const GEOID_DISAGREEMENT: f64 = 1.0;
+
+impl GenCamGUI {
+    /// Starts loading the geoid grid on its own thread; grids like the 1' EGM2008 take
+    /// seconds to read.
+    pub(super) fn load_geoid(&mut self) {
+        let path = self.position_settings.geoid_path.clone();
+        let (sender, receiver) = mpsc::channel();
+        let file = path.clone();
+        let load = move || {
+            let _ = sender.send(GeoidGrid::load(std::path::Path::new(&file)));
+        };
+        // Targets without threads load it here instead.
+        if let Err(e) = std::thread::Builder::new().spawn(load.clone()) {
+            log::debug!("Loading the geoid grid in the foreground: {}", e);
+            load();
+        }
+        self.geoid_loading = Some((path, receiver));
+    }
+
+    /// Takes the geoid grid once the background load finishes.
+    pub(super) fn poll_geoid(&mut self, ctx: &egui::Context) {
+        let Some((path, receiver)) = &self.geoid_loading else {
+            return;
+        };
+        let result = match receiver.try_recv() {
+            Ok(result) => result,
+            Err(mpsc::TryRecvError::Empty) => {
+                ctx.request_repaint_after(Duration::from_millis(100));
+                return;
+            }
+            Err(mpsc::TryRecvError::Disconnected) => Err(std::io::Error::other("loading stopped")),
+        };
+        let path = path.clone();
+        self.geoid_loading = None;
+        match result {
+            Ok(grid) => self.geoid = Some(grid),
+            Err(e) => {
+                self.geoid = None;
+                self.dialog(
+                    DialogType::Error,
+                    &format!("Failed to load the geoid grid {}: {}", path, e),
+                );
+            }
+        }
+    }
+
+    /// Appends a row to the position CSV, starting the file with a header. When the file
+    /// has other columns, because the formats changed since it was started, the row goes to
+    /// a new numbered file instead, which becomes the CSV path.
+    fn record_position(&mut self, row: &str) -> std::io::Result<()> {
+        let settings = &mut self.position_settings;
+        let mut header = "time,datum,epoch,latitude,longitude,height".to_string();
+        for format in &settings.formats {
+            header.push(',');
+            header.push_str(format.name());
+        }
+
+        let path = std::path::PathBuf::from(&settings.csv_path);
+        let mut candidate = path.clone();
+        for n in 2.. {
+            let mut first = String::new();
+            let read = std::fs::File::open(&candidate)
+                .and_then(|f| std::io::BufReader::new(f).read_line(&mut first));
+            match read {
+                Ok(len) if len > 0 && first.trim_end() != header => {
+                    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+                    let name = match path.extension() {
+                        Some(extension) => {
+                            format!("{}_{}.{}", stem, n, extension.to_string_lossy())
+                        }
+                        None => format!("{}_{}", stem, n),
+                    };
+                    candidate = path.with_file_name(name);
+                }
+                Ok(_) => break,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => break,
+                Err(e) => return Err(e),
+            }
+        }
+        if candidate != path {
+            settings.csv_path = candidate.to_string_lossy().into_owned();
+            let message = format!(
+                "The columns changed; recording to {} instead.",
+                settings.csv_path
+            );
+            self.notify(DialogType::Info, &message);
+        }
+
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&candidate)?;
+        if file.metadata()?.len() == 0 {
+            writeln!(file, "{}", header)?;
+        }
+        writeln!(file, "{}", row)
+    }
+
+    pub(super) fn ui_position_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_position_window;
+        let mut load_geoid = false;
+        let mut record = None;
+        egui::Window::new("Position")
+            .open(&mut open)
+            .vscroll(true)
+            .show(ctx, |ui| {
+                let state = &self.receivers[self.active_receiver].state;
+                let settings = &mut self.position_settings;
+
+                match state.fix.as_ref().filter(|f| f.fix_type.has_position()) {
+                    Some(fix) => {
+                        ui.label(format!(
+                            "Fix: {}, {}, {} satellites",
+                            fix.fix_type.name(),
+                            state.rtk.name(),
+                            fix.num_sv
+                        ));
+                        // Plate motion is taken at the receiver's time, not the host clock's.
+                        let (secs, nanos) = state.now(Utc::now()).utc_unix(state.leap.gps_utc);
+                        let time = DateTime::from_timestamp(secs, nanos).unwrap_or_else(Utc::now);
+                        let epoch = datum::decimal_year(time);
+                        let (latitude, longitude, height) = settings.datum.from_wgs84(
+                            fix.latitude,
+                            fix.longitude,
+                            fix.height.unwrap_or(0.0),
+                            epoch,
+                            &settings.custom_datum,
+                        );
+                        let height = fix.height.map(|_| height);
+                        if settings.datum != Datum::Wgs84 {
+                            ui.label(format!(
+                                "Datum: {} at epoch {:.3}",
+                                settings.datum.name(),
+                                epoch
+                            ));
+                        }
+                        egui::Grid::new("position_grid")
+                            .num_columns(2)
+                            .striped(true)
+                            .show(ui, |ui| {
+                                for format in &settings.formats {
+                                    ui.label(format.name());
+                                    ui.monospace(format.format(
+                                        latitude,
+                                        longitude,
+                                        settings.utm_zone,
+                                    ));
+                                    ui.end_row();
+                                }
+                                let metres = |v: Option<f64>| {
+                                    v.map_or("-".to_string(), |v| format!("{:.3} m", v))
+                                };
+                                ui.label("Ellipsoidal height");
+                                ui.label(metres(height));
+                                ui.end_row();
+                                ui.label("Height above MSL");
+                                ui.label(metres(fix.height_msl));
+                                ui.end_row();
+                                let undulation = self
+                                    .geoid
+                                    .as_ref()
+                                    .and_then(|g| g.undulation(fix.latitude, fix.longitude));
+                                if let (Some(geoid), Some(n)) = (&self.geoid, undulation) {
+                                    ui.label(format!("Orthometric height ({})", geoid.name));
+                                    ui.label(metres(fix.height.map(|h| h - n)));
+                                    ui.end_row();
+                                    ui.label("Geoid undulation");
+                                    ui.label(format!("{:.3} m", n));
+                                    ui.end_row();
+                                    let receiver = fix.height.zip(fix.height_msl).map(|(h, msl)| h - msl);
+                                    if let Some(receiver) = receiver {
+                                        ui.label("Receiver separation");
+                                        let text = format!("{:.3} m", receiver);
+                                        if (receiver - n).abs() > GEOID_DISAGREEMENT {
+                                            ui.colored_label(
+                                                egui::Color32::YELLOW,
+                                                format!(
+                                                    "{} ({:+.2} m from the model, the receiver may use a coarser geoid)",
+                                                    text,
+                                                    receiver - n
+                                                ),
+                                            );
+                                        } else {
+                                            ui.label(text);
+                                        }
+                                        ui.end_row();
+                                    }
+                                }
+                                ui.label("Accuracy (H/V)");
+                                ui.label(format!("{} / {}", metres(fix.h_acc), metres(fix.v_acc)));
+                                ui.end_row();
+                                if let Some(error) = &state.position_error {
+                                    ui.label("Sigma (E/N/U)");
+                                    ui.label(format!(
+                                        "{:.3} / {:.3} / {}",
+                                        error.east,
+                                        error.north,
+                                        metres(error.up)
+                                    ));
+                                    ui.end_row();
+                                    let ellipse = &error.ellipse;
+                                    ui.label("Error ellipse (1σ)");
+                                    ui.label(format!(
+                                        "{:.3} × {:.3} m, major axis at {:.1}°",
+                                        ellipse.semi_major,
+                                        ellipse.semi_minor,
+                                        ellipse.orientation
+                                    ));
+                                    ui.end_row();
+                                }
+
+                                if let (Some(height), Some(wgs84_height)) = (height, fix.height) {
+                                    let ecef =
+                                        geodesy::geodetic_to_ecef(latitude, longitude, height);
+                                    ui.label("ECEF");
+                                    ui.monospace(format!(
+                                        "X {:.3} m, Y {:.3} m, Z {:.3} m",
+                                        ecef[0], ecef[1], ecef[2]
+                                    ));
+                                    ui.end_row();
+                                    // The origin is stored in WGS84, like the fix.
+                                    if let Some(reference) = settings.enu_reference {
+                                        let ecef = geodesy::geodetic_to_ecef(
+                                            fix.latitude,
+                                            fix.longitude,
+                                            wgs84_height,
+                                        );
+                                        let [e, n, u] = geodesy::ecef_to_enu(ecef, reference);
+                                        ui.label("ENU");
+                                        ui.monospace(format!(
+                                            "E {:.3} m, N {:.3} m, U {:.3} m",
+                                            e, n, u
+                                        ));
+                                        ui.end_row();
+                                    }
+                                }
+                            });
+
+                        ui.horizontal(|ui| {
+                            ui.label("CSV file:");
+                            ui.text_edit_singleline(&mut settings.csv_path);
+                            if ui.button("Record").clicked() {
+                                let mut row = format!(
+                                    "{},{},{:.3},{:.9},{:.9},{}",
+                                    time.to_rfc3339(),
+                                    settings.datum.name(),
+                                    epoch,
+                                    latitude,
+                                    longitude,
+                                    height.map_or(String::new(), |h| format!("{:.4}", h))
+                                );
+                                for format in &settings.formats {
+                                    // Quoted, as the formats contain commas and quotes.
+                                    let text = format
+                                        .format(latitude, longitude, settings.utm_zone)
+                                        .replace('"', "\"\"");
+                                    row.push_str(&format!(",\"{}\"", text));
+                                }
+                                record = Some(row);
+                            }
+                        });
+
+                        ui.collapsing("Scatter", |ui| {
+                            // Local east/north of the recent positions, about their mean.
+                            let Some(&[lat0, lon0]) = state.track.back() else {
+                                return;
+                            };
+                            let local = |lat: f64, lon: f64| {
+                                let ecef = geodesy::geodetic_to_ecef(lat, lon, 0.0);
+                                let [e, n, _] = geodesy::ecef_to_enu(ecef, (lat0, lon0, 0.0));
+                                [e, n]
+                            };
+                            let points: Vec<[f64; 2]> =
+                                state.track.iter().map(|p| local(p[0], p[1])).collect();
+                            let Some((mean, sigma_e, sigma_n, observed)) = spread(&points) else {
+                                ui.label("Needs at least two positions.");
+                                return;
+                            };
+                            ui.label(format!(
+                                "Observed sigma (E/N) over {} positions: {:.3} / {:.3} m",
+                                points.len(),
+                                sigma_e,
+                                sigma_n
+                            ));
+                            if let Some(error) = &state.position_error {
+                                ui.label(format!(
+                                    "Reported sigma (E/N): {:.3} / {:.3} m",
+                                    error.east, error.north
+                                ));
+                            }
+                            let current = local(fix.latitude, fix.longitude);
+                            let around = |center: [f64; 2], e: &ErrorEllipse, scale: f64| {
+                                let outline: Vec<[f64; 2]> = e
+                                    .outline(scale)
+                                    .into_iter()
+                                    .map(|[x, y]| [center[0] + x, center[1] + y])
+                                    .collect();
+                                egui_plot::Polygon::new(PlotPoints::from(outline))
+                            };
+                            Plot::new("scatter_plot")
+                                .height(250.0)
+                                .data_aspect(1.0)
+                                .x_axis_label("East (m)")
+                                .y_axis_label("North (m)")
+                                .legend(egui_plot::Legend::default())
+                                .show(ui, |plot_ui| {
+                                    plot_ui.points(
+                                        egui_plot::Points::new(PlotPoints::from(points))
+                                            .radius(1.5)
+                                            .name("Positions"),
+                                    );
+                                    plot_ui.polygon(around(mean, &observed, 1.0).name("Observed 1σ"));
+                                    if let Some(error) = &state.position_error {
+                                        plot_ui.polygon(
+                                            around(current, &error.ellipse, 1.0).name("Reported 1σ"),
+                                        );
+                                        plot_ui.polygon(
+                                            around(current, &error.ellipse, ELLIPSE_95)
+                                                .name("Reported 95%"),
+                                        );
+                                    }
+                                    plot_ui.points(
+                                        egui_plot::Points::new(vec![current])
+                                            .radius(4.0)
+                                            .filled(true)
+                                            .name("Current"),
+                                    );
+                                });
+                        });
+                    }
+                    None => {
+                        ui.label("No position fix.");
+                    }
+                }
+
+                ui.collapsing("Display", |ui| {
+                    egui::ComboBox::from_label("Datum")
+                        .selected_text(settings.datum.name())
+                        .show_ui(ui, |ui| {
+                            for datum in Datum::ALL {
+                                ui.selectable_value(&mut settings.datum, datum, datum.name());
+                            }
+                        });
+                    if settings.datum == Datum::Custom {
+                        let custom = &mut settings.custom_datum;
+                        egui::Grid::new("custom_datum_grid")
+                            .num_columns(5)
+                            .show(ui, |ui| {
+                                ui.label("");
+                                for axis in ["X", "Y", "Z"] {
+                                    ui.label(axis);
+                                }
+                                ui.label("Scale (ppb)");
+                                ui.end_row();
+                                ui.label("Translation (mm)");
+                                for t in &mut custom.t {
+                                    ui.add(egui::DragValue::new(t).speed(0.1));
+                                }
+                                ui.add(egui::DragValue::new(&mut custom.d).speed(0.01));
+                                ui.end_row();
+                                ui.label("Rotation (mas)");
+                                for r in &mut custom.r {
+                                    ui.add(egui::DragValue::new(r).speed(0.01));
+                                }
+                                ui.end_row();
+                                ui.label("Rates per year");
+                                for t in &mut custom.t_rate {
+                                    ui.add(egui::DragValue::new(t).speed(0.01));
+                                }
+                                ui.add(egui::DragValue::new(&mut custom.d_rate).speed(0.001));
+                                ui.end_row();
+                                ui.label("");
+                                for r in &mut custom.r_rate {
+                                    ui.add(egui::DragValue::new(r).speed(0.001));
+                                }
+                                ui.end_row();
+                                ui.label("Reference epoch");
+                                ui.add(egui::DragValue::new(&mut custom.epoch).speed(0.1));
+                                ui.end_row();
+                            });
+                        ui.label("Position vector convention, from WGS84. Leave the rates at zero for a 7-parameter transformation.");
+                    }
+
+                    ui.horizontal(|ui| {
+                        for format in CoordinateFormat::ALL {
+                            let mut shown = settings.formats.contains(&format);
+                            if ui.checkbox(&mut shown, format.name()).changed() {
+                                settings.formats.retain(|f| *f != format);
+                                if shown {
+                                    settings.formats.push(format);
+                                    settings.formats.sort_by_key(|f| {
+                                        CoordinateFormat::ALL.iter().position(|a| a == f)
+                                    });
+                                }
+                            }
+                        }
+                    });
+
+                    ui.horizontal(|ui| {
+                        let mut override_zone = settings.utm_zone.is_some();
+                        ui.checkbox(&mut override_zone, "UTM zone override");
+                        match (override_zone, settings.utm_zone) {
+                            (true, None) => {
+                                let zone = state
+                                    .fix
+                                    .as_ref()
+                                    .map_or(31, |f| geodesy::Utm::zone_of(f.latitude, f.longitude));
+                                settings.utm_zone = Some(zone);
+                            }
+                            (false, Some(_)) => settings.utm_zone = None,
+                            _ => {}
+                        }
+                        if let Some(zone) = &mut settings.utm_zone {
+                            ui.add(egui::DragValue::new(zone).range(1..=60));
+                        }
+                    });
+
+                    ui.horizontal(|ui| {
+                        ui.label(match settings.enu_reference {
+                            Some((lat, lon, h)) => {
+                                format!("ENU origin: {:.8}°, {:.8}°, {:.3} m", lat, lon, h)
+                            }
+                            None => "ENU origin: not set".to_string(),
+                        });
+                    });
+                    ui.horizontal(|ui| {
+                        let current = state
+                            .fix
+                            .as_ref()
+                            .filter(|f| f.fix_type.has_position())
+                            .and_then(|f| Some((f.latitude, f.longitude, f.height?)));
+                        if ui
+                            .add_enabled(
+                                current.is_some(),
+                                egui::Button::new("Use current position"),
+                            )
+                            .clicked()
+                        {
+                            settings.enu_reference = current;
+                        }
+                        egui::ComboBox::from_id_source("enu_site")
+                            .selected_text("Use site")
+                            .show_ui(ui, |ui| {
+                                for site in &self.sites {
+                                    if ui.selectable_label(false, &site.name).clicked() {
+                                        settings.enu_reference =
+                                            Some((site.latitude, site.longitude, site.height));
+                                    }
+                                }
+                            });
+                        if ui.button("Clear").clicked() {
+                            settings.enu_reference = None;
+                        }
+                    });
+
+                    ui.horizontal(|ui| {
+                        ui.label("Geoid grid:");
+                        ui.text_edit_singleline(&mut settings.geoid_path)
+                            .on_hover_text(
+                                "GeographicLib .pgm (e.g. egm2008-5.pgm) or NGA .GRD (e.g. \
+                                 WW15MGH.GRD). egm2008-1.pgm is more detailed but takes about \
+                                 470 MB of memory.",
+                            );
+                        load_geoid = ui
+                            .add_enabled(self.geoid_loading.is_none(), egui::Button::new("Load"))
+                            .clicked();
+                    });
+                    match (&self.geoid_loading, &self.geoid) {
+                        (Some((path, _)), _) => {
+                            ui.horizontal(|ui| {
+                                ui.spinner();
+                                ui.label(format!("Loading {}...", path));
+                            });
+                        }
+                        (None, Some(geoid)) => {
+                            ui.label(format!("Loaded {}", geoid.name));
+                        }
+                        (None, None) => {
+                            ui.label("No geoid grid loaded.");
+                        }
+                    }
+                });
+            });
+        self.show_position_window = open;
+
+        if load_geoid {
+            self.load_geoid();
+        }
+        if let Some(row) = record {
+            if let Err(e) = self.record_position(&row) {
+                self.dialog(
+                    DialogType::Error,
+                    &format!("Failed to write {}: {}", self.position_settings.csv_path, e),
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use eframe::egui;
+
+use crate::ubx;
+
+use super::{DialogType, GenCamGUI, Modal};
+
+impl GenCamGUI {
+    pub(super) fn ui_receiver_info_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_receiver_info_window;
+        let mut query = false;
+        egui::Window::new("Receiver Information")
+            .open(&mut open)
+            .vscroll(true)
+            .show(ctx, |ui| {
+                query = ui
+                    .add_enabled(
+                        self.receivers[self.active_receiver].is_connected(),
+                        egui::Button::new("Query"),
+                    )
+                    .on_hover_text(
+                        "Poll UBX MON-VER, MON-HW and MON-HW3, and MON-RF for the antenna \
+                         status of receivers that no longer report it in MON-HW.",
+                    )
+                    .clicked();
+
+                let info = &self.receivers[self.active_receiver].state.info;
+                let text = |value: &Option<String>| value.clone().unwrap_or("-".to_string());
+                let flag = |value: Option<bool>| match value {
+                    Some(true) => "Yes",
+                    Some(false) => "No",
+                    None => "-",
+                };
+                egui::Grid::new("receiver_info_grid")
+                    .num_columns(2)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.label("Module");
+                        ui.label(text(&info.module));
+                        ui.end_row();
+                        ui.label("Software");
+                        ui.label(text(&info.software));
+                        ui.end_row();
+                        ui.label("Hardware");
+                        ui.label(text(&info.hardware));
+                        ui.end_row();
+                        ui.label("Firmware");
+                        ui.label(text(&info.firmware));
+                        ui.end_row();
+                        ui.label("Protocol");
+                        ui.label(text(&info.protocol));
+                        ui.end_row();
+                        ui.label("Constellations");
+                        ui.label(match info.constellations.is_empty() {
+                            true => "-".to_string(),
+                            false => info.constellations.join(", "),
+                        });
+                        ui.end_row();
+                        ui.label("Antenna status");
+                        let status = text(&info.antenna_status);
+                        let upper = status.to_ascii_uppercase();
+                        let color = match upper.as_str() {
+                            "OK" => egui::Color32::GREEN,
+                            _ if upper.contains("SHORT") || upper.contains("OPEN") => {
+                                egui::Color32::RED
+                            }
+                            _ => ui.visuals().text_color(),
+                        };
+                        ui.colored_label(color, status);
+                        ui.end_row();
+                        ui.label("Antenna power (LNA)");
+                        ui.label(text(&info.antenna_power));
+                        ui.end_row();
+                        ui.label("RTC calibrated");
+                        ui.label(flag(info.rtc_calibrated));
+                        ui.end_row();
+                        ui.label("Safe boot");
+                        ui.label(flag(info.safe_boot));
+                        ui.end_row();
+                        ui.label("Crystal absent");
+                        ui.label(flag(info.xtal_absent));
+                        ui.end_row();
+                    });
+
+                if !info.extensions.is_empty() {
+                    ui.separator();
+                    ui.label("Extensions");
+                    for extension in &info.extensions {
+                        ui.monospace(extension);
+                    }
+                }
+            });
+        self.show_receiver_info_window = open;
+
+        if query {
+            let mut bytes = ubx::UbxFrame::new(ubx::CLASS_MON, ubx::ID_MON_VER, vec![]).to_bytes();
+            for id in [ubx::ID_MON_HW, ubx::ID_MON_HW3, ubx::ID_MON_RF] {
+                bytes.extend(ubx::UbxFrame::new(ubx::CLASS_MON, id, vec![]).to_bytes());
+            }
+            if let Err(e) = self.receivers[self.active_receiver].send(&bytes) {
+                self.dialog(
+                    DialogType::Error,
+                    &format!("Failed to query the receiver: {}", e),
+                );
+            }
+        }
+    }
+}
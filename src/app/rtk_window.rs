@@ -0,0 +1,130 @@
+use chrono::Local;
+use eframe::egui;
+use egui_plot::{Line, Plot, PlotPoints};
+
+use crate::gnss::RtkStatus;
+
+use super::GenCamGUI;
+
+impl GenCamGUI {
+    pub(super) fn ui_rtk_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_rtk_window;
+        egui::Window::new("RTK")
+            .open(&mut open)
+            .vscroll(true)
+            .show(ctx, |ui| {
+                let state = &self.receivers[self.active_receiver].state;
+                let color = match state.rtk {
+                    RtkStatus::Fixed => egui::Color32::GREEN,
+                    RtkStatus::Float => egui::Color32::YELLOW,
+                    RtkStatus::Dgnss => egui::Color32::LIGHT_BLUE,
+                    RtkStatus::None => ui.visuals().text_color(),
+                };
+                ui.horizontal(|ui| {
+                    ui.label("Status:");
+                    ui.colored_label(color, state.rtk.name());
+                });
+                ui.label(match state.correction_age {
+                    Some(age) if age.is_infinite() => "Correction age: over 120 s".to_string(),
+                    Some(age) => format!("Correction age: {:.1} s", age),
+                    None => "Correction age: -".to_string(),
+                });
+
+                ui.separator();
+                match &state.relpos {
+                    Some(relpos) => {
+                        ui.label(format!(
+                            "Reference station {}{}",
+                            relpos.ref_station_id,
+                            if relpos.is_moving() {
+                                " (moving base)"
+                            } else {
+                                ""
+                            }
+                        ));
+                        if !relpos.rel_pos_valid() {
+                            ui.colored_label(egui::Color32::RED, "Relative position not valid.");
+                        }
+                        if relpos.ref_pos_missing() || relpos.ref_obs_missing() {
+                            ui.colored_label(
+                                egui::Color32::YELLOW,
+                                "Reference position or observations missing, extrapolating.",
+                            );
+                        }
+                        egui::Grid::new("rtk_relpos_grid")
+                            .num_columns(2)
+                            .striped(true)
+                            .show(ui, |ui| {
+                                ui.label("Baseline");
+                                ui.label(match relpos.acc_length {
+                                    Some(acc) => {
+                                        format!("{:.4} m ± {:.4} m", relpos.baseline(), acc)
+                                    }
+                                    None => format!("{:.4} m", relpos.baseline()),
+                                });
+                                ui.end_row();
+                                for (i, axis) in ["North", "East", "Down"].iter().enumerate() {
+                                    ui.label(*axis);
+                                    ui.label(format!(
+                                        "{:.4} m ± {:.4} m",
+                                        relpos.rel_pos[i], relpos.acc[i]
+                                    ));
+                                    ui.end_row();
+                                }
+                                ui.label("Heading");
+                                ui.label(match (relpos.heading, relpos.acc_heading) {
+                                    (Some(heading), Some(acc)) if relpos.heading_valid() => {
+                                        format!("{:.2}° ± {:.2}°", heading, acc)
+                                    }
+                                    _ => "-".to_string(),
+                                });
+                                ui.end_row();
+                            });
+                    }
+                    None => {
+                        ui.label("No NAV-RELPOSNED received.");
+                    }
+                }
+
+                ui.separator();
+                let points: Vec<[f64; 2]> = state.rtk_history.iter().copied().collect();
+                Plot::new("rtk_status_plot")
+                    .height(150.0)
+                    .x_axis_label("Time (s)")
+                    .include_y(0.0)
+                    .include_y(3.0)
+                    .y_axis_formatter(|mark, _| {
+                        match mark.value >= 0.0 && mark.value.fract() == 0.0 {
+                            true => RtkStatus::ALL
+                                .get(mark.value as usize)
+                                .map_or(String::new(), |s| s.name().to_string()),
+                            false => String::new(),
+                        }
+                    })
+                    .show(ui, |plot_ui| {
+                        plot_ui.line(Line::new(PlotPoints::from(points)));
+                    });
+
+                egui::Grid::new("rtk_transitions_grid")
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for transition in state.rtk_transitions.iter().rev() {
+                            ui.label(
+                                transition
+                                    .time
+                                    .with_timezone(&Local)
+                                    .format("%Y-%m-%d %H:%M:%S")
+                                    .to_string(),
+                            );
+                            ui.label(format!(
+                                "{} → {}",
+                                transition.from.name(),
+                                transition.to.name()
+                            ));
+                            ui.end_row();
+                        }
+                    });
+            });
+        self.show_rtk_window = open;
+    }
+}
@@ -0,0 +1,137 @@
+use eframe::egui;
+
+use crate::gnss::{Constellation, Satellite};
+use crate::sbas::SbasProvider;
+
+use super::GenCamGUI;
+
+impl GenCamGUI {
+    pub(super) fn ui_sbas_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_sbas_window;
+        egui::Window::new("SBAS")
+            .open(&mut open)
+            .vscroll(true)
+            .show(ctx, |ui| {
+                let state = &self.receivers[self.active_receiver].state;
+                let providers = state.sbas_providers();
+                egui::Grid::new("sbas_status")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        ui.label("Provider");
+                        ui.label(match providers.is_empty() {
+                            true => "None".to_string(),
+                            false => providers
+                                .iter()
+                                .map(|p| format!("{} ({})", p.name(), p.region()))
+                                .collect::<Vec<_>>()
+                                .join(", "),
+                        });
+                        ui.end_row();
+                        ui.label("Fix");
+                        match state.sbas_aided() {
+                            Some(true) => ui.colored_label(egui::Color32::GREEN, "SBAS-aided"),
+                            Some(false) => ui.label("Not SBAS-aided"),
+                            None => ui.label("Differential, source unknown"),
+                        };
+                        ui.end_row();
+                        if let Some(sbas) = &state.nav_sbas {
+                            ui.label("Satellite in use");
+                            ui.label(match sbas.geo {
+                                0 => "-".to_string(),
+                                prn => format!("PRN {}", prn),
+                            });
+                            ui.end_row();
+                            ui.label("Mode");
+                            ui.label(match (sbas.enabled(), sbas.test_mode()) {
+                                (false, _) => "Disabled",
+                                (true, true) => "Enabled, test mode",
+                                (true, false) => "Enabled",
+                            });
+                            ui.end_row();
+                            ui.label("Services");
+                            ui.label(sbas.services.summary());
+                            ui.end_row();
+                            ui.label("Integrity");
+                            ui.label(match sbas.integrity_used() {
+                                Some(true) => "Used",
+                                Some(false) => "Not available or not used",
+                                None => "Unknown",
+                            });
+                            ui.end_row();
+                        }
+                    });
+
+                let tracked: Vec<&Satellite> = state
+                    .satellites
+                    .iter()
+                    .filter(|s| s.id.constellation == Constellation::Sbas)
+                    .collect();
+                ui.collapsing("SBAS satellites", |ui| {
+                    if tracked.is_empty() {
+                        ui.label("No SBAS satellites in view.");
+                        return;
+                    }
+                    egui::Grid::new("sbas_satellites")
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for title in ["PRN", "Provider", "C/N0", "Elevation", "Azimuth"] {
+                                ui.strong(title);
+                            }
+                            ui.end_row();
+                            for sat in tracked {
+                                let angle = |v: Option<f32>| {
+                                    v.map_or("-".to_string(), |v| format!("{:.0}°", v))
+                                };
+                                ui.label(sat.id.prn.to_string());
+                                ui.label(
+                                    SbasProvider::from_prn(sat.id.prn).map_or("?", |p| p.name()),
+                                );
+                                ui.label(match sat.cno {
+                                    0 => "-".to_string(),
+                                    cno => format!("{} dB-Hz", cno),
+                                });
+                                ui.label(angle(sat.elevation));
+                                ui.label(angle(sat.azimuth));
+                                ui.end_row();
+                            }
+                        });
+                });
+
+                ui.collapsing("Corrections (NAV-SBAS)", |ui| {
+                    let Some(sbas) = &state.nav_sbas else {
+                        ui.label("No NAV-SBAS received.");
+                        return;
+                    };
+                    egui::Grid::new("sbas_corrections")
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for title in
+                                ["Satellite", "UDRE", "Services", "Range", "Iono", "Applied"]
+                            {
+                                ui.strong(title);
+                            }
+                            ui.end_row();
+                            for sv in &sbas.satellites {
+                                let udre = sv.udre();
+                                ui.label(sv.id.to_string());
+                                match udre.usable() {
+                                    true => ui.label(udre.name()),
+                                    false => {
+                                        ui.colored_label(ui.visuals().warn_fg_color, udre.name())
+                                    }
+                                };
+                                ui.label(sv.services.summary());
+                                ui.label(format!("{:.2} m", sv.prc));
+                                ui.label(format!("{:.2} m", sv.ic));
+                                ui.label(match state.sbas_corrected.contains(&sv.id) {
+                                    true => "Yes",
+                                    false => "-",
+                                });
+                                ui.end_row();
+                            }
+                        });
+                });
+            });
+        self.show_sbas_window = open;
+    }
+}
@@ -0,0 +1,86 @@
+use eframe::egui;
+use egui_plot::{Line, Plot, PlotPoints};
+
+use crate::spectrum::BAND_MARKERS;
+
+use super::GenCamGUI;
+
+impl GenCamGUI {
+    pub(super) fn ui_spectrum_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_spectrum_window;
+        egui::Window::new("RF Spectrum")
+            .open(&mut open)
+            .vscroll(true)
+            .show(ctx, |ui| {
+                if self.receivers[self.active_receiver].state.spectrum.is_empty() {
+                    ui.label("No MON-SPAN received.");
+                    return;
+                }
+
+                for (block, trace) in self.receivers[self.active_receiver].state.spectrum.iter_mut() {
+                    ui.horizontal(|ui| {
+                        ui.label(format!(
+                            "RF block {}: centre {:.3} MHz, span {:.1} MHz, resolution {:.1} kHz, PGA {} dB, {} sweeps",
+                            block,
+                            trace.center_hz * 1e-6,
+                            trace.span_hz * 1e-6,
+                            trace.res_hz * 1e-3,
+                            trace.pga_db,
+                            trace.sweeps
+                        ));
+                        if ui
+                            .button("Reset")
+                            .on_hover_text("Restart the max-hold and average traces.")
+                            .clicked()
+                        {
+                            trace.reset();
+                        }
+                    });
+
+                    let mhz: Vec<f64> = trace.frequencies().iter().map(|f| f * 1e-6).collect();
+                    let series = |values: &[f64]| -> Vec<[f64; 2]> {
+                        mhz.iter().zip(values).map(|(f, v)| [*f, *v]).collect()
+                    };
+                    let (low, high) = (
+                        (trace.center_hz - trace.span_hz / 2.0) * 1e-6,
+                        (trace.center_hz + trace.span_hz / 2.0) * 1e-6,
+                    );
+                    let top = trace.max_hold.iter().copied().fold(0.0, f64::max) + 3.0;
+
+                    Plot::new(format!("spectrum_plot_{}", block))
+                        .height(220.0)
+                        .legend(egui_plot::Legend::default())
+                        .x_axis_label("Frequency (MHz)")
+                        .y_axis_label("Power (dB)")
+                        .show(ui, |plot_ui| {
+                            plot_ui.line(Line::new(PlotPoints::from(series(&trace.live))).name("Live"));
+                            plot_ui.line(
+                                Line::new(PlotPoints::from(series(&trace.max_hold))).name("Max hold"),
+                            );
+                            plot_ui.line(
+                                Line::new(PlotPoints::from(series(&trace.average))).name("Average"),
+                            );
+
+                            let centre = trace.center_hz * 1e-6;
+                            plot_ui.vline(egui_plot::VLine::new(centre).style(egui_plot::LineStyle::dotted_dense()));
+                            plot_ui.text(egui_plot::Text::new(
+                                egui_plot::PlotPoint::new(centre, top + 2.0),
+                                format!("{:.3} MHz", centre),
+                            ));
+                            for (label, hz) in BAND_MARKERS {
+                                let f = hz * 1e-6;
+                                if f < low || f > high {
+                                    continue;
+                                }
+                                plot_ui.vline(
+                                    egui_plot::VLine::new(f).style(egui_plot::LineStyle::dashed_loose()),
+                                );
+                                plot_ui.text(egui_plot::Text::new(egui_plot::PlotPoint::new(f, top), label));
+                            }
+                        });
+                    ui.separator();
+                }
+            });
+        self.show_spectrum_window = open;
+    }
+}
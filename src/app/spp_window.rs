@@ -0,0 +1,178 @@
+use chrono::Utc;
+use eframe::egui;
+
+use crate::ephemeris;
+use crate::geodesy;
+use crate::spp;
+use crate::time_scales::SECONDS_PER_WEEK;
+
+use super::{DialogType, GenCamGUI, Modal};
+
+impl GenCamGUI {
+    pub(super) fn ui_spp_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_spp_window;
+        let mut load = false;
+        egui::Window::new("Single Point Positioning")
+            .open(&mut open)
+            .vscroll(true)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("RINEX navigation file:");
+                    ui.text_edit_singleline(&mut self.rinex_nav_path);
+                    load = ui.button("Load").clicked();
+                });
+                let state = &self.receivers[self.active_receiver].state;
+                let nav = &state.navigation;
+                ui.label(format!(
+                    "Ephemerides for {} satellites, almanacs for {}, {}.",
+                    nav.satellites(),
+                    nav.almanacs.len(),
+                    match nav.klobuchar {
+                        Some(_) => "Klobuchar ionosphere model",
+                        None => "no ionosphere model",
+                    }
+                ));
+                if !nav.utc.is_empty() {
+                    let now = state.now(Utc::now()).as_gps_secs_f64();
+                    let offsets: Vec<String> = nav
+                        .utc
+                        .iter()
+                        .map(|(c, utc)| format!("{} {:.9} s", c.name(), utc.offset(now)))
+                        .collect();
+                    ui.label(format!("System time - UTC: {}", offsets.join(", ")));
+                }
+
+                let Some(raw) = &state.raw else {
+                    ui.label("No UBX-RXM-RAWX received; enable raw measurements on the receiver.");
+                    return;
+                };
+                let Some(solution) = &state.spp else {
+                    let time = raw.week as f64 * SECONDS_PER_WEEK as f64 + raw.rcv_tow;
+                    let observations = spp::observations(raw);
+                    let usable = observations
+                        .iter()
+                        .filter(|o| nav.select(o.id, time).is_some())
+                        .count();
+                    ui.label(format!(
+                        "No solution: {} pseudoranges, {} with a valid ephemeris.",
+                        observations.len(),
+                        usable
+                    ));
+                    return;
+                };
+
+                let fix = state.fix.as_ref().filter(|f| f.fix_type.has_position());
+                egui::Grid::new("spp_compare_grid")
+                    .num_columns(3)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.label("");
+                        ui.strong("Receiver");
+                        ui.strong("Local SPP");
+                        ui.end_row();
+                        let dash = || "-".to_string();
+                        ui.label("Latitude");
+                        ui.monospace(fix.map_or_else(dash, |f| format!("{:.8}°", f.latitude)));
+                        ui.monospace(format!("{:.8}°", solution.latitude));
+                        ui.end_row();
+                        ui.label("Longitude");
+                        ui.monospace(fix.map_or_else(dash, |f| format!("{:.8}°", f.longitude)));
+                        ui.monospace(format!("{:.8}°", solution.longitude));
+                        ui.end_row();
+                        ui.label("Ellipsoidal height");
+                        ui.monospace(
+                            fix.and_then(|f| f.height)
+                                .map_or_else(dash, |h| format!("{:.3} m", h)),
+                        );
+                        ui.monospace(format!("{:.3} m", solution.height));
+                        ui.end_row();
+                        ui.label("Satellites");
+                        ui.label(fix.map_or_else(dash, |f| f.num_sv.to_string()));
+                        ui.label(solution.satellites.len().to_string());
+                        ui.end_row();
+                    });
+
+                if let Some((fix, height)) = fix.and_then(|f| Some((f, f.height?))) {
+                    let [e, n, u] = geodesy::ecef_to_enu(
+                        solution.ecef,
+                        (fix.latitude, fix.longitude, height),
+                    );
+                    ui.label(format!(
+                        "SPP minus receiver: E {:+.3} m, N {:+.3} m, U {:+.3} m ({:.3} m horizontal)",
+                        e,
+                        n,
+                        u,
+                        e.hypot(n)
+                    ));
+                }
+                for (constellation, bias) in &solution.clock {
+                    ui.label(format!(
+                        "Clock bias to {} time: {:.3} m ({:.3} µs)",
+                        constellation,
+                        bias,
+                        bias / ephemeris::SPEED_OF_LIGHT * 1e6
+                    ));
+                }
+                ui.label(format!(
+                    "PDOP {:.2}, chi-square {:.2} with {} degrees of freedom, {} iterations",
+                    solution.pdop,
+                    solution.chi_square,
+                    solution.degrees_of_freedom,
+                    solution.iterations
+                ));
+
+                ui.separator();
+                egui::Grid::new("spp_residual_grid")
+                    .num_columns(7)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for title in [
+                            "Satellite",
+                            "Elevation",
+                            "Azimuth",
+                            "Residual",
+                            "Iono",
+                            "Tropo",
+                            "Sigma",
+                        ] {
+                            ui.strong(title);
+                        }
+                        ui.end_row();
+                        for sat in &solution.satellites {
+                            ui.label(sat.id.to_string());
+                            ui.label(format!("{:.1}°", sat.elevation));
+                            ui.label(format!("{:.1}°", sat.azimuth));
+                            ui.monospace(format!("{:+.2} m", sat.residual));
+                            ui.monospace(format!("{:.2} m", sat.iono));
+                            ui.monospace(format!("{:.2} m", sat.tropo));
+                            ui.monospace(format!("{:.2} m", sat.sigma));
+                            ui.end_row();
+                        }
+                    });
+            });
+        self.show_spp_window = open;
+
+        if load {
+            let read = std::fs::read_to_string(&self.rinex_nav_path)
+                .and_then(|text| ephemeris::parse_rinex_nav(&text));
+            match read {
+                Ok(data) => {
+                    let message = format!(
+                        "Loaded ephemerides for {} satellites from {}",
+                        data.satellites(),
+                        self.rinex_nav_path
+                    );
+                    self.receivers[self.active_receiver]
+                        .state
+                        .navigation
+                        .merge(data);
+                    self.notify(DialogType::Info, &message);
+                }
+                Err(e) => self.dialog(
+                    DialogType::Error,
+                    &format!("Failed to load {}: {}", self.rinex_nav_path, e),
+                ),
+            }
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use std::time::Duration;
+
+use chrono::{Local, Utc};
+use eframe::egui;
+
+use crate::time_scales::WeekTow;
+
+use super::widgets::plot_history;
+use super::GenCamGUI;
+
+impl GenCamGUI {
+    pub(super) fn ui_time_window(&mut self, ctx: &egui::Context) {
+        let host = Utc::now();
+        let state = &self.receivers[self.active_receiver].state;
+        let now = state.now(host);
+        let leap = state.leap.gps_utc;
+
+        let week_tow = |t: WeekTow| format!("week {}, TOW {:.3} s", t.week, t.tow);
+        let (utc_secs, utc_nanos) = now.utc_unix(leap);
+        let local = chrono::DateTime::from_timestamp(utc_secs, utc_nanos)
+            .map(|t| {
+                t.with_timezone(&Local)
+                    .format("%Y-%m-%d %H:%M:%S%.3f (UTC%:z)")
+            })
+            .map_or("-".to_owned(), |t| t.to_string());
+        let glonass = now.glonass(leap);
+
+        egui::Window::new("Time Scales")
+            .open(&mut self.show_time_window)
+            .show(ctx, |ui| {
+                egui::Grid::new("time_scales_grid")
+                    .num_columns(2)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.label("Reference");
+                        ui.label(match state.time {
+                            Some(_) => "Receiver",
+                            None => "Host clock (no receiver time yet)",
+                        });
+                        ui.end_row();
+
+                        ui.label("UTC");
+                        ui.label(now.utc(leap).to_string());
+                        ui.end_row();
+
+                        ui.label("Local");
+                        ui.label(local);
+                        ui.end_row();
+
+                        ui.label("GPS");
+                        ui.label(week_tow(now.gps()));
+                        ui.end_row();
+
+                        ui.label("Galileo GST");
+                        ui.label(week_tow(now.gst()));
+                        ui.end_row();
+
+                        ui.label("BeiDou BDT");
+                        ui.label(week_tow(now.bdt()));
+                        ui.end_row();
+
+                        ui.label("GLONASS");
+                        ui.label(format!(
+                            "{} (N4 {}, NT {}, TOD {:.3} s)",
+                            now.glonass_calendar(leap),
+                            glonass.n4,
+                            glonass.nt,
+                            glonass.tod
+                        ));
+                        ui.end_row();
+
+                        ui.label("TAI");
+                        ui.label(now.tai().to_string());
+                        ui.end_row();
+
+                        ui.label("Leap seconds");
+                        ui.label(format!(
+                            "GPS − UTC = {} s, TAI − UTC = {} s ({})",
+                            leap,
+                            leap + 19,
+                            state.leap.source
+                        ));
+                        ui.end_row();
+
+                        ui.label("Host − receiver");
+                        ui.label(match state.clock_offsets.back() {
+                            Some([_, ms]) => format!("{:.3} ms", ms),
+                            None => "-".to_owned(),
+                        });
+                        ui.end_row();
+                    });
+
+                ui.separator();
+                ui.label("Host clock − receiver time");
+                plot_history(ui, "clock_offset_plot", "Offset (ms)", &state.clock_offsets);
+            });
+
+        // Keep the clocks ticking.
+        ctx.request_repaint_after(Duration::from_millis(100));
+    }
+}
@@ -0,0 +1,163 @@
+use eframe::egui;
+use egui_plot::{Line, Plot, PlotPoints};
+
+use crate::stability;
+
+use super::widgets::plot_history;
+use super::{DialogType, GenCamGUI, Modal};
+
+impl GenCamGUI {
+    pub(super) fn ui_timing_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_timing_window;
+        let mut export = false;
+        egui::Window::new("Timing")
+            .open(&mut open)
+            .vscroll(true)
+            .show(ctx, |ui| {
+                let state = &self.receivers[self.active_receiver].state;
+
+                ui.collapsing("Time Pulse (TIM-TP)", |ui| {
+                    match &state.time_pulse {
+                        Some(tp) => {
+                            ui.label(format!(
+                                "Next pulse: week {}, TOW {:.9} s ({})",
+                                tp.week,
+                                tp.tow(),
+                                if tp.utc_base() { "UTC" } else { "GNSS" }
+                            ));
+                            ui.label(match tp.q_err_invalid() {
+                                true => "Quantization error: invalid".to_owned(),
+                                false => format!("Quantization error: {} ps", tp.q_err_ps),
+                            });
+                        }
+                        None => {
+                            ui.label("No TIM-TP received.");
+                        }
+                    }
+                    plot_history(ui, "q_err_plot", "qErr (ps)", &state.q_err_history);
+                });
+
+                ui.collapsing("Time Mark (TIM-TM2)", |ui| match &state.time_mark {
+                    Some(tm) => {
+                        ui.label(format!("Channel {}, count {}", tm.channel, tm.count));
+                        ui.label(format!(
+                            "Rising edge: week {}, TOW {:.9} s{}",
+                            tm.wn_r,
+                            tm.tow_rising(),
+                            if tm.new_rising_edge() { " (new)" } else { "" }
+                        ));
+                        ui.label(format!(
+                            "Falling edge: week {}, TOW {:.9} s{}",
+                            tm.wn_f,
+                            tm.tow_falling(),
+                            if tm.new_falling_edge() { " (new)" } else { "" }
+                        ));
+                        ui.label(format!(
+                            "Accuracy estimate: {} ns{}",
+                            tm.acc_est_ns,
+                            if tm.time_valid() { "" } else { " (time not valid)" }
+                        ));
+                    }
+                    None => {
+                        ui.label("No TIM-TM2 received.");
+                    }
+                });
+
+                ui.collapsing("Receiver Clock (NAV-CLOCK)", |ui| {
+                    match &state.clock {
+                        Some(clock) => {
+                            ui.label(format!(
+                                "Bias: {} ns (±{} ns)",
+                                clock.clk_b_ns, clock.t_acc_ns
+                            ));
+                            ui.label(format!(
+                                "Drift: {} ns/s (±{} ps/s)",
+                                clock.clk_d_ns_s, clock.f_acc_ps_s
+                            ));
+                        }
+                        None => {
+                            ui.label("No NAV-CLOCK received.");
+                        }
+                    }
+                    plot_history(ui, "clock_bias_plot", "Bias (ns)", &state.clock_bias_history);
+                    plot_history(
+                        ui,
+                        "clock_drift_plot",
+                        "Drift (ns/s)",
+                        &state.clock_drift_history,
+                    );
+                });
+
+                ui.collapsing("Stability", |ui| {
+                    ui.label(format!(
+                        "{} clock solutions recorded.",
+                        state.clock_samples.len()
+                    ));
+                    ui.horizontal(|ui| {
+                        if ui
+                            .button("Compute ADEV/TDEV")
+                            .on_hover_text(
+                                "Overlapping Allan deviation and time deviation of the recorded clock bias.",
+                            )
+                            .clicked()
+                        {
+                            self.stability = state
+                                .clock_phase()
+                                .map(|(phase, tau0)| {
+                                    let phase = stability::remove_millisecond_jumps(&phase);
+                                    stability::stability_curve(&phase, tau0)
+                                })
+                                .unwrap_or_default();
+                        }
+                    });
+
+                    let adev: Vec<[f64; 2]> = self
+                        .stability
+                        .iter()
+                        .map(|p| [p.tau.log10(), p.adev.log10()])
+                        .collect();
+                    let tdev: Vec<[f64; 2]> = self
+                        .stability
+                        .iter()
+                        .map(|p| [p.tau.log10(), p.tdev.log10()])
+                        .collect();
+                    let log_axis = |mark: egui_plot::GridMark, _: &_| {
+                        format!("{:.0e}", 10f64.powf(mark.value))
+                    };
+                    Plot::new("stability_plot")
+                        .height(200.0)
+                        .legend(egui_plot::Legend::default())
+                        .x_axis_label("Tau (s)")
+                        .x_axis_formatter(log_axis)
+                        .y_axis_formatter(log_axis)
+                        .show(ui, |plot_ui| {
+                            plot_ui.line(Line::new(PlotPoints::from(adev)).name("ADEV"));
+                            plot_ui.line(Line::new(PlotPoints::from(tdev)).name("TDEV (s)"));
+                        });
+
+                    ui.horizontal(|ui| {
+                        ui.label("CSV file:");
+                        ui.text_edit_singleline(&mut self.stability_csv_path);
+                        if ui
+                            .add_enabled(!self.stability.is_empty(), egui::Button::new("Export"))
+                            .clicked()
+                        {
+                            export = true;
+                        }
+                    });
+                });
+            });
+
+        if export {
+            if let Err(e) =
+                std::fs::write(&self.stability_csv_path, stability::to_csv(&self.stability))
+            {
+                self.dialog(
+                    DialogType::Error,
+                    &format!("Failed to write {}: {}", self.stability_csv_path, e),
+                );
+            }
+        }
+        self.show_timing_window = open;
+    }
+}
@@ -0,0 +1,197 @@
+use chrono::Utc;
+use eframe::egui;
+use egui_plot::Plot;
+
+use crate::ttff::{self, ResetProtocol, StartMode};
+
+use super::{DialogType, GenCamGUI, Modal};
+
+impl GenCamGUI {
+    pub(super) fn ui_ttff_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_ttff_window;
+        let mut export = false;
+        egui::Window::new("Time to First Fix")
+            .open(&mut open)
+            .vscroll(true)
+            .show(ctx, |ui| {
+                let connected = self.receivers[self.active_receiver].is_connected();
+                let runner = &mut self.receivers[self.active_receiver].ttff;
+                let running = runner.is_running();
+
+                ui.add_enabled_ui(!running, |ui| {
+                    egui::Grid::new("ttff_config_grid")
+                        .num_columns(2)
+                        .show(ui, |ui| {
+                            let config = &mut runner.config;
+                            ui.label("Start");
+                            egui::ComboBox::from_id_source("ttff_mode")
+                                .selected_text(config.mode.name())
+                                .show_ui(ui, |ui| {
+                                    for mode in StartMode::ALL {
+                                        ui.selectable_value(&mut config.mode, mode, mode.name());
+                                    }
+                                });
+                            ui.end_row();
+                            ui.label("Reset command");
+                            egui::ComboBox::from_id_source("ttff_protocol")
+                                .selected_text(config.protocol.name())
+                                .show_ui(ui, |ui| {
+                                    for protocol in ResetProtocol::ALL {
+                                        ui.selectable_value(
+                                            &mut config.protocol,
+                                            protocol,
+                                            protocol.name(),
+                                        );
+                                    }
+                                });
+                            ui.end_row();
+                            ui.label("Repeats");
+                            ui.add(egui::DragValue::new(&mut config.repeats).range(1..=1000));
+                            ui.end_row();
+                            ui.label("Timeout");
+                            ui.add(
+                                egui::DragValue::new(&mut config.timeout)
+                                    .range(10.0..=3600.0)
+                                    .suffix(" s"),
+                            );
+                            ui.end_row();
+                            ui.label("Pause between runs");
+                            ui.add(
+                                egui::DragValue::new(&mut config.pause)
+                                    .range(0.0..=600.0)
+                                    .suffix(" s"),
+                            );
+                            ui.end_row();
+                            ui.label("");
+                            ui.checkbox(&mut config.wait_for_rtk, "Wait for RTK fixed");
+                            ui.end_row();
+                        });
+                });
+
+                ui.horizontal(|ui| {
+                    if running {
+                        if ui.button("Stop").clicked() {
+                            runner.stop();
+                        }
+                    } else if ui
+                        .add_enabled(connected, egui::Button::new("Start"))
+                        .on_hover_text("Reset the receiver and time the first fixes.")
+                        .clicked()
+                    {
+                        runner.start(Utc::now());
+                    }
+                    if ui
+                        .add_enabled(!running, egui::Button::new("Clear results"))
+                        .clicked()
+                    {
+                        runner.clear();
+                    }
+                });
+
+                if running {
+                    let time =
+                        |v: Option<f64>| v.map_or("-".to_string(), |v| format!("{:.1} s", v));
+                    match runner.current() {
+                        Some(run) => ui.label(format!(
+                            "Run {} of {}: {:.0} s since reset, 2D {}, 3D {}, RTK {}",
+                            runner.config.repeats - runner.remaining() + 1,
+                            runner.config.repeats,
+                            (Utc::now() - run.started).num_milliseconds() as f64 * 1e-3,
+                            time(run.fix_2d),
+                            time(run.fix_3d),
+                            time(run.fix_rtk)
+                        )),
+                        None => ui.label(format!(
+                            "Waiting to reset, {} runs left",
+                            runner.remaining()
+                        )),
+                    };
+                }
+
+                ui.separator();
+                egui::Grid::new("ttff_summary_grid")
+                    .num_columns(6)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for header in ["Start", "Fix", "Runs", "Min (s)", "Median (s)", "Max (s)"] {
+                            ui.strong(header);
+                        }
+                        ui.end_row();
+                        for mode in StartMode::ALL {
+                            for (fix, summary) in
+                                ["2D", "3D", "RTK"].iter().zip(runner.summaries(mode))
+                            {
+                                let Some(summary) = summary else {
+                                    continue;
+                                };
+                                ui.label(mode.name());
+                                ui.label(*fix);
+                                ui.label(summary.count.to_string());
+                                ui.label(format!("{:.1}", summary.min));
+                                ui.label(format!("{:.1}", summary.median));
+                                ui.label(format!("{:.1}", summary.max));
+                                ui.end_row();
+                            }
+                        }
+                    });
+                let timed_out = runner.runs.iter().filter(|r| r.timed_out).count();
+                if timed_out > 0 {
+                    ui.label(format!("{} runs timed out or were stopped.", timed_out));
+                }
+
+                ui.horizontal(|ui| {
+                    ui.label("Histogram of 3D TTFF, bin width:");
+                    ui.add(
+                        egui::DragValue::new(&mut self.ttff_bin_width)
+                            .range(0.1..=60.0)
+                            .suffix(" s"),
+                    );
+                });
+                let mode = runner.config.mode;
+                let times: Vec<f64> = runner.finished(mode).filter_map(|r| r.fix_3d).collect();
+                let bars: Vec<egui_plot::Bar> = ttff::histogram(&times, self.ttff_bin_width)
+                    .into_iter()
+                    .map(|(start, count)| {
+                        egui_plot::Bar::new(start + self.ttff_bin_width / 2.0, count as f64)
+                            .width(self.ttff_bin_width)
+                    })
+                    .collect();
+                Plot::new("ttff_histogram")
+                    .height(180.0)
+                    .x_axis_label(format!("{} start TTFF (s)", mode.name()))
+                    .y_axis_label("Runs")
+                    .show(ui, |plot_ui| {
+                        plot_ui.bar_chart(egui_plot::BarChart::new(bars));
+                    });
+
+                ui.horizontal(|ui| {
+                    ui.label("CSV file:");
+                    ui.text_edit_singleline(&mut self.ttff_csv_path);
+                    if ui
+                        .add_enabled(!runner.runs.is_empty(), egui::Button::new("Save"))
+                        .clicked()
+                    {
+                        export = true;
+                    }
+                });
+            });
+
+        if export {
+            let receiver = format!(
+                "{} ({})",
+                self.receivers[self.active_receiver].state.info.summary(),
+                self.receivers[self.active_receiver].address
+            );
+            if let Err(e) = std::fs::write(
+                &self.ttff_csv_path,
+                self.receivers[self.active_receiver].ttff.to_csv(&receiver),
+            ) {
+                self.dialog(
+                    DialogType::Error,
+                    &format!("Failed to write {}: {}", self.ttff_csv_path, e),
+                );
+            }
+        }
+        self.show_ttff_window = open;
+    }
+}
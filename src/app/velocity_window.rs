@@ -0,0 +1,160 @@
+use circular_buffer::CircularBuffer;
+use eframe::egui;
+use egui_plot::{Line, Plot, PlotPoints};
+
+use crate::gnss::SpeedUnit;
+use crate::receiver::HISTORY_LEN;
+
+use super::GenCamGUI;
+
+/// Draws a compass rose with the course over ground and, when known, the vehicle heading,
+/// both in degrees from true north, and a text in the centre.
+fn compass(ui: &mut egui::Ui, course: Option<f64>, heading: Option<f64>, text: &str) {
+    let size = ui.available_width().clamp(120.0, 240.0);
+    let (response, painter) = ui.allocate_painter(egui::vec2(size, size), egui::Sense::hover());
+    let center = response.rect.center();
+    let radius = size / 2.0 - 14.0;
+    let visuals = ui.visuals();
+    let stroke = visuals.widgets.noninteractive.fg_stroke;
+    let direction = |degrees: f64| {
+        let a = degrees.to_radians() as f32;
+        egui::vec2(a.sin(), -a.cos())
+    };
+
+    painter.circle_stroke(center, radius, stroke);
+    for tick in (0..360).step_by(10) {
+        let length = if tick % 90 == 0 {
+            10.0
+        } else if tick % 30 == 0 {
+            6.0
+        } else {
+            3.0
+        };
+        let d = direction(tick as f64);
+        painter.line_segment(
+            [center + d * radius, center + d * (radius - length)],
+            stroke,
+        );
+    }
+    for (label, degrees) in [("N", 0.0), ("E", 90.0), ("S", 180.0), ("W", 270.0)] {
+        painter.text(
+            center + direction(degrees) * (radius + 8.0),
+            egui::Align2::CENTER_CENTER,
+            label,
+            egui::FontId::proportional(12.0),
+            visuals.text_color(),
+        );
+    }
+    let arrow = |degrees: f64, color: egui::Color32| {
+        let d = direction(degrees);
+        painter.arrow(
+            center + d * radius * 0.35,
+            d * radius * 0.6,
+            egui::Stroke::new(3.0, color),
+        );
+    };
+    if let Some(heading) = heading {
+        arrow(heading, egui::Color32::LIGHT_BLUE);
+    }
+    if let Some(course) = course {
+        arrow(course, egui::Color32::YELLOW);
+    }
+    painter.text(
+        center,
+        egui::Align2::CENTER_CENTER,
+        text,
+        egui::FontId::proportional(16.0),
+        visuals.strong_text_color(),
+    );
+}
+
+impl GenCamGUI {
+    pub(super) fn ui_velocity_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_velocity_window;
+        egui::Window::new("Velocity")
+            .open(&mut open)
+            .vscroll(true)
+            .show(ctx, |ui| {
+                let state = &self.receivers[self.active_receiver].state;
+                let unit = &mut self.position_settings.speed_unit;
+                egui::ComboBox::from_label("Speed unit")
+                    .selected_text(unit.name())
+                    .show_ui(ui, |ui| {
+                        for u in SpeedUnit::ALL {
+                            ui.selectable_value(unit, u, u.name());
+                        }
+                    });
+                let unit = *unit;
+
+                let Some(velocity) = &state.velocity else {
+                    ui.label("No velocity; needs RMC, VTG or UBX-NAV-PVT with a fix.");
+                    return;
+                };
+                let speed = format!("{:.1} {}", unit.from_mps(velocity.speed), unit.name());
+                compass(ui, velocity.course, velocity.heading, &speed);
+                ui.horizontal(|ui| {
+                    ui.colored_label(egui::Color32::YELLOW, "━ Course over ground");
+                    if velocity.heading.is_some() {
+                        ui.colored_label(egui::Color32::LIGHT_BLUE, "━ Vehicle heading");
+                    }
+                });
+
+                let degrees = |v: Option<f64>| v.map_or("-".to_string(), |v| format!("{:.1}°", v));
+                egui::Grid::new("velocity_grid")
+                    .num_columns(2)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.label("Speed over ground");
+                        ui.label(match velocity.speed_acc {
+                            Some(acc) => format!("{} ± {:.2}", speed, unit.from_mps(acc)),
+                            None => speed.clone(),
+                        });
+                        ui.end_row();
+                        ui.label("Course over ground");
+                        ui.label(match velocity.course_acc {
+                            Some(acc) if velocity.course.is_some() => {
+                                format!("{} ± {:.1}°", degrees(velocity.course), acc)
+                            }
+                            _ => degrees(velocity.course),
+                        });
+                        ui.end_row();
+                        ui.label("Vehicle heading");
+                        ui.label(degrees(velocity.heading));
+                        ui.end_row();
+                        ui.label("Slip angle");
+                        ui.label(degrees(velocity.slip()));
+                        ui.end_row();
+                        ui.label("Vertical velocity");
+                        ui.label(velocity.vertical.map_or("-".to_string(), |v| {
+                            format!("{:+.2} {}", unit.from_mps(v), unit.name())
+                        }));
+                        ui.end_row();
+                    });
+
+                ui.separator();
+                let label = format!("Speed ({})", unit.name());
+                let convert = |history: &CircularBuffer<HISTORY_LEN, [f64; 2]>| -> Vec<[f64; 2]> {
+                    history
+                        .iter()
+                        .map(|&[t, v]| [t, unit.from_mps(v)])
+                        .collect()
+                };
+                let (speed, vertical) = (
+                    convert(&state.speed_history),
+                    convert(&state.vertical_speed_history),
+                );
+                Plot::new("speed_plot")
+                    .height(150.0)
+                    .x_axis_label("Time (s)")
+                    .y_axis_label(label)
+                    .legend(egui_plot::Legend::default())
+                    .show(ui, |plot_ui| {
+                        plot_ui.line(Line::new(PlotPoints::from(speed)).name("Over ground"));
+                        if !vertical.is_empty() {
+                            plot_ui.line(Line::new(PlotPoints::from(vertical)).name("Vertical"));
+                        }
+                    });
+            });
+        self.show_velocity_window = open;
+    }
+}
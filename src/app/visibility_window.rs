@@ -0,0 +1,181 @@
+use chrono::Utc;
+use eframe::egui;
+
+use crate::astronomy;
+use crate::visibility::{self, OrbitSource, Visibility};
+
+use super::widgets::sky_plot;
+use super::GenCamGUI;
+
+impl GenCamGUI {
+    pub(super) fn ui_visibility_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_visibility_window;
+        let mut clear = false;
+        egui::Window::new("Visibility")
+            .open(&mut open)
+            .vscroll(true)
+            .show(ctx, |ui| {
+                let state = &self.receivers[self.active_receiver].state;
+                ui.horizontal(|ui| {
+                    ui.label("Elevation mask");
+                    ui.add(
+                        egui::DragValue::new(&mut self.visibility_mask)
+                            .range(0.0..=60.0)
+                            .speed(1.0)
+                            .suffix("°"),
+                    );
+                });
+                let mask = self.visibility_mask;
+                let above: Vec<&Visibility> = state
+                    .visibility
+                    .iter()
+                    .filter(|v| v.elevation >= mask)
+                    .collect();
+                let missing: Vec<&Visibility> =
+                    above.iter().copied().filter(|v| !v.tracked).collect();
+                match state.visibility.is_empty() {
+                    true => ui.label(
+                        "No prediction; needs a position and ephemerides or almanacs from \
+                         UBX-RXM-SFRBX or a RINEX file.",
+                    ),
+                    false => ui.label(format!(
+                        "{} satellites predicted above the mask, {} of them tracked.",
+                        above.len(),
+                        above.len() - missing.len()
+                    )),
+                };
+                if !missing.is_empty() {
+                    ui.colored_label(ui.visuals().warn_fg_color, "Predicted but not tracked:");
+                    egui::Grid::new("untracked_satellites")
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for title in ["Satellite", "Azimuth", "Elevation", "Orbit"] {
+                                ui.strong(title);
+                            }
+                            ui.end_row();
+                            for v in &missing {
+                                ui.label(v.id.to_string());
+                                ui.label(format!("{:.0}°", v.azimuth));
+                                ui.label(format!("{:.0}°", v.elevation));
+                                ui.label(match v.source {
+                                    OrbitSource::Ephemeris => "Ephemeris",
+                                    OrbitSource::Almanac => "Almanac",
+                                });
+                                ui.end_row();
+                            }
+                        });
+                }
+
+                ui.separator();
+                let map = &state.obstruction;
+                ui.horizontal(|ui| {
+                    ui.label(format!("Obstruction map over {} epochs", map.epochs));
+                    clear = ui.button("Clear").clicked();
+                });
+                let cells = map.cells();
+                let (_, painter, axes) = sky_plot(ui, |painter, axes| {
+                    for cell in &cells {
+                        let f = cell.tracked_fraction();
+                        let color = egui::Color32::from_rgba_unmultiplied(
+                            (230.0 * (1.0 - f)) as u8,
+                            (200.0 * f) as u8,
+                            60,
+                            150,
+                        );
+                        axes.fill_cell(
+                            painter,
+                            [cell.azimuth, cell.azimuth + visibility::AZIMUTH_STEP],
+                            [cell.elevation, cell.elevation + visibility::ELEVATION_STEP],
+                            color,
+                        );
+                    }
+                    painter.circle_stroke(
+                        axes.center,
+                        axes.radius * (1.0 - mask as f32 / 90.0),
+                        egui::Stroke::new(1.0, egui::Color32::GOLD),
+                    );
+                });
+                // Sun and Moon under the satellites, for solar outages and camera planning.
+                let time = state.now(Utc::now()).utc_unix(state.leap.gps_utc);
+                let unix = time.0 as f64 + time.1 as f64 * 1e-9;
+                let bodies: Vec<(&str, f64, f64, egui::Color32)> = match state.sky_position() {
+                    Some(position) => [
+                        ("Sun", astronomy::sun_position(unix), egui::Color32::YELLOW),
+                        (
+                            "Moon",
+                            astronomy::moon_position(unix),
+                            egui::Color32::LIGHT_GRAY,
+                        ),
+                    ]
+                    .into_iter()
+                    .map(|(name, ecef, color)| {
+                        let (azimuth, elevation) = visibility::look_angles(ecef, position);
+                        (name, azimuth, elevation, color)
+                    })
+                    .collect(),
+                    None => Vec::new(),
+                };
+                for &(name, azimuth, elevation, color) in &bodies {
+                    if elevation < 0.0 {
+                        continue;
+                    }
+                    let at = axes.point(azimuth, elevation);
+                    painter.circle(
+                        at,
+                        7.0,
+                        color,
+                        egui::Stroke::new(1.0, egui::Color32::DARK_GRAY),
+                    );
+                    painter.text(
+                        at + egui::vec2(0.0, 9.0),
+                        egui::Align2::CENTER_TOP,
+                        name,
+                        egui::FontId::proportional(10.0),
+                        ui.visuals().text_color(),
+                    );
+                }
+                for v in &state.visibility {
+                    let color = match (v.tracked, v.elevation >= mask) {
+                        (true, _) => egui::Color32::GREEN,
+                        (false, true) => egui::Color32::RED,
+                        (false, false) => egui::Color32::GRAY,
+                    };
+                    let at = axes.point(v.azimuth, v.elevation);
+                    painter.circle_filled(at, 4.0, color);
+                    painter.text(
+                        at + egui::vec2(6.0, 0.0),
+                        egui::Align2::LEFT_CENTER,
+                        v.id.to_string(),
+                        egui::FontId::proportional(10.0),
+                        ui.visuals().text_color(),
+                    );
+                }
+                ui.label(
+                    "Cells run from red, never tracked, to green, always tracked. Dots are the \
+                     current predictions: green tracked, red missing above the mask (gold).",
+                );
+                for (name, azimuth, elevation, _) in &bodies {
+                    let mut text = format!(
+                        "{}: azimuth {:.1}°, elevation {:.1}°",
+                        name, azimuth, elevation
+                    );
+                    if *name == "Moon" {
+                        let illuminated =
+                            astronomy::moon_illumination(astronomy::julian_date(unix));
+                        text.push_str(&format!(", {:.0}% illuminated", 100.0 * illuminated));
+                    }
+                    if *elevation < 0.0 {
+                        text.push_str(" (below the horizon)");
+                    }
+                    ui.label(text);
+                }
+            });
+        self.show_visibility_window = open;
+        if clear {
+            self.receivers[self.active_receiver]
+                .state
+                .obstruction
+                .clear();
+        }
+    }
+}
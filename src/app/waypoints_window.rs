@@ -0,0 +1,271 @@
+use chrono::Local;
+use eframe::egui;
+
+use crate::geodesy;
+use crate::waypoints::{self, FenceShape, Geofence, Waypoint};
+
+use super::{DialogType, GenCamGUI, Modal};
+
+impl GenCamGUI {
+    pub(super) fn ui_waypoints_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_waypoints_window;
+        let mut import = false;
+        egui::Window::new("Waypoints & Geofences")
+            .open(&mut open)
+            .vscroll(true)
+            .show(ctx, |ui| {
+                let fix = self.receivers[self.active_receiver]
+                    .state
+                    .fix
+                    .as_ref()
+                    .filter(|f| f.fix_type.has_position());
+                let nav = &mut self.navigator;
+                let distance = |metres: f64| match metres {
+                    m if m < 1000.0 => format!("{:.2} m", m),
+                    m => format!("{:.3} km", m / 1000.0),
+                };
+
+                match (fix, nav.target) {
+                    (_, None) => ui.label("No target selected."),
+                    (None, Some(_)) => ui.label("No position fix."),
+                    (Some(fix), Some(_)) => match nav.to_target(fix.latitude, fix.longitude) {
+                        Some((target, geodesic)) => ui.strong(format!(
+                            "To {}: {}, bearing {:.1}° (arriving on {:.1}°)",
+                            target.name,
+                            distance(geodesic.distance),
+                            geodesic.initial_bearing,
+                            geodesic.final_bearing
+                        )),
+                        None => ui.label("Target is antipodal, no distance available."),
+                    },
+                };
+
+                ui.separator();
+                let manual = &mut self.manual_waypoint;
+                ui.horizontal(|ui| {
+                    ui.label("Name:");
+                    ui.text_edit_singleline(&mut manual.name);
+                    if ui
+                        .add_enabled(fix.is_some(), egui::Button::new("Add current position"))
+                        .clicked()
+                    {
+                        if let Some(fix) = fix {
+                            nav.waypoints.push(Waypoint {
+                                name: match manual.name.is_empty() {
+                                    true => format!("WPT{:03}", nav.waypoints.len() + 1),
+                                    false => manual.name.clone(),
+                                },
+                                latitude: fix.latitude,
+                                longitude: fix.longitude,
+                                height: fix.height,
+                            });
+                        }
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Latitude");
+                    ui.add(
+                        egui::DragValue::new(&mut manual.latitude)
+                            .range(-90.0..=90.0)
+                            .speed(1e-6)
+                            .max_decimals(9)
+                            .suffix("°"),
+                    );
+                    ui.label("Longitude");
+                    ui.add(
+                        egui::DragValue::new(&mut manual.longitude)
+                            .range(-180.0..=180.0)
+                            .speed(1e-6)
+                            .max_decimals(9)
+                            .suffix("°"),
+                    );
+                    if ui
+                        .add_enabled(!manual.name.is_empty(), egui::Button::new("Add"))
+                        .clicked()
+                    {
+                        nav.waypoints.push(manual.clone());
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label("GPX file:");
+                    ui.text_edit_singleline(&mut self.gpx_path);
+                    import = ui.button("Import").clicked();
+                });
+
+                if nav.waypoints.is_empty() {
+                    ui.label("No waypoints.");
+                }
+                let mut remove = None;
+                egui::Grid::new("waypoints_grid")
+                    .num_columns(7)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.strong("Target");
+                        ui.strong("Vertex").on_hover_text(
+                            "Pick waypoints, in order, to build a polygon geofence.",
+                        );
+                        ui.strong("Name");
+                        ui.strong("Position");
+                        ui.strong("Distance");
+                        ui.strong("Bearing");
+                        ui.end_row();
+                        for (i, waypoint) in nav.waypoints.iter().enumerate() {
+                            ui.radio_value(&mut nav.target, Some(i), "");
+                            let mut picked = self.fence_vertices.contains(&i);
+                            if ui.checkbox(&mut picked, "").changed() {
+                                self.fence_vertices.retain(|v| *v != i);
+                                if picked {
+                                    self.fence_vertices.push(i);
+                                }
+                            }
+                            ui.label(&waypoint.name);
+                            ui.monospace(format!(
+                                "{:.8}°, {:.8}°",
+                                waypoint.latitude, waypoint.longitude
+                            ));
+                            let geodesic = fix.and_then(|f| {
+                                geodesy::geodesic_inverse(
+                                    f.latitude,
+                                    f.longitude,
+                                    waypoint.latitude,
+                                    waypoint.longitude,
+                                )
+                            });
+                            match geodesic {
+                                Some(g) => {
+                                    ui.label(distance(g.distance));
+                                    ui.label(format!("{:.1}°", g.initial_bearing));
+                                }
+                                None => {
+                                    ui.label("-");
+                                    ui.label("-");
+                                }
+                            }
+                            if ui.button("Delete").clicked() {
+                                remove = Some(i);
+                            }
+                            ui.end_row();
+                        }
+                    });
+                if let Some(i) = remove {
+                    nav.remove_waypoint(i);
+                    self.fence_vertices.retain(|v| *v != i);
+                    for v in &mut self.fence_vertices {
+                        if *v > i {
+                            *v -= 1;
+                        }
+                    }
+                }
+
+                ui.separator();
+                ui.strong("Geofences");
+                ui.horizontal(|ui| {
+                    ui.label("Name:");
+                    ui.text_edit_singleline(&mut self.fence_name);
+                    ui.label("Radius");
+                    ui.add(
+                        egui::DragValue::new(&mut self.fence_radius)
+                            .range(1.0..=1e6)
+                            .suffix(" m"),
+                    );
+                });
+                ui.horizontal(|ui| {
+                    let named = !self.fence_name.is_empty();
+                    let target = nav.target.and_then(|t| nav.waypoints.get(t));
+                    if ui
+                        .add_enabled(
+                            named && target.is_some(),
+                            egui::Button::new("Circle around target"),
+                        )
+                        .clicked()
+                    {
+                        if let Some(target) = target {
+                            let shape = FenceShape::Circle {
+                                latitude: target.latitude,
+                                longitude: target.longitude,
+                                radius: self.fence_radius,
+                            };
+                            nav.geofences.push(Geofence::new(&self.fence_name, shape));
+                        }
+                    }
+                    if ui
+                        .add_enabled(
+                            named && self.fence_vertices.len() >= 3,
+                            egui::Button::new("Polygon from picked waypoints"),
+                        )
+                        .clicked()
+                    {
+                        let vertices = self
+                            .fence_vertices
+                            .iter()
+                            .map(|&v| (nav.waypoints[v].latitude, nav.waypoints[v].longitude))
+                            .collect();
+                        nav.geofences.push(Geofence::new(
+                            &self.fence_name,
+                            FenceShape::Polygon(vertices),
+                        ));
+                        self.fence_vertices.clear();
+                    }
+                });
+
+                if nav.geofences.is_empty() {
+                    ui.label("No geofences.");
+                }
+                let mut remove = None;
+                egui::Grid::new("geofences_grid")
+                    .num_columns(6)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for (i, fence) in nav.geofences.iter_mut().enumerate() {
+                            ui.label(&fence.name);
+                            ui.label(fence.shape.describe());
+                            ui.label(match fence.inside {
+                                Some(true) => "Inside",
+                                Some(false) => "Outside",
+                                None => "-",
+                            });
+                            ui.checkbox(&mut fence.alarm_on_entry, "Alarm on entry");
+                            ui.checkbox(&mut fence.alarm_on_exit, "Alarm on exit");
+                            if ui.button("Delete").clicked() {
+                                remove = Some(i);
+                            }
+                            ui.end_row();
+                        }
+                    });
+                if let Some(i) = remove {
+                    nav.geofences.remove(i);
+                }
+
+                ui.collapsing("Crossings", |ui| {
+                    if ui.button("Clear").clicked() {
+                        nav.crossings.clear();
+                    }
+                    for crossing in nav.crossings.iter().rev() {
+                        ui.label(format!(
+                            "{} {}",
+                            crossing.time.with_timezone(&Local).format("%H:%M:%S"),
+                            crossing.message()
+                        ));
+                    }
+                });
+            });
+        self.show_waypoints_window = open;
+
+        if import {
+            let read = std::fs::read_to_string(&self.gpx_path)
+                .and_then(|text| waypoints::parse_gpx(&text));
+            match read {
+                Ok(points) => {
+                    let message =
+                        format!("Imported {} waypoints from {}", points.len(), self.gpx_path);
+                    self.navigator.waypoints.extend(points);
+                    self.notify(DialogType::Info, &message);
+                }
+                Err(e) => self.dialog(
+                    DialogType::Error,
+                    &format!("Failed to import {}: {}", self.gpx_path, e),
+                ),
+            }
+        }
+    }
+}
@@ -0,0 +1,108 @@
+use circular_buffer::CircularBuffer;
+use eframe::egui;
+use egui_plot::{Line, Plot, PlotPoints};
+
+use crate::receiver::HISTORY_LEN;
+
+/// Draws a time series recorded by the receiver state.
+pub(super) fn plot_history(
+    ui: &mut egui::Ui,
+    id: &str,
+    y_label: &str,
+    history: &CircularBuffer<HISTORY_LEN, [f64; 2]>,
+) {
+    let points: Vec<[f64; 2]> = history.iter().copied().collect();
+    Plot::new(id)
+        .height(150.0)
+        .x_axis_label("Time (s)")
+        .y_axis_label(y_label)
+        .show(ui, |plot_ui| {
+            plot_ui.line(Line::new(PlotPoints::from(points)));
+        });
+}
+
+/// Projection of a polar sky plot: north up, the zenith in the centre and the horizon on the
+/// rim.
+pub(super) struct SkyAxes {
+    pub(super) center: egui::Pos2,
+    pub(super) radius: f32,
+}
+
+impl SkyAxes {
+    pub(super) fn point(&self, azimuth: f64, elevation: f64) -> egui::Pos2 {
+        let r = self.radius * (1.0 - elevation.clamp(0.0, 90.0) as f32 / 90.0);
+        let a = azimuth.to_radians() as f32;
+        self.center + egui::vec2(a.sin(), -a.cos()) * r
+    }
+
+    /// Azimuth and elevation under a screen position, if it is inside the horizon.
+    pub(super) fn sky_at(&self, pos: egui::Pos2) -> Option<(f64, f64)> {
+        let d = pos - self.center;
+        let r = d.length() / self.radius;
+        (r <= 1.0).then(|| {
+            (
+                (d.x.atan2(-d.y) as f64).to_degrees().rem_euclid(360.0),
+                90.0 * (1.0 - r as f64),
+            )
+        })
+    }
+
+    /// Fills the part of the sky between two azimuths and two elevations.
+    pub(super) fn fill_cell(
+        &self,
+        painter: &egui::Painter,
+        azimuth: [f64; 2],
+        elevation: [f64; 2],
+        color: egui::Color32,
+    ) {
+        const STEPS: u32 = 4;
+        let mut mesh = egui::Mesh::default();
+        for i in 0..=STEPS {
+            let az = azimuth[0] + (azimuth[1] - azimuth[0]) * i as f64 / STEPS as f64;
+            mesh.colored_vertex(self.point(az, elevation[0]), color);
+            mesh.colored_vertex(self.point(az, elevation[1]), color);
+        }
+        for k in (0..2 * STEPS).step_by(2) {
+            mesh.add_triangle(k, k + 1, k + 2);
+            mesh.add_triangle(k + 1, k + 2, k + 3);
+        }
+        painter.add(mesh);
+    }
+}
+
+/// Allocates a square sky plot, lets `background` paint under the elevation rings and
+/// cardinal directions, and returns the painter for what goes on top.
+pub(super) fn sky_plot(
+    ui: &mut egui::Ui,
+    background: impl FnOnce(&egui::Painter, &SkyAxes),
+) -> (egui::Response, egui::Painter, SkyAxes) {
+    let size = ui.available_width().clamp(160.0, 320.0);
+    let (response, painter) = ui.allocate_painter(egui::vec2(size, size), egui::Sense::hover());
+    let axes = SkyAxes {
+        center: response.rect.center(),
+        radius: size / 2.0 - 14.0,
+    };
+    background(&painter, &axes);
+
+    let visuals = ui.visuals();
+    let stroke = visuals.widgets.noninteractive.fg_stroke;
+    for elevation in [0.0, 30.0, 60.0] {
+        painter.circle_stroke(
+            axes.center,
+            axes.radius * (1.0 - elevation / 90.0),
+            egui::Stroke::new(if elevation == 0.0 { 1.5 } else { 0.5 }, stroke.color),
+        );
+    }
+    for (label, azimuth) in [("N", 0.0), ("E", 90.0), ("S", 180.0), ("W", 270.0)] {
+        let rim = axes.point(azimuth, 0.0);
+        painter.line_segment([axes.center, rim], egui::Stroke::new(0.5, stroke.color));
+        painter.text(
+            axes.center + (rim - axes.center) * ((axes.radius + 8.0) / axes.radius),
+            egui::Align2::CENTER_CENTER,
+            label,
+            egui::FontId::proportional(12.0),
+            visuals.text_color(),
+        );
+    }
+    (response, painter, axes)
+}
@@ -0,0 +1,194 @@
+/// Julian date of the Unix epoch.
+const UNIX_EPOCH_JD: f64 = 2_440_587.5;
+/// Julian date of J2000.0.
+const J2000: f64 = 2_451_545.0;
+/// Astronomical unit in metres.
+const AU: f64 = 1.495_978_707e11;
+
+/// Julian date of a Unix timestamp in seconds. UTC stands in for both UT1 and dynamical time,
+/// which is well within the accuracy of the series below.
+pub fn julian_date(unix: f64) -> f64 {
+    unix / 86_400.0 + UNIX_EPOCH_JD
+}
+
+/// Greenwich mean sidereal time in degrees.
+fn sidereal_time(jd: f64) -> f64 {
+    let t = (jd - J2000) / 36_525.0;
+    (280.460_618_37 + 360.985_647_366_29 * (jd - J2000) + 0.000_387_933 * t * t).rem_euclid(360.0)
+}
+
+/// Mean obliquity of the ecliptic in degrees.
+fn obliquity(jd: f64) -> f64 {
+    23.439_291 - 0.013_004_2 * (jd - J2000) / 36_525.0
+}
+
+/// Right ascension and declination in degrees of ecliptic longitude and latitude.
+fn equatorial(longitude: f64, latitude: f64, jd: f64) -> (f64, f64) {
+    let (l, b, e) = (
+        longitude.to_radians(),
+        latitude.to_radians(),
+        obliquity(jd).to_radians(),
+    );
+    let ra = (l.sin() * e.cos() - b.tan() * e.sin()).atan2(l.cos());
+    let dec = (b.sin() * e.cos() + b.cos() * e.sin() * l.sin()).asin();
+    (ra.to_degrees().rem_euclid(360.0), dec.to_degrees())
+}
+
+/// Earth-fixed position of a body at a right ascension, declination and distance in metres.
+fn earth_fixed(ra: f64, dec: f64, distance: f64, jd: f64) -> [f64; 3] {
+    let h = (ra - sidereal_time(jd)).to_radians();
+    let dec = dec.to_radians();
+    [
+        distance * dec.cos() * h.cos(),
+        distance * dec.cos() * h.sin(),
+        distance * dec.sin(),
+    ]
+}
+
+/// Geocentric right ascension and declination in degrees and distance in metres of the Sun,
+/// from the low precision formulae of the Astronomical Almanac (about 0.01°).
+pub fn sun_equatorial(jd: f64) -> (f64, f64, f64) {
+    let n = jd - J2000;
+    let l = 280.460 + 0.985_647_4 * n;
+    let g = (357.528 + 0.985_600_3 * n).to_radians();
+    let longitude = l + 1.915 * g.sin() + 0.020 * (2.0 * g).sin();
+    let distance = 1.000_14 - 0.016_71 * g.cos() - 0.000_14 * (2.0 * g).cos();
+    let (ra, dec) = equatorial(longitude, 0.0, jd);
+    (ra, dec, distance * AU)
+}
+
+/// Fundamental arguments of the lunar theory in degrees: the Moon's mean longitude, mean
+/// elongation D, the Sun's mean anomaly M, the Moon's mean anomaly M' and argument of
+/// latitude F.
+fn lunar_arguments(jd: f64) -> [f64; 5] {
+    let t = (jd - J2000) / 36_525.0;
+    [
+        218.316_447_7 + 481_267.881_234_21 * t,
+        297.850_192_1 + 445_267.111_403_4 * t,
+        357.529_109_2 + 35_999.050_290_9 * t,
+        134.963_396_4 + 477_198.867_505_5 * t,
+        93.272_095_0 + 483_202.017_523_3 * t,
+    ]
+}
+
+/// Geocentric ecliptic longitude and latitude in degrees and distance in metres of the Moon,
+/// from the largest terms of Meeus' series (about 0.1° and 100 km).
+pub fn moon_ecliptic(jd: f64) -> (f64, f64, f64) {
+    // Multiples of D, M, M' and F with the coefficient in 1e-6 degrees or metres.
+    const LONGITUDE: [(f64, f64, f64, f64, f64); 16] = [
+        (0.0, 0.0, 1.0, 0.0, 6_288_774.0),
+        (2.0, 0.0, -1.0, 0.0, 1_274_027.0),
+        (2.0, 0.0, 0.0, 0.0, 658_314.0),
+        (0.0, 0.0, 2.0, 0.0, 213_618.0),
+        (0.0, 1.0, 0.0, 0.0, -185_116.0),
+        (0.0, 0.0, 0.0, 2.0, -114_332.0),
+        (2.0, 0.0, -2.0, 0.0, 58_793.0),
+        (2.0, -1.0, -1.0, 0.0, 57_066.0),
+        (2.0, 0.0, 1.0, 0.0, 53_322.0),
+        (2.0, -1.0, 0.0, 0.0, 45_758.0),
+        (0.0, 1.0, -1.0, 0.0, -40_923.0),
+        (1.0, 0.0, 0.0, 0.0, -34_720.0),
+        (0.0, 1.0, 1.0, 0.0, -30_383.0),
+        (2.0, 0.0, 0.0, -2.0, 15_327.0),
+        (0.0, 0.0, 1.0, 2.0, -12_528.0),
+        (0.0, 0.0, 1.0, -2.0, 10_980.0),
+    ];
+    const LATITUDE: [(f64, f64, f64, f64, f64); 10] = [
+        (0.0, 0.0, 0.0, 1.0, 5_128_122.0),
+        (0.0, 0.0, 1.0, 1.0, 280_602.0),
+        (0.0, 0.0, 1.0, -1.0, 277_693.0),
+        (2.0, 0.0, 0.0, -1.0, 173_237.0),
+        (2.0, 0.0, -1.0, 1.0, 55_413.0),
+        (2.0, 0.0, -1.0, -1.0, 46_271.0),
+        (2.0, 0.0, 0.0, 1.0, 32_573.0),
+        (0.0, 0.0, 2.0, 1.0, 17_198.0),
+        (2.0, 0.0, 1.0, -1.0, 9_266.0),
+        (0.0, 0.0, 2.0, -1.0, 8_822.0),
+    ];
+    const DISTANCE: [(f64, f64, f64, f64, f64); 13] = [
+        (0.0, 0.0, 1.0, 0.0, -20_905_355.0),
+        (2.0, 0.0, -1.0, 0.0, -3_699_111.0),
+        (2.0, 0.0, 0.0, 0.0, -2_955_968.0),
+        (0.0, 0.0, 2.0, 0.0, -569_925.0),
+        (0.0, 1.0, 0.0, 0.0, 48_888.0),
+        (0.0, 0.0, 0.0, 2.0, -3_149.0),
+        (2.0, 0.0, -2.0, 0.0, 246_158.0),
+        (2.0, -1.0, -1.0, 0.0, -152_138.0),
+        (2.0, 0.0, 1.0, 0.0, -170_733.0),
+        (2.0, -1.0, 0.0, 0.0, -204_586.0),
+        (0.0, 1.0, -1.0, 0.0, -129_620.0),
+        (1.0, 0.0, 0.0, 0.0, 108_743.0),
+        (0.0, 1.0, 1.0, 0.0, 104_755.0),
+    ];
+    let [l, d, m, mp, f] = lunar_arguments(jd);
+    let argument =
+        |(a, b, c, e, _): &(f64, f64, f64, f64, f64)| (a * d + b * m + c * mp + e * f).to_radians();
+    let sum = |terms: &[(f64, f64, f64, f64, f64)], trig: fn(f64) -> f64| {
+        terms.iter().map(|t| t.4 * trig(argument(t))).sum::<f64>()
+    };
+    (
+        (l + sum(&LONGITUDE, f64::sin) * 1e-6).rem_euclid(360.0),
+        sum(&LATITUDE, f64::sin) * 1e-6,
+        385_000_560.0 + sum(&DISTANCE, f64::cos),
+    )
+}
+
+/// Fraction of the Moon's disc that is illuminated.
+pub fn moon_illumination(jd: f64) -> f64 {
+    let [_, d, m, mp, _] = lunar_arguments(jd);
+    let (d, m, mp) = (d.to_radians(), m.to_radians(), mp.to_radians());
+    let phase = 180.0 - d.to_degrees() - 6.289 * mp.sin() + 2.100 * m.sin()
+        - 1.274 * (2.0 * d - mp).sin()
+        - 0.658 * (2.0 * d).sin()
+        - 0.214 * (2.0 * mp).sin()
+        - 0.110 * d.sin();
+    (1.0 + phase.to_radians().cos()) / 2.0
+}
+
+/// ECEF position of the Sun in metres at a Unix time.
+pub fn sun_position(unix: f64) -> [f64; 3] {
+    let jd = julian_date(unix);
+    let (ra, dec, distance) = sun_equatorial(jd);
+    earth_fixed(ra, dec, distance, jd)
+}
+
+/// ECEF position of the Moon in metres at a Unix time. Seen from the ground its direction
+/// differs from the geocentric one by up to a degree, which look angles computed from this
+/// position include.
+pub fn moon_position(unix: f64) -> [f64; 3] {
+    let jd = julian_date(unix);
+    let (longitude, latitude, distance) = moon_ecliptic(jd);
+    let (ra, dec) = equatorial(longitude, latitude, jd);
+    earth_fixed(ra, dec, distance, jd)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sun_and_moon_match_meeus() {
+        // Meeus, Astronomical Algorithms, example 25.a: 1992 October 13.0 TD.
+        let (ra, dec, distance) = sun_equatorial(2_448_908.5);
+        assert!((ra - 198.380_83).abs() < 0.02, "{}", ra);
+        assert!((dec + 7.785_07).abs() < 0.02, "{}", dec);
+        assert!((distance / AU - 0.997_6).abs() < 1e-3);
+
+        // Example 47.a and 48.a: 1992 April 12.0 TD.
+        let (longitude, latitude, distance) = moon_ecliptic(2_448_724.5);
+        assert!((longitude - 133.162_655).abs() < 0.1, "{}", longitude);
+        assert!((latitude + 3.229_126).abs() < 0.05, "{}", latitude);
+        assert!((distance - 368_409_700.0).abs() < 100_000.0, "{}", distance);
+        assert!((moon_illumination(2_448_724.5) - 0.678_6).abs() < 0.005);
+    }
+
+    #[test]
+    fn sun_is_overhead_at_the_equinox_noon() {
+        // 2024-03-20 12:00 UTC, a few hours after the March equinox.
+        let sun = sun_position(1_710_936_000.0);
+        let (lat, lon, _) = crate::geodesy::ecef_to_geodetic(sun);
+        assert!(lat.abs() < 0.5, "{}", lat);
+        // Noon in Greenwich, less the equation of time of about 7.5 minutes.
+        assert!((lon - 1.9).abs() < 0.5, "{}", lon);
+    }
+}
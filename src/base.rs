@@ -0,0 +1,236 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::geodesy;
+use crate::receiver::GnssMessage;
+use crate::ubx::{CfgTmode3, UbxMessage};
+
+/// A named base station position.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Site {
+    pub name: String,
+    /// Degrees, positive north.
+    pub latitude: f64,
+    /// Degrees, positive east.
+    pub longitude: f64,
+    /// Height above the WGS84 ellipsoid in metres.
+    pub height: f64,
+    /// Position accuracy in metres.
+    pub accuracy: f64,
+}
+
+impl Default for Site {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            latitude: 0.0,
+            longitude: 0.0,
+            height: 0.0,
+            accuracy: 0.05,
+        }
+    }
+}
+
+impl Site {
+    /// The CFG-TMODE3 command putting the receiver into fixed mode at this position.
+    pub fn fixed_command(&self) -> Vec<u8> {
+        // Split into the standard and high precision fields.
+        let split = |value: f64, unit: f64, hp_unit: f64| -> (i32, i8) {
+            let whole = (value / unit).trunc();
+            let hp = ((value - whole * unit) / hp_unit).round();
+            (whole as i32, hp.clamp(-99.0, 99.0) as i8)
+        };
+        let (lat, lat_hp) = split(self.latitude, 1e-7, 1e-9);
+        let (lon, lon_hp) = split(self.longitude, 1e-7, 1e-9);
+        let (height, height_hp) = split(self.height, 1e-2, 1e-4);
+        CfgTmode3 {
+            mode: 2,
+            lla: true,
+            position: [lat, lon, height],
+            position_hp: [lat_hp, lon_hp, height_hp],
+            fixed_pos_acc: (self.accuracy * 1e4).round() as u32,
+            ..Default::default()
+        }
+        .to_frame()
+        .to_bytes()
+    }
+}
+
+/// Targets a survey-in has to meet before it completes.
+#[derive(Debug, Clone)]
+pub struct SurveyInConfig {
+    /// Minimum observation time in seconds.
+    pub min_duration: u32,
+    /// Required accuracy of the mean position in metres.
+    pub accuracy_limit: f64,
+}
+
+impl Default for SurveyInConfig {
+    fn default() -> Self {
+        Self {
+            min_duration: 300,
+            accuracy_limit: 2.0,
+        }
+    }
+}
+
+impl SurveyInConfig {
+    pub fn command(&self) -> Vec<u8> {
+        CfgTmode3 {
+            mode: 1,
+            svin_min_dur: self.min_duration,
+            svin_acc_limit: (self.accuracy_limit * 1e4).round() as u32,
+            ..Default::default()
+        }
+        .to_frame()
+        .to_bytes()
+    }
+}
+
+/// The time mode we last asked the receiver for.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BaseMode {
+    Disabled,
+    SurveyIn {
+        /// Whether NAV-SVIN has reported the survey running since it was started, so results
+        /// of an earlier survey are not mistaken for this one.
+        seen_active: bool,
+    },
+    Fixed(Site),
+}
+
+/// Drives the survey-in and fixed base workflow of a UBX receiver.
+pub struct BaseStation {
+    pub survey: SurveyInConfig,
+    pub mode: BaseMode,
+    /// Result of the last completed survey-in.
+    pub surveyed: Option<Site>,
+    pending: Option<Vec<u8>>,
+}
+
+impl Default for BaseStation {
+    fn default() -> Self {
+        Self {
+            survey: SurveyInConfig::default(),
+            mode: BaseMode::Disabled,
+            surveyed: None,
+            pending: None,
+        }
+    }
+}
+
+impl BaseStation {
+    pub fn start_survey(&mut self) {
+        self.pending = Some(self.survey.command());
+        self.mode = BaseMode::SurveyIn { seen_active: false };
+        self.surveyed = None;
+    }
+
+    pub fn set_fixed(&mut self, site: &Site) {
+        self.pending = Some(site.fixed_command());
+        self.mode = BaseMode::Fixed(site.clone());
+    }
+
+    pub fn disable(&mut self) {
+        self.pending = Some(CfgTmode3::default().to_frame().to_bytes());
+        self.mode = BaseMode::Disabled;
+    }
+
+    /// Watches NAV-SVIN for the end of a survey-in we started, then switches the receiver to
+    /// fixed mode at the surveyed position.
+    pub fn observe(&mut self, message: &GnssMessage, host: DateTime<Utc>) {
+        let GnssMessage::Ubx(UbxMessage::NavSvin(svin)) = message else {
+            return;
+        };
+        let BaseMode::SurveyIn { seen_active } = &mut self.mode else {
+            return;
+        };
+        if svin.active {
+            *seen_active = true;
+            return;
+        }
+        if !*seen_active || !svin.valid {
+            return;
+        }
+
+        let (latitude, longitude, height) = geodesy::ecef_to_geodetic(svin.mean);
+        let site = Site {
+            name: format!("Survey {}", host.format("%Y-%m-%d %H:%M")),
+            latitude,
+            longitude,
+            height,
+            accuracy: svin.mean_acc,
+        };
+        self.set_fixed(&site);
+        self.surveyed = Some(site);
+    }
+
+    /// A command waiting to be sent to the receiver.
+    pub fn poll(&mut self) -> Option<Vec<u8>> {
+        self.pending.take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ubx::{UbxFrame, CLASS_NAV, ID_NAV_SVIN};
+
+    fn svin(active: bool, valid: bool, mean_cm: [i32; 3]) -> GnssMessage {
+        let mut p = vec![0; 40];
+        for (i, v) in mean_cm.iter().enumerate() {
+            p[12 + 4 * i..16 + 4 * i].copy_from_slice(&v.to_le_bytes());
+        }
+        p[28..32].copy_from_slice(&15_000u32.to_le_bytes());
+        p[36] = valid as u8;
+        p[37] = active as u8;
+        let frame = UbxFrame::new(CLASS_NAV, ID_NAV_SVIN, p);
+        GnssMessage::Ubx(UbxMessage::decode(&frame).unwrap())
+    }
+
+    #[test]
+    fn fixed_command_splits_high_precision() {
+        let site = Site {
+            latitude: 47.123_456_789,
+            longitude: -122.5,
+            height: 12.3456,
+            accuracy: 0.02,
+            ..Default::default()
+        };
+        let bytes = site.fixed_command();
+        let payload = &bytes[6..46];
+        assert_eq!(u16::from_le_bytes([payload[2], payload[3]]), 0x102);
+        assert_eq!(
+            i32::from_le_bytes(payload[4..8].try_into().unwrap()),
+            471_234_567
+        );
+        assert_eq!(payload[16] as i8, 89);
+        assert_eq!(
+            i32::from_le_bytes(payload[12..16].try_into().unwrap()),
+            1234
+        );
+        assert_eq!(payload[18] as i8, 56);
+        assert_eq!(u32::from_le_bytes(payload[20..24].try_into().unwrap()), 200);
+    }
+
+    #[test]
+    fn fixes_position_after_survey() {
+        let mut base = BaseStation::default();
+        let host = Utc::now();
+        let ecef = geodesy::geodetic_to_ecef(52.0, 4.5, 45.0).map(|v| (v * 100.0).round() as i32);
+
+        base.start_survey();
+        assert!(base.poll().is_some());
+        // A result left over from an earlier survey is ignored.
+        base.observe(&svin(false, true, [1, 2, 3]), host);
+        assert!(base.surveyed.is_none());
+
+        base.observe(&svin(true, false, ecef), host);
+        base.observe(&svin(false, true, ecef), host);
+        let site = base.surveyed.clone().unwrap();
+        assert!((site.latitude - 52.0).abs() < 1e-6 && (site.height - 45.0).abs() < 0.01);
+        assert_eq!(site.accuracy, 1.5);
+        assert_eq!(base.mode, BaseMode::Fixed(site));
+        assert!(base.poll().is_some());
+    }
+}
@@ -0,0 +1,264 @@
+use std::collections::{BTreeMap, VecDeque};
+
+use crate::gnss::{Constellation, Satellite};
+use crate::spp;
+use crate::visibility::{self, AZIMUTH_STEP, ELEVATION_STEP, SKY_CELLS};
+
+/// Number of (elevation, C/N0) samples kept per constellation for the scatter plot.
+pub const MAX_SAMPLES: usize = 10_000;
+
+/// Running sums of the C/N0 samples in one sky cell.
+#[derive(Debug, Clone, Copy, Default)]
+struct Accumulator {
+    count: u32,
+    sum: f64,
+    sum_sq: f64,
+}
+
+/// C/N0 statistics of one sky cell.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CnoCell {
+    /// Lower azimuth and elevation bounds in degrees.
+    pub azimuth: f64,
+    pub elevation: f64,
+    pub samples: u32,
+    /// Mean and standard deviation in dB-Hz.
+    pub mean: f64,
+    pub std_dev: f64,
+}
+
+/// Quadratic fit of C/N0 against elevation for one constellation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ElevationFit {
+    /// Coefficients for `evaluate_curve`.
+    pub coefficients: [f64; 3],
+    /// Lowest and highest elevation sampled, in degrees.
+    pub low: f64,
+    pub high: f64,
+}
+
+/// C/N0 of the tracked satellites over a session, binned by azimuth and elevation, for
+/// comparing antennas and placements.
+#[derive(Debug, Clone)]
+pub struct CnoMap {
+    cells: Vec<Accumulator>,
+    /// Recent (elevation, C/N0) samples of each constellation.
+    pub samples: BTreeMap<Constellation, VecDeque<[f64; 2]>>,
+    /// Fit of each constellation's samples, redone when they change.
+    fits: BTreeMap<Constellation, ElevationFit>,
+    pub epochs: u32,
+}
+
+impl Default for CnoMap {
+    fn default() -> Self {
+        Self {
+            cells: vec![Accumulator::default(); SKY_CELLS],
+            samples: BTreeMap::new(),
+            fits: BTreeMap::new(),
+            epochs: 0,
+        }
+    }
+}
+
+impl CnoMap {
+    /// Adds one epoch of the tracked satellites with a known position in the sky.
+    pub fn add(&mut self, satellites: &[Satellite]) {
+        let mut changed = Vec::new();
+        for sat in satellites.iter().filter(|s| s.cno > 0) {
+            let (Some(azimuth), Some(elevation)) = (sat.azimuth, sat.elevation) else {
+                continue;
+            };
+            if elevation < 0.0 {
+                continue;
+            }
+            let cno = sat.cno as f64;
+            let cell = &mut self.cells[visibility::sky_cell(azimuth as f64, elevation as f64)];
+            cell.count += 1;
+            cell.sum += cno;
+            cell.sum_sq += cno * cno;
+
+            let samples = self.samples.entry(sat.id.constellation).or_default();
+            if samples.len() == MAX_SAMPLES {
+                samples.pop_front();
+            }
+            samples.push_back([elevation as f64, cno]);
+            if !changed.contains(&sat.id.constellation) {
+                changed.push(sat.id.constellation);
+            }
+        }
+        for constellation in changed {
+            let samples = &self.samples[&constellation];
+            match fit_elevation_curve(samples) {
+                Some(coefficients) => {
+                    let (low, high) = samples
+                        .iter()
+                        .fold((90.0, 0.0), |(l, h), [e, _]| (e.min(l), e.max(h)));
+                    let fit = ElevationFit {
+                        coefficients,
+                        low,
+                        high,
+                    };
+                    self.fits.insert(constellation, fit);
+                }
+                None => {
+                    self.fits.remove(&constellation);
+                }
+            }
+        }
+        self.epochs += 1;
+    }
+
+    /// The fit of a constellation's samples, once they are enough for one.
+    pub fn fit(&self, constellation: Constellation) -> Option<&ElevationFit> {
+        self.fits.get(&constellation)
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    fn cell(&self, index: usize) -> Option<CnoCell> {
+        let a = self.cells[index];
+        if a.count == 0 {
+            return None;
+        }
+        let n = a.count as f64;
+        let mean = a.sum / n;
+        let (azimuth, elevation) = visibility::sky_cell_origin(index);
+        Some(CnoCell {
+            azimuth,
+            elevation,
+            samples: a.count,
+            mean,
+            std_dev: (a.sum_sq / n - mean * mean).max(0.0).sqrt(),
+        })
+    }
+
+    /// The cells with at least one sample.
+    pub fn cells(&self) -> Vec<CnoCell> {
+        (0..self.cells.len()).filter_map(|i| self.cell(i)).collect()
+    }
+
+    /// The cell an azimuth and elevation fall in, if it has samples.
+    pub fn cell_at(&self, azimuth: f64, elevation: f64) -> Option<CnoCell> {
+        self.cell(visibility::sky_cell(azimuth, elevation))
+    }
+
+    /// Formats the binned grid as CSV with a header row, one line per cell with samples.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from(
+            "azimuth_from_deg,azimuth_to_deg,elevation_from_deg,elevation_to_deg,samples,\
+             mean_cno_dbhz,std_cno_dbhz\n",
+        );
+        for c in self.cells() {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{:.2},{:.2}\n",
+                c.azimuth,
+                c.azimuth + AZIMUTH_STEP,
+                c.elevation,
+                c.elevation + ELEVATION_STEP,
+                c.samples,
+                c.mean,
+                c.std_dev
+            ));
+        }
+        csv
+    }
+}
+
+/// Least squares fit of C/N0 = c0 + c1·x + c2·x², with x the elevation over 90°, to
+/// (elevation, C/N0) samples. `None` with too few samples or too narrow a spread.
+pub fn fit_elevation_curve<'a>(
+    samples: impl IntoIterator<Item = &'a [f64; 2]>,
+) -> Option<[f64; 3]> {
+    // Normal equations, summed directly as there can be thousands of samples.
+    let mut n = vec![vec![0.0; 3]; 3];
+    let mut b = [0.0; 3];
+    let mut count = 0;
+    for [elevation, cno] in samples {
+        let x = elevation / 90.0;
+        let row = [1.0, x, x * x];
+        for a in 0..3 {
+            for c in 0..3 {
+                n[a][c] += row[a] * row[c];
+            }
+            b[a] += row[a] * cno;
+        }
+        count += 1;
+    }
+    if count < 3 {
+        return None;
+    }
+    let q = spp::invert(n)?;
+    let c: Vec<f64> = q
+        .iter()
+        .map(|row| row.iter().zip(&b).map(|(q, b)| q * b).sum())
+        .collect();
+    c.iter().all(|c| c.is_finite()).then(|| [c[0], c[1], c[2]])
+}
+
+/// Value of a fitted curve at an elevation in degrees.
+pub fn evaluate_curve(c: &[f64; 3], elevation: f64) -> f64 {
+    let x = elevation / 90.0;
+    c[0] + c[1] * x + c[2] * x * x
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gnss::SatelliteId;
+
+    #[test]
+    fn bins_and_fits_samples() {
+        let mut map = CnoMap::default();
+        for elevation in (5..90).step_by(5) {
+            let model = evaluate_curve(&[30.0, 25.0, -10.0], elevation as f64);
+            let satellites = [
+                Satellite {
+                    id: SatelliteId::new(Constellation::Gps, 1),
+                    cno: model.round() as u8,
+                    elevation: Some(elevation as f32),
+                    azimuth: Some(123.0),
+                    used: true,
+                },
+                // Not tracked, so not sampled.
+                Satellite {
+                    id: SatelliteId::new(Constellation::Gps, 2),
+                    cno: 0,
+                    elevation: Some(elevation as f32),
+                    azimuth: Some(123.0),
+                    used: false,
+                },
+            ];
+            map.add(&satellites);
+            map.add(&satellites[..1]);
+        }
+        assert_eq!(map.epochs, 34);
+        assert_eq!(map.samples[&Constellation::Gps].len(), 34);
+
+        let cell = map.cell_at(125.0, 47.0).unwrap();
+        assert_eq!(
+            (cell.azimuth, cell.elevation, cell.samples),
+            (120.0, 45.0, 2)
+        );
+        assert_eq!(cell.std_dev, 0.0);
+        assert!(map.cell_at(10.0, 47.0).is_none());
+        let csv = map.to_csv();
+        assert_eq!(csv.lines().count(), 1 + 17);
+        assert!(csv.contains("\n120,130,45,50,2,"));
+
+        let fit = map.fit(Constellation::Gps).unwrap();
+        assert_eq!((fit.low, fit.high), (5.0, 85.0));
+        assert!(map.fit(Constellation::Galileo).is_none());
+        let c = fit.coefficients;
+        assert_eq!(
+            Some(c),
+            fit_elevation_curve(&map.samples[&Constellation::Gps])
+        );
+        for elevation in [10.0, 45.0, 80.0] {
+            let fitted = evaluate_curve(&c, elevation);
+            let model = evaluate_curve(&[30.0, 25.0, -10.0], elevation);
+            assert!((fitted - model).abs() < 0.5, "{} {}", fitted, model);
+        }
+    }
+}
@@ -0,0 +1,275 @@
+use std::collections::{BTreeMap, VecDeque};
+
+use crate::geodesy;
+use crate::gnss::{Constellation, Fix, Satellite, SatelliteId, DAY_MS};
+
+/// Position differences kept, an hour at one per second.
+pub const MAX_SAMPLES: usize = 3_600;
+/// Milliseconds of receiver time between position difference samples.
+const SAMPLE_INTERVAL_MS: u32 = 1_000;
+/// Recent fixes kept per receiver while waiting for the other, five seconds at 10 Hz.
+const MAX_PENDING: usize = 50;
+
+/// One satellite as seen by the two receivers compared.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SatelliteComparison {
+    pub id: SatelliteId,
+    /// C/N0 in dB-Hz of the reference and the other receiver, `None` where not tracked.
+    pub cno: [Option<u8>; 2],
+}
+
+impl SatelliteComparison {
+    /// Other minus reference C/N0 in dB, when both track the satellite.
+    pub fn difference(&self) -> Option<i16> {
+        match self.cno {
+            [Some(a), Some(b)] => Some(b as i16 - a as i16),
+            _ => None,
+        }
+    }
+}
+
+/// The satellites tracked by either receiver, in order.
+pub fn compare_satellites(
+    reference: &[Satellite],
+    other: &[Satellite],
+) -> Vec<SatelliteComparison> {
+    let tracked = |list: &[Satellite]| -> BTreeMap<SatelliteId, u8> {
+        list.iter()
+            .filter(|s| s.cno > 0)
+            .map(|s| (s.id, s.cno))
+            .collect()
+    };
+    let (a, b) = (tracked(reference), tracked(other));
+    let mut ids: Vec<SatelliteId> = a.keys().chain(b.keys()).copied().collect();
+    ids.sort();
+    ids.dedup();
+    ids.into_iter()
+        .map(|id| SatelliteComparison {
+            id,
+            cno: [a.get(&id).copied(), b.get(&id).copied()],
+        })
+        .collect()
+}
+
+/// Mean C/N0 difference and the number of satellites in common per constellation.
+pub fn mean_cno_difference(
+    satellites: &[SatelliteComparison],
+) -> BTreeMap<Constellation, (f64, usize)> {
+    let mut sums: BTreeMap<Constellation, (f64, usize)> = BTreeMap::new();
+    for (s, d) in satellites.iter().filter_map(|s| Some((s, s.difference()?))) {
+        let sum = sums.entry(s.id.constellation).or_default();
+        sum.0 += d as f64;
+        sum.1 += 1;
+    }
+    sums.into_iter()
+        .map(|(c, (sum, n))| (c, (sum / n as f64, n)))
+        .collect()
+}
+
+/// Position of the other receiver relative to the reference at one time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PositionDifference {
+    /// Seconds since the comparison started.
+    pub time: f64,
+    /// East, north and up in metres.
+    pub enu: [f64; 3],
+}
+
+impl PositionDifference {
+    pub fn horizontal(&self) -> f64 {
+        self.enu[0].hypot(self.enu[1])
+    }
+}
+
+/// Receiver epoch, UTC milliseconds of the day, and geodetic position of a fix.
+type EpochPosition = (u32, (f64, f64, f64));
+
+/// Differences between the fixes of two receivers over time.
+#[derive(Debug, Clone, Default)]
+pub struct FixComparison {
+    pub samples: VecDeque<PositionDifference>,
+    /// Recent fixes of the reference and the other receiver not yet paired, oldest first.
+    pending: [VecDeque<EpochPosition>; 2],
+    /// Receiver epoch of the last sample.
+    last: Option<u32>,
+}
+
+impl FixComparison {
+    /// Adds the latest fixes of the two receivers at `time` seconds since the comparison
+    /// started, and records the difference of every epoch both have reported, at most once
+    /// per sample interval of receiver time. Fixes are buffered so receivers at different
+    /// rates or with delayed output still pair up; a fix already seen is not added again.
+    /// Fixes without an epoch, a position or a height are skipped.
+    pub fn add(&mut self, time: f64, reference: Option<&Fix>, other: Option<&Fix>) {
+        for (pending, fix) in self.pending.iter_mut().zip([reference, other]) {
+            let Some(fix) = fix.filter(|f| f.fix_type.has_position()) else {
+                continue;
+            };
+            let (Some(epoch), Some(height)) = (fix.epoch, fix.height) else {
+                continue;
+            };
+            if pending.back().is_some_and(|(e, _)| *e == epoch) {
+                continue;
+            }
+            if pending.len() == MAX_PENDING {
+                pending.pop_front();
+            }
+            pending.push_back((epoch, (fix.latitude, fix.longitude, height)));
+        }
+
+        while let Some((i, j)) = self.pending[0].iter().enumerate().find_map(|(i, (e, _))| {
+            let j = self.pending[1].iter().position(|(o, _)| o == e)?;
+            Some((i, j))
+        }) {
+            let (epoch, a) = self.pending[0][i];
+            let (_, b) = self.pending[1][j];
+            // Older unpaired fixes will not find a partner any more.
+            self.pending[0].drain(..=i);
+            self.pending[1].drain(..=j);
+            // Receiver time going backwards, e.g. after a restart, wraps to a long interval.
+            let since_last = |last: u32| (epoch as i64 - last as i64).rem_euclid(DAY_MS as i64);
+            if self
+                .last
+                .is_some_and(|last| since_last(last) < SAMPLE_INTERVAL_MS as i64)
+            {
+                continue;
+            }
+            let enu = geodesy::ecef_to_enu(geodesy::geodetic_to_ecef(b.0, b.1, b.2), a);
+            if self.samples.len() == MAX_SAMPLES {
+                self.samples.pop_front();
+            }
+            self.samples.push_back(PositionDifference { time, enu });
+            self.last = Some(epoch);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Mean east, north and up difference and the RMS horizontal difference, in metres.
+    pub fn statistics(&self) -> Option<([f64; 3], f64)> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        let n = self.samples.len() as f64;
+        let mean = [0, 1, 2].map(|i| self.samples.iter().map(|s| s.enu[i]).sum::<f64>() / n);
+        let rms = (self
+            .samples
+            .iter()
+            .map(|s| s.horizontal().powi(2))
+            .sum::<f64>()
+            / n)
+            .sqrt();
+        Some((mean, rms))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gnss::FixType;
+
+    fn satellite(constellation: Constellation, prn: u8, cno: u8) -> Satellite {
+        Satellite {
+            id: SatelliteId::new(constellation, prn),
+            cno,
+            elevation: None,
+            azimuth: None,
+            used: cno > 0,
+        }
+    }
+
+    #[test]
+    fn compares_satellites_and_fixes() {
+        let a = [
+            satellite(Constellation::Gps, 3, 40),
+            satellite(Constellation::Gps, 7, 35),
+            satellite(Constellation::Galileo, 2, 30),
+            satellite(Constellation::Galileo, 9, 0),
+        ];
+        let b = [
+            satellite(Constellation::Gps, 3, 44),
+            satellite(Constellation::Gps, 7, 37),
+            satellite(Constellation::Galileo, 9, 28),
+        ];
+        let list = compare_satellites(&a, &b);
+        assert_eq!(list.len(), 4);
+        assert_eq!(list[0].difference(), Some(4));
+        let galileo: Vec<_> = list
+            .iter()
+            .filter(|s| s.id.constellation == Constellation::Galileo)
+            .map(|s| s.cno)
+            .collect();
+        assert_eq!(galileo, [[Some(30), None], [None, Some(28)]]);
+        let means = mean_cno_difference(&list);
+        assert_eq!(means[&Constellation::Gps], (3.0, 2));
+        assert!(!means.contains_key(&Constellation::Galileo));
+
+        let fix = |epoch: u32, latitude: f64, height: f64| Fix {
+            fix_type: FixType::Fix3D,
+            latitude,
+            longitude: 5.0,
+            height: Some(height),
+            height_msl: None,
+            num_sv: 10,
+            h_acc: None,
+            v_acc: None,
+            epoch: Some(epoch),
+        };
+        let mut comparison = FixComparison::default();
+        let (reference, other) = (fix(1_000, 52.0, 40.0), fix(1_000, 52.00001, 42.0));
+        comparison.add(0.0, Some(&reference), Some(&other));
+        // The same epoch again, and a faster receiver within the sample interval.
+        comparison.add(0.5, Some(&reference), Some(&other));
+        comparison.add(
+            0.6,
+            Some(&fix(1_200, 52.0, 40.0)),
+            Some(&fix(1_200, 52.0, 40.0)),
+        );
+        // The other receiver stalled, or gone.
+        let stale = fix(1_000, 52.0, 40.0);
+        comparison.add(1.0, Some(&fix(2_000, 52.0, 40.0)), Some(&stale));
+        comparison.add(1.1, Some(&fix(2_000, 52.0, 40.0)), None);
+        comparison.add(
+            2.0,
+            Some(&fix(3_000, 52.0, 40.0)),
+            Some(&fix(3_000, 52.00001, 42.0)),
+        );
+        assert_eq!(comparison.samples.len(), 2);
+        assert_eq!(comparison.samples[1].time, 2.0);
+        let ([east, north, up], rms) = comparison.statistics().unwrap();
+        assert!(east.abs() < 1e-3);
+        assert!((north - 1.113).abs() < 0.01, "{}", north);
+        assert!((up - 2.0).abs() < 1e-3);
+        assert!((rms - north).abs() < 1e-3);
+    }
+
+    #[test]
+    fn pairs_delayed_epochs() {
+        let fix = |epoch: u32, latitude: f64| Fix {
+            fix_type: FixType::Fix3D,
+            latitude,
+            longitude: 5.0,
+            height: Some(40.0),
+            height_msl: None,
+            num_sv: 10,
+            h_acc: None,
+            v_acc: None,
+            epoch: Some(epoch),
+        };
+        // Every half second the reference reports at 1 Hz, and the other at 2 Hz with its
+        // output 1.5 s late, so their latest fixes never share an epoch.
+        let mut comparison = FixComparison::default();
+        for step in 0..20u32 {
+            let reference = fix(step / 2 * 1_000, 52.0);
+            let other = step.checked_sub(3).map(|s| fix(s * 500, 52.00001));
+            comparison.add(step as f64 / 2.0, Some(&reference), other.as_ref());
+        }
+        assert_eq!(comparison.samples.len(), 9);
+        assert!(comparison
+            .samples
+            .iter()
+            .all(|s| (s.enu[1] - 1.113).abs() < 0.01));
+    }
+}
@@ -0,0 +1,257 @@
+use chrono::{DateTime, Datelike, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::geodesy;
+
+/// Milliarcseconds to radians.
+const MAS: f64 = std::f64::consts::PI / (180.0 * 3_600_000.0);
+
+/// A 14-parameter Helmert transformation in the IERS (position vector) convention:
+/// `X' = X + T + D X + R X` with `R = [[0, -r3, r2], [r3, 0, -r1], [-r2, r1, 0]]`, each
+/// parameter changing linearly from the reference epoch. A 7-parameter transformation is
+/// one with all rates zero.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Helmert {
+    /// Translation in millimetres.
+    pub t: [f64; 3],
+    /// Scale in parts per billion.
+    pub d: f64,
+    /// Rotation in milliarcseconds.
+    pub r: [f64; 3],
+    /// Translation rate in millimetres per year.
+    pub t_rate: [f64; 3],
+    /// Scale rate in parts per billion per year.
+    pub d_rate: f64,
+    /// Rotation rate in milliarcseconds per year.
+    pub r_rate: [f64; 3],
+    /// Reference epoch of the parameters as a decimal year.
+    pub epoch: f64,
+}
+
+impl Default for Helmert {
+    fn default() -> Self {
+        Self {
+            t: [0.0; 3],
+            d: 0.0,
+            r: [0.0; 3],
+            t_rate: [0.0; 3],
+            d_rate: 0.0,
+            r_rate: [0.0; 3],
+            epoch: 2010.0,
+        }
+    }
+}
+
+impl Helmert {
+    /// Parameters published in the coordinate frame convention (NGS, ICSM) have their
+    /// rotations negated.
+    const fn coordinate_frame(mut self) -> Self {
+        self.r = [-self.r[0], -self.r[1], -self.r[2]];
+        self.r_rate = [-self.r_rate[0], -self.r_rate[1], -self.r_rate[2]];
+        self
+    }
+
+    /// Transforms ECEF metres at `epoch` (decimal year).
+    pub fn apply(&self, ecef: [f64; 3], epoch: f64) -> [f64; 3] {
+        let dt = epoch - self.epoch;
+        let t: [f64; 3] = std::array::from_fn(|i| (self.t[i] + self.t_rate[i] * dt) * 1e-3);
+        let d = (self.d + self.d_rate * dt) * 1e-9;
+        let [r1, r2, r3]: [f64; 3] =
+            std::array::from_fn(|i| (self.r[i] + self.r_rate[i] * dt) * MAS);
+        let [x, y, z] = ecef;
+        [
+            x + t[0] + d * x - r3 * y + r2 * z,
+            y + t[1] + r3 * x + d * y - r1 * z,
+            z + t[2] - r2 * x + r1 * y + d * z,
+        ]
+    }
+}
+
+// ITRF2020 to ITRF2014, IERS.
+const ITRF2020_TO_ITRF2014: Helmert = Helmert {
+    t: [-1.4, -0.9, 1.4],
+    d: -0.42,
+    r: [0.0, 0.0, 0.0],
+    t_rate: [0.0, -0.1, 0.2],
+    d_rate: 0.0,
+    r_rate: [0.0, 0.0, 0.0],
+    epoch: 2015.0,
+};
+
+// ITRF2014 to ETRF2000, EUREF Technical Note 1.
+const ITRF2014_TO_ETRF2000: Helmert = Helmert {
+    t: [54.7, 52.2, -74.1],
+    d: 2.12,
+    r: [1.701, 10.290, -16.632],
+    t_rate: [0.1, 0.1, -1.9],
+    d_rate: 0.11,
+    r_rate: [0.081, 0.490, -0.792],
+    epoch: 2010.0,
+};
+
+// ITRF2014 to NAD83(2011), NGS (Pearson & Snay), coordinate frame convention.
+const ITRF2014_TO_NAD83_2011: Helmert = Helmert {
+    t: [1005.30, -1902.10, -541.57],
+    d: 0.36891,
+    r: [26.78138, -0.42027, 10.93206],
+    t_rate: [0.79, -0.60, -1.44],
+    d_rate: -0.07201,
+    r_rate: [0.06667, -0.75744, -0.05133],
+    epoch: 2010.0,
+}
+.coordinate_frame();
+
+// ITRF2014 to GDA2020, ICSM GDA2020 Technical Manual, coordinate frame convention. Only the
+// Australian plate motion, zero at 2020.0.
+const ITRF2014_TO_GDA2020: Helmert = Helmert {
+    t: [0.0, 0.0, 0.0],
+    d: 0.0,
+    r: [0.0, 0.0, 0.0],
+    t_rate: [0.0, 0.0, 0.0],
+    d_rate: 0.0,
+    r_rate: [1.50379, 1.18346, 1.20716],
+    epoch: 2020.0,
+}
+.coordinate_frame();
+
+/// Reference frames positions can be shown in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Datum {
+    /// As reported by the receiver.
+    Wgs84,
+    Itrf2020,
+    Itrf2014,
+    Etrf2000,
+    Nad83_2011,
+    Gda2020,
+    /// User supplied Helmert parameters from WGS84.
+    Custom,
+}
+
+impl Datum {
+    pub const ALL: [Datum; 7] = [
+        Datum::Wgs84,
+        Datum::Itrf2020,
+        Datum::Itrf2014,
+        Datum::Etrf2000,
+        Datum::Nad83_2011,
+        Datum::Gda2020,
+        Datum::Custom,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Datum::Wgs84 => "WGS84",
+            Datum::Itrf2020 => "ITRF2020",
+            Datum::Itrf2014 => "ITRF2014",
+            Datum::Etrf2000 => "ETRF2000",
+            Datum::Nad83_2011 => "NAD83(2011)",
+            Datum::Gda2020 => "GDA2020",
+            Datum::Custom => "Custom",
+        }
+    }
+
+    /// The transformations from WGS84, in order. Current WGS84 realizations agree with
+    /// ITRF2020 at the centimetre level, so the two are treated as the same frame.
+    fn chain(&self, custom: &Helmert) -> Vec<Helmert> {
+        match self {
+            Datum::Wgs84 | Datum::Itrf2020 => vec![],
+            Datum::Itrf2014 => vec![ITRF2020_TO_ITRF2014],
+            Datum::Etrf2000 => vec![ITRF2020_TO_ITRF2014, ITRF2014_TO_ETRF2000],
+            Datum::Nad83_2011 => vec![ITRF2020_TO_ITRF2014, ITRF2014_TO_NAD83_2011],
+            Datum::Gda2020 => vec![ITRF2020_TO_ITRF2014, ITRF2014_TO_GDA2020],
+            Datum::Custom => vec![*custom],
+        }
+    }
+
+    /// Transforms a WGS84 position (degrees, ellipsoidal metres) observed at `epoch` (decimal
+    /// year) into this datum, returned on the same ellipsoid.
+    pub fn from_wgs84(
+        &self,
+        latitude: f64,
+        longitude: f64,
+        height: f64,
+        epoch: f64,
+        custom: &Helmert,
+    ) -> (f64, f64, f64) {
+        let chain = self.chain(custom);
+        if chain.is_empty() {
+            return (latitude, longitude, height);
+        }
+        let ecef = geodesy::geodetic_to_ecef(latitude, longitude, height);
+        let ecef = chain.iter().fold(ecef, |x, h| h.apply(x, epoch));
+        geodesy::ecef_to_geodetic(ecef)
+    }
+}
+
+/// A time as a decimal year, the epoch used by the Helmert rates.
+pub fn decimal_year(time: DateTime<Utc>) -> f64 {
+    let year = time.year();
+    let start = |y: i32| {
+        chrono::NaiveDate::from_ymd_opt(y, 1, 1)
+            .and_then(|d| d.and_hms_opt(0, 0, 0))
+            .map_or(0, |d| d.and_utc().timestamp())
+    };
+    let (this, next) = (start(year), start(year + 1));
+    year as f64 + (time.timestamp() - this) as f64 / (next - this) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Horizontal offset (east, north) in metres of a transformed position from the original.
+    fn shift(datum: Datum, lat: f64, lon: f64, epoch: f64) -> (f64, f64) {
+        let (lat2, lon2, h2) = datum.from_wgs84(lat, lon, 50.0, epoch, &Helmert::default());
+        let ecef = geodesy::geodetic_to_ecef(lat2, lon2, h2);
+        let [e, n, _] = geodesy::ecef_to_enu(ecef, (lat, lon, 50.0));
+        (e, n)
+    }
+
+    #[test]
+    fn plate_fixed_frames_lag_behind_itrf() {
+        // GDA2020 equals ITRF2014 at 2020.0 and Australia moves about 7 cm/yr north-east.
+        let (e, n) = shift(Datum::Gda2020, -33.86, 151.21, 2020.0);
+        assert!(e.abs() < 0.01 && n.abs() < 0.01);
+        let (e, n) = shift(Datum::Gda2020, -33.86, 151.21, 2025.0);
+        let speed = e.hypot(n) / 5.0;
+        assert!((0.05..0.08).contains(&speed) && n < 0.0);
+
+        // Europe moves about 2.5 cm/yr north-east in ITRF; ETRF2000 is fixed to the plate.
+        let (e, n) = shift(Datum::Etrf2000, 48.85, 2.29, 2020.0);
+        assert!(e < 0.0 && n < 0.0 && (0.4..0.9).contains(&e.hypot(n)));
+
+        // NAD83 is offset from ITRF by a metre or two across the continental US.
+        let (e, n) = shift(Datum::Nad83_2011, 39.0, -98.0, 2020.0);
+        assert!((0.5..2.5).contains(&e.hypot(n)));
+    }
+
+    #[test]
+    fn helmert_parameters() {
+        assert_eq!(
+            Datum::Itrf2020.from_wgs84(10.0, 20.0, 30.0, 2024.0, &Helmert::default()),
+            (10.0, 20.0, 30.0)
+        );
+        // A pure 1 m translation and 1 ppm scale, as a 7-parameter transformation.
+        let h = Helmert {
+            t: [1000.0, 0.0, 0.0],
+            d: 1000.0,
+            ..Default::default()
+        };
+        let out = h.apply([6_378_137.0, 0.0, 0.0], 2030.0);
+        assert!((out[0] - (6_378_137.0 + 1.0 + 6.378_137)).abs() < 1e-6);
+        // Rates are applied relative to the reference epoch.
+        let h = Helmert {
+            t_rate: [10.0, 0.0, 0.0],
+            epoch: 2020.0,
+            ..Default::default()
+        };
+        assert!((h.apply([0.0; 3], 2022.5)[0] - 0.025).abs() < 1e-12);
+    }
+
+    #[test]
+    fn decimal_years() {
+        let t = DateTime::parse_from_rfc3339("2021-07-02T12:00:00Z").unwrap();
+        assert!((decimal_year(t.with_timezone(&Utc)) - 2021.5).abs() < 1e-9);
+    }
+}
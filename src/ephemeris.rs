@@ -0,0 +1,665 @@
+use std::collections::BTreeMap;
+use std::f64::consts::PI;
+use std::io::{self, ErrorKind};
+
+use crate::gnss::{Constellation, SatelliteId};
+use crate::time_scales::{
+    CalendarTime, BDT_WEEK_OFFSET, GPS_BDT_OFFSET, GPS_EPOCH_UNIX, SECONDS_PER_WEEK,
+};
+
+/// Speed of light in m/s.
+pub const SPEED_OF_LIGHT: f64 = 299_792_458.0;
+/// GPS L1 and Galileo E1 carrier frequency in Hz.
+pub const FREQ_L1: f64 = 1_575.42e6;
+/// BeiDou B1I carrier frequency in Hz.
+pub const FREQ_B1I: f64 = 1_561.098e6;
+/// Earth rotation rate in rad/s, as used by GPS and for the Sagnac correction.
+pub const EARTH_ROTATION: f64 = 7.292_115_146_7e-5;
+
+/// Earth's gravitational constant in m³/s² of a constellation's interface specification.
+fn gm(constellation: Constellation) -> f64 {
+    match constellation {
+        Constellation::Gps | Constellation::Qzss => 3.986_005e14,
+        _ => 3.986_004_418e14,
+    }
+}
+
+fn earth_rotation(constellation: Constellation) -> f64 {
+    match constellation {
+        Constellation::BeiDou => 7.292_115e-5,
+        _ => EARTH_ROTATION,
+    }
+}
+
+/// Seconds from the reference time an ephemeris is used for.
+fn max_age(constellation: Constellation) -> f64 {
+    match constellation {
+        Constellation::Galileo => 14_400.0,
+        Constellation::BeiDou => 21_600.0,
+        _ => 7_200.0,
+    }
+}
+
+/// Broadcast Keplerian ephemeris and clock parameters of one satellite.
+///
+/// Times are seconds since the GPS epoch on the GPS time scale, whatever the satellite's own
+/// system time, so they can be compared directly with receiver time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ephemeris {
+    pub id: SatelliteId,
+    /// Clock reference time.
+    pub toc: f64,
+    /// Ephemeris reference time.
+    pub toe: f64,
+    /// Clock bias (s), drift (s/s) and drift rate (s/s²).
+    pub af0: f64,
+    pub af1: f64,
+    pub af2: f64,
+    /// Issue of data of the ephemeris: IODE for GPS, IODnav for Galileo, AODE for BeiDou.
+    pub iode: u32,
+    /// Issue of data of the clock: IODC for GPS, AODC for BeiDou, IODnav for Galileo.
+    pub iodc: u32,
+    pub sqrt_a: f64,
+    pub e: f64,
+    /// Angles in radians and rates in rad/s.
+    pub i0: f64,
+    pub omega0: f64,
+    pub omega: f64,
+    pub m0: f64,
+    pub delta_n: f64,
+    pub omega_dot: f64,
+    pub idot: f64,
+    /// Harmonic corrections: radians for the latitude and inclination terms, metres for the
+    /// radius terms.
+    pub cuc: f64,
+    pub cus: f64,
+    pub crc: f64,
+    pub crs: f64,
+    pub cic: f64,
+    pub cis: f64,
+    /// Satellite health; zero when healthy.
+    pub health: u32,
+    /// Group delay of the single frequency signal in seconds: TGD for GPS L1 C/A, BGD E5b/E1
+    /// for Galileo E1, TGD1 for BeiDou B1I.
+    pub tgd: f64,
+    /// User range accuracy (URA, SISA) in metres.
+    pub accuracy: f64,
+}
+
+impl Ephemeris {
+    /// BeiDou geostationary satellites, whose orbits are broadcast in an inclined frame.
+    pub fn is_geo(&self) -> bool {
+        self.id.constellation == Constellation::BeiDou && (self.id.prn <= 5 || self.id.prn >= 59)
+    }
+
+    /// Whether the ephemeris may be used at time `t`.
+    pub fn valid_at(&self, t: f64) -> bool {
+        (t - self.toe).abs() <= max_age(self.id.constellation)
+    }
+
+    /// Seconds since the GPS epoch of the start of the week the ephemeris reference time is in,
+    /// on the satellite's system time scale.
+    fn week_start(&self) -> f64 {
+        let offset = match self.id.constellation {
+            Constellation::BeiDou => GPS_BDT_OFFSET as f64,
+            _ => 0.0,
+        };
+        let week = SECONDS_PER_WEEK as f64;
+        ((self.toe - offset) / week).floor() * week + offset
+    }
+
+    fn eccentric_anomaly(&self, tk: f64) -> f64 {
+        let a = self.sqrt_a * self.sqrt_a;
+        let n = (gm(self.id.constellation) / (a * a * a)).sqrt() + self.delta_n;
+        let m = self.m0 + n * tk;
+        let mut e = m;
+        for _ in 0..30 {
+            let next = m + self.e * e.sin();
+            if (next - e).abs() < 1e-14 {
+                return next;
+            }
+            e = next;
+        }
+        e
+    }
+
+    /// Satellite clock offset in seconds at time `t`, including the relativistic correction
+    /// and the group delay of the single frequency signal.
+    pub fn clock(&self, t: f64) -> f64 {
+        let dt = t - self.toc;
+        let ek = self.eccentric_anomaly(t - self.toe);
+        let f = -2.0 * gm(self.id.constellation).sqrt() / (SPEED_OF_LIGHT * SPEED_OF_LIGHT);
+        self.af0 + self.af1 * dt + self.af2 * dt * dt + f * self.e * self.sqrt_a * ek.sin()
+            - self.tgd
+    }
+
+    /// Satellite antenna position in ECEF metres at time `t`, in the frame at time `t`.
+    pub fn position(&self, t: f64) -> [f64; 3] {
+        self.orbit_position(t, self.is_geo())
+    }
+
+    /// `geo` selects the inclined frame BeiDou broadcasts its geostationary orbits in.
+    fn orbit_position(&self, t: f64, geo: bool) -> [f64; 3] {
+        let tk = t - self.toe;
+        let a = self.sqrt_a * self.sqrt_a;
+        let ek = self.eccentric_anomaly(tk);
+        let nu = ((1.0 - self.e * self.e).sqrt() * ek.sin()).atan2(ek.cos() - self.e);
+        let phi = nu + self.omega;
+        let (sin2, cos2) = (2.0 * phi).sin_cos();
+        let u = phi + self.cus * sin2 + self.cuc * cos2;
+        let r = a * (1.0 - self.e * ek.cos()) + self.crs * sin2 + self.crc * cos2;
+        let i = self.i0 + self.idot * tk + self.cis * sin2 + self.cic * cos2;
+        let (x, y) = (r * u.cos(), r * u.sin());
+
+        let we = earth_rotation(self.id.constellation);
+        let toe_sow = self.toe - self.week_start();
+        if geo {
+            // Inertial-ish frame first, then rotated by -5° about x and by the earth rotation.
+            let omega = self.omega0 + self.omega_dot * tk - we * toe_sow;
+            let (so, co) = omega.sin_cos();
+            let (si, ci) = i.sin_cos();
+            let g = [x * co - y * ci * so, x * so + y * ci * co, y * si];
+            let (sx, cx) = (-5f64).to_radians().sin_cos();
+            let (sz, cz) = (we * tk).sin_cos();
+            let (gy, gz) = (g[1] * cx + g[2] * sx, -g[1] * sx + g[2] * cx);
+            return [g[0] * cz + gy * sz, -g[0] * sz + gy * cz, gz];
+        }
+        let omega = self.omega0 + (self.omega_dot - we) * tk - we * toe_sow;
+        let (so, co) = omega.sin_cos();
+        let (si, ci) = i.sin_cos();
+        [x * co - y * ci * so, x * so + y * ci * co, y * si]
+    }
+}
+
+/// GPS broadcast ionosphere model (IS-GPS-200, 20.3.3.5.2.5).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Klobuchar {
+    pub alpha: [f64; 4],
+    pub beta: [f64; 4],
+}
+
+impl Klobuchar {
+    /// Ionospheric delay in metres on the GPS L1 frequency, for a receiver at `latitude` and
+    /// `longitude` seeing a satellite at `azimuth` and `elevation` (all degrees), at `tow`
+    /// seconds of the GPS week.
+    pub fn delay(
+        &self,
+        latitude: f64,
+        longitude: f64,
+        azimuth: f64,
+        elevation: f64,
+        tow: f64,
+    ) -> f64 {
+        // The model works in semicircles.
+        let el = elevation / 180.0;
+        let az = azimuth.to_radians();
+        let psi = 0.0137 / (el + 0.11) - 0.022;
+        let phi_i = (latitude / 180.0 + psi * az.cos()).clamp(-0.416, 0.416);
+        let lambda_i = longitude / 180.0 + psi * az.sin() / (phi_i * PI).cos();
+        let phi_m = phi_i + 0.064 * ((lambda_i - 1.617) * PI).cos();
+        let t = (4.32e4 * lambda_i + tow).rem_euclid(86_400.0);
+        let f = 1.0 + 16.0 * (0.53 - el).powi(3);
+        let poly = |c: &[f64; 4]| c.iter().rev().fold(0.0, |acc, c| acc * phi_m + c);
+        let amplitude = poly(&self.alpha).max(0.0);
+        let period = poly(&self.beta).max(72_000.0);
+        let x = 2.0 * PI * (t - 50_400.0) / period;
+        let delay = match x.abs() < 1.57 {
+            true => 5e-9 + amplitude * (1.0 - x * x / 2.0 + x.powi(4) / 24.0),
+            false => 5e-9,
+        };
+        f * delay * SPEED_OF_LIGHT
+    }
+
+    /// Ionospheric delay in metres on the BeiDou B1I frequency, with BeiDou's variant of the
+    /// model (BDS-SIS-ICD-B1I 5.2.4.7): a single layer at 375 km with the pierce point in
+    /// geographic rather than geomagnetic latitude. The 14 s between `tow` in GPS time and
+    /// BDT are negligible here.
+    pub fn bds_delay(
+        &self,
+        latitude: f64,
+        longitude: f64,
+        azimuth: f64,
+        elevation: f64,
+        tow: f64,
+    ) -> f64 {
+        const RATIO: f64 = 6_378.0 / (6_378.0 + 375.0);
+        let (el, az) = (elevation.to_radians(), azimuth.to_radians());
+        let (lat, lon) = (latitude.to_radians(), longitude.to_radians());
+        let psi = PI / 2.0 - el - (RATIO * el.cos()).asin();
+        let phi_m = (lat.sin() * psi.cos() + lat.cos() * psi.sin() * az.cos()).asin();
+        let lambda_m = lon + (psi.sin() * az.sin() / phi_m.cos()).asin();
+        let t = (tow + lambda_m / PI * 43_200.0).rem_euclid(86_400.0);
+        let poly = |c: &[f64; 4]| {
+            let phi = (phi_m / PI).abs();
+            c.iter().rev().fold(0.0, |acc, c| acc * phi + c)
+        };
+        let amplitude = poly(&self.alpha).max(0.0);
+        let period = poly(&self.beta).clamp(72_000.0, 172_800.0);
+        let delay = match (t - 50_400.0).abs() < period / 4.0 {
+            true => 5e-9 + amplitude * (2.0 * PI * (t - 50_400.0) / period).cos(),
+            false => 5e-9,
+        };
+        delay / (1.0 - (RATIO * el.cos()).powi(2)).sqrt() * SPEED_OF_LIGHT
+    }
+}
+
+/// Galileo broadcast ionosphere model coefficients (NeQuick G effective ionisation level).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NeQuick {
+    /// ai0 in sfu, ai1 in sfu/degree, ai2 in sfu/degree².
+    pub ai: [f64; 3],
+}
+
+/// Reduced precision orbit and clock of a satellite from the almanac, good to a few
+/// kilometres for planning and visibility rather than positioning.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Almanac {
+    pub id: SatelliteId,
+    /// Reference time, seconds since the GPS epoch on the GPS time scale.
+    pub toa: f64,
+    pub sqrt_a: f64,
+    pub e: f64,
+    /// Angles in radians and rates in rad/s; `i0` is the full inclination.
+    pub i0: f64,
+    pub omega0: f64,
+    pub omega: f64,
+    pub m0: f64,
+    pub omega_dot: f64,
+    /// Clock bias (s) and drift (s/s).
+    pub af0: f64,
+    pub af1: f64,
+    /// Satellite health as broadcast in the almanac; zero when healthy.
+    pub health: u32,
+}
+
+impl Almanac {
+    /// Satellite position in ECEF metres at time `t`.
+    pub fn position(&self, t: f64) -> [f64; 3] {
+        // Almanac orbits are Keplerian in the usual frame, also for BeiDou GEO satellites.
+        Ephemeris {
+            id: self.id,
+            toc: self.toa,
+            toe: self.toa,
+            af0: self.af0,
+            af1: self.af1,
+            af2: 0.0,
+            iode: 0,
+            iodc: 0,
+            sqrt_a: self.sqrt_a,
+            e: self.e,
+            i0: self.i0,
+            omega0: self.omega0,
+            omega: self.omega,
+            m0: self.m0,
+            delta_n: 0.0,
+            omega_dot: self.omega_dot,
+            idot: 0.0,
+            cuc: 0.0,
+            cus: 0.0,
+            crc: 0.0,
+            crs: 0.0,
+            cic: 0.0,
+            cis: 0.0,
+            health: self.health,
+            tgd: 0.0,
+            accuracy: 0.0,
+        }
+        .orbit_position(t, false)
+    }
+}
+
+/// Broadcast relation of a system time scale to UTC (IS-GPS-200, 20.3.3.5.2.4).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UtcParameters {
+    /// Bias (s) and drift (s/s) of the fractional offset, referenced to `tot`.
+    pub a0: f64,
+    pub a1: f64,
+    /// Reference time, seconds since the GPS epoch on the GPS time scale.
+    pub tot: f64,
+    /// Whole seconds of system time minus UTC, before and after `leap_time`.
+    pub leap_seconds: i32,
+    pub leap_seconds_future: i32,
+    /// End of the day the announced leap second count takes effect, seconds since the GPS
+    /// epoch.
+    pub leap_time: f64,
+}
+
+impl UtcParameters {
+    /// System time minus UTC in seconds at time `t`, away from the leap second itself.
+    pub fn offset(&self, t: f64) -> f64 {
+        let leap = match t >= self.leap_time {
+            true => self.leap_seconds_future,
+            false => self.leap_seconds,
+        };
+        leap as f64 + self.a0 + self.a1 * (t - self.tot)
+    }
+}
+
+/// Broadcast navigation data: ephemerides and almanacs per satellite, ionosphere models and
+/// UTC parameters.
+#[derive(Debug, Clone, Default)]
+pub struct NavigationData {
+    /// Ephemerides of each satellite, ordered by reference time.
+    pub ephemerides: BTreeMap<SatelliteId, Vec<Ephemeris>>,
+    pub almanacs: BTreeMap<SatelliteId, Almanac>,
+    /// GPS ionosphere model, the one the local solution corrects with.
+    pub klobuchar: Option<Klobuchar>,
+    /// BeiDou's Klobuchar coefficients, for [`Klobuchar::bds_delay`].
+    pub bds_klobuchar: Option<Klobuchar>,
+    pub nequick: Option<NeQuick>,
+    pub utc: BTreeMap<Constellation, UtcParameters>,
+}
+
+impl NavigationData {
+    /// Adds an ephemeris, replacing one with the same reference time and issue of data.
+    pub fn insert(&mut self, ephemeris: Ephemeris) {
+        let list = self.ephemerides.entry(ephemeris.id).or_default();
+        list.retain(|e| e.toe != ephemeris.toe || e.iode != ephemeris.iode);
+        let at = list.partition_point(|e| e.toe <= ephemeris.toe);
+        list.insert(at, ephemeris);
+    }
+
+    pub fn merge(&mut self, other: NavigationData) {
+        for ephemeris in other.ephemerides.into_values().flatten() {
+            self.insert(ephemeris);
+        }
+        self.almanacs.extend(other.almanacs);
+        self.utc.extend(other.utc);
+        self.klobuchar = other.klobuchar.or(self.klobuchar);
+        self.bds_klobuchar = other.bds_klobuchar.or(self.bds_klobuchar);
+        self.nequick = other.nequick.or(self.nequick);
+    }
+
+    /// The ephemeris of a satellite with the latest reference time, whatever its health.
+    pub fn latest(&self, id: SatelliteId) -> Option<&Ephemeris> {
+        self.ephemerides.get(&id)?.last()
+    }
+
+    /// The healthy ephemeris of a satellite with the reference time closest to `t`, if one is
+    /// valid then.
+    pub fn select(&self, id: SatelliteId, t: f64) -> Option<&Ephemeris> {
+        self.ephemerides
+            .get(&id)?
+            .iter()
+            .filter(|e| e.health == 0 && e.valid_at(t))
+            .min_by(|a, b| (a.toe - t).abs().total_cmp(&(b.toe - t).abs()))
+    }
+
+    /// Number of satellites with at least one ephemeris.
+    pub fn satellites(&self) -> usize {
+        self.ephemerides.len()
+    }
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, message.to_owned())
+}
+
+/// A fixed width field of a line, empty past its end.
+fn column(line: &str, start: usize, width: usize) -> &str {
+    let end = (start + width).min(line.len());
+    line.get(start.min(end)..end).unwrap_or("")
+}
+
+/// A RINEX number, which may use a `D` exponent; blank fields are zero.
+fn number(field: &str) -> io::Result<f64> {
+    let field = field.trim();
+    if field.is_empty() {
+        return Ok(0.0);
+    }
+    field
+        .replace(['D', 'd'], "E")
+        .parse()
+        .map_err(|_| invalid(&format!("bad number '{}'", field)))
+}
+
+/// Seconds since the GPS epoch of a calendar epoch read as GPS time.
+fn gps_seconds(year: i32, fields: &[&str]) -> io::Result<f64> {
+    let value = |i: usize| {
+        fields
+            .get(i)
+            .and_then(|v| v.parse::<f64>().ok())
+            .ok_or_else(|| invalid("bad epoch"))
+    };
+    let time = CalendarTime {
+        year,
+        month: value(0)? as u32,
+        day: value(1)? as u32,
+        hour: value(2)? as u32,
+        minute: value(3)? as u32,
+        second: 0,
+        nanos: 0,
+    };
+    Ok((time.to_unix() - GPS_EPOCH_UNIX) as f64 + value(4)?)
+}
+
+/// Builds an ephemeris from the 29 values of a GPS, Galileo or BeiDou record, in file order
+/// starting with the clock bias. `toc` is in the satellite's system time.
+fn ephemeris_from_record(id: SatelliteId, toc: f64, v: &[f64]) -> Ephemeris {
+    let week = SECONDS_PER_WEEK as f64;
+    let (toc, toe, tgd, iodc) = match id.constellation {
+        Constellation::BeiDou => {
+            let offset = GPS_BDT_OFFSET as f64;
+            let toe = (v[21] + BDT_WEEK_OFFSET as f64) * week + v[11] + offset;
+            (toc + offset, toe, v[25], v[28] as u32)
+        }
+        Constellation::Galileo => (toc, v[21] * week + v[11], v[26], v[3] as u32),
+        _ => (toc, v[21] * week + v[11], v[25], v[26] as u32),
+    };
+    Ephemeris {
+        id,
+        toc,
+        toe,
+        af0: v[0],
+        af1: v[1],
+        af2: v[2],
+        iode: v[3] as u32,
+        iodc,
+        crs: v[4],
+        delta_n: v[5],
+        m0: v[6],
+        cuc: v[7],
+        e: v[8],
+        cus: v[9],
+        sqrt_a: v[10],
+        cic: v[12],
+        omega0: v[13],
+        cis: v[14],
+        i0: v[15],
+        crc: v[16],
+        omega: v[17],
+        omega_dot: v[18],
+        idot: v[19],
+        accuracy: v[23],
+        health: v[24] as u32,
+        tgd,
+    }
+}
+
+/// Reads a RINEX 2 GPS or RINEX 3 mixed navigation file: the GPS, Galileo and BeiDou
+/// ephemerides and the GPS ionosphere parameters. Other systems are skipped, as are Galileo
+/// F/NAV records, whose clocks refer to E5a rather than E1.
+pub fn parse_rinex_nav(text: &str) -> io::Result<NavigationData> {
+    let mut lines = text.lines();
+    let first = lines.next().ok_or_else(|| invalid("empty file"))?;
+    let version: f64 = column(first, 0, 9)
+        .trim()
+        .parse()
+        .map_err(|_| invalid("not a RINEX file"))?;
+    if column(first, 20, 1) != "N" || !(2.0..4.0).contains(&version) {
+        return Err(invalid("not a RINEX 2 or 3 navigation file"));
+    }
+
+    let mut data = NavigationData::default();
+    let (mut alpha, mut beta) = (None, None);
+    for line in lines.by_ref() {
+        let label = column(line, 60, 20).trim();
+        let values = || -> io::Result<[f64; 4]> {
+            let fields: Vec<&str> = column(line, 0, 60)
+                .split_whitespace()
+                .filter(|f| !f.starts_with("GPS"))
+                .collect();
+            let mut out = [0.0; 4];
+            for (o, f) in out.iter_mut().zip(&fields) {
+                *o = number(f)?;
+            }
+            Ok(out)
+        };
+        match label {
+            "ION ALPHA" => alpha = Some(values()?),
+            "ION BETA" => beta = Some(values()?),
+            "IONOSPHERIC CORR" if line.starts_with("GPSA") => alpha = Some(values()?),
+            "IONOSPHERIC CORR" if line.starts_with("GPSB") => beta = Some(values()?),
+            "END OF HEADER" => break,
+            _ => {}
+        }
+    }
+    if let (Some(alpha), Some(beta)) = (alpha, beta) {
+        data.klobuchar = Some(Klobuchar { alpha, beta });
+    }
+
+    // RINEX 2 records start with the PRN in two columns, RINEX 3 records with the system
+    // letter; continuation lines are indented by three or four columns.
+    let (indent, first_values) = if version < 3.0 { (3, 22) } else { (4, 23) };
+    let lines: Vec<&str> = lines.filter(|l| !l.trim().is_empty()).collect();
+    let mut at = 0;
+    while at < lines.len() {
+        let line = lines[at];
+        let (constellation, prn, epoch, year) = if version < 3.0 {
+            let year: i32 = column(line, 3, 2)
+                .trim()
+                .parse()
+                .map_err(|_| invalid("bad epoch"))?;
+            let epoch: Vec<&str> = column(line, 5, 17).split_whitespace().collect();
+            let year = if year < 80 { 2000 + year } else { 1900 + year };
+            (Constellation::Gps, column(line, 0, 2), epoch, year)
+        } else {
+            let constellation = match column(line, 0, 1) {
+                "G" => Constellation::Gps,
+                "E" => Constellation::Galileo,
+                "C" => Constellation::BeiDou,
+                "J" => Constellation::Qzss,
+                "R" => Constellation::Glonass,
+                "S" => Constellation::Sbas,
+                "I" => Constellation::NavIc,
+                _ => Constellation::Unknown,
+            };
+            let epoch: Vec<&str> = column(line, 4, 19).split_whitespace().collect();
+            let year = epoch.first().and_then(|y| y.parse().ok()).unwrap_or(0);
+            let epoch = epoch.get(1..).unwrap_or_default().to_vec();
+            (constellation, column(line, 1, 2), epoch, year)
+        };
+        let continuation = match constellation {
+            Constellation::Glonass | Constellation::Sbas => 3,
+            _ => 7,
+        };
+        let record = lines.get(at..at + 1 + continuation);
+        at += 1 + continuation;
+        let wanted = matches!(
+            constellation,
+            Constellation::Gps | Constellation::Galileo | Constellation::BeiDou
+        );
+        let Some(record) = record.filter(|_| wanted) else {
+            continue;
+        };
+
+        let mut values = Vec::with_capacity(31);
+        for i in 0..3 {
+            values.push(number(column(record[0], first_values + 19 * i, 19))?);
+        }
+        for line in &record[1..] {
+            for i in 0..4 {
+                values.push(number(column(line, indent + 19 * i, 19))?);
+            }
+        }
+        // I/NAV records have bit 0 or 9 of the data sources set, F/NAV records bit 1.
+        if constellation == Constellation::Galileo && (values[20] as u32) & 0x201 == 0 {
+            continue;
+        }
+        let prn: u8 = prn
+            .trim()
+            .parse()
+            .map_err(|_| invalid("bad satellite number"))?;
+        let toc = gps_seconds(year, &epoch)?;
+        data.insert(ephemeris_from_record(
+            SatelliteId::new(constellation, prn),
+            toc,
+            &values,
+        ));
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A GPS record from a RINEX 3 broadcast file.
+    const RINEX3: &str =
+        "     3.04           N: GNSS NAV DATA    M: MIXED            RINEX VERSION / TYPE
+GPSA   1.1176E-08  7.4506E-09 -5.9605E-08 -5.9605E-08       IONOSPHERIC CORR
+GPSB   9.0112E+04  4.9152E+04 -1.3107E+05 -3.2768E+05       IONOSPHERIC CORR
+                                                            END OF HEADER
+G05 2024 01 15 02 00 00-1.572398468852E-04-1.136868377216E-12 0.000000000000E+00
+     4.900000000000E+01-1.137500000000E+02 4.003738543478E-09 2.089876283419E+00
+    -5.999580025673E-06 5.640652612783E-03 6.442889571190E-06 5.153696207047E+03
+     9.360000000000E+04 5.029141902924E-08-1.062318437889E+00 7.450580596924E-08
+     9.794095236453E-01 2.508437500000E+02 1.096396532804E+00-7.973546988016E-09
+     1.271481539036E-10 1.000000000000E+00 2.297000000000E+03 0.000000000000E+00
+     2.000000000000E+00 0.000000000000E+00-1.024454832077E-08 4.900000000000E+01
+     8.641800000000E+04 4.000000000000E+00
+R01 2024 01 15 00 15 00 7.513165473938E-06 0.000000000000E+00 8.640000000000E+04
+     1.165590625000E+04-1.045389175415E+00 1.862645149231E-09 0.000000000000E+00
+    -1.112893945312E+04-2.347002029419E+00 2.793967723846E-09 1.000000000000E+00
+     1.990224462891E+04 2.212270736694E+00-1.862645149231E-09 0.000000000000E+00
+";
+
+    #[test]
+    fn reads_rinex_navigation() {
+        let data = parse_rinex_nav(RINEX3).unwrap();
+        assert_eq!(data.satellites(), 1);
+        let klobuchar = data.klobuchar.unwrap();
+        assert_eq!(klobuchar.beta[3], -3.2768e5);
+        let id = SatelliteId::new(Constellation::Gps, 5);
+        let eph = &data.ephemerides[&id][0];
+        assert_eq!((eph.iode, eph.iodc, eph.health), (49, 49, 0));
+        // Monday 02:00 of GPS week 2297.
+        let week_start = 2297.0 * SECONDS_PER_WEEK as f64;
+        assert_eq!(eph.toe, week_start + 93_600.0);
+        assert_eq!(eph.toc, eph.toe);
+        assert!(data.select(id, eph.toe + 7_000.0).is_some());
+        assert!(data.select(id, eph.toe + 8_000.0).is_none());
+
+        // A GPS orbit is about 26 560 km from the centre and takes half a sidereal day.
+        let p = eph.position(eph.toe);
+        let radius = (p[0] * p[0] + p[1] * p[1] + p[2] * p[2]).sqrt();
+        assert!((radius - 26_560e3).abs() < 300e3, "{}", radius);
+        let clock = eph.clock(eph.toe);
+        assert!((clock - (-1.572398468852e-4 + 1.024454832077e-8)).abs() < 1e-7);
+    }
+
+    #[test]
+    fn klobuchar_delay() {
+        // The worked example of the model: a few metres by day, 5 ns times the obliquity at
+        // night.
+        let model = Klobuchar {
+            alpha: [3.82e-8, 1.49e-8, -1.79e-7, 0.0],
+            beta: [1.43e5, 0.0, -3.28e5, 1.13e5],
+        };
+        let day = model.delay(40.0, -100.0, 210.0, 20.0, 593_100.0);
+        assert!((day - 23.784).abs() < 0.01, "{}", day);
+        let zenith_night = model.delay(40.0, -100.0, 0.0, 90.0, 30_000.0);
+        assert!((zenith_night - 5e-9 * SPEED_OF_LIGHT).abs() < 0.01);
+
+        // BeiDou's variant peaks at 14:00 local time at the pierce point and has the same
+        // night time floor.
+        let noon = model.bds_delay(40.0, 0.0, 0.0, 90.0, 50_400.0);
+        let expected =
+            5e-9 + 3.82e-8 + 1.49e-8 * 40.0 / 180.0 - 1.79e-7 * (40.0f64 / 180.0).powi(2);
+        assert!((noon - expected * SPEED_OF_LIGHT).abs() < 0.01, "{}", noon);
+        let night = model.bds_delay(40.0, 0.0, 0.0, 90.0, 10_000.0);
+        assert!((night - 5e-9 * SPEED_OF_LIGHT).abs() < 0.01);
+        let low = model.bds_delay(40.0, 0.0, 0.0, 15.0, 10_000.0);
+        assert!((low / night - 2.441).abs() < 0.01, "{}", low / night);
+    }
+}
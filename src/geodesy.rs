@@ -0,0 +1,612 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// WGS84 semi-major axis in metres.
+pub const WGS84_A: f64 = 6_378_137.0;
+/// WGS84 flattening.
+pub const WGS84_F: f64 = 1.0 / 298.257_223_563;
+/// WGS84 first eccentricity squared.
+pub const WGS84_E2: f64 = WGS84_F * (2.0 - WGS84_F);
+
+/// Converts latitude/longitude (degrees) and ellipsoidal height (m) to ECEF metres.
+pub fn geodetic_to_ecef(latitude: f64, longitude: f64, height: f64) -> [f64; 3] {
+    let (phi, lambda) = (latitude.to_radians(), longitude.to_radians());
+    let n = WGS84_A / (1.0 - WGS84_E2 * phi.sin().powi(2)).sqrt();
+    [
+        (n + height) * phi.cos() * lambda.cos(),
+        (n + height) * phi.cos() * lambda.sin(),
+        (n * (1.0 - WGS84_E2) + height) * phi.sin(),
+    ]
+}
+
+/// Converts ECEF metres to latitude/longitude (degrees) and ellipsoidal height (m).
+pub fn ecef_to_geodetic(ecef: [f64; 3]) -> (f64, f64, f64) {
+    let [x, y, z] = ecef;
+    let p = x.hypot(y);
+    let lambda = y.atan2(x);
+
+    // Bowring's initial guess followed by a few fixed-point iterations, which converges to
+    // well below a millimetre anywhere near the Earth's surface.
+    let mut phi = z.atan2(p * (1.0 - WGS84_E2));
+    let mut height = 0.0;
+    for _ in 0..5 {
+        let n = WGS84_A / (1.0 - WGS84_E2 * phi.sin().powi(2)).sqrt();
+        height = match phi.cos().abs() > 1e-12 {
+            true => p / phi.cos() - n,
+            false => z.abs() - n * (1.0 - WGS84_E2),
+        };
+        phi = z.atan2(p * (1.0 - WGS84_E2 * n / (n + height)));
+    }
+    (phi.to_degrees(), lambda.to_degrees(), height)
+}
+
+/// Rotation matrix rows from ECEF to east/north/up at a reference latitude/longitude.
+fn enu_rotation(latitude: f64, longitude: f64) -> [[f64; 3]; 3] {
+    let (sp, cp) = latitude.to_radians().sin_cos();
+    let (sl, cl) = longitude.to_radians().sin_cos();
+    [
+        [-sl, cl, 0.0],
+        [-sp * cl, -sp * sl, cp],
+        [cp * cl, cp * sl, sp],
+    ]
+}
+
+/// East/north/up of an ECEF point relative to a geodetic reference (degrees, metres).
+pub fn ecef_to_enu(ecef: [f64; 3], reference: (f64, f64, f64)) -> [f64; 3] {
+    let (lat, lon, h) = reference;
+    let origin = geodetic_to_ecef(lat, lon, h);
+    let d = [
+        ecef[0] - origin[0],
+        ecef[1] - origin[1],
+        ecef[2] - origin[2],
+    ];
+    enu_rotation(lat, lon).map(|row| row[0] * d[0] + row[1] * d[1] + row[2] * d[2])
+}
+
+/// ECEF position of an east/north/up offset from a geodetic reference (degrees, metres).
+pub fn enu_to_ecef(enu: [f64; 3], reference: (f64, f64, f64)) -> [f64; 3] {
+    let (lat, lon, h) = reference;
+    let origin = geodetic_to_ecef(lat, lon, h);
+    let r = enu_rotation(lat, lon);
+    // The rotation is orthonormal, so its inverse is the transpose.
+    std::array::from_fn(|i| origin[i] + r[0][i] * enu[0] + r[1][i] * enu[1] + r[2][i] * enu[2])
+}
+
+/// A geodesic between two points on the WGS84 ellipsoid.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Geodesic {
+    /// Length in metres.
+    pub distance: f64,
+    /// Azimuth at the start, degrees clockwise from north.
+    pub initial_bearing: f64,
+    /// Azimuth at the end, degrees clockwise from north.
+    pub final_bearing: f64,
+}
+
+/// Solves the inverse geodesic problem between two positions in degrees with Vincenty's
+/// method. Returns `None` for nearly antipodal points, where the iteration does not converge.
+pub fn geodesic_inverse(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> Option<Geodesic> {
+    let f = WGS84_F;
+    let b = WGS84_A * (1.0 - f);
+    let l = (lon2 - lon1).to_radians();
+    let u1 = ((1.0 - f) * lat1.to_radians().tan()).atan();
+    let u2 = ((1.0 - f) * lat2.to_radians().tan()).atan();
+    let (sin_u1, cos_u1) = u1.sin_cos();
+    let (sin_u2, cos_u2) = u2.sin_cos();
+
+    let mut lambda = l;
+    let mut converged = false;
+    let (mut sin_sigma, mut cos_sigma, mut sigma) = (0.0, 0.0, 0.0);
+    let (mut cos2_alpha, mut cos_2sigma_m) = (0.0, 0.0);
+    for _ in 0..200 {
+        let (sin_lambda, cos_lambda) = lambda.sin_cos();
+        sin_sigma = ((cos_u2 * sin_lambda).powi(2)
+            + (cos_u1 * sin_u2 - sin_u1 * cos_u2 * cos_lambda).powi(2))
+        .sqrt();
+        if sin_sigma == 0.0 {
+            // Coincident points.
+            return Some(Geodesic {
+                distance: 0.0,
+                initial_bearing: 0.0,
+                final_bearing: 0.0,
+            });
+        }
+        cos_sigma = sin_u1 * sin_u2 + cos_u1 * cos_u2 * cos_lambda;
+        sigma = sin_sigma.atan2(cos_sigma);
+        let sin_alpha = cos_u1 * cos_u2 * sin_lambda / sin_sigma;
+        cos2_alpha = 1.0 - sin_alpha * sin_alpha;
+        // Zero on the equator, where the geodesic is a line of latitude.
+        cos_2sigma_m = match cos2_alpha {
+            c if c != 0.0 => cos_sigma - 2.0 * sin_u1 * sin_u2 / c,
+            _ => 0.0,
+        };
+        let c = f / 16.0 * cos2_alpha * (4.0 + f * (4.0 - 3.0 * cos2_alpha));
+        let previous = lambda;
+        lambda = l
+            + (1.0 - c)
+                * f
+                * sin_alpha
+                * (sigma
+                    + c * sin_sigma
+                        * (cos_2sigma_m + c * cos_sigma * (-1.0 + 2.0 * cos_2sigma_m.powi(2))));
+        if (lambda - previous).abs() < 1e-12 {
+            converged = true;
+            break;
+        }
+    }
+    if !converged {
+        return None;
+    }
+
+    let u_sq = cos2_alpha * (WGS84_A * WGS84_A - b * b) / (b * b);
+    let a_coef = 1.0 + u_sq / 16384.0 * (4096.0 + u_sq * (-768.0 + u_sq * (320.0 - 175.0 * u_sq)));
+    let b_coef = u_sq / 1024.0 * (256.0 + u_sq * (-128.0 + u_sq * (74.0 - 47.0 * u_sq)));
+    let delta_sigma = b_coef
+        * sin_sigma
+        * (cos_2sigma_m
+            + b_coef / 4.0
+                * (cos_sigma * (-1.0 + 2.0 * cos_2sigma_m.powi(2))
+                    - b_coef / 6.0
+                        * cos_2sigma_m
+                        * (-3.0 + 4.0 * sin_sigma.powi(2))
+                        * (-3.0 + 4.0 * cos_2sigma_m.powi(2))));
+    let (sin_lambda, cos_lambda) = lambda.sin_cos();
+    let initial = (cos_u2 * sin_lambda).atan2(cos_u1 * sin_u2 - sin_u1 * cos_u2 * cos_lambda);
+    let last = (cos_u1 * sin_lambda).atan2(-sin_u1 * cos_u2 + cos_u1 * sin_u2 * cos_lambda);
+    Some(Geodesic {
+        distance: b * a_coef * (sigma - delta_sigma),
+        initial_bearing: initial.to_degrees().rem_euclid(360.0),
+        final_bearing: last.to_degrees().rem_euclid(360.0),
+    })
+}
+
+/// UTM scale factor on the central meridian.
+const UTM_K0: f64 = 0.9996;
+const UTM_FALSE_EASTING: f64 = 500_000.0;
+const UTM_FALSE_NORTHING_SOUTH: f64 = 10_000_000.0;
+/// Latitude bands from 80°S, 8° each except X which spans 72°N to 84°N.
+const UTM_BANDS: &[u8; 20] = b"CDEFGHJKLMNPQRSTUVWX";
+
+/// Third flattening, rectifying radius and the Krüger series coefficients (to n^6, after
+/// Karney 2011) for the forward and inverse transverse Mercator projection.
+struct Kruger {
+    e: f64,
+    a: f64,
+    alpha: [f64; 6],
+    beta: [f64; 6],
+}
+
+impl Kruger {
+    fn wgs84() -> Self {
+        let n = WGS84_F / (2.0 - WGS84_F);
+        let (n2, n3, n4, n5, n6) = (n * n, n.powi(3), n.powi(4), n.powi(5), n.powi(6));
+        Self {
+            e: WGS84_E2.sqrt(),
+            a: WGS84_A / (1.0 + n) * (1.0 + n2 / 4.0 + n4 / 64.0 + n6 / 256.0),
+            alpha: [
+                n / 2.0 - 2.0 / 3.0 * n2 + 5.0 / 16.0 * n3 + 41.0 / 180.0 * n4 - 127.0 / 288.0 * n5
+                    + 7891.0 / 37800.0 * n6,
+                13.0 / 48.0 * n2 - 3.0 / 5.0 * n3 + 557.0 / 1440.0 * n4 + 281.0 / 630.0 * n5
+                    - 1983433.0 / 1935360.0 * n6,
+                61.0 / 240.0 * n3 - 103.0 / 140.0 * n4
+                    + 15061.0 / 26880.0 * n5
+                    + 167603.0 / 181440.0 * n6,
+                49561.0 / 161280.0 * n4 - 179.0 / 168.0 * n5 + 6601661.0 / 7257600.0 * n6,
+                34729.0 / 80640.0 * n5 - 3418889.0 / 1995840.0 * n6,
+                212378941.0 / 319334400.0 * n6,
+            ],
+            beta: [
+                n / 2.0 - 2.0 / 3.0 * n2 + 37.0 / 96.0 * n3 - 1.0 / 360.0 * n4 - 81.0 / 512.0 * n5
+                    + 96199.0 / 604800.0 * n6,
+                n2 / 48.0 + n3 / 15.0 - 437.0 / 1440.0 * n4 + 46.0 / 105.0 * n5
+                    - 1118711.0 / 3870720.0 * n6,
+                17.0 / 480.0 * n3 - 37.0 / 840.0 * n4 - 209.0 / 4480.0 * n5 + 5569.0 / 90720.0 * n6,
+                4397.0 / 161280.0 * n4 - 11.0 / 504.0 * n5 - 830251.0 / 7257600.0 * n6,
+                4583.0 / 161280.0 * n5 - 108847.0 / 3991680.0 * n6,
+                20648693.0 / 638668800.0 * n6,
+            ],
+        }
+    }
+
+    /// Projects latitude and longitude from the central meridian (radians) to (easting,
+    /// northing) in metres, before scaling and false origins.
+    fn forward(&self, phi: f64, lambda: f64) -> (f64, f64) {
+        let e = self.e;
+        let t = (phi.sin().atanh() - e * (e * phi.sin()).atanh()).sinh();
+        let xi1 = t.atan2(lambda.cos());
+        let eta1 = (lambda.sin() / (1.0 + t * t).sqrt()).atanh();
+        let (mut xi, mut eta) = (xi1, eta1);
+        for (j, alpha) in self.alpha.iter().enumerate() {
+            let k = 2.0 * (j + 1) as f64;
+            xi += alpha * (k * xi1).sin() * (k * eta1).cosh();
+            eta += alpha * (k * xi1).cos() * (k * eta1).sinh();
+        }
+        (self.a * eta, self.a * xi)
+    }
+
+    /// Inverse of `forward`.
+    fn inverse(&self, x: f64, y: f64) -> (f64, f64) {
+        let (xi, eta) = (y / self.a, x / self.a);
+        let (mut xi1, mut eta1) = (xi, eta);
+        for (j, beta) in self.beta.iter().enumerate() {
+            let k = 2.0 * (j + 1) as f64;
+            xi1 -= beta * (k * xi).sin() * (k * eta).cosh();
+            eta1 -= beta * (k * xi).cos() * (k * eta).sinh();
+        }
+        let tau1 = xi1.sin() / (eta1.sinh().powi(2) + xi1.cos().powi(2)).sqrt();
+        let lambda = eta1.sinh().atan2(xi1.cos());
+
+        // Newton's method for the geodetic from the conformal latitude tangent.
+        let e = self.e;
+        let mut tau = tau1;
+        for _ in 0..5 {
+            let sigma = (e * (e * tau / (1.0 + tau * tau).sqrt()).atanh()).sinh();
+            let tau_i = tau * (1.0 + sigma * sigma).sqrt() - sigma * (1.0 + tau * tau).sqrt();
+            tau += (tau1 - tau_i) / (1.0 + tau_i * tau_i).sqrt()
+                * (1.0 + (1.0 - WGS84_E2) * tau * tau)
+                / ((1.0 - WGS84_E2) * (1.0 + tau * tau).sqrt());
+        }
+        (tau.atan(), lambda)
+    }
+}
+
+/// A Universal Transverse Mercator coordinate.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Utm {
+    pub zone: u8,
+    /// Latitude band letter, `None` outside 80°S to 84°N.
+    pub band: Option<char>,
+    pub north: bool,
+    pub easting: f64,
+    pub northing: f64,
+}
+
+impl Utm {
+    /// The standard zone of a position, including the Norway and Svalbard exceptions.
+    pub fn zone_of(latitude: f64, longitude: f64) -> u8 {
+        let lon = (longitude + 180.0).rem_euclid(360.0) - 180.0;
+        if (56.0..64.0).contains(&latitude) && (3.0..12.0).contains(&lon) {
+            return 32;
+        }
+        if (72.0..84.0).contains(&latitude) && (0.0..42.0).contains(&lon) {
+            return match lon {
+                l if l < 9.0 => 31,
+                l if l < 21.0 => 33,
+                l if l < 33.0 => 35,
+                _ => 37,
+            };
+        }
+        (((lon + 180.0) / 6.0).floor() as u8).min(59) + 1
+    }
+
+    pub fn band_of(latitude: f64) -> Option<char> {
+        if !(-80.0..=84.0).contains(&latitude) {
+            return None;
+        }
+        let index = (((latitude + 80.0) / 8.0).floor() as usize).min(UTM_BANDS.len() - 1);
+        Some(UTM_BANDS[index] as char)
+    }
+
+    /// Projects a WGS84 position, in `zone` if given, otherwise its standard zone.
+    pub fn from_geodetic(latitude: f64, longitude: f64, zone: Option<u8>) -> Self {
+        let zone = zone
+            .filter(|z| (1..=60).contains(z))
+            .unwrap_or_else(|| Self::zone_of(latitude, longitude));
+        let central = zone as f64 * 6.0 - 183.0;
+        let dlon = (longitude - central + 180.0).rem_euclid(360.0) - 180.0;
+        let (x, y) = Kruger::wgs84().forward(latitude.to_radians(), dlon.to_radians());
+        let north = latitude >= 0.0;
+        Self {
+            zone,
+            band: Self::band_of(latitude),
+            north,
+            easting: UTM_FALSE_EASTING + UTM_K0 * x,
+            northing: UTM_K0 * y + if north { 0.0 } else { UTM_FALSE_NORTHING_SOUTH },
+        }
+    }
+
+    /// Latitude and longitude in degrees.
+    pub fn to_geodetic(&self) -> (f64, f64) {
+        let x = (self.easting - UTM_FALSE_EASTING) / UTM_K0;
+        let false_northing = if self.north {
+            0.0
+        } else {
+            UTM_FALSE_NORTHING_SOUTH
+        };
+        let y = (self.northing - false_northing) / UTM_K0;
+        let (phi, lambda) = Kruger::wgs84().inverse(x, y);
+        let central = self.zone as f64 * 6.0 - 183.0;
+        let lon = (central + lambda.to_degrees() + 180.0).rem_euclid(360.0) - 180.0;
+        (phi.to_degrees(), lon)
+    }
+
+    /// The MGRS reference of this coordinate, `None` in the polar regions.
+    pub fn to_mgrs(&self) -> Option<Mgrs> {
+        const COLUMNS: [&[u8; 8]; 3] = [b"STUVWXYZ", b"ABCDEFGH", b"JKLMNPQR"];
+        const ROWS: &[u8; 20] = b"ABCDEFGHJKLMNPQRSTUV";
+        let band = self.band?;
+        let column = (self.easting / 100_000.0).floor() as usize;
+        let row = (self.northing / 100_000.0).floor() as usize;
+        let row_offset = if self.zone % 2 == 0 { 5 } else { 0 };
+        Some(Mgrs {
+            zone: self.zone,
+            band,
+            square: [
+                *COLUMNS[self.zone as usize % 3].get(column.checked_sub(1)?)? as char,
+                ROWS[(row + row_offset) % 20] as char,
+            ],
+            easting: self.easting.rem_euclid(100_000.0).floor() as u32,
+            northing: self.northing.rem_euclid(100_000.0).floor() as u32,
+        })
+    }
+}
+
+impl fmt::Display for Utm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let band = self.band.unwrap_or(if self.north { 'N' } else { 'S' });
+        write!(
+            f,
+            "{}{} {:.2}E {:.2}N",
+            self.zone, band, self.easting, self.northing
+        )
+    }
+}
+
+/// A Military Grid Reference System reference with metre resolution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mgrs {
+    pub zone: u8,
+    pub band: char,
+    /// 100 km square column and row letters.
+    pub square: [char; 2],
+    pub easting: u32,
+    pub northing: u32,
+}
+
+impl Mgrs {
+    pub fn from_geodetic(latitude: f64, longitude: f64) -> Option<Self> {
+        Utm::from_geodetic(latitude, longitude, None).to_mgrs()
+    }
+}
+
+impl fmt::Display for Mgrs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}{} {}{} {:05} {:05}",
+            self.zone, self.band, self.square[0], self.square[1], self.easting, self.northing
+        )
+    }
+}
+
+/// How positions are displayed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CoordinateFormat {
+    /// Decimal degrees.
+    Dd,
+    /// Degrees, minutes and seconds.
+    Dms,
+    /// Degrees and decimal minutes.
+    Ddm,
+    Utm,
+    Mgrs,
+}
+
+impl CoordinateFormat {
+    pub const ALL: [CoordinateFormat; 5] = [
+        CoordinateFormat::Dd,
+        CoordinateFormat::Dms,
+        CoordinateFormat::Ddm,
+        CoordinateFormat::Utm,
+        CoordinateFormat::Mgrs,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CoordinateFormat::Dd => "DD",
+            CoordinateFormat::Dms => "DMS",
+            CoordinateFormat::Ddm => "DDM",
+            CoordinateFormat::Utm => "UTM",
+            CoordinateFormat::Mgrs => "MGRS",
+        }
+    }
+
+    /// Formats a position, using `utm_zone` instead of the standard zone for UTM.
+    pub fn format(&self, latitude: f64, longitude: f64, utm_zone: Option<u8>) -> String {
+        match self {
+            CoordinateFormat::Dd => format!("{:.8}°, {:.8}°", latitude, longitude),
+            CoordinateFormat::Dms => format!(
+                "{} {}",
+                format_dms(latitude, ['N', 'S']),
+                format_dms(longitude, ['E', 'W'])
+            ),
+            CoordinateFormat::Ddm => format!(
+                "{} {}",
+                format_ddm(latitude, ['N', 'S']),
+                format_ddm(longitude, ['E', 'W'])
+            ),
+            CoordinateFormat::Utm => Utm::from_geodetic(latitude, longitude, utm_zone).to_string(),
+            CoordinateFormat::Mgrs => Mgrs::from_geodetic(latitude, longitude)
+                .map_or("Not defined in polar regions".to_string(), |m| {
+                    m.to_string()
+                }),
+        }
+    }
+}
+
+/// `ddd°mm'ss.sss"H`, `hemispheres` being the positive and negative letters.
+pub fn format_dms(degrees: f64, hemispheres: [char; 2]) -> String {
+    let hemisphere = if degrees < 0.0 {
+        hemispheres[1]
+    } else {
+        hemispheres[0]
+    };
+    // Round once at the displayed resolution so 59.9999" does not show as 60".
+    let milliseconds = (degrees.abs() * 3_600_000.0).round() as u64;
+    format!(
+        "{}°{:02}'{:02}.{:03}\"{}",
+        milliseconds / 3_600_000,
+        milliseconds / 60_000 % 60,
+        milliseconds / 1000 % 60,
+        milliseconds % 1000,
+        hemisphere
+    )
+}
+
+/// `ddd°mm.mmmmm'H`, `hemispheres` being the positive and negative letters.
+pub fn format_ddm(degrees: f64, hemispheres: [char; 2]) -> String {
+    let hemisphere = if degrees < 0.0 {
+        hemispheres[1]
+    } else {
+        hemispheres[0]
+    };
+    let units = (degrees.abs() * 6_000_000.0).round() as u64;
+    format!(
+        "{}°{:02}.{:05}'{}",
+        units / 6_000_000,
+        units / 100_000 % 60,
+        units % 100_000,
+        hemisphere
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ecef_round_trip() {
+        for (lat, lon, h) in [
+            (52.0, 4.5, 45.0),
+            (-33.9, 151.2, 10.0),
+            (89.9, -120.0, 3000.0),
+        ] {
+            let (lat2, lon2, h2) = ecef_to_geodetic(geodetic_to_ecef(lat, lon, h));
+            assert!((lat - lat2).abs() < 1e-9);
+            assert!((lon - lon2).abs() < 1e-9);
+            assert!((h - h2).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn ecef_reference_points() {
+        let close = |a: [f64; 3], b: [f64; 3]| a.iter().zip(b).all(|(x, y)| (x - y).abs() < 1e-3);
+        assert!(close(
+            geodetic_to_ecef(0.0, 0.0, 0.0),
+            [6_378_137.0, 0.0, 0.0]
+        ));
+        assert!(close(
+            geodetic_to_ecef(0.0, 90.0, 100.0),
+            [0.0, 6_378_237.0, 0.0]
+        ));
+        // Semi-minor axis b = a (1 - f).
+        assert!(close(
+            geodetic_to_ecef(90.0, 0.0, 0.0),
+            [0.0, 0.0, 6_356_752.314]
+        ));
+    }
+
+    #[test]
+    fn enu() {
+        let reference = (45.0, 7.0, 300.0);
+        let origin = geodetic_to_ecef(45.0, 7.0, 300.0);
+        let up = geodetic_to_ecef(45.0, 7.0, 310.0);
+        let [e, n, u] = ecef_to_enu(up, reference);
+        assert!(e.abs() < 1e-6 && n.abs() < 1e-6 && (u - 10.0).abs() < 1e-6);
+        assert!(ecef_to_enu(origin, reference)
+            .iter()
+            .all(|v| v.abs() < 1e-6));
+
+        let offset = [120.0, -35.5, 2.25];
+        let back = ecef_to_enu(enu_to_ecef(offset, reference), reference);
+        assert!(back.iter().zip(offset).all(|(a, b)| (a - b).abs() < 1e-6));
+    }
+
+    #[test]
+    fn utm_reference_points() {
+        // The origin of the geographic grid, 31N 166021.443 0.
+        let utm = Utm::from_geodetic(0.0, 0.0, None);
+        assert_eq!((utm.zone, utm.band, utm.north), (31, Some('N'), true));
+        assert!((utm.easting - 166_021.443).abs() < 1e-3);
+        assert!(utm.northing.abs() < 1e-3);
+
+        // On the central meridian the northing is k0 times the meridian arc, 4984944.378 m
+        // from the equator to 45°.
+        let utm = Utm::from_geodetic(45.0, 9.0, None);
+        assert_eq!(utm.zone, 32);
+        assert!((utm.easting - 500_000.0).abs() < 1e-6);
+        assert!((utm.northing - 0.9996 * 4_984_944.378).abs() < 1e-3);
+
+        // Southern hemisphere false northing.
+        let utm = Utm::from_geodetic(-45.0, 9.0, None);
+        assert!((utm.northing - (10_000_000.0 - 0.9996 * 4_984_944.378)).abs() < 1e-3);
+        assert_eq!(utm.band, Some('G'));
+    }
+
+    #[test]
+    fn utm_zones_and_round_trip() {
+        assert_eq!(Utm::zone_of(60.0, 5.0), 32);
+        assert_eq!(Utm::zone_of(78.0, 15.0), 33);
+        assert_eq!(Utm::zone_of(10.0, 179.999), 60);
+        assert_eq!(Utm::zone_of(10.0, -180.0), 1);
+        assert_eq!(Utm::band_of(83.0), Some('X'));
+        assert_eq!(Utm::band_of(85.0), None);
+
+        for (lat, lon, zone) in [
+            (52.37, 4.89, None),
+            (-33.86, 151.21, None),
+            (40.0, -105.0, Some(12)),
+            // Overridden to the neighbouring zone.
+            (51.5, 5.9, Some(32)),
+        ] {
+            let utm = Utm::from_geodetic(lat, lon, zone);
+            let (lat2, lon2) = utm.to_geodetic();
+            assert!((lat - lat2).abs() < 1e-9 && (lon - lon2).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn mgrs_reference_points() {
+        let mgrs = Mgrs::from_geodetic(0.0, 0.0).unwrap();
+        assert_eq!(mgrs.to_string(), "31N AA 66021 00000");
+        // Even zones offset the row letters by five.
+        let mgrs = Mgrs::from_geodetic(45.0, 9.0).unwrap();
+        assert_eq!(mgrs.to_string(), "32T NQ 00000 82950");
+        // Washington Monument, 18S UJ 234 064 at 100 m resolution.
+        let mgrs = Mgrs::from_geodetic(38.8895, -77.0353).unwrap();
+        assert_eq!((mgrs.zone, mgrs.band, mgrs.square), (18, 'S', ['U', 'J']));
+        assert_eq!((mgrs.easting / 100, mgrs.northing / 100), (234, 64));
+        assert_eq!(Mgrs::from_geodetic(85.0, 0.0), None);
+    }
+
+    #[test]
+    fn vincenty_reference_line() {
+        // Flinders Peak to Buninyong, the example in Vincenty's paper.
+        let dms = |d: f64, m: f64, s: f64| d.signum() * (d.abs() + m / 60.0 + s / 3600.0);
+        let g = geodesic_inverse(
+            dms(-37.0, 57.0, 3.72030),
+            dms(144.0, 25.0, 29.52440),
+            dms(-37.0, 39.0, 10.15610),
+            dms(143.0, 55.0, 35.38390),
+        )
+        .unwrap();
+        assert!((g.distance - 54_972.271).abs() < 1e-3);
+        assert!((g.initial_bearing - dms(306.0, 52.0, 5.37)).abs() < 1e-5);
+        assert!((g.final_bearing - dms(307.0, 10.0, 25.07)).abs() < 1e-5);
+        // A quarter of the equator.
+        let g = geodesic_inverse(0.0, 0.0, 0.0, 90.0).unwrap();
+        assert!((g.distance - WGS84_A * std::f64::consts::FRAC_PI_2).abs() < 1e-3);
+        assert_eq!(
+            geodesic_inverse(10.0, 10.0, 10.0, 10.0).unwrap().distance,
+            0.0
+        );
+    }
+
+    #[test]
+    fn angle_formats() {
+        assert_eq!(format_dms(52.5, ['N', 'S']), "52°30'00.000\"N");
+        assert_eq!(format_dms(-0.999_999_99, ['E', 'W']), "1°00'00.000\"W");
+        assert_eq!(format_ddm(-33.8568, ['N', 'S']), "33°51.40800'S");
+    }
+}
@@ -0,0 +1,264 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, ErrorKind, Read};
+use std::path::Path;
+
+/// Geoid undulation above the WGS84 ellipsoid on a regular latitude/longitude grid, e.g.
+/// EGM96 or EGM2008.
+///
+/// Reads GeographicLib's 16-bit PGM files (`egm96-5.pgm`, `egm2008-1.pgm`, ...) and NGA's
+/// ASCII `.GRD` grids (`WW15MGH.GRD`). PGM samples are kept as stored, two bytes each, so
+/// the 1' EGM2008 grid takes about 470 MB of memory.
+#[derive(Debug, Clone)]
+pub struct GeoidGrid {
+    /// File name the grid was loaded from, shown next to derived heights.
+    pub name: String,
+    /// Latitude of the first row in degrees; rows run southwards.
+    north: f64,
+    /// Longitude of the first column in degrees; columns run eastwards.
+    west: f64,
+    /// Row and column spacing in degrees.
+    dlat: f64,
+    dlon: f64,
+    rows: usize,
+    cols: usize,
+    values: Samples,
+}
+
+/// Undulations row by row.
+#[derive(Debug, Clone)]
+enum Samples {
+    /// PGM values; the undulation in metres is `offset + scale * value`.
+    Scaled {
+        raw: Vec<u16>,
+        offset: f64,
+        scale: f64,
+    },
+    Metres(Vec<f32>),
+}
+
+impl Samples {
+    fn get(&self, index: usize) -> f64 {
+        match self {
+            Samples::Scaled { raw, offset, scale } => offset + scale * raw[index] as f64,
+            Samples::Metres(values) => values[index] as f64,
+        }
+    }
+}
+
+/// Most samples a grid may have, those of a global grid at 1' spacing with both edges.
+const MAX_SAMPLES: usize = 10_801 * 21_601;
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, message.to_owned())
+}
+
+impl GeoidGrid {
+    /// Loads a grid file, recognizing the format from its content. Large grids take a while;
+    /// call this off the UI thread.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let mut reader = BufReader::new(File::open(path)?);
+        let name = path
+            .file_name()
+            .map_or(String::new(), |n| n.to_string_lossy().into_owned());
+        if reader.fill_buf()?.starts_with(b"P5") {
+            Self::from_pgm(reader, &name)
+        } else {
+            let mut text = String::new();
+            reader
+                .read_to_string(&mut text)
+                .map_err(|_| invalid("not a geoid grid"))?;
+            Self::from_grd(&text, &name)
+        }
+    }
+
+    /// Parses a GeographicLib geoid PGM: a binary PGM with `# Offset` and `# Scale` comments,
+    /// rows from 90°N to 90°S and columns from 0°E covering the globe.
+    pub fn from_pgm(mut reader: impl BufRead, name: &str) -> io::Result<Self> {
+        let (mut offset, mut scale) = (None, None);
+        let mut fields = Vec::new();
+        // Header: magic, width, height and maxval, separated by whitespace and comment lines.
+        while fields.len() < 4 {
+            let mut line = Vec::new();
+            if reader.read_until(b'\n', &mut line)? == 0 || line.last() != Some(&b'\n') {
+                return Err(invalid("truncated PGM header"));
+            }
+            let line = String::from_utf8_lossy(&line).into_owned();
+            if let Some(comment) = line.strip_prefix('#') {
+                let mut words = comment.split_whitespace();
+                match (
+                    words.next(),
+                    words.next().and_then(|v| v.parse::<f64>().ok()),
+                ) {
+                    (Some("Offset"), Some(v)) => offset = Some(v),
+                    (Some("Scale"), Some(v)) => scale = Some(v),
+                    _ => {}
+                }
+                continue;
+            }
+            fields.extend(line.split_whitespace().map(str::to_owned));
+        }
+        if fields[0] != "P5" || fields[3] != "65535" {
+            return Err(invalid("not a 16-bit binary PGM"));
+        }
+        let (Some(offset), Some(scale)) = (offset, scale) else {
+            return Err(invalid("PGM has no geoid Offset/Scale comments"));
+        };
+        let cols: usize = fields[1].parse().map_err(|_| invalid("bad PGM width"))?;
+        let rows: usize = fields[2].parse().map_err(|_| invalid("bad PGM height"))?;
+        if cols < 2 || rows < 2 {
+            return Err(invalid("bad PGM size"));
+        }
+        // The header is not trusted with the allocation below.
+        if !rows.checked_mul(cols).is_some_and(|n| n <= MAX_SAMPLES) {
+            return Err(invalid("PGM too large for a geoid grid"));
+        }
+
+        // A row at a time, so the file is never held in memory next to the samples.
+        let mut raw = Vec::with_capacity(rows * cols);
+        let mut row = vec![0; 2 * cols];
+        for _ in 0..rows {
+            reader.read_exact(&mut row).map_err(|e| match e.kind() {
+                ErrorKind::UnexpectedEof => invalid("truncated PGM data"),
+                _ => e,
+            })?;
+            raw.extend(
+                row.chunks_exact(2)
+                    .map(|b| u16::from_be_bytes([b[0], b[1]])),
+            );
+        }
+        Ok(Self {
+            name: name.to_owned(),
+            north: 90.0,
+            west: 0.0,
+            dlat: 180.0 / (rows - 1) as f64,
+            dlon: 360.0 / cols as f64,
+            rows,
+            cols,
+            values: Samples::Scaled { raw, offset, scale },
+        })
+    }
+
+    /// Parses an NGA ASCII grid: a header of south, north, west, east, latitude spacing and
+    /// longitude spacing, then values from the north-west corner, row by row.
+    pub fn from_grd(text: &str, name: &str) -> io::Result<Self> {
+        let mut numbers = text.split_whitespace().map(|v| v.parse::<f64>());
+        let mut header = [0.0; 6];
+        for h in header.iter_mut() {
+            *h = numbers
+                .next()
+                .and_then(Result::ok)
+                .ok_or_else(|| invalid("bad grid header"))?;
+        }
+        let [south, north, west, east, dlat, dlon] = header;
+        if dlat <= 0.0 || dlon <= 0.0 || north <= south || east <= west {
+            return Err(invalid("bad grid extent"));
+        }
+        let rows = ((north - south) / dlat).round() + 1.0;
+        let cols = ((east - west) / dlon).round() + 1.0;
+        let size = rows * cols;
+        if !size.is_finite() || size > MAX_SAMPLES as f64 {
+            return Err(invalid("grid too large for a geoid grid"));
+        }
+        let (rows, cols) = (rows as usize, cols as usize);
+        let values = numbers
+            .take(rows * cols)
+            .map(|v| v.map(|v| v as f32))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| invalid("bad grid value"))?;
+        if values.len() != rows * cols {
+            return Err(invalid("truncated grid"));
+        }
+        Ok(Self {
+            name: name.to_owned(),
+            north,
+            west,
+            dlat,
+            dlon,
+            rows,
+            cols,
+            values: Samples::Metres(values),
+        })
+    }
+
+    /// Whether the columns go all the way round, so the last column neighbours the first.
+    fn wraps(&self) -> bool {
+        self.cols as f64 * self.dlon >= 360.0 - 1e-9
+    }
+
+    /// Geoid undulation in metres at a position, bilinearly interpolated; `None` outside the
+    /// grid.
+    pub fn undulation(&self, latitude: f64, longitude: f64) -> Option<f64> {
+        let y = (self.north - latitude) / self.dlat;
+        let x = match self.wraps() {
+            true => (longitude - self.west).rem_euclid(360.0) / self.dlon,
+            false => (longitude - self.west) / self.dlon,
+        };
+        let (max_y, max_x) = ((self.rows - 1) as f64, (self.cols - 1) as f64);
+        if !(0.0..=max_y).contains(&y) || x < 0.0 || (!self.wraps() && x > max_x) {
+            return None;
+        }
+
+        let row = (y.floor() as usize).min(self.rows - 2);
+        let col = (x.floor() as usize).min(self.cols - 1);
+        let next_col = match col + 1 {
+            c if c < self.cols => c,
+            _ if self.wraps() => 0,
+            _ => col,
+        };
+        let (fy, fx) = (y - row as f64, x - col as f64);
+        let at = |r: usize, c: usize| self.values.get(r * self.cols + c);
+        let top = at(row, col) * (1.0 - fx) + at(row, next_col) * fx;
+        let bottom = at(row + 1, col) * (1.0 - fx) + at(row + 1, next_col) * fx;
+        Some(top * (1.0 - fy) + bottom * fy)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interpolates_grd() {
+        // 2x3 grid from 10°N to 9°N and 20°E to 22°E.
+        let text = "9.0 10.0 20.0 22.0 1.0 1.0\n 1.0 2.0 3.0\n 3.0 4.0 5.0\n";
+        let grid = GeoidGrid::from_grd(text, "test.grd").unwrap();
+        assert_eq!(grid.undulation(10.0, 20.0), Some(1.0));
+        assert_eq!(grid.undulation(9.0, 22.0), Some(5.0));
+        assert_eq!(grid.undulation(9.5, 20.5), Some(2.5));
+        assert_eq!(grid.undulation(8.9, 20.0), None);
+        assert_eq!(grid.undulation(9.5, 22.5), None);
+        assert!(GeoidGrid::from_grd("9.0 10.0 20.0 22.0 1.0 1.0\n 1.0", "short").is_err());
+        for header in [
+            "-90 90 0 360 1e-300 1e-300",
+            "-90 90 0 360 1e-3 1e-3",
+            "NaN 90 0 360 1 1",
+        ] {
+            let error = GeoidGrid::from_grd(header, "huge").unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::InvalidData);
+        }
+    }
+
+    #[test]
+    fn reads_pgm_and_wraps() {
+        // Four columns of 90° and three rows of 90°, values are row * 10 + column.
+        let mut pgm = b"P5\n# Offset -100\n# Scale 0.5\n4 3\n65535\n".to_vec();
+        for row in 0..3u16 {
+            for col in 0..4u16 {
+                let raw = ((row * 10 + col) as f64 + 100.0) / 0.5;
+                pgm.extend_from_slice(&(raw as u16).to_be_bytes());
+            }
+        }
+        assert!(GeoidGrid::from_pgm(&pgm[..pgm.len() - 1], "short").is_err());
+        for size in ["18446744073709551615 2", "100000 100000"] {
+            let header = format!("P5\n# Offset -100\n# Scale 0.5\n{size}\n65535\n");
+            let error = GeoidGrid::from_pgm(header.as_bytes(), "huge").unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::InvalidData);
+        }
+        let grid = GeoidGrid::from_pgm(&pgm[..], "test.pgm").unwrap();
+        assert_eq!(grid.undulation(0.0, 90.0), Some(11.0));
+        assert_eq!(grid.undulation(0.0, -90.0), Some(13.0));
+        // Between the last column at 270°E and the first at 0°E.
+        assert_eq!(grid.undulation(-90.0, 315.0), Some(21.5));
+        assert_eq!(grid.undulation(45.0, 0.0), Some(5.0));
+    }
+}
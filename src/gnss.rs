@@ -0,0 +1,352 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Constellation {
+    Gps,
+    Sbas,
+    Galileo,
+    BeiDou,
+    Qzss,
+    Glonass,
+    NavIc,
+    Unknown,
+}
+
+impl Constellation {
+    pub const ALL: [Constellation; 8] = [
+        Constellation::Gps,
+        Constellation::Sbas,
+        Constellation::Galileo,
+        Constellation::BeiDou,
+        Constellation::Qzss,
+        Constellation::Glonass,
+        Constellation::NavIc,
+        Constellation::Unknown,
+    ];
+
+    /// Maps a UBX `gnssId`.
+    pub fn from_ubx(gnss_id: u8) -> Self {
+        match gnss_id {
+            0 => Constellation::Gps,
+            1 => Constellation::Sbas,
+            2 => Constellation::Galileo,
+            3 => Constellation::BeiDou,
+            5 => Constellation::Qzss,
+            6 => Constellation::Glonass,
+            7 => Constellation::NavIc,
+            _ => Constellation::Unknown,
+        }
+    }
+
+    /// Maps an NMEA talker ID. `GN` (combined) and unknown talkers map to `Unknown`.
+    pub fn from_talker(talker: &str) -> Self {
+        match talker {
+            "GP" => Constellation::Gps,
+            "GL" => Constellation::Glonass,
+            "GA" => Constellation::Galileo,
+            "GB" | "BD" => Constellation::BeiDou,
+            "GQ" | "QZ" => Constellation::Qzss,
+            "GI" => Constellation::NavIc,
+            _ => Constellation::Unknown,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Constellation::Gps => "GPS",
+            Constellation::Sbas => "SBAS",
+            Constellation::Galileo => "Galileo",
+            Constellation::BeiDou => "BeiDou",
+            Constellation::Qzss => "QZSS",
+            Constellation::Glonass => "GLONASS",
+            Constellation::NavIc => "NavIC",
+            Constellation::Unknown => "Unknown",
+        }
+    }
+
+    /// The operator of the constellation.
+    pub fn country(&self) -> &'static str {
+        match self {
+            Constellation::Gps => "USA",
+            Constellation::Sbas => "Various",
+            Constellation::Galileo => "EU",
+            Constellation::BeiDou => "China",
+            Constellation::Qzss => "Japan",
+            Constellation::Glonass => "Russia",
+            Constellation::NavIc => "India",
+            Constellation::Unknown => "Unknown",
+        }
+    }
+}
+
+impl fmt::Display for Constellation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A satellite, identified by constellation and the constellation's own PRN/slot number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct SatelliteId {
+    pub constellation: Constellation,
+    pub prn: u8,
+}
+
+impl SatelliteId {
+    pub fn new(constellation: Constellation, prn: u8) -> Self {
+        Self { constellation, prn }
+    }
+}
+
+impl fmt::Display for SatelliteId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let prefix = match self.constellation {
+            Constellation::Gps => "G",
+            Constellation::Sbas => "S",
+            Constellation::Galileo => "E",
+            Constellation::BeiDou => "C",
+            Constellation::Qzss => "J",
+            Constellation::Glonass => "R",
+            Constellation::NavIc => "I",
+            Constellation::Unknown => "?",
+        };
+        write!(f, "{}{:02}", prefix, self.prn)
+    }
+}
+
+/// What the receiver reports about one satellite in view.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Satellite {
+    pub id: SatelliteId,
+    /// Carrier to noise density in dB-Hz, zero when not tracked.
+    pub cno: u8,
+    pub elevation: Option<f32>,
+    pub azimuth: Option<f32>,
+    pub used: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FixType {
+    NoFix,
+    DeadReckoning,
+    Fix2D,
+    Fix3D,
+    GnssDeadReckoning,
+    TimeOnly,
+    /// Position entered by hand (GGA quality 7), not a solution.
+    ManualInput,
+    /// Position from a simulator (GGA quality 8).
+    Simulated,
+}
+
+impl FixType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            FixType::NoFix => "No fix",
+            FixType::DeadReckoning => "Dead reckoning",
+            FixType::Fix2D => "2D",
+            FixType::Fix3D => "3D",
+            FixType::GnssDeadReckoning => "GNSS + dead reckoning",
+            FixType::TimeOnly => "Time only",
+            FixType::ManualInput => "Manual input",
+            FixType::Simulated => "Simulator",
+        }
+    }
+
+    pub fn has_position(&self) -> bool {
+        matches!(
+            self,
+            FixType::Fix2D | FixType::Fix3D | FixType::GnssDeadReckoning
+        )
+    }
+}
+
+/// Differential and carrier phase state of the solution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RtkStatus {
+    None,
+    Dgnss,
+    Float,
+    Fixed,
+}
+
+impl RtkStatus {
+    /// In order of increasing precision, matching the discriminants.
+    pub const ALL: [RtkStatus; 4] = [
+        RtkStatus::None,
+        RtkStatus::Dgnss,
+        RtkStatus::Float,
+        RtkStatus::Fixed,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            RtkStatus::None => "Autonomous",
+            RtkStatus::Dgnss => "DGNSS",
+            RtkStatus::Float => "RTK float",
+            RtkStatus::Fixed => "RTK fixed",
+        }
+    }
+
+    /// Maps a GGA quality indicator. Dead reckoning (6), manual input (7) and simulator (8)
+    /// positions have no differential state.
+    pub fn from_gga_quality(quality: u8) -> Self {
+        match quality {
+            2 => RtkStatus::Dgnss,
+            4 => RtkStatus::Fixed,
+            5 => RtkStatus::Float,
+            // Also no fix (0), autonomous (1), PPS (3) and any unknown value.
+            _ => RtkStatus::None,
+        }
+    }
+}
+
+/// A navigation solution.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fix {
+    pub fix_type: FixType,
+    /// Degrees, positive north.
+    pub latitude: f64,
+    /// Degrees, positive east.
+    pub longitude: f64,
+    /// Height above the WGS84 ellipsoid in metres.
+    pub height: Option<f64>,
+    /// Height above mean sea level in metres.
+    pub height_msl: Option<f64>,
+    pub num_sv: u8,
+    /// Horizontal accuracy estimate in metres.
+    pub h_acc: Option<f64>,
+    /// Vertical accuracy estimate in metres.
+    pub v_acc: Option<f64>,
+    /// UTC time of day of the solution in milliseconds, when the receiver reports it.
+    pub epoch: Option<u32>,
+}
+
+/// Milliseconds in a day.
+pub const DAY_MS: u32 = 86_400_000;
+
+/// UTC time of day in milliseconds, with `nanos` rounded to the millisecond.
+pub fn time_of_day_ms(hour: u32, minute: u32, second: u32, nanos: i64) -> u32 {
+    let seconds = hour as i64 * 3_600 + minute as i64 * 60 + second as i64;
+    (seconds * 1_000 + (nanos as f64 / 1e6).round() as i64).rem_euclid(DAY_MS as i64) as u32
+}
+
+/// Scale from a 1-sigma error ellipse to the one containing 95% of horizontal positions.
+pub const ELLIPSE_95: f64 = 2.447_7;
+
+/// Horizontal position error ellipse.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ErrorEllipse {
+    /// Standard deviation along the semi-major axis in metres.
+    pub semi_major: f64,
+    /// Standard deviation along the semi-minor axis in metres.
+    pub semi_minor: f64,
+    /// Direction of the semi-major axis, degrees clockwise from true north in [0, 180).
+    pub orientation: f64,
+}
+
+impl ErrorEllipse {
+    /// The ellipse of east and north variances and their covariance, in square metres.
+    pub fn from_covariance(ee: f64, nn: f64, en: f64) -> Self {
+        let mean = (ee + nn) / 2.0;
+        let radius = ((ee - nn) / 2.0).hypot(en);
+        // Angle of the major axis counterclockwise from east.
+        let angle = 0.5 * (2.0 * en).atan2(ee - nn);
+        Self {
+            semi_major: (mean + radius).max(0.0).sqrt(),
+            semi_minor: (mean - radius).max(0.0).sqrt(),
+            orientation: (90.0 - angle.to_degrees()).rem_euclid(180.0),
+        }
+    }
+
+    /// Outline as (east, north) offsets in metres, scaled by `scale` standard deviations.
+    pub fn outline(&self, scale: f64) -> Vec<[f64; 2]> {
+        let (sin, cos) = self.orientation.to_radians().sin_cos();
+        (0..64)
+            .map(|i| {
+                let t = i as f64 / 64.0 * std::f64::consts::TAU;
+                let (major, minor) = (
+                    scale * self.semi_major * t.cos(),
+                    scale * self.semi_minor * t.sin(),
+                );
+                [major * sin + minor * cos, major * cos - minor * sin]
+            })
+            .collect()
+    }
+}
+
+/// Position uncertainty reported by the receiver, from NMEA GST or UBX-NAV-COV.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PositionError {
+    /// Standard deviations in metres.
+    pub east: f64,
+    pub north: f64,
+    pub up: Option<f64>,
+    pub ellipse: ErrorEllipse,
+}
+
+/// Velocity over ground of a navigation solution.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Velocity {
+    /// Horizontal speed in m/s.
+    pub speed: f64,
+    /// Course over ground (heading of motion), degrees clockwise from true north.
+    pub course: Option<f64>,
+    /// Vertical speed in m/s, positive up.
+    pub vertical: Option<f64>,
+    /// Heading of the vehicle, degrees clockwise from true north, when the receiver knows it
+    /// (dead reckoning or a moving base).
+    pub heading: Option<f64>,
+    /// Speed accuracy estimate in m/s.
+    pub speed_acc: Option<f64>,
+    /// Heading accuracy estimate in degrees.
+    pub course_acc: Option<f64>,
+}
+
+impl Velocity {
+    /// Angle in degrees from the course to the vehicle heading, in (-180, 180]; the side-slip
+    /// or crab angle.
+    pub fn slip(&self) -> Option<f64> {
+        let d = (self.heading? - self.course?).rem_euclid(360.0);
+        Some(if d > 180.0 { d - 360.0 } else { d })
+    }
+}
+
+/// Units speeds can be shown in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SpeedUnit {
+    MetresPerSecond,
+    KilometresPerHour,
+    Knots,
+    MilesPerHour,
+}
+
+impl SpeedUnit {
+    pub const ALL: [SpeedUnit; 4] = [
+        SpeedUnit::MetresPerSecond,
+        SpeedUnit::KilometresPerHour,
+        SpeedUnit::Knots,
+        SpeedUnit::MilesPerHour,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SpeedUnit::MetresPerSecond => "m/s",
+            SpeedUnit::KilometresPerHour => "km/h",
+            SpeedUnit::Knots => "kn",
+            SpeedUnit::MilesPerHour => "mph",
+        }
+    }
+
+    /// Converts a speed in m/s to this unit.
+    pub fn from_mps(&self, speed: f64) -> f64 {
+        match self {
+            SpeedUnit::MetresPerSecond => speed,
+            SpeedUnit::KilometresPerHour => speed * 3.6,
+            SpeedUnit::Knots => speed * 3600.0 / 1852.0,
+            SpeedUnit::MilesPerHour => speed * 3600.0 / 1609.344,
+        }
+    }
+}
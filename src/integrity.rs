@@ -0,0 +1,597 @@
+use std::collections::{BTreeMap, VecDeque};
+
+use chrono::{DateTime, Utc};
+
+use crate::gnss::SatelliteId;
+use crate::nmea::NmeaMessage;
+use crate::receiver::{host_instant, GnssMessage, ReceiverState};
+use crate::time_scales::GnssInstant;
+use crate::ubx::{JammingState, SpoofingState, UbxMessage};
+
+/// Mean Earth radius in metres, for the jump heuristics.
+const EARTH_RADIUS: f64 = 6_371_008.8;
+/// Events kept in the log, oldest dropped first.
+pub const MAX_EVENTS: usize = 1_000;
+/// Seconds of epochs the time jump check takes the least delayed arrival from. Data held
+/// up for less than this, then delivered in a burst, is not mistaken for a jump.
+const TIME_JUMP_WINDOW: f64 = 10.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Info,
+    Warning,
+    Critical,
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Severity::Info => "INFO",
+            Severity::Warning => "WARN",
+            Severity::Critical => "CRITICAL",
+        }
+    }
+}
+
+/// A timestamped interference or integrity event.
+#[derive(Debug, Clone)]
+pub struct IntegrityEvent {
+    /// Host clock when the event was detected.
+    pub time: DateTime<Utc>,
+    pub severity: Severity,
+    pub message: String,
+}
+
+/// Limits used by the heuristics computed in the GUI.
+#[derive(Debug, Clone)]
+pub struct IntegrityThresholds {
+    /// Fewest tracked satellites for the C/N0 uniformity check to run.
+    pub min_satellites: usize,
+    /// C/N0 standard deviation (dB-Hz) below which the signals look like a single transmitter.
+    pub min_cno_spread: f64,
+    /// Implied speed (m/s) between fixes above which a position jump is reported.
+    pub max_speed: f64,
+    /// Disagreement (s) between receiver and host elapsed time that counts as a time jump.
+    pub max_time_jump: f64,
+    /// Change in clock drift (ns/s) between solutions that counts as an anomaly.
+    pub max_drift_step: f64,
+    /// Horizontal and vertical alert limits (m) the RAIM protection levels are checked
+    /// against.
+    pub horizontal_alert_limit: f64,
+    pub vertical_alert_limit: f64,
+}
+
+impl Default for IntegrityThresholds {
+    fn default() -> Self {
+        Self {
+            min_satellites: 5,
+            min_cno_spread: 1.5,
+            max_speed: 150.0,
+            max_time_jump: 0.5,
+            max_drift_step: 50.0,
+            horizontal_alert_limit: 40.0,
+            vertical_alert_limit: 50.0,
+        }
+    }
+}
+
+/// Watches the receiver's interference and integrity reports, plus a few heuristics of our
+/// own, and records an event each time something changes for the worse.
+#[derive(Default)]
+pub struct IntegrityMonitor {
+    pub thresholds: IntegrityThresholds,
+    pub events: VecDeque<IntegrityEvent>,
+    /// Events not yet handed out by `take_new`.
+    unread: Vec<IntegrityEvent>,
+
+    jamming: BTreeMap<u8, JammingState>,
+    spoofing: Option<SpoofingState>,
+    cno_uniform: bool,
+    /// Standard deviation of C/N0 over tracked satellites at the last check.
+    pub cno_spread: Option<f64>,
+    /// Receiver time, latitude and longitude of the last fix that moved.
+    last_fix: Option<(GnssInstant, f64, f64)>,
+    /// Arrival time, receiver time and host clock minus receiver time (s) of the recent
+    /// epochs.
+    time_offsets: VecDeque<(DateTime<Utc>, GnssInstant, f64)>,
+    /// Smallest offset in `time_offsets` at the previous epoch: the epoch that was delayed
+    /// least on its way to us.
+    time_floor: Option<f64>,
+    last_drift: Option<f64>,
+    /// Whether the last raw measurements gave a local solution to run RAIM on.
+    raim_solution: bool,
+    raim_excluded: Vec<SatelliteId>,
+    raim_fault: bool,
+    protection_exceeded: bool,
+}
+
+impl IntegrityMonitor {
+    /// Events recorded since the last call.
+    pub fn take_new(&mut self) -> Vec<IntegrityEvent> {
+        std::mem::take(&mut self.unread)
+    }
+
+    /// Forgets what the heuristics have seen so far, keeping the thresholds and event log.
+    pub fn reset(&mut self) {
+        *self = Self {
+            thresholds: self.thresholds.clone(),
+            events: std::mem::take(&mut self.events),
+            unread: std::mem::take(&mut self.unread),
+            ..Default::default()
+        };
+    }
+
+    pub fn clear(&mut self) {
+        self.events.clear();
+        self.unread.clear();
+    }
+
+    fn raise(&mut self, time: DateTime<Utc>, severity: Severity, message: String) {
+        let event = IntegrityEvent {
+            time,
+            severity,
+            message,
+        };
+        if self.events.len() == MAX_EVENTS {
+            self.events.pop_front();
+        }
+        self.events.push_back(event.clone());
+        self.unread.push(event);
+    }
+
+    /// Runs the checks relevant to `message`, which has already been applied to `state`.
+    pub fn check(&mut self, state: &ReceiverState, message: &GnssMessage, host: DateTime<Utc>) {
+        match message {
+            GnssMessage::Ubx(UbxMessage::MonRf(rf)) => {
+                for block in &rf.blocks {
+                    let previous = self.jamming.insert(block.block_id, block.jamming);
+                    if previous == Some(block.jamming) {
+                        continue;
+                    }
+                    let severity = match block.jamming {
+                        JammingState::Warning => Severity::Warning,
+                        JammingState::Critical => Severity::Critical,
+                        JammingState::Ok if previous.is_some() => Severity::Info,
+                        _ => continue,
+                    };
+                    self.raise(
+                        host,
+                        severity,
+                        format!(
+                            "RF block {}: jamming state {} (AGC {:.0}%, CW indicator {})",
+                            block.block_id,
+                            block.jamming.name(),
+                            block.agc_percent(),
+                            block.jam_ind
+                        ),
+                    );
+                }
+            }
+            GnssMessage::Ubx(UbxMessage::NavStatus(status)) => {
+                let spoofing = status.spoofing();
+                let previous = self.spoofing.replace(spoofing);
+                let severity = match spoofing {
+                    _ if previous == Some(spoofing) => None,
+                    SpoofingState::Indicated => Some(Severity::Warning),
+                    SpoofingState::Multiple => Some(Severity::Critical),
+                    SpoofingState::None if previous.is_some() => Some(Severity::Info),
+                    _ => None,
+                };
+                if let Some(severity) = severity {
+                    self.raise(
+                        host,
+                        severity,
+                        format!("Receiver reports: {}", spoofing.name()),
+                    );
+                }
+            }
+            GnssMessage::Ubx(UbxMessage::NavSat(_)) | GnssMessage::Nmea(NmeaMessage::Gsv(_)) => {
+                self.check_cno_uniformity(state, host);
+            }
+            GnssMessage::Ubx(UbxMessage::RxmRawx(_)) => self.check_raim(state, host),
+            GnssMessage::Ubx(UbxMessage::NavClock(clock)) => {
+                let drift = clock.clk_d_ns_s as f64;
+                if let Some(last) = self.last_drift.replace(drift) {
+                    if (drift - last).abs() > self.thresholds.max_drift_step {
+                        self.raise(
+                            host,
+                            Severity::Warning,
+                            format!("Clock drift changed from {:.0} to {:.0} ns/s", last, drift),
+                        );
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        self.check_position_jump(state, host);
+        self.check_time_jump(state, host);
+    }
+
+    fn check_cno_uniformity(&mut self, state: &ReceiverState, host: DateTime<Utc>) {
+        let cnos: Vec<f64> = state
+            .satellites
+            .iter()
+            .filter(|s| s.cno > 0)
+            .map(|s| s.cno as f64)
+            .collect();
+        if cnos.len() < self.thresholds.min_satellites {
+            self.cno_spread = None;
+            return;
+        }
+        let mean = cnos.iter().sum::<f64>() / cnos.len() as f64;
+        let spread =
+            (cnos.iter().map(|c| (c - mean).powi(2)).sum::<f64>() / cnos.len() as f64).sqrt();
+        self.cno_spread = Some(spread);
+
+        let uniform = spread < self.thresholds.min_cno_spread;
+        if uniform && !self.cno_uniform {
+            self.raise(
+                host,
+                Severity::Warning,
+                format!(
+                    "C/N0 is unusually uniform across {} satellites (σ {:.1} dB-Hz)",
+                    cnos.len(),
+                    spread
+                ),
+            );
+        }
+        self.cno_uniform = uniform;
+    }
+
+    fn check_raim(&mut self, state: &ReceiverState, host: DateTime<Utc>) {
+        let Some(raim) = &state.raim else {
+            // Nothing is monitored any more, so nothing stays latched.
+            if std::mem::take(&mut self.raim_solution) {
+                self.raise(
+                    host,
+                    Severity::Warning,
+                    "RAIM unavailable: no local solution".to_owned(),
+                );
+            }
+            self.raim_excluded.clear();
+            self.raim_fault = false;
+            self.protection_exceeded = false;
+            return;
+        };
+        self.raim_solution = true;
+        for (id, standardized) in &raim.excluded {
+            if !self.raim_excluded.contains(id) {
+                self.raise(
+                    host,
+                    Severity::Warning,
+                    format!(
+                        "RAIM excluded {} (standardized residual {:.1})",
+                        id, standardized
+                    ),
+                );
+            }
+        }
+        self.raim_excluded = raim.excluded.iter().map(|(id, _)| *id).collect();
+
+        if raim.fault != self.raim_fault {
+            let (severity, message) = match raim.fault {
+                true => (
+                    Severity::Critical,
+                    format!(
+                        "RAIM detected a fault it cannot exclude (chi-square {:.1}, threshold {:.1})",
+                        raim.chi_square,
+                        raim.threshold.unwrap_or(0.0)
+                    ),
+                ),
+                false => (Severity::Info, "RAIM fault cleared".to_owned()),
+            };
+            self.raise(host, severity, message);
+            self.raim_fault = raim.fault;
+        }
+
+        let exceeded = raim
+            .hpl
+            .is_some_and(|h| h > self.thresholds.horizontal_alert_limit)
+            || raim
+                .vpl
+                .is_some_and(|v| v > self.thresholds.vertical_alert_limit);
+        if exceeded && !self.protection_exceeded {
+            self.raise(
+                host,
+                Severity::Warning,
+                format!(
+                    "Protection levels exceed the alert limits (HPL {:.1} m, VPL {:.1} m)",
+                    raim.hpl.unwrap_or(0.0),
+                    raim.vpl.unwrap_or(0.0)
+                ),
+            );
+        }
+        self.protection_exceeded = exceeded;
+    }
+
+    fn check_position_jump(&mut self, state: &ReceiverState, host: DateTime<Utc>) {
+        let Some(fix) = state.fix.as_ref().filter(|f| f.fix_type.has_position()) else {
+            return;
+        };
+        let Some(time) = state.time else {
+            return;
+        };
+        let current = (time.instant, fix.latitude, fix.longitude);
+        let Some((then, lat, lon)) = self.last_fix else {
+            self.last_fix = Some(current);
+            return;
+        };
+        if (lat, lon) == (fix.latitude, fix.longitude) {
+            return;
+        }
+        self.last_fix = Some(current);
+
+        // Receiver time, so fixes that reach us late or together are not mistaken for speed.
+        let dt = time.instant.seconds_since(&then);
+        let distance = great_circle_distance(lat, lon, fix.latitude, fix.longitude);
+        let noise = 3.0 * fix.h_acc.unwrap_or(0.0);
+        if dt > 0.0 && distance > noise && distance / dt > self.thresholds.max_speed {
+            self.raise(
+                host,
+                Severity::Warning,
+                format!(
+                    "Position jumped {:.0} m in {:.1} s ({:.0} m/s)",
+                    distance,
+                    dt,
+                    distance / dt
+                ),
+            );
+        }
+    }
+
+    /// Compares each receiver epoch with the least delayed recent one. Data can only arrive
+    /// late, so an epoch arriving earlier than that means the receiver clock stepped forward,
+    /// and every epoch of a whole window arriving later means it stepped back.
+    fn check_time_jump(&mut self, state: &ReceiverState, host: DateTime<Utc>) {
+        let Some(time) = state.time else {
+            return;
+        };
+        if self
+            .time_offsets
+            .back()
+            .is_some_and(|(_, instant, _)| *instant == time.instant)
+        {
+            return;
+        }
+        let offset = host_instant(time.received, state.leap.gps_utc).seconds_since(&time.instant);
+        self.time_offsets
+            .push_back((time.received, time.instant, offset));
+        while self.time_offsets.front().is_some_and(|(received, _, _)| {
+            (time.received - *received).num_milliseconds() as f64 * 1e-3 > TIME_JUMP_WINDOW
+        }) {
+            self.time_offsets.pop_front();
+        }
+        let floor = self
+            .time_offsets
+            .iter()
+            .map(|(_, _, offset)| *offset)
+            .fold(f64::INFINITY, f64::min);
+        let Some(previous) = self.time_floor.replace(floor) else {
+            return;
+        };
+
+        let jump = previous - floor;
+        if jump.abs() > self.thresholds.max_time_jump {
+            self.raise(
+                host,
+                Severity::Warning,
+                format!(
+                    "Receiver time jumped {:+.3} s relative to the host clock",
+                    jump
+                ),
+            );
+            // Measure from the new clock only.
+            self.time_offsets
+                .retain(|(_, instant, _)| *instant == time.instant);
+            self.time_floor = Some(offset);
+        }
+    }
+}
+
+/// Haversine distance in metres on a spherical Earth.
+fn great_circle_distance(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+    let (phi1, phi2) = (lat1.to_radians(), lat2.to_radians());
+    let dphi = phi2 - phi1;
+    let dlambda = (lon2 - lon1).to_radians();
+    let a = (dphi / 2.0).sin().powi(2) + phi1.cos() * phi2.cos() * (dlambda / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS * a.sqrt().asin()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gnss::{Fix, FixType};
+    use crate::raim::Raim;
+    use crate::receiver::ReceiverTime;
+    use crate::ubx::{NavStatus, RxmRawx, UbxFrame, CLASS_MON, ID_MON_RF};
+    use chrono::Duration;
+
+    fn rf(jamming: u8) -> GnssMessage {
+        let mut p = vec![0; 28];
+        p[1] = 1;
+        p[5] = jamming;
+        let frame = UbxFrame::new(CLASS_MON, ID_MON_RF, p);
+        GnssMessage::Ubx(UbxMessage::decode(&frame).unwrap())
+    }
+
+    fn status(flags2: u8) -> GnssMessage {
+        GnssMessage::Ubx(UbxMessage::NavStatus(NavStatus {
+            itow_ms: 0,
+            fix_type: FixType::Fix3D,
+            flags: 0,
+            fix_stat: 0,
+            flags2,
+            ttff_ms: 0,
+            msss_ms: 0,
+        }))
+    }
+
+    #[test]
+    fn reports_jamming_changes() {
+        let (state, host) = (ReceiverState::default(), Utc::now());
+        let mut monitor = IntegrityMonitor::default();
+        // Starting out fine raises nothing, a warning and the recovery do.
+        for jamming in [1, 1, 2, 2, 3, 1] {
+            monitor.check(&state, &rf(jamming), host);
+        }
+        let severities: Vec<Severity> = monitor.take_new().iter().map(|e| e.severity).collect();
+        assert_eq!(
+            severities,
+            [Severity::Warning, Severity::Critical, Severity::Info]
+        );
+        assert!(monitor.take_new().is_empty());
+        assert_eq!(monitor.events.len(), 3);
+    }
+
+    #[test]
+    fn reports_spoofing_flags() {
+        let (state, host) = (ReceiverState::default(), Utc::now());
+        let mut monitor = IntegrityMonitor::default();
+        for spoofing in [1, 2, 2, 3, 1] {
+            monitor.check(&state, &status(spoofing << 3), host);
+        }
+        let events = monitor.take_new();
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0].severity, Severity::Warning);
+        assert_eq!(events[1].severity, Severity::Critical);
+        assert_eq!(events[2].message, "Receiver reports: No spoofing indicated");
+    }
+
+    #[test]
+    fn reports_time_jumps_but_not_delayed_epochs() {
+        let mut state = ReceiverState::default();
+        let mut monitor = IntegrityMonitor::default();
+        let start = Utc::now();
+        let gps_utc = state.leap.gps_utc;
+        let mut epoch = |monitor: &mut IntegrityMonitor, gps: f64, host: f64| {
+            let received = start + Duration::milliseconds((host * 1e3) as i64);
+            state.time = Some(ReceiverTime {
+                instant: host_instant(start, gps_utc).offset(gps),
+                received,
+            });
+            monitor.check(&state, &status(0), received);
+        };
+        // One epoch a second, then a stall holding four epochs back until they arrive
+        // together, then back to normal.
+        for t in 0..4 {
+            epoch(&mut monitor, t as f64, t as f64 + 0.05);
+        }
+        for t in 4..8 {
+            epoch(&mut monitor, t as f64, 7.5);
+        }
+        for t in 8..12 {
+            epoch(&mut monitor, t as f64, t as f64 + 0.05);
+        }
+        assert!(monitor.take_new().is_empty());
+
+        // The receiver steps 5 s ahead.
+        epoch(&mut monitor, 17.0, 12.05);
+        let events = monitor.take_new();
+        assert_eq!(events.len(), 1);
+        assert!(
+            events[0].message.contains("+5.000 s"),
+            "{}",
+            events[0].message
+        );
+
+        // Then 3 s back, which only shows once every recent epoch arrived late.
+        for t in 13..30 {
+            epoch(&mut monitor, t as f64 + 2.0, t as f64 + 0.05);
+        }
+        let events = monitor.take_new();
+        assert_eq!(events.len(), 1);
+        assert!(
+            events[0].message.contains("-3.000 s"),
+            "{}",
+            events[0].message
+        );
+    }
+
+    #[test]
+    fn measures_position_jumps_on_receiver_time() {
+        let mut state = ReceiverState::default();
+        let mut monitor = IntegrityMonitor::default();
+        let start = Utc::now();
+        let gps_utc = state.leap.gps_utc;
+        let mut fix = |monitor: &mut IntegrityMonitor, gps: f64, host: f64, latitude: f64| {
+            let received = start + Duration::milliseconds((host * 1e3) as i64);
+            state.time = Some(ReceiverTime {
+                instant: host_instant(start, gps_utc).offset(gps),
+                received,
+            });
+            state.fix = Some(Fix {
+                fix_type: FixType::Fix3D,
+                latitude,
+                longitude: 0.0,
+                height: None,
+                height_msl: None,
+                num_sv: 8,
+                h_acc: Some(2.0),
+                v_acc: None,
+                epoch: None,
+            });
+            monitor.check(&state, &status(0), received);
+        };
+        // 100 m/s, with one fix held up until just before the next.
+        fix(&mut monitor, 0.0, 0.0, 0.0);
+        fix(&mut monitor, 1.0, 1.9, 0.0009);
+        fix(&mut monitor, 2.0, 2.0, 0.0018);
+        assert!(monitor.take_new().is_empty());
+
+        // 1 km in a second.
+        fix(&mut monitor, 3.0, 3.0, 0.0108);
+        assert_eq!(monitor.take_new().len(), 1);
+    }
+
+    #[test]
+    fn unlatches_raim_when_the_solution_drops_out() {
+        let mut state = ReceiverState::default();
+        let mut monitor = IntegrityMonitor::default();
+        let host = Utc::now();
+        let rawx = GnssMessage::Ubx(UbxMessage::RxmRawx(RxmRawx {
+            rcv_tow: 0.0,
+            week: 2300,
+            leap_s: 18,
+            rec_stat: 0,
+            measurements: Vec::new(),
+        }));
+        let faulty = Raim {
+            chi_square: 40.0,
+            threshold: Some(20.0),
+            fault: true,
+            excluded: Vec::new(),
+            hpl: Some(80.0),
+            vpl: Some(90.0),
+        };
+
+        state.raim = Some(faulty.clone());
+        monitor.check(&state, &rawx, host);
+        let severities: Vec<Severity> = monitor.take_new().iter().map(|e| e.severity).collect();
+        assert_eq!(severities, [Severity::Critical, Severity::Warning]);
+
+        state.raim = None;
+        monitor.check(&state, &rawx, host);
+        monitor.check(&state, &rawx, host);
+        let events = monitor.take_new();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].message, "RAIM unavailable: no local solution");
+
+        // The fault is reported again once there is a solution showing it.
+        state.raim = Some(faulty);
+        monitor.check(&state, &rawx, host);
+        assert_eq!(monitor.take_new().len(), 2);
+    }
+
+    #[test]
+    fn bounds_the_event_log() {
+        let host = Utc::now();
+        let mut monitor = IntegrityMonitor::default();
+        for i in 0..MAX_EVENTS + 10 {
+            monitor.raise(host, Severity::Info, i.to_string());
+        }
+        assert_eq!(monitor.events.len(), MAX_EVENTS);
+        assert_eq!(monitor.events[0].message, "10");
+        assert_eq!(monitor.take_new().len(), MAX_EVENTS + 10);
+    }
+}
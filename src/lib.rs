@@ -1,5 +1,32 @@
 mod app;
 pub use app::GenCamGUI;
 
+pub mod astronomy;
+pub mod base;
+pub mod cno_map;
+pub mod comparison;
+pub mod datum;
+pub mod ephemeris;
+pub mod geodesy;
+pub mod geoid;
+pub mod gnss;
+pub mod integrity;
+pub mod map;
+pub mod mbtiles;
+pub mod multipath;
+pub mod nmea;
+pub mod raim;
+pub mod receiver;
+pub mod sbas;
+pub mod spectrum;
+pub mod spp;
+pub mod stability;
+pub mod subframe;
+pub mod time_scales;
+pub mod ttff;
+pub mod ubx;
+pub mod visibility;
+pub mod waypoints;
+
 #[cfg(target_arch = "wasm32")]
 mod web;
@@ -6,7 +6,6 @@ use gencam_gui::GenCamGUI;
 // When compiling natively:
 #[cfg(not(target_arch = "wasm32"))]
 fn main() -> eframe::Result {
-
     env_logger::init(); // Log to stderr (if you run with `RUST_LOG=debug`).
 
     let native_options = eframe::NativeOptions {
@@ -26,7 +25,7 @@ fn main() -> eframe::Result {
         Box::new(|cc| {
             // This gives us image support:
             egui_extras::install_image_loaders(&cc.egui_ctx);
-            Ok(Box::<GenCamGUI>::default())
+            Ok(Box::new(GenCamGUI::new(cc)))
         }),
     )
 }
@@ -47,7 +46,7 @@ fn main() {
                 Box::new(|cc| {
                     // This gives us image support:
                     egui_extras::install_image_loaders(&cc.egui_ctx);
-                    Ok(Box::<GenCamGUI>::default())
+                    Ok(Box::new(GenCamGUI::new(cc)))
                 }),
             )
             .await;
@@ -70,4 +69,4 @@ fn main() {
             }
         }
     });
-}
\ No newline at end of file
+}
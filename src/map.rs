@@ -0,0 +1,299 @@
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+
+use image::RgbaImage;
+use serde::{Deserialize, Serialize};
+
+use crate::geodesy::WGS84_A;
+use crate::gnss::ErrorEllipse;
+use crate::mbtiles::MbTiles;
+
+/// Half the extent of the Web Mercator world in metres.
+pub const MERCATOR_EXTENT: f64 = std::f64::consts::PI * WGS84_A;
+/// Latitude where Web Mercator is cut off, making the world square.
+pub const MAX_LATITUDE: f64 = 85.051_128_78;
+
+/// Web Mercator (EPSG:3857) coordinates in metres of a position in degrees.
+pub fn mercator(latitude: f64, longitude: f64) -> [f64; 2] {
+    let phi = latitude.clamp(-MAX_LATITUDE, MAX_LATITUDE).to_radians();
+    [
+        WGS84_A * longitude.to_radians(),
+        WGS84_A * (std::f64::consts::FRAC_PI_4 + phi / 2.0).tan().ln(),
+    ]
+}
+
+/// Latitude and longitude in degrees of Web Mercator coordinates.
+pub fn inverse_mercator(point: [f64; 2]) -> (f64, f64) {
+    let latitude = 2.0 * (point[1] / WGS84_A).exp().atan() - std::f64::consts::FRAC_PI_2;
+    (latitude.to_degrees(), (point[0] / WGS84_A).to_degrees())
+}
+
+/// Web Mercator metres per metre on the ground at a latitude.
+pub fn mercator_scale(latitude: f64) -> f64 {
+    1.0 / latitude
+        .clamp(-MAX_LATITUDE, MAX_LATITUDE)
+        .to_radians()
+        .cos()
+}
+
+/// Outline in Web Mercator metres of a circle of `radius` metres on the ground. Uses the
+/// scale at the centre, which is fine for circles of a few kilometres.
+pub fn circle(latitude: f64, longitude: f64, radius: f64) -> Vec<[f64; 2]> {
+    let [x, y] = mercator(latitude, longitude);
+    let r = radius * mercator_scale(latitude);
+    (0..64)
+        .map(|i| {
+            let angle = i as f64 / 64.0 * std::f64::consts::TAU;
+            [x + r * angle.cos(), y + r * angle.sin()]
+        })
+        .collect()
+}
+
+/// Outline in Web Mercator metres of an error ellipse around a position, scaled by `scale`
+/// standard deviations.
+pub fn ellipse(latitude: f64, longitude: f64, ellipse: &ErrorEllipse, scale: f64) -> Vec<[f64; 2]> {
+    let [x, y] = mercator(latitude, longitude);
+    let k = mercator_scale(latitude);
+    ellipse
+        .outline(scale)
+        .into_iter()
+        .map(|[e, n]| [x + k * e, y + k * n])
+        .collect()
+}
+
+/// Tile size in pixels of the slippy map scheme.
+pub const TILE_SIZE: f64 = 256.0;
+
+/// South-west and north-east corners in Web Mercator metres of an XYZ tile.
+pub fn tile_bounds(zoom: u8, x: u32, y: u32) -> ([f64; 2], [f64; 2]) {
+    let size = 2.0 * MERCATOR_EXTENT / (1u64 << zoom) as f64;
+    let west = -MERCATOR_EXTENT + x as f64 * size;
+    let north = MERCATOR_EXTENT - y as f64 * size;
+    ([west, north - size], [west + size, north])
+}
+
+/// The XYZ tile containing a point in Web Mercator metres, clamped to the world.
+pub fn tile_at(zoom: u8, point: [f64; 2]) -> (u32, u32) {
+    let n = (1u64 << zoom) as f64;
+    let index = |v: f64| {
+        ((v / (2.0 * MERCATOR_EXTENT)) * n)
+            .floor()
+            .clamp(0.0, n - 1.0) as u32
+    };
+    (
+        index(point[0] + MERCATOR_EXTENT),
+        index(MERCATOR_EXTENT - point[1]),
+    )
+}
+
+/// The lowest zoom level whose tiles have at least the given resolution in metres per pixel.
+pub fn zoom_for(metres_per_pixel: f64) -> u8 {
+    let world_pixels = 2.0 * MERCATOR_EXTENT / metres_per_pixel.max(1e-3);
+    (world_pixels / TILE_SIZE).log2().ceil().clamp(0.0, 24.0) as u8
+}
+
+/// Longest side of a georeferenced image; larger images are scaled down to fit a texture.
+const MAX_IMAGE_SIZE: u32 = 4096;
+
+/// An image placed on the map by a world file.
+pub struct GeoImage {
+    pub name: String,
+    pub image: RgbaImage,
+    /// South-west and north-east corners in Web Mercator metres.
+    pub bounds: ([f64; 2], [f64; 2]),
+}
+
+/// The world file next to an image: `.pgw` or `.pngw` for `.png`, `.wld` for anything.
+fn world_file(path: &Path) -> Option<PathBuf> {
+    let ext = path.extension()?.to_string_lossy().to_lowercase();
+    let mut short = String::new();
+    short.extend(ext.chars().next());
+    short.extend(ext.chars().last());
+    short.push('w');
+    [short, format!("{}w", ext), "wld".to_string()]
+        .iter()
+        .map(|e| path.with_extension(e))
+        .find(|p| p.exists())
+}
+
+impl GeoImage {
+    /// Loads an image and its world file. The world file may be in degrees (EPSG:4326) or
+    /// Web Mercator metres, told apart by the size of the coordinates; rotation terms are not
+    /// supported.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let invalid = |message: &str| io::Error::new(ErrorKind::InvalidData, message.to_owned());
+        let world = world_file(path).ok_or_else(|| invalid("no world file next to the image"))?;
+        let terms = std::fs::read_to_string(world)?
+            .split_whitespace()
+            .map(|v| v.parse::<f64>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| invalid("bad world file"))?;
+        let [a, d, b, e, c, f] = terms[..] else {
+            return Err(invalid("a world file has six lines"));
+        };
+        if d != 0.0 || b != 0.0 {
+            return Err(invalid("rotated world files are not supported"));
+        }
+        let image = image::open(path).map_err(|e| invalid(&e.to_string()))?;
+
+        // The world file locates the centre of the top left pixel.
+        let (left, top) = (c - a / 2.0, f - e / 2.0);
+        let right = left + a * image.width() as f64;
+        let bottom = top + e * image.height() as f64;
+        let geographic = c.abs() <= 360.0 && f.abs() <= 90.0;
+        let corner = |x: f64, y: f64| match geographic {
+            true => mercator(y, x),
+            false => [x, y],
+        };
+        let (sw, ne) = (
+            corner(left.min(right), top.min(bottom)),
+            corner(left.max(right), top.max(bottom)),
+        );
+        let image = match image.width().max(image.height()) {
+            size if size > MAX_IMAGE_SIZE => image.thumbnail(MAX_IMAGE_SIZE, MAX_IMAGE_SIZE),
+            _ => image,
+        };
+        Ok(Self {
+            name: path
+                .file_name()
+                .map_or(String::new(), |n| n.to_string_lossy().into_owned()),
+            image: image.to_rgba8(),
+            bounds: (sw, ne),
+        })
+    }
+}
+
+/// The kind of offline map to load from a path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MapSourceKind {
+    MbTiles,
+    TileDirectory,
+    Image,
+}
+
+impl MapSourceKind {
+    pub const ALL: [MapSourceKind; 3] = [
+        MapSourceKind::MbTiles,
+        MapSourceKind::TileDirectory,
+        MapSourceKind::Image,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            MapSourceKind::MbTiles => "MBTiles file",
+            MapSourceKind::TileDirectory => "Tile directory (z/x/y.png)",
+            MapSourceKind::Image => "Image with world file",
+        }
+    }
+}
+
+/// A local source of map imagery.
+pub enum MapSource {
+    MbTiles(MbTiles),
+    /// A `{z}/{x}/{y}.png` (or `.jpg`) tree and its highest zoom level.
+    TileDirectory(PathBuf, u8),
+    Image(GeoImage),
+}
+
+impl MapSource {
+    pub fn load(kind: MapSourceKind, path: &Path) -> io::Result<Self> {
+        match kind {
+            MapSourceKind::MbTiles => MbTiles::open(path).map(MapSource::MbTiles),
+            MapSourceKind::TileDirectory => {
+                let max_zoom = std::fs::read_dir(path)?
+                    .filter_map(|e| e.ok()?.file_name().to_str()?.parse::<u8>().ok())
+                    .max()
+                    .ok_or_else(|| {
+                        io::Error::new(ErrorKind::InvalidData, "no zoom level directories")
+                    })?;
+                Ok(MapSource::TileDirectory(path.to_owned(), max_zoom))
+            }
+            MapSourceKind::Image => GeoImage::load(path).map(MapSource::Image),
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            MapSource::MbTiles(tiles) => tiles.name.clone(),
+            MapSource::TileDirectory(path, _) => path.display().to_string(),
+            MapSource::Image(image) => image.name.clone(),
+        }
+    }
+
+    /// Highest zoom level of a tiled source.
+    pub fn max_zoom(&self) -> Option<u8> {
+        match self {
+            MapSource::MbTiles(tiles) => Some(tiles.max_zoom),
+            MapSource::TileDirectory(_, zoom) => Some(*zoom),
+            MapSource::Image(_) => None,
+        }
+    }
+
+    /// A decoded XYZ tile, or `None` if the source does not have it.
+    pub fn tile(&mut self, zoom: u8, x: u32, y: u32) -> io::Result<Option<RgbaImage>> {
+        let bytes = match self {
+            MapSource::MbTiles(tiles) => tiles.tile(zoom, x, y)?,
+            MapSource::TileDirectory(root, _) => {
+                let dir = root.join(zoom.to_string()).join(x.to_string());
+                ["png", "jpg", "jpeg"]
+                    .iter()
+                    .map(|ext| dir.join(format!("{}.{}", y, ext)))
+                    .find(|p| p.exists())
+                    .map(std::fs::read)
+                    .transpose()?
+            }
+            MapSource::Image(_) => None,
+        };
+        bytes
+            .map(|b| {
+                image::load_from_memory(&b)
+                    .map(|i| i.to_rgba8())
+                    .map_err(|e| io::Error::new(ErrorKind::InvalidData, e.to_string()))
+            })
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn web_mercator_tiles() {
+        let p = mercator(52.37, 4.89);
+        let (lat, lon) = inverse_mercator(p);
+        assert!((lat - 52.37).abs() < 1e-9 && (lon - 4.89).abs() < 1e-9);
+        assert_eq!(
+            mercator(MAX_LATITUDE, 180.0).map(|v| v.round()),
+            [MERCATOR_EXTENT.round(), MERCATOR_EXTENT.round()]
+        );
+
+        // Amsterdam is tile 16/33658/21537 in the OpenStreetMap scheme.
+        assert_eq!(tile_at(16, p), (33658, 21537));
+        let (sw, ne) = tile_bounds(16, 33658, 21537);
+        assert!(sw[0] <= p[0] && p[0] < ne[0] && sw[1] <= p[1] && p[1] < ne[1]);
+        assert_eq!(tile_bounds(0, 0, 0).1, [MERCATOR_EXTENT, MERCATOR_EXTENT]);
+        // Zoom 0 is 156 km per pixel, each level halves it.
+        assert_eq!(zoom_for(156_543.0), 1);
+        assert_eq!(zoom_for(156_544.0), 0);
+        assert_eq!(zoom_for(1.0), 18);
+    }
+
+    #[test]
+    fn places_image_by_world_file() {
+        let dir = std::env::temp_dir().join(format!("geoimage-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("site.png");
+        RgbaImage::new(4, 2).save(&path).unwrap();
+        // 0.25° pixels from 10°E, 50°N.
+        std::fs::write(dir.join("site.pgw"), "0.25\n0\n0\n-0.25\n10.125\n49.875\n").unwrap();
+        let image = GeoImage::load(&path).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let (sw, ne) = image.bounds;
+        let (south, west) = inverse_mercator(sw);
+        let (north, east) = inverse_mercator(ne);
+        assert!((west - 10.0).abs() < 1e-9 && (east - 11.0).abs() < 1e-9);
+        assert!((north - 50.0).abs() < 1e-9 && (south - 49.5).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,163 @@
+use std::io::{self, ErrorKind};
+use std::path::Path;
+
+#[cfg(not(target_arch = "wasm32"))]
+use rusqlite::{Connection, OpenFlags, OptionalExtension};
+
+#[cfg(not(target_arch = "wasm32"))]
+fn invalid(error: rusqlite::Error) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, error)
+}
+
+/// Read-only access to the tiles of an MBTiles file.
+///
+/// MBTiles are SQLite databases with a `tiles` table or view, which covers both the plain
+/// layout and the deduplicated `map` and `images` layout. Opening only reads the schema and
+/// the zoom range, tiles are looked up when drawn.
+pub struct MbTiles {
+    pub name: String,
+    #[cfg(not(target_arch = "wasm32"))]
+    connection: Connection,
+    pub max_zoom: u8,
+}
+
+impl MbTiles {
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn open(path: &Path) -> io::Result<Self> {
+        if !path.is_file() {
+            return Err(io::Error::new(ErrorKind::NotFound, "no such file"));
+        }
+        let connection = Connection::open_with_flags(
+            path,
+            OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )
+        .map_err(invalid)?;
+        // The metadata table is optional; without it the zoom range comes from the tiles.
+        let metadata: Option<String> = connection
+            .query_row(
+                "SELECT value FROM metadata WHERE name = 'maxzoom'",
+                [],
+                |row| row.get(0),
+            )
+            .optional()
+            .unwrap_or(None);
+        let max_zoom = match metadata.and_then(|v| v.trim().parse::<u8>().ok()) {
+            Some(zoom) => zoom,
+            None => connection
+                .query_row("SELECT MAX(zoom_level) FROM tiles", [], |row| {
+                    row.get::<_, Option<u8>>(0)
+                })
+                .map_err(invalid)?
+                .unwrap_or(0),
+        };
+        Ok(Self {
+            name: path
+                .file_name()
+                .map_or(String::new(), |n| n.to_string_lossy().into_owned()),
+            connection,
+            max_zoom,
+        })
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn open(_path: &Path) -> io::Result<Self> {
+        Err(io::Error::new(
+            ErrorKind::Unsupported,
+            "MBTiles are not available on the web",
+        ))
+    }
+
+    /// The encoded image of a tile in XYZ numbering (row 0 at the north), if present.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn tile(&mut self, zoom: u8, x: u32, y: u32) -> io::Result<Option<Vec<u8>>> {
+        let Some(row) = 1u32
+            .checked_shl(zoom as u32)
+            .and_then(|n| n.checked_sub(y.checked_add(1)?))
+        else {
+            return Ok(None);
+        };
+        self.connection
+            .prepare_cached(
+                "SELECT tile_data FROM tiles \
+                 WHERE zoom_level = ?1 AND tile_column = ?2 AND tile_row = ?3",
+            )
+            .and_then(|mut statement| {
+                statement
+                    .query_row((zoom, x, row), |r| r.get::<_, Vec<u8>>(0))
+                    .optional()
+            })
+            .map_err(invalid)
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn tile(&mut self, _zoom: u8, _x: u32, _y: u32) -> io::Result<Option<Vec<u8>>> {
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("mbtiles-{}-{}.mbtiles", name, std::process::id()))
+    }
+
+    /// Writes an MBTiles file with a plain `tiles` table.
+    fn write_tiles(path: &Path, metadata: bool) {
+        let _ = std::fs::remove_file(path);
+        let db = Connection::open(path).unwrap();
+        db.execute_batch(
+            "CREATE TABLE tiles (zoom_level integer, tile_column integer, tile_row integer, \
+             tile_data blob, PRIMARY KEY (zoom_level, tile_column, tile_row));
+             INSERT INTO tiles VALUES (0, 0, 0, CAST('world' AS BLOB));
+             INSERT INTO tiles VALUES (1, 1, 0, CAST('south-east' AS BLOB));",
+        )
+        .unwrap();
+        if metadata {
+            db.execute_batch(
+                "CREATE TABLE metadata (name text, value text);
+                 INSERT INTO metadata VALUES ('maxzoom', '1');",
+            )
+            .unwrap();
+        }
+    }
+
+    #[test]
+    fn reads_tiles_table() {
+        for metadata in [false, true] {
+            let path = temp_path(&format!("plain-{}", metadata));
+            write_tiles(&path, metadata);
+            let mut mbtiles = MbTiles::open(&path).unwrap();
+            assert_eq!(mbtiles.max_zoom, 1);
+            assert_eq!(mbtiles.tile(0, 0, 0).unwrap().unwrap(), b"world");
+            // TMS row 0 is the southern row, XYZ row 1 at zoom 1.
+            assert_eq!(mbtiles.tile(1, 1, 1).unwrap().unwrap(), b"south-east");
+            assert_eq!(mbtiles.tile(1, 1, 0).unwrap(), None);
+            assert_eq!(mbtiles.tile(40, 0, u32::MAX).unwrap(), None);
+            drop(mbtiles);
+            std::fs::remove_file(&path).unwrap();
+        }
+    }
+
+    #[test]
+    fn rejects_truncated_and_corrupt_files() {
+        let path = temp_path("damaged");
+        write_tiles(&path, false);
+        let bytes = std::fs::read(&path).unwrap();
+        // Reading a damaged file is an error, never a panic.
+        let read = |bytes: &[u8]| {
+            std::fs::write(&path, bytes).unwrap();
+            MbTiles::open(&path).and_then(|mut t| t.tile(0, 0, 0))
+        };
+        assert!(read(&bytes[..50]).is_err());
+        assert!(read(&bytes[..bytes.len() / 2]).is_err());
+        let mut corrupt = bytes.clone();
+        for b in corrupt[100..].iter_mut() {
+            *b = 0xA5;
+        }
+        assert!(read(&corrupt).is_err());
+        assert!(read(b"not a database at all, just some text").is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,453 @@
+use std::collections::{BTreeMap, VecDeque};
+
+use crate::ephemeris::SPEED_OF_LIGHT;
+use crate::gnss::{Constellation, Satellite, SatelliteId};
+use crate::time_scales::SECONDS_PER_WEEK;
+use crate::ubx::{RawMeasurement, RxmRawx};
+
+/// Samples kept per series, an hour of 2 Hz measurements.
+pub const MAX_SAMPLES: usize = 7_200;
+/// Seconds without a measurement after which a satellite starts a new arc.
+pub const MAX_GAP: f64 = 60.0;
+/// Epochs an arc needs before its mean is removed and it is shown.
+pub const MIN_ARC_EPOCHS: usize = 10;
+/// Largest change of the geometry-free phase combination without a cycle slip, in m/s; the
+/// ionospheric rate limit of teqc, 400 cm/min.
+pub const IONOSPHERE_RATE: f64 = 4.0 / 60.0;
+/// Smallest geometry-free jump reported as a slip regardless of the epoch interval, in metres.
+const GEOMETRY_FREE_FLOOR: f64 = 0.05;
+/// Slip events kept.
+const MAX_SLIPS: usize = 1_000;
+
+/// Name and carrier frequency in Hz of a UBX signal (`gnssId`, `sigId`, and `freqId` for the
+/// GLONASS frequency slot).
+pub fn signal(
+    constellation: Constellation,
+    sig_id: u8,
+    freq_id: u8,
+) -> Option<(&'static str, f64)> {
+    const L1: f64 = 1_575.42e6;
+    const L2: f64 = 1_227.60e6;
+    const L5: f64 = 1_176.45e6;
+    const E5B: f64 = 1_207.14e6;
+    let slot = freq_id as f64 - 7.0;
+    Some(match (constellation, sig_id) {
+        (Constellation::Gps, 0) => ("L1C/A", L1),
+        (Constellation::Gps, 3) => ("L2CL", L2),
+        (Constellation::Gps, 4) => ("L2CM", L2),
+        (Constellation::Gps, 6) => ("L5I", L5),
+        (Constellation::Gps, 7) => ("L5Q", L5),
+        (Constellation::Sbas, 0) => ("L1C/A", L1),
+        (Constellation::Galileo, 0) => ("E1C", L1),
+        (Constellation::Galileo, 1) => ("E1B", L1),
+        (Constellation::Galileo, 3) => ("E5aI", L5),
+        (Constellation::Galileo, 4) => ("E5aQ", L5),
+        (Constellation::Galileo, 5) => ("E5bI", E5B),
+        (Constellation::Galileo, 6) => ("E5bQ", E5B),
+        (Constellation::BeiDou, 0) => ("B1I D1", 1_561.098e6),
+        (Constellation::BeiDou, 1) => ("B1I D2", 1_561.098e6),
+        (Constellation::BeiDou, 2) => ("B2I D1", E5B),
+        (Constellation::BeiDou, 3) => ("B2I D2", E5B),
+        (Constellation::BeiDou, 5) => ("B1Cp", L1),
+        (Constellation::BeiDou, 6) => ("B1Cd", L1),
+        (Constellation::BeiDou, 7) => ("B2ap", L5),
+        (Constellation::BeiDou, 8) => ("B2ad", L5),
+        (Constellation::Qzss, 0) => ("L1C/A", L1),
+        (Constellation::Qzss, 1) => ("L1S", L1),
+        (Constellation::Qzss, 4) => ("L2CM", L2),
+        (Constellation::Qzss, 5) => ("L2CL", L2),
+        (Constellation::Qzss, 8) => ("L5I", L5),
+        (Constellation::Qzss, 9) => ("L5Q", L5),
+        (Constellation::Glonass, 0) => ("L1OF", 1_602.0e6 + slot * 0.5625e6),
+        (Constellation::Glonass, 2) => ("L2OF", 1_246.0e6 + slot * 0.4375e6),
+        (Constellation::NavIc, 0) => ("L5A", L5),
+        _ => return None,
+    })
+}
+
+/// Quantity of a multipath series.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Combination {
+    /// Pseudorange minus carrier phase of the first signal, in metres.
+    CodeMinusCarrier,
+    /// Dual-frequency multipath of the first and second signal's pseudorange.
+    Mp1,
+    Mp2,
+}
+
+impl Combination {
+    pub const ALL: [Combination; 3] = [
+        Combination::CodeMinusCarrier,
+        Combination::Mp1,
+        Combination::Mp2,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Combination::CodeMinusCarrier => "Code minus carrier",
+            Combination::Mp1 => "MP1",
+            Combination::Mp2 => "MP2",
+        }
+    }
+}
+
+/// Why a cycle slip was flagged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlipCause {
+    /// The receiver restarted its carrier lock time or lost the phase.
+    LockLoss,
+    /// The geometry-free phase changed faster than the ionosphere can.
+    Ionosphere,
+}
+
+impl SlipCause {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SlipCause::LockLoss => "Loss of lock",
+            SlipCause::Ionosphere => "Ionospheric jump",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CycleSlip {
+    pub id: SatelliteId,
+    pub sig_id: u8,
+    /// Seconds since the GPS epoch.
+    pub time: f64,
+    pub elevation: Option<f64>,
+    pub cause: SlipCause,
+}
+
+/// One value of a combination.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sample {
+    /// Seconds since the GPS epoch.
+    pub time: f64,
+    pub elevation: Option<f64>,
+    /// Metres, still including the arc's constant ambiguity and hardware biases.
+    pub value: f64,
+    pub arc: u32,
+}
+
+/// The values of one combination for one satellite.
+#[derive(Debug, Clone, Default)]
+pub struct Series {
+    samples: VecDeque<Sample>,
+}
+
+impl Series {
+    fn push(&mut self, sample: Sample) {
+        if self.samples.len() == MAX_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// The samples of arcs at least `MIN_ARC_EPOCHS` long with the arc mean removed, which
+    /// leaves the multipath and noise (and, for code minus carrier, twice the ionospheric
+    /// change over the arc).
+    pub fn levelled(&self) -> Vec<Sample> {
+        let mut means: BTreeMap<u32, (f64, usize)> = BTreeMap::new();
+        for s in &self.samples {
+            let m = means.entry(s.arc).or_default();
+            m.0 += s.value;
+            m.1 += 1;
+        }
+        self.samples
+            .iter()
+            .filter_map(|s| {
+                let (sum, n) = means[&s.arc];
+                (n >= MIN_ARC_EPOCHS).then(|| Sample {
+                    value: s.value - sum / n as f64,
+                    ..*s
+                })
+            })
+            .collect()
+    }
+
+    /// Root mean square of the levelled values, if any arc is long enough.
+    pub fn rms(&self) -> Option<f64> {
+        let levelled = self.levelled();
+        (!levelled.is_empty()).then(|| {
+            (levelled.iter().map(|s| s.value * s.value).sum::<f64>() / levelled.len() as f64).sqrt()
+        })
+    }
+}
+
+/// Continuity state of a tracked satellite.
+#[derive(Debug, Clone)]
+struct Track {
+    time: f64,
+    arc: u32,
+    /// Carrier lock time of each signal, in milliseconds.
+    lock: BTreeMap<u8, u16>,
+    /// Geometry-free phase combination in metres, with the signal pair it was formed from.
+    geometry_free: Option<(u8, u8, f64)>,
+}
+
+/// Signals of a satellite used for the combinations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SignalPair {
+    pub first: u8,
+    pub second: Option<u8>,
+}
+
+/// Code-minus-carrier, MP1/MP2 multipath and cycle slips of each satellite from the raw
+/// measurements, along the lines of a teqc quality check.
+#[derive(Debug, Clone, Default)]
+pub struct MultipathMonitor {
+    pub series: BTreeMap<(SatelliteId, Combination), Series>,
+    /// The signals each satellite's combinations were last formed from.
+    pub signals: BTreeMap<SatelliteId, SignalPair>,
+    pub slips: VecDeque<CycleSlip>,
+    /// Phase observations processed and slips seen, for the observations-per-slip ratio.
+    pub observations: u64,
+    pub slip_count: u64,
+    pub epochs: u32,
+    /// Time of the first epoch, in seconds since the GPS epoch.
+    pub start: Option<f64>,
+    tracks: BTreeMap<SatelliteId, Track>,
+}
+
+impl MultipathMonitor {
+    /// Adds an epoch of raw measurements, with the elevations taken from `satellites`.
+    pub fn add(&mut self, raw: &RxmRawx, satellites: &[Satellite]) {
+        let time = raw.week as f64 * SECONDS_PER_WEEK as f64 + raw.rcv_tow;
+        let mut by_satellite: BTreeMap<SatelliteId, Vec<(&RawMeasurement, f64)>> = BTreeMap::new();
+        for m in raw.measurements.iter() {
+            let Some((_, frequency)) = signal(m.id.constellation, m.sig_id, m.freq_id) else {
+                continue;
+            };
+            if m.pr_valid() && m.cp_valid() && m.carrier_phase != 0.0 {
+                by_satellite.entry(m.id).or_default().push((m, frequency));
+            }
+        }
+        for (id, mut measurements) in by_satellite {
+            let elevation = satellites
+                .iter()
+                .find(|s| s.id == id)
+                .and_then(|s| s.elevation)
+                .map(|e| e as f64);
+            // Highest frequency first, then the lowest signal id on it.
+            measurements.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.sig_id.cmp(&b.0.sig_id)));
+            self.add_satellite(id, time, elevation, &measurements);
+        }
+        self.start.get_or_insert(time);
+        self.epochs += 1;
+    }
+
+    fn add_satellite(
+        &mut self,
+        id: SatelliteId,
+        time: f64,
+        elevation: Option<f64>,
+        measurements: &[(&RawMeasurement, f64)],
+    ) {
+        let (m1, f1) = measurements[0];
+        let second = measurements.iter().find(|(_, f)| *f != f1).copied();
+        self.observations += measurements.len() as u64;
+
+        let mut slips: Vec<(u8, SlipCause)> = Vec::new();
+        let track = self.tracks.entry(id).or_insert(Track {
+            time,
+            arc: 0,
+            lock: BTreeMap::new(),
+            geometry_free: None,
+        });
+        let dt = time - track.time;
+        let gap = dt > MAX_GAP;
+        for (m, _) in measurements {
+            if !gap
+                && track
+                    .lock
+                    .get(&m.sig_id)
+                    .is_some_and(|&l| m.lock_time_ms < l)
+            {
+                slips.push((m.sig_id, SlipCause::LockLoss));
+            }
+            track.lock.insert(m.sig_id, m.lock_time_ms);
+        }
+        let geometry_free = second.map(|(m2, f2)| {
+            (
+                m1.sig_id,
+                m2.sig_id,
+                SPEED_OF_LIGHT * (m1.carrier_phase / f1 - m2.carrier_phase / f2),
+            )
+        });
+        if let (Some((a, b, gf)), Some((pa, pb, previous))) = (geometry_free, track.geometry_free) {
+            let limit = (IONOSPHERE_RATE * dt).max(GEOMETRY_FREE_FLOOR);
+            if !gap && (a, b) == (pa, pb) && slips.is_empty() && (gf - previous).abs() > limit {
+                slips.push((b, SlipCause::Ionosphere));
+            }
+        }
+        track.geometry_free = geometry_free;
+        track.time = time;
+        if gap || !slips.is_empty() {
+            track.arc += 1;
+        }
+        let arc = track.arc;
+
+        for (sig_id, cause) in slips {
+            self.slip_count += 1;
+            if self.slips.len() == MAX_SLIPS {
+                self.slips.pop_front();
+            }
+            self.slips.push_back(CycleSlip {
+                id,
+                sig_id,
+                time,
+                elevation,
+                cause,
+            });
+        }
+
+        // A change of signals changes the biases, so starts a new arc as well.
+        let pair = SignalPair {
+            first: m1.sig_id,
+            second: second.map(|(m, _)| m.sig_id),
+        };
+        let arc = match self.signals.insert(id, pair) {
+            Some(previous) if previous != pair => {
+                let track = self.tracks.get_mut(&id).expect("track inserted above");
+                track.arc += 1;
+                track.arc
+            }
+            _ => arc,
+        };
+
+        let mut push = |combination, value| {
+            self.series
+                .entry((id, combination))
+                .or_default()
+                .push(Sample {
+                    time,
+                    elevation,
+                    value,
+                    arc,
+                })
+        };
+        let l1 = SPEED_OF_LIGHT / f1 * m1.carrier_phase;
+        push(Combination::CodeMinusCarrier, m1.pseudorange - l1);
+        if let Some((m2, f2)) = second {
+            let l2 = SPEED_OF_LIGHT / f2 * m2.carrier_phase;
+            let alpha = (f1 / f2).powi(2);
+            push(
+                Combination::Mp1,
+                m1.pseudorange - (1.0 + 2.0 / (alpha - 1.0)) * l1 + 2.0 / (alpha - 1.0) * l2,
+            );
+            push(
+                Combination::Mp2,
+                m2.pseudorange - 2.0 * alpha / (alpha - 1.0) * l1
+                    + (2.0 * alpha / (alpha - 1.0) - 1.0) * l2,
+            );
+        }
+    }
+
+    /// Phase observations per cycle slip, teqc's o/slps; `None` before the first slip.
+    pub fn observations_per_slip(&self) -> Option<f64> {
+        (self.slip_count > 0).then(|| self.observations as f64 / self.slip_count as f64)
+    }
+
+    /// Slips of one satellite still in the event list.
+    pub fn slip_count_of(&self, id: SatelliteId) -> usize {
+        self.slips.iter().filter(|s| s.id == id).count()
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const F1: f64 = 1_575.42e6;
+    const F2: f64 = 1_227.60e6;
+
+    fn measurement(sig_id: u8, range: f64, iono: f64, f: f64, lock: u16) -> RawMeasurement {
+        let iono = iono * (F1 / f).powi(2);
+        RawMeasurement {
+            id: SatelliteId::new(Constellation::Gps, 5),
+            sig_id,
+            freq_id: 0,
+            pseudorange: range + iono,
+            // Arbitrary integer ambiguity.
+            carrier_phase: (range - iono) * f / SPEED_OF_LIGHT + 1_000.0,
+            doppler: 0.0,
+            lock_time_ms: lock,
+            cno: 45,
+            pr_stdev: 0.3,
+            cp_stdev: 0.004,
+            do_stdev: 0.1,
+            trk_stat: 0x07,
+        }
+    }
+
+    #[test]
+    fn combinations_and_slips() {
+        let mut monitor = MultipathMonitor::default();
+        let satellites = [Satellite {
+            id: SatelliteId::new(Constellation::Gps, 5),
+            cno: 45,
+            elevation: Some(30.0),
+            azimuth: Some(100.0),
+            used: true,
+        }];
+        for t in 0..40u16 {
+            let range = 21e6 + 500.0 * t as f64;
+            let iono = 5.0 + 0.01 * t as f64;
+            // Multipath of ±0.5 m on L1 code only.
+            let multipath = if t % 2 == 0 { 0.5 } else { -0.5 };
+            let mut l1 = measurement(0, range, iono, F1, 1000 * t);
+            l1.pseudorange += multipath;
+            let mut l2 = measurement(3, range, iono, F2, 1000 * t);
+            if t >= 20 {
+                // One L2 cycle slip at t = 20.
+                l2.carrier_phase += 1.0;
+            }
+            let raw = RxmRawx {
+                rcv_tow: 100_000.0 + t as f64,
+                week: 2300,
+                leap_s: 18,
+                rec_stat: 1,
+                measurements: vec![l1, l2],
+            };
+            monitor.add(&raw, &satellites);
+        }
+        let id = SatelliteId::new(Constellation::Gps, 5);
+        assert_eq!(monitor.slips.len(), 1);
+        let slip = &monitor.slips[0];
+        assert_eq!((slip.sig_id, slip.cause), (3, SlipCause::Ionosphere));
+        assert_eq!(slip.time, 2300.0 * 604_800.0 + 100_020.0);
+        assert_eq!(monitor.observations_per_slip(), Some(80.0));
+
+        // MP1 is the code multipath alone, across the two arcs.
+        let mp1 = monitor.series[&(id, Combination::Mp1)].levelled();
+        assert_eq!(mp1.len(), 40);
+        assert!(mp1.iter().all(|s| (s.value.abs() - 0.5).abs() < 1e-3));
+        assert!((monitor.series[&(id, Combination::Mp1)].rms().unwrap() - 0.5).abs() < 1e-3);
+        assert!(monitor.series[&(id, Combination::Mp2)].rms().unwrap() < 1e-3);
+        assert_eq!(mp1[0].elevation, Some(30.0));
+
+        // A restarted lock time is a slip too.
+        let raw = RxmRawx {
+            rcv_tow: 100_040.0,
+            week: 2300,
+            leap_s: 18,
+            rec_stat: 1,
+            measurements: vec![measurement(0, 21.02e6, 5.4, F1, 0)],
+        };
+        monitor.add(&raw, &satellites);
+        assert_eq!(monitor.slips[1].cause, SlipCause::LockLoss);
+    }
+}
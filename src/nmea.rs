@@ -0,0 +1,392 @@
+use crate::gnss::{Constellation, SatelliteId};
+use crate::time_scales::CalendarTime;
+
+/// XOR checksum over the characters between `$` and `*`.
+pub fn checksum(body: &str) -> u8 {
+    body.bytes().fold(0, |acc, b| acc ^ b)
+}
+
+/// Wraps a sentence body (without `$` and `*hh`) into a complete sentence with checksum.
+pub fn encode(body: &str) -> String {
+    format!("${}*{:02X}\r\n", body, checksum(body))
+}
+
+/// A checksum-verified NMEA 0183 sentence split into its fields.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NmeaSentence {
+    /// Two-letter talker ID (`GP`, `GN`, ...), or `P` for proprietary sentences.
+    pub talker: String,
+    /// Sentence formatter, e.g. `GGA`, or the manufacturer code for proprietary sentences.
+    pub formatter: String,
+    pub fields: Vec<String>,
+}
+
+impl NmeaSentence {
+    /// Parses one line, `None` if it is not a well formed sentence or the checksum is wrong.
+    pub fn parse(line: &str) -> Option<Self> {
+        let line = line.trim_end_matches(['\r', '\n']);
+        let body = line.strip_prefix('$')?;
+        let body = match body.rsplit_once('*') {
+            Some((body, ck)) => {
+                if u8::from_str_radix(ck, 16).ok()? != checksum(body) {
+                    return None;
+                }
+                body
+            }
+            None => body,
+        };
+
+        let mut parts = body.split(',');
+        let address = parts.next()?;
+        if !address.is_ascii() || address.len() < 2 {
+            return None;
+        }
+        let (talker, formatter) = if let Some(code) = address.strip_prefix('P') {
+            ("P", code)
+        } else {
+            address.split_at(2)
+        };
+
+        Some(Self {
+            talker: talker.to_owned(),
+            formatter: formatter.to_owned(),
+            fields: parts.map(str::to_owned).collect(),
+        })
+    }
+
+    pub fn field(&self, index: usize) -> &str {
+        self.fields.get(index).map_or("", String::as_str)
+    }
+
+    pub fn f64(&self, index: usize) -> Option<f64> {
+        self.field(index).parse().ok()
+    }
+
+    pub fn u32(&self, index: usize) -> Option<u32> {
+        self.field(index).parse().ok()
+    }
+}
+
+/// Parses an `hhmmss.sss` field into (hour, minute, second, nanoseconds). Seconds up to 61
+/// allow for a leap second.
+fn parse_time(field: &str) -> Option<(u32, u32, u32, u32)> {
+    if field.len() < 6 || !field.is_ascii() {
+        return None;
+    }
+    let hour: u32 = field[0..2].parse().ok()?;
+    let minute: u32 = field[2..4].parse().ok()?;
+    let seconds: f64 = field[4..].parse().ok()?;
+    if hour > 23 || minute > 59 || !(0.0..61.0).contains(&seconds) {
+        return None;
+    }
+    let second = seconds.floor();
+    Some((
+        hour,
+        minute,
+        second as u32,
+        (((seconds - second) * 1e9).round() as u32).min(999_999_999),
+    ))
+}
+
+/// Parses a `ddmmyy` field into (year, month, day).
+fn parse_date(field: &str) -> Option<(i32, u32, u32)> {
+    if field.len() != 6 || !field.is_ascii() {
+        return None;
+    }
+    let day = field[0..2].parse().ok()?;
+    let month = field[2..4].parse().ok()?;
+    let year: i32 = field[4..6].parse().ok()?;
+    Some((2000 + year, month, day))
+}
+
+/// Parses a `(d)ddmm.mmmm` coordinate and its hemisphere into signed decimal degrees.
+fn parse_coordinate(value: &str, hemisphere: &str) -> Option<f64> {
+    let raw: f64 = value.parse().ok()?;
+    let degrees = (raw / 100.0).trunc();
+    let decimal = degrees + (raw - degrees * 100.0) / 60.0;
+    match hemisphere {
+        "N" | "E" => Some(decimal),
+        "S" | "W" => Some(-decimal),
+        _ => None,
+    }
+}
+
+/// Maps an NMEA satellite number to a satellite. GPS and combined (`GN`) talkers use the
+/// NMEA 4.x shared numbering ranges, the other talkers number their own satellites.
+pub fn satellite_id(talker: &str, number: u32) -> SatelliteId {
+    let system = Constellation::from_talker(talker);
+    let id = |constellation, prn: u32| SatelliteId::new(constellation, prn.min(255) as u8);
+    match (system, number) {
+        (Constellation::Gps | Constellation::Unknown, 1..=32) => id(Constellation::Gps, number),
+        (Constellation::Gps | Constellation::Unknown, 33..=64) => {
+            id(Constellation::Sbas, number + 87)
+        }
+        (Constellation::Gps | Constellation::Unknown, 120..=158) => id(Constellation::Sbas, number),
+        (Constellation::Gps | Constellation::Unknown | Constellation::Glonass, 65..=96) => {
+            id(Constellation::Glonass, number - 64)
+        }
+        (Constellation::Gps | Constellation::Unknown | Constellation::Qzss, 193..=202) => {
+            id(Constellation::Qzss, number - 192)
+        }
+        _ => id(system, number),
+    }
+}
+
+/// One satellite entry of a GSV sentence.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GsvSatellite {
+    pub id: SatelliteId,
+    pub elevation: Option<f32>,
+    pub azimuth: Option<f32>,
+    /// dB-Hz, `None` when not tracked.
+    pub snr: Option<u8>,
+}
+
+/// GSV: satellites in view. One sentence of a group that together lists every satellite.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gsv {
+    pub constellation: Constellation,
+    pub total: u32,
+    pub number: u32,
+    pub in_view: u32,
+    pub satellites: Vec<GsvSatellite>,
+    /// NMEA 4.10 signal ID, when present the group only covers that signal.
+    pub signal_id: Option<u8>,
+}
+
+impl Gsv {
+    fn decode(s: &NmeaSentence) -> Option<Self> {
+        let satellites = s.fields[3.min(s.fields.len())..]
+            .chunks_exact(4)
+            .filter_map(|sat| {
+                let number = sat[0].parse().ok()?;
+                Some(GsvSatellite {
+                    id: satellite_id(&s.talker, number),
+                    elevation: sat[1].parse().ok(),
+                    azimuth: sat[2].parse().ok(),
+                    snr: sat[3].parse().ok(),
+                })
+            })
+            .collect();
+        Some(Self {
+            constellation: Constellation::from_talker(&s.talker),
+            total: s.u32(0)?,
+            number: s.u32(1)?,
+            in_view: s.u32(2).unwrap_or(0),
+            satellites,
+            signal_id: match s.fields.len() % 4 {
+                0 => s.fields.last().and_then(|f| u8::from_str_radix(f, 16).ok()),
+                _ => None,
+            },
+        })
+    }
+}
+
+/// GGA: fix data.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gga {
+    /// UTC (hour, minute, second, nanoseconds).
+    pub time: Option<(u32, u32, u32, u32)>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    /// 0 invalid, 1 GPS, 2 DGPS, 4 RTK fixed, 5 RTK float, 6 dead reckoning.
+    pub quality: u8,
+    pub num_sv: u8,
+    pub hdop: Option<f64>,
+    /// Altitude above mean sea level in metres.
+    pub altitude: Option<f64>,
+    /// Geoid separation in metres.
+    pub separation: Option<f64>,
+    /// Age of differential corrections in seconds.
+    pub diff_age: Option<f64>,
+    pub station_id: Option<u32>,
+}
+
+impl Gga {
+    fn decode(s: &NmeaSentence) -> Option<Self> {
+        Some(Self {
+            time: parse_time(s.field(0)),
+            latitude: parse_coordinate(s.field(1), s.field(2)),
+            longitude: parse_coordinate(s.field(3), s.field(4)),
+            quality: s.field(5).parse().ok()?,
+            num_sv: s.field(6).parse().unwrap_or(0),
+            hdop: s.f64(7),
+            altitude: s.f64(8),
+            separation: s.f64(10),
+            diff_age: s.f64(12),
+            station_id: s.u32(13),
+        })
+    }
+}
+
+/// TXT: free text, used by receivers for banners and status such as `ANTSTATUS=OK`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Txt {
+    /// 00 error, 01 warning, 02 notice, 07 user.
+    pub kind: u32,
+    pub text: String,
+}
+
+impl Txt {
+    fn decode(s: &NmeaSentence) -> Option<Self> {
+        Some(Self {
+            kind: s.u32(2).unwrap_or(2),
+            // The text itself may contain commas.
+            text: s.fields.get(3..)?.join(","),
+        })
+    }
+}
+
+/// ZDA: UTC date and time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Zda {
+    pub time: CalendarTime,
+}
+
+impl Zda {
+    fn decode(s: &NmeaSentence) -> Option<Self> {
+        let (hour, minute, second, nanos) = parse_time(s.field(0))?;
+        Some(Self {
+            time: CalendarTime {
+                year: s.field(3).parse().ok()?,
+                month: s.u32(2)?,
+                day: s.u32(1)?,
+                hour,
+                minute,
+                second,
+                nanos,
+            },
+        })
+    }
+}
+
+/// RMC: recommended minimum navigation data.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rmc {
+    pub time: Option<CalendarTime>,
+    pub valid: bool,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    /// Speed over ground in knots.
+    pub speed_knots: Option<f64>,
+    /// Course over ground in degrees from true north.
+    pub course: Option<f64>,
+}
+
+impl Rmc {
+    fn decode(s: &NmeaSentence) -> Option<Self> {
+        let time = match (parse_time(s.field(0)), parse_date(s.field(8))) {
+            (Some((hour, minute, second, nanos)), Some((year, month, day))) => Some(CalendarTime {
+                year,
+                month,
+                day,
+                hour,
+                minute,
+                second,
+                nanos,
+            }),
+            _ => None,
+        };
+        Some(Self {
+            time,
+            valid: s.field(1) == "A",
+            latitude: parse_coordinate(s.field(2), s.field(3)),
+            longitude: parse_coordinate(s.field(4), s.field(5)),
+            speed_knots: s.f64(6),
+            course: s.f64(7),
+        })
+    }
+}
+
+/// GST: pseudorange error statistics, the receiver's estimate of the position error.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gst {
+    /// UTC (hour, minute, second, nanoseconds).
+    pub time: Option<(u32, u32, u32, u32)>,
+    /// RMS of the pseudorange residuals in metres.
+    pub rms: Option<f64>,
+    /// Standard deviations of the error ellipse axes in metres.
+    pub semi_major: Option<f64>,
+    pub semi_minor: Option<f64>,
+    /// Direction of the semi-major axis in degrees from true north.
+    pub orientation: Option<f64>,
+    /// Standard deviations of latitude, longitude and altitude in metres.
+    pub latitude_sigma: Option<f64>,
+    pub longitude_sigma: Option<f64>,
+    pub altitude_sigma: Option<f64>,
+}
+
+impl Gst {
+    fn decode(s: &NmeaSentence) -> Option<Self> {
+        Some(Self {
+            time: parse_time(s.field(0)),
+            rms: s.f64(1),
+            semi_major: s.f64(2),
+            semi_minor: s.f64(3),
+            orientation: s.f64(4),
+            latitude_sigma: s.f64(5),
+            longitude_sigma: s.f64(6),
+            altitude_sigma: s.f64(7),
+        })
+    }
+}
+
+/// VTG: course and speed over ground.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Vtg {
+    /// Degrees from true north.
+    pub course_true: Option<f64>,
+    /// Degrees from magnetic north.
+    pub course_magnetic: Option<f64>,
+    pub speed_knots: Option<f64>,
+    pub speed_kmh: Option<f64>,
+    /// NMEA 2.3 mode indicator: A autonomous, D differential, E estimated, N not valid.
+    pub mode: Option<char>,
+}
+
+impl Vtg {
+    fn decode(s: &NmeaSentence) -> Option<Self> {
+        Some(Self {
+            course_true: s.f64(0),
+            course_magnetic: s.f64(2),
+            speed_knots: s.f64(4),
+            speed_kmh: s.f64(6),
+            mode: s.field(8).chars().next(),
+        })
+    }
+
+    /// Speed over ground in m/s.
+    pub fn speed(&self) -> Option<f64> {
+        self.speed_kmh
+            .map(|v| v / 3.6)
+            .or(self.speed_knots.map(|v| v * 1852.0 / 3600.0))
+    }
+}
+
+/// The NMEA sentences this application understands.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NmeaMessage {
+    Zda(Zda),
+    Rmc(Rmc),
+    Gsv(Gsv),
+    Gga(Gga),
+    Txt(Txt),
+    Vtg(Vtg),
+    Gst(Gst),
+}
+
+impl NmeaMessage {
+    /// Decodes a sentence into a known message, `None` for unsupported or malformed sentences.
+    pub fn decode(sentence: &NmeaSentence) -> Option<Self> {
+        match sentence.formatter.as_str() {
+            "ZDA" => Zda::decode(sentence).map(NmeaMessage::Zda),
+            "RMC" => Rmc::decode(sentence).map(NmeaMessage::Rmc),
+            "GSV" => Gsv::decode(sentence).map(NmeaMessage::Gsv),
+            "GGA" => Gga::decode(sentence).map(NmeaMessage::Gga),
+            "TXT" => Txt::decode(sentence).map(NmeaMessage::Txt),
+            "VTG" => Vtg::decode(sentence).map(NmeaMessage::Vtg),
+            "GST" => Gst::decode(sentence).map(NmeaMessage::Gst),
+            _ => None,
+        }
+    }
+}
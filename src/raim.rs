@@ -0,0 +1,290 @@
+use crate::ephemeris::NavigationData;
+use crate::gnss::SatelliteId;
+use crate::spp::{self, Observation, SppSolution};
+
+/// Probability of a false alarm of the chi-square test per epoch.
+pub const PROBABILITY_FALSE_ALARM: f64 = 1e-5;
+/// Standard normal quantile of the allowed probability of missed detection, 1e-3.
+const K_MISSED_DETECTION: f64 = 3.09;
+/// Most satellites excluded from one epoch.
+pub const MAX_EXCLUSIONS: usize = 2;
+
+/// Natural logarithm of the gamma function (Lanczos approximation).
+fn ln_gamma(x: f64) -> f64 {
+    const G: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+    let x = x - 1.0;
+    let sum = G[1..]
+        .iter()
+        .enumerate()
+        .fold(G[0], |acc, (i, g)| acc + g / (x + i as f64 + 1.0));
+    let t = x + 7.5;
+    0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + sum.ln()
+}
+
+/// Regularized upper incomplete gamma function Q(a, x).
+fn upper_gamma(a: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 1.0;
+    }
+    let prefix = (-x + a * x.ln() - ln_gamma(a)).exp();
+    if x < a + 1.0 {
+        // Series for the lower function.
+        let (mut term, mut sum) = (1.0 / a, 1.0 / a);
+        for n in 1..1000 {
+            term *= x / (a + n as f64);
+            sum += term;
+            if term.abs() < sum.abs() * 1e-15 {
+                break;
+            }
+        }
+        return 1.0 - sum * prefix;
+    }
+    // Continued fraction, modified Lentz.
+    let tiny = 1e-300;
+    let mut b = x + 1.0 - a;
+    let mut c = 1.0 / tiny;
+    let mut d = 1.0 / b;
+    let mut h = d;
+    for i in 1..1000 {
+        let an = -(i as f64) * (i as f64 - a);
+        b += 2.0;
+        d = an * d + b;
+        d = if d.abs() < tiny { tiny } else { d };
+        c = b + an / c;
+        c = if c.abs() < tiny { tiny } else { c };
+        d = 1.0 / d;
+        let delta = d * c;
+        h *= delta;
+        if (delta - 1.0).abs() < 1e-15 {
+            break;
+        }
+    }
+    prefix * h
+}
+
+/// The value a chi-square variable with `dof` degrees of freedom exceeds with probability
+/// `p`.
+pub fn chi_square_threshold(dof: usize, p: f64) -> f64 {
+    let k = dof as f64 / 2.0;
+    let tail = |x: f64| upper_gamma(k, x / 2.0);
+    let (mut low, mut high) = (0.0, dof as f64 + 10.0);
+    while tail(high) > p {
+        high *= 2.0;
+    }
+    for _ in 0..200 {
+        let mid = (low + high) / 2.0;
+        if tail(mid) > p {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    (low + high) / 2.0
+}
+
+/// Outcome of the integrity checks on a local solution.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Raim {
+    /// Chi-square of the final solution and the detection threshold for its degrees of
+    /// freedom; no threshold when there is no redundancy to test with.
+    pub chi_square: f64,
+    pub threshold: Option<f64>,
+    /// Whether the final solution still fails the test.
+    pub fault: bool,
+    /// Satellites excluded, in order, with their standardized residual when excluded.
+    pub excluded: Vec<(SatelliteId, f64)>,
+    /// Horizontal and vertical protection levels in metres, when available.
+    pub hpl: Option<f64>,
+    pub vpl: Option<f64>,
+}
+
+impl Raim {
+    pub fn available(&self) -> bool {
+        self.threshold.is_some()
+    }
+
+    pub fn is_excluded(&self, id: SatelliteId) -> bool {
+        self.excluded.iter().any(|(e, _)| *e == id)
+    }
+}
+
+/// Per-satellite test quantities of a solution, in the residual space normalized by the
+/// measurement sigmas.
+struct Geometry {
+    /// Residuals divided by their own standard deviation (w-test statistics).
+    standardized: Vec<f64>,
+    /// Horizontal and vertical position error per unit of test statistic caused by a bias on
+    /// each satellite.
+    slope_h: Vec<f64>,
+    slope_v: Vec<f64>,
+    /// Largest horizontal and the vertical position standard deviation in metres.
+    sigma_h: f64,
+    sigma_v: f64,
+}
+
+fn geometry(solution: &SppSolution) -> Option<Geometry> {
+    let systems: Vec<_> = solution.clock.keys().copied().collect();
+    let design: Vec<Vec<f64>> = solution
+        .satellites
+        .iter()
+        .map(|s| {
+            let row = spp::design_row(s, &systems);
+            row.iter().map(|h| h / s.sigma).collect()
+        })
+        .collect();
+    let q = spp::normal_inverse(&design, &vec![1.0; design.len()])?;
+    let k = q.len();
+
+    // Rows of the ECEF to east/north/up rotation.
+    let (lat, lon) = (
+        solution.latitude.to_radians(),
+        solution.longitude.to_radians(),
+    );
+    let (sp, cp, sl, cl) = (lat.sin(), lat.cos(), lon.sin(), lon.cos());
+    let enu = [
+        [-sl, cl, 0.0],
+        [-sp * cl, -sp * sl, cp],
+        [cp * cl, cp * sl, sp],
+    ];
+    let rotate = |v: &[f64]| enu.map(|r| r[0] * v[0] + r[1] * v[1] + r[2] * v[2]);
+
+    // Gain from normalized measurements to position, A = Q Hᵀ, one column per satellite.
+    let gain: Vec<Vec<f64>> = design
+        .iter()
+        .map(|row| {
+            (0..k)
+                .map(|a| (0..k).map(|b| q[a][b] * row[b]).sum())
+                .collect()
+        })
+        .collect();
+    let mut geometry = Geometry {
+        standardized: Vec::new(),
+        slope_h: Vec::new(),
+        slope_v: Vec::new(),
+        sigma_h: 0.0,
+        sigma_v: 0.0,
+    };
+    for ((sat, row), a) in solution.satellites.iter().zip(&design).zip(&gain) {
+        // Diagonal of the residual projection I - H Q Hᵀ.
+        let s = (1.0 - row.iter().zip(a).map(|(h, a)| h * a).sum::<f64>()).max(1e-12);
+        geometry
+            .standardized
+            .push(sat.residual / sat.sigma / s.sqrt());
+        let [e, n, u] = rotate(&a[..3]);
+        geometry.slope_h.push(e.hypot(n) / s.sqrt());
+        geometry.slope_v.push(u.abs() / s.sqrt());
+    }
+
+    // Position covariance in east/north/up.
+    let qxyz: Vec<[f64; 3]> = (0..3).map(|i| [q[i][0], q[i][1], q[i][2]]).collect();
+    let columns: Vec<[f64; 3]> = qxyz.iter().map(|c| rotate(c)).collect();
+    let qenu: [[f64; 3]; 3] =
+        std::array::from_fn(|i| rotate(&[columns[0][i], columns[1][i], columns[2][i]]));
+    let (ee, nn, en) = (qenu[0][0], qenu[1][1], qenu[0][1]);
+    let major = (ee + nn) / 2.0 + ((ee - nn) / 2.0).hypot(en);
+    geometry.sigma_h = major.max(0.0).sqrt();
+    geometry.sigma_v = qenu[2][2].max(0.0).sqrt();
+    Some(geometry)
+}
+
+/// Solves with fault detection and exclusion: while the chi-square test on the residuals
+/// fails, the satellite with the largest standardized residual is excluded and the position
+/// solved again. Protection levels follow the weighted RAIM slope method,
+/// `max(slope) · sqrt(threshold) + k_md · sigma`.
+pub fn fde(
+    observations: &[Observation],
+    time: f64,
+    nav: &NavigationData,
+    initial: Option<[f64; 3]>,
+) -> Option<(SppSolution, Raim)> {
+    let mut excluded: Vec<(SatelliteId, f64)> = Vec::new();
+    let mut last: Option<(SppSolution, Raim)> = None;
+    loop {
+        let ids: Vec<SatelliteId> = excluded.iter().map(|(id, _)| *id).collect();
+        let Some(solution) = spp::solve(observations, time, nav, initial, &ids) else {
+            // Excluding left too few satellites: keep the faulty solution.
+            return last;
+        };
+        let geometry = geometry(&solution)?;
+        let dof = solution.degrees_of_freedom;
+        let threshold = (dof > 0).then(|| chi_square_threshold(dof, PROBABILITY_FALSE_ALARM));
+        let fault = threshold.is_some_and(|t| solution.chi_square > t);
+        let max = |v: &[f64]| v.iter().copied().fold(0.0, f64::max);
+        let raim = Raim {
+            chi_square: solution.chi_square,
+            threshold,
+            fault,
+            excluded: excluded.clone(),
+            hpl: threshold
+                .map(|t| max(&geometry.slope_h) * t.sqrt() + K_MISSED_DETECTION * geometry.sigma_h),
+            vpl: threshold
+                .map(|t| max(&geometry.slope_v) * t.sqrt() + K_MISSED_DETECTION * geometry.sigma_v),
+        };
+        // An exclusion needs a test left afterwards to confirm it.
+        if !fault || dof < 2 || excluded.len() == MAX_EXCLUSIONS {
+            return Some((solution, raim));
+        }
+        let worst = (0..geometry.standardized.len()).max_by(|&a, &b| {
+            geometry.standardized[a]
+                .abs()
+                .total_cmp(&geometry.standardized[b].abs())
+        })?;
+        excluded.push((solution.satellites[worst].id, geometry.standardized[worst]));
+        last = Some((solution, raim));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geodesy;
+    use crate::gnss::Constellation;
+    use crate::time_scales::SECONDS_PER_WEEK;
+
+    #[test]
+    fn chi_square_quantiles() {
+        for (dof, p, expected) in [
+            (1, 0.05, 3.841),
+            (4, 0.05, 9.488),
+            (10, 0.01, 23.209),
+            (1, 1e-5, 19.511),
+        ] {
+            let x = chi_square_threshold(dof, p);
+            assert!((x - expected).abs() < 1e-3, "{} {} {}", dof, p, x);
+        }
+    }
+
+    #[test]
+    fn excludes_faulty_satellite() {
+        let receiver = geodesy::geodetic_to_ecef(52.0, 5.0, 50.0);
+        let time = 2297.0 * SECONDS_PER_WEEK as f64 + 200_000.0;
+        let (nav, mut observations) = crate::spp::tests::simulate(
+            receiver,
+            time,
+            &[(Constellation::Gps, 0.0), (Constellation::Galileo, 0.0)],
+        );
+
+        let (_, raim) = fde(&observations, time, &nav, None).unwrap();
+        assert!(raim.available() && !raim.fault && raim.excluded.is_empty());
+        let (hpl, vpl) = (raim.hpl.unwrap(), raim.vpl.unwrap());
+        assert!(hpl > 1.0 && vpl > hpl, "{} {}", hpl, vpl);
+
+        let faulty = observations[3].id;
+        observations[3].pseudorange += 80.0;
+        let (solution, raim) = fde(&observations, time, &nav, None).unwrap();
+        assert!(!raim.fault, "{:?}", raim);
+        assert_eq!(raim.excluded.len(), 1);
+        assert!(raim.is_excluded(faulty));
+        assert!(solution.satellites.iter().all(|s| s.id != faulty));
+    }
+}
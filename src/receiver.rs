@@ -0,0 +1,1384 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::{Shutdown, TcpStream};
+use std::sync::mpsc::{self, TryRecvError};
+use std::thread;
+
+use chrono::{DateTime, Utc};
+use circular_buffer::CircularBuffer;
+
+use crate::base::BaseStation;
+use crate::cno_map::CnoMap;
+use crate::ephemeris::NavigationData;
+use crate::geodesy;
+use crate::gnss::{
+    time_of_day_ms, Constellation, ErrorEllipse, Fix, FixType, PositionError, RtkStatus, Satellite,
+    SatelliteId, Velocity,
+};
+use crate::integrity::IntegrityMonitor;
+use crate::multipath::MultipathMonitor;
+use crate::nmea::{Gga, Gst, Gsv, NmeaMessage, NmeaSentence, Txt, Vtg};
+use crate::raim::{self, Raim};
+use crate::sbas::SbasProvider;
+use crate::spectrum::SpectrumTrace;
+use crate::spp::{self, SppSolution};
+use crate::subframe::SubframeDecoder;
+use crate::time_scales::{
+    CalendarTime, GnssInstant, LeapSecondSource, LeapSeconds, SECONDS_PER_WEEK,
+};
+use crate::ttff::TtffRunner;
+use crate::ubx::{
+    self, FrameParse, MonRf, MonVer, NavClock, NavPvt, NavRelPosNed, NavSat, NavSbas, NavStatus,
+    NavSvin, RfBlock, RxmRawx, TimTm2, TimTp, UbxFrame, UbxMessage,
+};
+use crate::visibility::{self, ObstructionMap, Visibility};
+
+/// Longest line accepted while waiting for the end of an NMEA sentence.
+const MAX_NMEA_LEN: usize = 256;
+
+/// Number of samples kept for the time series plots.
+pub const HISTORY_LEN: usize = 3600;
+
+/// Number of clock solutions kept for stability analysis (a day at 1 Hz).
+pub const MAX_CLOCK_SAMPLES: usize = 86_400;
+/// Number of RTK status changes kept.
+pub const MAX_RTK_TRANSITIONS: usize = 1000;
+
+/// A decoded message from either protocol.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GnssMessage {
+    Ubx(UbxMessage),
+    Nmea(NmeaMessage),
+}
+
+/// Splits an interleaved UBX/NMEA byte stream into decoded messages.
+#[derive(Default)]
+pub struct StreamDecoder {
+    buffer: Vec<u8>,
+    /// Host clock reading when each part of `buffer` arrived, as (length, time), oldest first.
+    arrivals: VecDeque<(usize, DateTime<Utc>)>,
+}
+
+impl StreamDecoder {
+    /// Appends bytes read from the receiver at `received`.
+    pub fn push(&mut self, bytes: &[u8], received: DateTime<Utc>) {
+        if bytes.is_empty() {
+            return;
+        }
+        self.buffer.extend_from_slice(bytes);
+        self.arrivals.push_back((bytes.len(), received));
+    }
+
+    /// Returns the next decodable message and when its last byte arrived, discarding garbage
+    /// and unsupported messages.
+    pub fn next_message(&mut self) -> Option<(GnssMessage, DateTime<Utc>)> {
+        loop {
+            let Some(start) = self
+                .buffer
+                .iter()
+                .position(|b| *b == ubx::SYNC[0] || *b == b'$')
+            else {
+                self.buffer.clear();
+                self.arrivals.clear();
+                return None;
+            };
+            self.consume(start);
+
+            if self.buffer[0] == b'$' {
+                let Some(end) = self.buffer.iter().position(|b| *b == b'\n') else {
+                    if self.buffer.len() > MAX_NMEA_LEN {
+                        self.consume(1);
+                        continue;
+                    }
+                    return None;
+                };
+                let line = self.buffer[..=end].to_vec();
+                let received = self.consume(end + 1);
+                let message = std::str::from_utf8(&line)
+                    .ok()
+                    .and_then(NmeaSentence::parse)
+                    .and_then(|s| NmeaMessage::decode(&s));
+                if let (Some(message), Some(received)) = (message, received) {
+                    return Some((GnssMessage::Nmea(message), received));
+                }
+            } else {
+                match UbxFrame::parse(&self.buffer) {
+                    FrameParse::Incomplete => return None,
+                    FrameParse::Invalid => {
+                        self.consume(1);
+                    }
+                    FrameParse::Frame(frame, len) => {
+                        let received = self.consume(len);
+                        if let (Some(message), Some(received)) =
+                            (UbxMessage::decode(&frame), received)
+                        {
+                            return Some((GnssMessage::Ubx(message), received));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Drops the first `len` bytes and returns when the last of them arrived.
+    fn consume(&mut self, len: usize) -> Option<DateTime<Utc>> {
+        self.buffer.drain(..len);
+        let mut remaining = len;
+        let mut received = None;
+        while remaining > 0 {
+            let Some((length, time)) = self.arrivals.front_mut() else {
+                break;
+            };
+            received = Some(*time);
+            if *length > remaining {
+                *length -= remaining;
+                break;
+            }
+            remaining -= *length;
+            self.arrivals.pop_front();
+        }
+        received
+    }
+}
+
+/// A receiver epoch and the host clock reading when it arrived.
+#[derive(Debug, Clone, Copy)]
+pub struct ReceiverTime {
+    pub instant: GnssInstant,
+    pub received: DateTime<Utc>,
+}
+
+/// One receiver clock solution.
+#[derive(Debug, Clone, Copy)]
+pub struct ClockSample {
+    pub itow_ms: u32,
+    pub bias_ns: f64,
+}
+
+/// A change of the RTK status.
+#[derive(Debug, Clone, Copy)]
+pub struct RtkTransition {
+    pub time: DateTime<Utc>,
+    pub from: RtkStatus,
+    pub to: RtkStatus,
+}
+
+/// Identity and hardware status of the receiver, from UBX-MON-VER/HW/HW3/RF or NMEA TXT.
+#[derive(Debug, Clone, Default)]
+pub struct ReceiverInfo {
+    pub software: Option<String>,
+    pub hardware: Option<String>,
+    pub firmware: Option<String>,
+    pub protocol: Option<String>,
+    pub module: Option<String>,
+    /// Supported constellations, e.g. `GPS`, `GLO`, `GAL`, `BDS`.
+    pub constellations: Vec<String>,
+    /// Extension strings not otherwise interpreted.
+    pub extensions: Vec<String>,
+    pub antenna_status: Option<String>,
+    pub antenna_power: Option<String>,
+    pub rtc_calibrated: Option<bool>,
+    pub safe_boot: Option<bool>,
+    pub xtal_absent: Option<bool>,
+}
+
+impl ReceiverInfo {
+    /// Interprets a `KEY=value` or `GPS;GLO;...` version extension.
+    fn apply_extension(&mut self, extension: &str) {
+        match extension.split_once('=') {
+            Some(("FWVER", v)) => self.firmware = Some(v.to_owned()),
+            Some(("PROTVER", v)) => self.protocol = Some(v.to_owned()),
+            Some(("MOD", v)) => self.module = Some(v.to_owned()),
+            Some(("ANTSTATUS", v)) => self.antenna_status = Some(v.to_owned()),
+            Some(("ANTPOWER", v)) => self.antenna_power = Some(v.to_owned()),
+            None if extension.contains(';') || Self::is_constellation(extension) => {
+                self.constellations = extension
+                    .split(';')
+                    .filter(|c| !c.is_empty())
+                    .map(str::to_owned)
+                    .collect();
+            }
+            _ => {
+                if !self.extensions.iter().any(|e| e == extension) {
+                    self.extensions.push(extension.to_owned());
+                }
+            }
+        }
+    }
+
+    fn is_constellation(name: &str) -> bool {
+        matches!(
+            name,
+            "GPS" | "GLO" | "GAL" | "BDS" | "QZSS" | "SBAS" | "IMES" | "NAVIC"
+        )
+    }
+
+    fn apply_mon_ver(&mut self, msg: &MonVer) {
+        self.software = Some(msg.sw_version.clone());
+        self.hardware = Some(msg.hw_version.clone());
+        for extension in &msg.extensions {
+            self.apply_extension(extension);
+        }
+    }
+
+    /// Antenna status and power from UBX-MON-RF, which newer receivers report in place of
+    /// UBX-MON-HW; blocks that disagree are listed separately.
+    fn apply_mon_rf(&mut self, msg: &MonRf) {
+        let describe = |name: fn(&RfBlock) -> &'static str| {
+            let first = name(msg.blocks.first()?);
+            if msg.blocks.iter().all(|b| name(b) == first) {
+                return Some(first.to_owned());
+            }
+            let names: Vec<String> = msg
+                .blocks
+                .iter()
+                .map(|b| format!("{} (block {})", name(b), b.block_id))
+                .collect();
+            Some(names.join(", "))
+        };
+        self.antenna_status = describe(|b| ubx::antenna_status_name(b.ant_status));
+        self.antenna_power = describe(|b| ubx::antenna_power_name(b.ant_power));
+    }
+
+    /// Picks version and antenna information out of start-up and status TXT messages.
+    fn apply_txt(&mut self, msg: &Txt) {
+        let text = msg.text.trim();
+        if let Some(hw) = text.strip_prefix("HW ") {
+            self.hardware = Some(hw.trim().to_owned());
+        } else if text.starts_with("ROM CORE") || text.starts_with("ROM BASE") {
+            self.software = Some(text.to_owned());
+        } else if text.contains('=') || text.contains(';') || Self::is_constellation(text) {
+            self.apply_extension(text);
+        }
+    }
+
+    /// A one-line summary for device lists.
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(module) = self.module.as_ref().or(self.hardware.as_ref()) {
+            parts.push(module.clone());
+        }
+        if let Some(fw) = self.firmware.as_ref().or(self.software.as_ref()) {
+            parts.push(fw.clone());
+        }
+        if !self.constellations.is_empty() {
+            parts.push(self.constellations.join("/"));
+        }
+        if let Some(antenna) = &self.antenna_status {
+            parts.push(format!("antenna {}", antenna));
+        }
+        match parts.is_empty() {
+            true => "Unidentified receiver".to_owned(),
+            false => parts.join(", "),
+        }
+    }
+}
+
+/// Everything known about a receiver, built up from the messages it sends.
+pub struct ReceiverState {
+    pub started: DateTime<Utc>,
+    pub time: Option<ReceiverTime>,
+    pub leap: LeapSeconds,
+    /// Host clock minus receiver time, as (seconds since `started`, milliseconds).
+    pub clock_offsets: Box<CircularBuffer<HISTORY_LEN, [f64; 2]>>,
+
+    pub time_pulse: Option<TimTp>,
+    pub time_mark: Option<TimTm2>,
+    pub clock: Option<NavClock>,
+    /// Time pulse quantization error, as (seconds since `started`, picoseconds).
+    pub q_err_history: Box<CircularBuffer<HISTORY_LEN, [f64; 2]>>,
+    /// Receiver clock bias, as (seconds since `started`, nanoseconds).
+    pub clock_bias_history: Box<CircularBuffer<HISTORY_LEN, [f64; 2]>>,
+    /// Receiver clock drift, as (seconds since `started`, nanoseconds per second).
+    pub clock_drift_history: Box<CircularBuffer<HISTORY_LEN, [f64; 2]>>,
+    pub clock_samples: VecDeque<ClockSample>,
+
+    pub info: ReceiverInfo,
+    /// Survey-in progress of a base receiver.
+    pub survey: Option<NavSvin>,
+
+    pub satellites: Vec<Satellite>,
+    pub fix: Option<Fix>,
+    /// Positions of the fixes with a position, as (latitude, longitude) in degrees.
+    pub track: Box<CircularBuffer<HISTORY_LEN, [f64; 2]>>,
+    pub position_error: Option<PositionError>,
+    pub velocity: Option<Velocity>,
+    /// Speed over ground, as (seconds since `started`, m/s).
+    pub speed_history: Box<CircularBuffer<HISTORY_LEN, [f64; 2]>>,
+    /// Vertical velocity, as (seconds since `started`, m/s up).
+    pub vertical_speed_history: Box<CircularBuffer<HISTORY_LEN, [f64; 2]>>,
+    pub rtk: RtkStatus,
+    /// Age of the differential corrections in seconds; an upper bound when from NAV-PVT.
+    pub correction_age: Option<f64>,
+    pub relpos: Option<NavRelPosNed>,
+    /// RTK status, as (seconds since `started`, 0 autonomous to 3 RTK fixed).
+    pub rtk_history: Box<CircularBuffer<HISTORY_LEN, [f64; 2]>>,
+    pub rtk_transitions: VecDeque<RtkTransition>,
+    pub nav_status: Option<NavStatus>,
+    pub nav_sbas: Option<NavSbas>,
+    /// Satellites whose measurements had SBAS corrections applied, from NAV-SAT.
+    pub sbas_corrected: Vec<SatelliteId>,
+    /// Latest raw measurements.
+    pub raw: Option<RxmRawx>,
+    /// Broadcast navigation data the local solution is computed with, decoded from
+    /// UBX-RXM-SFRBX or loaded from a RINEX file.
+    pub navigation: NavigationData,
+    /// Single point position computed from `raw`, independently of the receiver's fix, after
+    /// fault exclusion.
+    pub spp: Option<SppSolution>,
+    /// Integrity of `spp`.
+    pub raim: Option<Raim>,
+    /// Multipath and cycle slips from `raw`.
+    pub multipath: MultipathMonitor,
+    /// Satellites predicted above the horizon at the current position.
+    pub visibility: Vec<Visibility>,
+    pub obstruction: ObstructionMap,
+    /// C/N0 of the tracked satellites binned by where they are in the sky.
+    pub cno_map: CnoMap,
+    /// When the sky was last sampled, in seconds since `started`.
+    sky_updated: Option<f64>,
+    pub rf: Option<MonRf>,
+    /// AGC per RF block, as (seconds since `started`, percent of full range).
+    pub agc_history: BTreeMap<u8, Box<CircularBuffer<HISTORY_LEN, [f64; 2]>>>,
+    /// Spectrum per RF block index, from UBX-MON-SPAN.
+    pub spectrum: BTreeMap<usize, SpectrumTrace>,
+    /// Whether UBX-NAV-PVT is being received, in which case GGA does not update the fix.
+    pvt_seen: bool,
+    /// Whether VTG is being received, in which case RMC does not update the velocity.
+    vtg_seen: bool,
+    /// Whether UBX-NAV-COV is being received, in which case GST does not update the position
+    /// error.
+    nav_cov_seen: bool,
+    /// Whether UBX-NAV-SAT is being received, in which case GSV does not update satellites.
+    nav_sat_seen: bool,
+    /// GSV groups being assembled and the last complete group, per talker and signal.
+    gsv_partial: BTreeMap<(Constellation, Option<u8>), Vec<Satellite>>,
+    gsv_complete: BTreeMap<(Constellation, Option<u8>), Vec<Satellite>>,
+    subframes: SubframeDecoder,
+}
+
+impl Default for ReceiverState {
+    fn default() -> Self {
+        Self {
+            started: Utc::now(),
+            time: None,
+            leap: LeapSeconds::default(),
+            clock_offsets: CircularBuffer::boxed(),
+
+            time_pulse: None,
+            time_mark: None,
+            clock: None,
+            q_err_history: CircularBuffer::boxed(),
+            clock_bias_history: CircularBuffer::boxed(),
+            clock_drift_history: CircularBuffer::boxed(),
+            clock_samples: VecDeque::new(),
+
+            info: ReceiverInfo::default(),
+            survey: None,
+
+            satellites: Vec::new(),
+            fix: None,
+            track: CircularBuffer::boxed(),
+            position_error: None,
+            velocity: None,
+            speed_history: CircularBuffer::boxed(),
+            vertical_speed_history: CircularBuffer::boxed(),
+            rtk: RtkStatus::None,
+            correction_age: None,
+            relpos: None,
+            rtk_history: CircularBuffer::boxed(),
+            rtk_transitions: VecDeque::new(),
+            nav_status: None,
+            nav_sbas: None,
+            sbas_corrected: Vec::new(),
+            raw: None,
+            navigation: NavigationData::default(),
+            spp: None,
+            raim: None,
+            multipath: MultipathMonitor::default(),
+            visibility: Vec::new(),
+            obstruction: ObstructionMap::default(),
+            cno_map: CnoMap::default(),
+            sky_updated: None,
+            rf: None,
+            agc_history: BTreeMap::new(),
+            spectrum: BTreeMap::new(),
+            pvt_seen: false,
+            vtg_seen: false,
+            nav_cov_seen: false,
+            nav_sat_seen: false,
+            gsv_partial: BTreeMap::new(),
+            gsv_complete: BTreeMap::new(),
+            subframes: SubframeDecoder::default(),
+        }
+    }
+}
+
+impl ReceiverState {
+    /// Best estimate of the current instant: the last receiver epoch propagated with the host
+    /// clock, or the host clock alone if the receiver has not reported a time yet.
+    pub fn now(&self, host: DateTime<Utc>) -> GnssInstant {
+        match &self.time {
+            Some(time) => time.instant.offset(seconds_between(time.received, host)),
+            None => host_instant(host, self.leap.gps_utc),
+        }
+    }
+
+    /// Seconds since this state was created, the x axis of every history.
+    pub fn elapsed(&self, host: DateTime<Utc>) -> f64 {
+        seconds_between(self.started, host)
+    }
+
+    /// The recorded clock bias as phase in seconds, and the sample interval in seconds.
+    ///
+    /// The interval is the most common spacing between solutions; gaps are not filled.
+    pub fn clock_phase(&self) -> Option<(Vec<f64>, f64)> {
+        let mut steps: Vec<u32> = self
+            .clock_samples
+            .iter()
+            .zip(self.clock_samples.iter().skip(1))
+            .map(|(a, b)| b.itow_ms.wrapping_sub(a.itow_ms))
+            .filter(|step| *step > 0)
+            .collect();
+        if steps.is_empty() {
+            return None;
+        }
+        steps.sort_unstable();
+        let tau0 = steps[steps.len() / 2] as f64 * 1e-3;
+        let phase = self
+            .clock_samples
+            .iter()
+            .map(|s| s.bias_ns * 1e-9)
+            .collect();
+        Some((phase, tau0))
+    }
+
+    pub fn apply(&mut self, message: &GnssMessage, host: DateTime<Utc>) {
+        let t = self.elapsed(host);
+        match message {
+            GnssMessage::Ubx(UbxMessage::NavTimeGps(msg)) => {
+                if msg.leap_valid {
+                    self.set_leap(msg.leap_s as i32, "NAV-TIMEGPS");
+                }
+                if msg.tow_valid && msg.week_valid {
+                    let instant = GnssInstant::from_gps_week_tow(msg.week as i64, msg.tow());
+                    self.set_time(instant, host);
+                }
+            }
+            GnssMessage::Ubx(UbxMessage::NavTimeUtc(msg)) => {
+                if msg.utc_valid {
+                    let time = CalendarTime {
+                        year: msg.year as i32,
+                        month: msg.month as u32,
+                        day: msg.day as u32,
+                        hour: msg.hour as u32,
+                        minute: msg.min as u32,
+                        second: msg.sec as u32,
+                        nanos: 0,
+                    };
+                    let instant = GnssInstant::from_utc_calendar(&time, self.leap.gps_utc)
+                        .offset(msg.nano as f64 * 1e-9);
+                    self.set_time(instant, host);
+                }
+            }
+            GnssMessage::Ubx(UbxMessage::NavTimeLs(msg)) => {
+                if msg.curr_ls_valid {
+                    let detail = format!("NAV-TIMELS, {}", msg.source_name());
+                    self.set_leap(msg.curr_ls as i32, &detail);
+                }
+            }
+            GnssMessage::Ubx(UbxMessage::NavClock(msg)) => {
+                self.clock_bias_history.push_back([t, msg.clk_b_ns as f64]);
+                self.clock_drift_history
+                    .push_back([t, msg.clk_d_ns_s as f64]);
+                if self.clock_samples.len() == MAX_CLOCK_SAMPLES {
+                    self.clock_samples.pop_front();
+                }
+                self.clock_samples.push_back(ClockSample {
+                    itow_ms: msg.itow_ms,
+                    bias_ns: msg.clk_b_ns as f64,
+                });
+                self.clock = Some(msg.clone());
+            }
+            GnssMessage::Ubx(UbxMessage::TimTp(msg)) => {
+                if !msg.q_err_invalid() {
+                    self.q_err_history.push_back([t, msg.q_err_ps as f64]);
+                }
+                self.time_pulse = Some(msg.clone());
+            }
+            GnssMessage::Ubx(UbxMessage::TimTm2(msg)) => {
+                self.time_mark = Some(msg.clone());
+            }
+            GnssMessage::Ubx(UbxMessage::NavPvt(msg)) => self.apply_nav_pvt(msg, host),
+            GnssMessage::Ubx(UbxMessage::NavStatus(msg)) => {
+                self.nav_status = Some(msg.clone());
+            }
+            GnssMessage::Ubx(UbxMessage::NavSbas(msg)) => {
+                self.nav_sbas = Some(msg.clone());
+            }
+            GnssMessage::Ubx(UbxMessage::NavSat(msg)) => {
+                self.apply_nav_sat(msg);
+                self.update_sky(host);
+            }
+            GnssMessage::Ubx(UbxMessage::MonRf(msg)) => {
+                for block in &msg.blocks {
+                    self.agc_history
+                        .entry(block.block_id)
+                        .or_insert_with(CircularBuffer::boxed)
+                        .push_back([t, block.agc_percent()]);
+                }
+                self.info.apply_mon_rf(msg);
+                self.rf = Some(msg.clone());
+            }
+            GnssMessage::Ubx(UbxMessage::MonSpan(msg)) => {
+                for (index, block) in msg.blocks.iter().enumerate() {
+                    match self.spectrum.get_mut(&index) {
+                        Some(trace) => trace.update(block),
+                        None => {
+                            self.spectrum.insert(index, SpectrumTrace::new(block));
+                        }
+                    }
+                }
+            }
+            GnssMessage::Ubx(UbxMessage::NavSvin(msg)) => self.survey = Some(msg.clone()),
+            GnssMessage::Ubx(UbxMessage::NavRelPosNed(msg)) => self.relpos = Some(msg.clone()),
+            GnssMessage::Ubx(UbxMessage::RxmRawx(msg)) => self.apply_rawx(msg),
+            GnssMessage::Ubx(UbxMessage::RxmSfrbx(msg)) => {
+                let reference = self.now(host).as_gps_secs_f64();
+                self.subframes.decode(msg, reference, &mut self.navigation);
+            }
+            GnssMessage::Ubx(UbxMessage::NavCov(msg)) => {
+                self.nav_cov_seen = true;
+                let [nn, ne, _, ee, _, dd] = msg.pos_cov;
+                self.position_error = msg.pos_cov_valid.then(|| PositionError {
+                    east: ee.max(0.0).sqrt(),
+                    north: nn.max(0.0).sqrt(),
+                    up: Some(dd.max(0.0).sqrt()),
+                    ellipse: ErrorEllipse::from_covariance(ee, nn, ne),
+                });
+            }
+            GnssMessage::Ubx(UbxMessage::MonVer(msg)) => self.info.apply_mon_ver(msg),
+            GnssMessage::Ubx(UbxMessage::MonHw(msg)) => {
+                self.info.antenna_status = Some(ubx::antenna_status_name(msg.a_status).to_owned());
+                self.info.antenna_power = Some(ubx::antenna_power_name(msg.a_power).to_owned());
+                self.info.rtc_calibrated = Some(msg.rtc_calibrated());
+                self.info.safe_boot = Some(msg.safe_boot());
+                self.info.xtal_absent = Some(msg.xtal_absent());
+            }
+            GnssMessage::Ubx(UbxMessage::MonHw3(msg)) => {
+                self.info.rtc_calibrated = Some(msg.rtc_calibrated());
+                self.info.safe_boot = Some(msg.safe_boot());
+                self.info.xtal_absent = Some(msg.xtal_absent());
+                if self.info.hardware.is_none() {
+                    self.info.hardware = Some(msg.hw_version.clone());
+                }
+            }
+            GnssMessage::Nmea(NmeaMessage::Txt(msg)) => self.info.apply_txt(msg),
+            GnssMessage::Nmea(NmeaMessage::Gsv(msg)) => {
+                self.apply_gsv(msg);
+                self.update_sky(host);
+            }
+            GnssMessage::Nmea(NmeaMessage::Gga(msg)) => self.apply_gga(msg, host),
+            GnssMessage::Nmea(NmeaMessage::Zda(msg)) => {
+                let instant = GnssInstant::from_utc_calendar(&msg.time, self.leap.gps_utc);
+                self.set_time(instant, host);
+            }
+            GnssMessage::Nmea(NmeaMessage::Rmc(msg)) => {
+                if let (true, Some(time)) = (msg.valid, msg.time) {
+                    let instant = GnssInstant::from_utc_calendar(&time, self.leap.gps_utc);
+                    self.set_time(instant, host);
+                }
+                if !self.pvt_seen && !self.vtg_seen {
+                    let velocity = msg.speed_knots.filter(|_| msg.valid).map(|knots| Velocity {
+                        speed: knots * 1852.0 / 3600.0,
+                        course: msg.course,
+                        ..Default::default()
+                    });
+                    self.set_velocity(velocity, host);
+                }
+            }
+            GnssMessage::Nmea(NmeaMessage::Vtg(msg)) => self.apply_vtg(msg, host),
+            GnssMessage::Nmea(NmeaMessage::Gst(msg)) => self.apply_gst(msg),
+        }
+    }
+
+    fn apply_nav_pvt(&mut self, msg: &NavPvt, host: DateTime<Utc>) {
+        self.pvt_seen = true;
+        self.fix = Some(Fix {
+            fix_type: if msg.gnss_fix_ok() {
+                msg.fix_type
+            } else {
+                FixType::NoFix
+            },
+            latitude: msg.lat,
+            longitude: msg.lon,
+            height: Some(msg.height),
+            height_msl: Some(msg.h_msl),
+            num_sv: msg.num_sv,
+            h_acc: Some(msg.h_acc),
+            v_acc: Some(msg.v_acc),
+            epoch: msg.time_valid().then(|| {
+                time_of_day_ms(
+                    msg.hour as u32,
+                    msg.min as u32,
+                    msg.sec as u32,
+                    msg.nano as i64,
+                )
+            }),
+        });
+        self.record_track();
+        let velocity = msg.gnss_fix_ok().then(|| Velocity {
+            speed: msg.g_speed,
+            course: Some(msg.head_mot),
+            vertical: Some(-msg.vel_ned[2]),
+            heading: msg.head_veh_valid().then_some(msg.head_veh),
+            speed_acc: Some(msg.s_acc),
+            course_acc: Some(msg.head_acc),
+        });
+        self.set_velocity(velocity, host);
+        let rtk = match (msg.gnss_fix_ok(), msg.carrier_solution(), msg.diff_soln()) {
+            (false, _, _) => RtkStatus::None,
+            (true, 2, _) => RtkStatus::Fixed,
+            (true, 1, _) => RtkStatus::Float,
+            (true, _, true) => RtkStatus::Dgnss,
+            _ => RtkStatus::None,
+        };
+        self.correction_age = msg.correction_age();
+        self.set_rtk(rtk, host);
+
+        if msg.time_valid() {
+            let time = CalendarTime {
+                year: msg.year as i32,
+                month: msg.month as u32,
+                day: msg.day as u32,
+                hour: msg.hour as u32,
+                minute: msg.min as u32,
+                second: msg.sec as u32,
+                nanos: 0,
+            };
+            let instant = GnssInstant::from_utc_calendar(&time, self.leap.gps_utc)
+                .offset(msg.nano as f64 * 1e-9);
+            self.set_time(instant, host);
+        }
+    }
+
+    fn apply_nav_sat(&mut self, msg: &NavSat) {
+        self.nav_sat_seen = true;
+        self.sbas_corrected = msg
+            .satellites
+            .iter()
+            .filter(|sat| sat.used() && sat.sbas_corrections_used())
+            .map(|sat| sat.id)
+            .collect();
+        self.satellites = msg
+            .satellites
+            .iter()
+            .map(|sat| Satellite {
+                id: sat.id,
+                cno: sat.cno,
+                elevation: (-90..=90).contains(&sat.elev).then_some(sat.elev as f32),
+                azimuth: (0..=360).contains(&sat.azim).then_some(sat.azim as f32),
+                used: sat.used(),
+            })
+            .collect();
+    }
+
+    /// The SBAS systems in use: from NAV-SBAS, or else those of the tracked SBAS satellites.
+    pub fn sbas_providers(&self) -> Vec<SbasProvider> {
+        match self.nav_sbas.as_ref().and_then(|s| s.provider) {
+            Some(provider) => vec![provider],
+            None => SbasProvider::of_satellites(
+                self.satellites.iter().filter(|s| s.cno > 0).map(|s| s.id),
+            ),
+        }
+    }
+
+    /// Whether the current fix is SBAS-aided; `None` when the messages received cannot tell
+    /// SBAS from other differential corrections.
+    pub fn sbas_aided(&self) -> Option<bool> {
+        if !self.fix.as_ref().is_some_and(|f| f.fix_type.has_position()) {
+            return Some(false);
+        }
+        if self.nav_sat_seen {
+            return Some(!self.sbas_corrected.is_empty());
+        }
+        if self.rtk != RtkStatus::Dgnss {
+            return Some(false);
+        }
+        match &self.nav_sbas {
+            Some(sbas) => Some(sbas.enabled() && sbas.services.corrections()),
+            // A differential fix while tracking SBAS, without RTCM input to tell them apart.
+            None => (!self.sbas_providers().is_empty()).then_some(true),
+        }
+    }
+
+    /// Latitude, longitude and height the sky is seen from: the receiver's fix, or else the
+    /// local solution.
+    pub fn sky_position(&self) -> Option<(f64, f64, f64)> {
+        self.fix
+            .as_ref()
+            .filter(|f| f.fix_type.has_position())
+            .and_then(|f| Some((f.latitude, f.longitude, f.height?)))
+            .or(self
+                .spp
+                .as_ref()
+                .map(|s| (s.latitude, s.longitude, s.height)))
+    }
+
+    /// Samples the C/N0 of the tracked satellites, predicts which satellites should be in view
+    /// and adds them to the obstruction map, at most once a second.
+    fn update_sky(&mut self, host: DateTime<Utc>) {
+        let elapsed = self.elapsed(host);
+        if self.sky_updated.is_some_and(|t| elapsed - t < 1.0) {
+            return;
+        }
+        self.sky_updated = Some(elapsed);
+        self.cno_map.add(&self.satellites);
+        let Some(position) = self.sky_position() else {
+            self.visibility.clear();
+            return;
+        };
+        let time = self.now(host).as_gps_secs_f64();
+        self.visibility = visibility::predict(&self.navigation, &self.satellites, position, time);
+        if !self.visibility.is_empty() {
+            let ecef = geodesy::geodetic_to_ecef(position.0, position.1, position.2);
+            self.obstruction.add(ecef, &self.visibility);
+        }
+    }
+
+    fn apply_gsv(&mut self, msg: &Gsv) {
+        let key = (msg.constellation, msg.signal_id);
+        let partial = self.gsv_partial.entry(key).or_default();
+        if msg.number == 1 {
+            partial.clear();
+        }
+        partial.extend(msg.satellites.iter().map(|sat| Satellite {
+            id: sat.id,
+            cno: sat.snr.unwrap_or(0),
+            elevation: sat.elevation,
+            azimuth: sat.azimuth,
+            used: false,
+        }));
+        if msg.number < msg.total {
+            return;
+        }
+
+        let complete = std::mem::take(partial);
+        self.gsv_complete.insert(key, complete);
+        if self.nav_sat_seen {
+            return;
+        }
+
+        // A satellite appears once per signal; keep its strongest C/N0.
+        let mut satellites: Vec<Satellite> = Vec::new();
+        for sat in self.gsv_complete.values().flatten() {
+            match satellites.iter_mut().find(|s| s.id == sat.id) {
+                Some(existing) => existing.cno = existing.cno.max(sat.cno),
+                None => satellites.push(sat.clone()),
+            }
+        }
+        self.satellites = satellites;
+    }
+
+    fn apply_gga(&mut self, msg: &Gga, host: DateTime<Utc>) {
+        if self.pvt_seen {
+            return;
+        }
+        self.correction_age = msg.diff_age;
+        self.set_rtk(RtkStatus::from_gga_quality(msg.quality), host);
+        let (Some(latitude), Some(longitude)) = (msg.latitude, msg.longitude) else {
+            self.fix = None;
+            return;
+        };
+        let fix_type = match msg.quality {
+            0 => FixType::NoFix,
+            6 => FixType::DeadReckoning,
+            // Not a live position: never used or logged as a fix.
+            7 => FixType::ManualInput,
+            8 => FixType::Simulated,
+            _ if msg.altitude.is_some() => FixType::Fix3D,
+            _ => FixType::Fix2D,
+        };
+        self.fix = Some(Fix {
+            fix_type,
+            latitude,
+            longitude,
+            height: msg.altitude.zip(msg.separation).map(|(a, n)| a + n),
+            height_msl: msg.altitude,
+            num_sv: msg.num_sv,
+            h_acc: None,
+            v_acc: None,
+            epoch: msg
+                .time
+                .map(|(h, m, s, ns)| time_of_day_ms(h, m, s, ns as i64)),
+        });
+        self.record_track();
+    }
+
+    fn apply_vtg(&mut self, msg: &Vtg, host: DateTime<Utc>) {
+        if self.pvt_seen {
+            return;
+        }
+        self.vtg_seen = true;
+        let velocity = match msg.mode {
+            Some('N') => None,
+            _ => msg.speed().map(|speed| Velocity {
+                speed,
+                course: msg.course_true,
+                ..Default::default()
+            }),
+        };
+        self.set_velocity(velocity, host);
+    }
+
+    fn apply_rawx(&mut self, msg: &RxmRawx) {
+        let time = msg.week as f64 * SECONDS_PER_WEEK as f64 + msg.rcv_tow;
+        let initial = self.spp.as_ref().map(|s| s.ecef);
+        let observations = spp::observations(msg);
+        let result = raim::fde(&observations, time, &self.navigation, initial);
+        (self.spp, self.raim) = result.unzip();
+        self.multipath.add(msg, &self.satellites);
+        self.raw = Some(msg.clone());
+    }
+
+    fn apply_gst(&mut self, msg: &Gst) {
+        if self.nav_cov_seen {
+            return;
+        }
+        let (Some(north), Some(east)) = (msg.latitude_sigma, msg.longitude_sigma) else {
+            self.position_error = None;
+            return;
+        };
+        // Receivers that leave out the ellipse still give the axis sigmas.
+        let ellipse = match (msg.semi_major, msg.semi_minor, msg.orientation) {
+            (Some(semi_major), Some(semi_minor), Some(orientation)) => ErrorEllipse {
+                semi_major,
+                semi_minor,
+                orientation: orientation.rem_euclid(180.0),
+            },
+            _ => ErrorEllipse::from_covariance(east * east, north * north, 0.0),
+        };
+        self.position_error = Some(PositionError {
+            east,
+            north,
+            up: msg.altitude_sigma,
+            ellipse,
+        });
+    }
+
+    fn set_velocity(&mut self, velocity: Option<Velocity>, host: DateTime<Utc>) {
+        if let Some(v) = &velocity {
+            let elapsed = self.elapsed(host);
+            self.speed_history.push_back([elapsed, v.speed]);
+            if let Some(vertical) = v.vertical {
+                self.vertical_speed_history.push_back([elapsed, vertical]);
+            }
+        }
+        self.velocity = velocity;
+    }
+
+    fn record_track(&mut self) {
+        if let Some(fix) = self.fix.as_ref().filter(|f| f.fix_type.has_position()) {
+            self.track.push_back([fix.latitude, fix.longitude]);
+        }
+    }
+
+    fn set_rtk(&mut self, status: RtkStatus, host: DateTime<Utc>) {
+        self.rtk_history
+            .push_back([self.elapsed(host), status as u8 as f64]);
+        if status == self.rtk {
+            return;
+        }
+        if self.rtk_transitions.len() == MAX_RTK_TRANSITIONS {
+            self.rtk_transitions.pop_front();
+        }
+        self.rtk_transitions.push_back(RtkTransition {
+            time: host,
+            from: self.rtk,
+            to: status,
+        });
+        self.rtk = status;
+    }
+
+    fn set_leap(&mut self, gps_utc: i32, detail: &str) {
+        self.leap = LeapSeconds {
+            gps_utc,
+            source: LeapSecondSource::Receiver(detail.to_owned()),
+        };
+    }
+
+    fn set_time(&mut self, instant: GnssInstant, host: DateTime<Utc>) {
+        // Several messages may carry the same epoch; only sample the clock offset once.
+        if self.time.is_some_and(|t| t.instant == instant) {
+            return;
+        }
+        self.time = Some(ReceiverTime {
+            instant,
+            received: host,
+        });
+
+        let offset = host_instant(host, self.leap.gps_utc).seconds_since(&instant);
+        self.clock_offsets
+            .push_back([self.elapsed(host), offset * 1e3]);
+    }
+}
+
+/// The host clock reading expressed on the GPS time scale.
+pub fn host_instant(host: DateTime<Utc>, gps_utc: i32) -> GnssInstant {
+    GnssInstant::from_utc_unix(host.timestamp(), host.timestamp_subsec_nanos(), gps_utc)
+}
+
+fn seconds_between(earlier: DateTime<Utc>, later: DateTime<Utc>) -> f64 {
+    (later - earlier).num_microseconds().unwrap_or(0) as f64 * 1e-6
+}
+
+/// Bytes read from the receiver and the host clock reading when they arrived.
+type Chunk = io::Result<(DateTime<Utc>, Vec<u8>)>;
+
+/// Reads the receiver on its own thread so every chunk is stamped when it arrives rather
+/// than when the UI next polls. Ends after sending the error that closed the stream.
+fn spawn_reader(mut stream: TcpStream) -> mpsc::Receiver<Chunk> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let mut buffer = [0; 4096];
+        loop {
+            let chunk = match stream.read(&mut buffer) {
+                Ok(0) => Err(io::Error::new(
+                    ErrorKind::UnexpectedEof,
+                    "receiver closed the connection",
+                )),
+                Ok(n) => Ok((Utc::now(), buffer[..n].to_vec())),
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => Err(e),
+            };
+            let failed = chunk.is_err();
+            if sender.send(chunk).is_err() || failed {
+                return;
+            }
+        }
+    });
+    receiver
+}
+
+/// A GNSS receiver reached over a TCP byte stream (e.g. ser2net or u-center's TCP server).
+pub struct GnssReceiver {
+    pub address: String,
+    /// Write half of the connection; the read half belongs to the reader thread.
+    stream: Option<TcpStream>,
+    chunks: Option<mpsc::Receiver<Chunk>>,
+    decoder: StreamDecoder,
+    pub state: ReceiverState,
+    pub integrity: IntegrityMonitor,
+    pub ttff: TtffRunner,
+    pub base: BaseStation,
+}
+
+impl GnssReceiver {
+    pub fn new(address: &str) -> Self {
+        Self {
+            address: address.to_owned(),
+            stream: None,
+            chunks: None,
+            decoder: StreamDecoder::default(),
+            state: ReceiverState::default(),
+            integrity: IntegrityMonitor::default(),
+            ttff: TtffRunner::default(),
+            base: BaseStation::default(),
+        }
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.stream.is_some()
+    }
+
+    pub fn connect(&mut self) -> io::Result<()> {
+        self.disconnect();
+        let stream = TcpStream::connect(&self.address)?;
+        self.chunks = Some(spawn_reader(stream.try_clone()?));
+        self.stream = Some(stream);
+        self.decoder = StreamDecoder::default();
+        self.state = ReceiverState::default();
+        self.integrity.reset();
+        self.ttff.stop();
+        Ok(())
+    }
+
+    pub fn disconnect(&mut self) {
+        if let Some(stream) = self.stream.take() {
+            // Wakes the reader thread up so it ends.
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+        self.chunks = None;
+        self.ttff.stop();
+    }
+
+    /// Writes a command to the receiver.
+    pub fn send(&mut self, bytes: &[u8]) -> io::Result<()> {
+        match self.stream.as_mut() {
+            Some(stream) => stream.write_all(bytes),
+            None => Err(io::Error::new(
+                ErrorKind::NotConnected,
+                "receiver not connected",
+            )),
+        }
+    }
+
+    /// Applies whatever the receiver has sent since the last call to the state, each message
+    /// at the time it arrived, and returns the decoded messages. Never blocks.
+    pub fn poll(&mut self) -> io::Result<Vec<GnssMessage>> {
+        let Some(chunks) = self.chunks.as_ref() else {
+            return Ok(Vec::new());
+        };
+
+        let mut lost = None;
+        loop {
+            match chunks.try_recv() {
+                Ok(Ok((received, bytes))) => self.decoder.push(&bytes, received),
+                Ok(Err(e)) => {
+                    lost = Some(e);
+                    break;
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    lost = Some(io::Error::new(
+                        ErrorKind::UnexpectedEof,
+                        "receiver reader stopped",
+                    ));
+                    break;
+                }
+            }
+        }
+
+        let mut messages = Vec::new();
+        while let Some((message, received)) = self.decoder.next_message() {
+            self.state.apply(&message, received);
+            self.integrity.check(&self.state, &message, received);
+            self.ttff.observe(&self.state, received);
+            self.base.observe(&message, received);
+            messages.push(message);
+        }
+
+        if let Some(e) = lost {
+            self.disconnect();
+            return Err(e);
+        }
+        if let Some(reset) = self.ttff.poll(Utc::now()) {
+            self.send(&reset)?;
+        }
+        if let Some(command) = self.base.poll() {
+            self.send(&command)?;
+        }
+        Ok(messages)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nmea;
+
+    #[test]
+    fn decodes_interleaved_stream() {
+        let mut timegps = vec![0; 16];
+        timegps[8..10].copy_from_slice(&2300i16.to_le_bytes());
+        timegps[10] = 18;
+        timegps[11] = 0x07;
+        let ubx = UbxFrame::new(ubx::CLASS_NAV, ubx::ID_NAV_TIMEGPS, timegps).to_bytes();
+        let zda = nmea::encode("GPZDA,120000.00,01,02,2024,,");
+
+        let first = Utc::now();
+        let second = first + chrono::Duration::milliseconds(250);
+
+        let mut stream = b"garbage$GPXXX,bad*00\r\n".to_vec();
+        stream.extend_from_slice(zda.as_bytes());
+        stream.extend_from_slice(&ubx[..10]);
+        let mut decoder = StreamDecoder::default();
+        decoder.push(&stream, first);
+        assert!(matches!(
+            decoder.next_message(),
+            Some((GnssMessage::Nmea(NmeaMessage::Zda(m)), t))
+                if m.time.year == 2024 && m.time.hour == 12 && t == first
+        ));
+        assert_eq!(decoder.next_message(), None);
+
+        // A message split across reads arrived with its last byte.
+        decoder.push(&ubx[10..], second);
+        assert!(matches!(
+            decoder.next_message(),
+            Some((GnssMessage::Ubx(UbxMessage::NavTimeGps(m)), t))
+                if m.week == 2300 && m.leap_s == 18 && t == second
+        ));
+        assert_eq!(decoder.next_message(), None);
+        assert!(decoder.arrivals.is_empty());
+    }
+
+    #[test]
+    fn assembles_gsv_groups() {
+        let mut state = ReceiverState::default();
+        let host = Utc::now();
+        for body in [
+            "GPGSV,2,1,05,01,40,083,46,02,17,308,41,12,07,344,39,14,22,228,45",
+            "GPGSV,2,2,05,66,10,010,30",
+        ] {
+            let sentence = NmeaSentence::parse(&nmea::encode(body)).unwrap();
+            let message = GnssMessage::Nmea(NmeaMessage::decode(&sentence).unwrap());
+            state.apply(&message, host);
+        }
+        assert_eq!(state.satellites.len(), 5);
+        assert_eq!(
+            state.satellites[4].id,
+            crate::gnss::SatelliteId::new(Constellation::Glonass, 2)
+        );
+        assert_eq!(state.satellites[0].cno, 46);
+    }
+
+    #[test]
+    fn collects_receiver_info() {
+        let mut state = ReceiverState::default();
+        let host = Utc::now();
+        for body in [
+            "GPTXT,01,01,02,u-blox ag - www.u-blox.com",
+            "GPTXT,01,01,02,HW UBX-M8030 00080000",
+            "GPTXT,01,01,02,FWVER=SPG 3.01",
+            "GPTXT,01,01,02,GPS;GLO;GAL;BDS",
+            "GPTXT,01,01,02,ANTSTATUS=OPEN",
+        ] {
+            let sentence = NmeaSentence::parse(&nmea::encode(body)).unwrap();
+            let message = GnssMessage::Nmea(NmeaMessage::decode(&sentence).unwrap());
+            state.apply(&message, host);
+        }
+        assert_eq!(state.info.hardware.as_deref(), Some("UBX-M8030 00080000"));
+        assert_eq!(state.info.firmware.as_deref(), Some("SPG 3.01"));
+        assert_eq!(state.info.constellations, ["GPS", "GLO", "GAL", "BDS"]);
+        assert_eq!(state.info.antenna_status.as_deref(), Some("OPEN"));
+
+        let mut payload = vec![0; 40 + 30];
+        payload[..8].copy_from_slice(b"ROM CORE");
+        payload[30..38].copy_from_slice(b"00080000");
+        payload[40..53].copy_from_slice(b"PROTVER=18.00");
+        let frame = UbxFrame::new(ubx::CLASS_MON, ubx::ID_MON_VER, payload);
+        let message = GnssMessage::Ubx(UbxMessage::decode(&frame).unwrap());
+        state.apply(&message, host);
+        assert_eq!(state.info.software.as_deref(), Some("ROM CORE"));
+        assert_eq!(state.info.protocol.as_deref(), Some("18.00"));
+
+        // Two RF blocks, L1 fine and L2 with an open antenna.
+        let mut payload = vec![0, 2, 0, 0];
+        for (block_id, ant_status) in [(0, 2), (1, 4)] {
+            let mut block = vec![0; 24];
+            block[0] = block_id;
+            block[2] = ant_status;
+            block[3] = 1;
+            payload.extend(block);
+        }
+        let frame = UbxFrame::new(ubx::CLASS_MON, ubx::ID_MON_RF, payload);
+        let message = GnssMessage::Ubx(UbxMessage::decode(&frame).unwrap());
+        state.apply(&message, host);
+        assert_eq!(
+            state.info.antenna_status.as_deref(),
+            Some("OK (block 0), Open (block 1)")
+        );
+        assert_eq!(state.info.antenna_power.as_deref(), Some("On"));
+    }
+
+    #[test]
+    fn prefers_vtg_velocity_over_rmc() {
+        let mut state = ReceiverState::default();
+        let host = Utc::now();
+        let mut apply = |body: &str| {
+            let sentence = NmeaSentence::parse(&nmea::encode(body)).unwrap();
+            let message = GnssMessage::Nmea(NmeaMessage::decode(&sentence).unwrap());
+            state.apply(&message, host);
+            state.velocity.clone()
+        };
+        let rmc = "GPRMC,120000.00,A,4807.038,N,01131.000,E,10.0,084.4,010224,,,A";
+        let v = apply(rmc).unwrap();
+        assert!((v.speed - 5.144_444).abs() < 1e-6);
+        assert_eq!(v.course, Some(84.4));
+        let v = apply("GPVTG,090.0,T,,M,9.0,N,18.0,K,A").unwrap();
+        assert_eq!((v.speed, v.course), (5.0, Some(90.0)));
+        assert_eq!(apply(rmc).unwrap().speed, 5.0);
+        assert_eq!(apply("GPVTG,,T,,M,,N,,K,N"), None);
+        assert_eq!(state.speed_history.len(), 2);
+
+        let v = Velocity {
+            course: Some(350.0),
+            heading: Some(10.0),
+            ..Default::default()
+        };
+        assert_eq!(v.slip(), Some(20.0));
+        assert!((crate::gnss::SpeedUnit::Knots.from_mps(5.144_444) - 10.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn reads_position_error() {
+        let mut state = ReceiverState::default();
+        let host = Utc::now();
+        let sentence = NmeaSentence::parse(&nmea::encode(
+            "GPGST,120000.00,1.2,3.0,1.0,210.0,2.6,1.7,4.1",
+        ))
+        .unwrap();
+        state.apply(
+            &GnssMessage::Nmea(NmeaMessage::decode(&sentence).unwrap()),
+            host,
+        );
+        let error = state.position_error.clone().unwrap();
+        assert_eq!((error.east, error.north, error.up), (1.7, 2.6, Some(4.1)));
+        assert_eq!(error.ellipse.orientation, 30.0);
+
+        // 4 m² north, 1 m² east, correlated: the major axis leans east of north.
+        let mut p = vec![0; 64];
+        p[5] = 1;
+        for (i, v) in [4.0f32, 1.0, 0.0, 1.0, 0.0, 9.0].iter().enumerate() {
+            p[16 + 4 * i..20 + 4 * i].copy_from_slice(&v.to_le_bytes());
+        }
+        let frame = UbxFrame::new(ubx::CLASS_NAV, ubx::ID_NAV_COV, p);
+        state.apply(&GnssMessage::Ubx(UbxMessage::decode(&frame).unwrap()), host);
+        let error = state.position_error.clone().unwrap();
+        assert_eq!((error.east, error.north, error.up), (1.0, 2.0, Some(3.0)));
+        let ellipse = error.ellipse;
+        // Eigenvalues of [[1, 1], [1, 4]] are (5 ± sqrt(13)) / 2.
+        assert!((ellipse.semi_major.powi(2) - (5.0 + 13f64.sqrt()) / 2.0).abs() < 1e-9);
+        assert!((ellipse.semi_minor.powi(2) - (5.0 - 13f64.sqrt()) / 2.0).abs() < 1e-9);
+        assert!((ellipse.orientation - 16.845_034).abs() < 1e-6);
+        let tip = ellipse.outline(1.0)[0];
+        assert!((tip[0].hypot(tip[1]) - ellipse.semi_major).abs() < 1e-9 && tip[0] > 0.0);
+
+        // GST is ignored once NAV-COV is received.
+        state.apply(
+            &GnssMessage::Nmea(NmeaMessage::decode(&sentence).unwrap()),
+            host,
+        );
+        assert_eq!(state.position_error.unwrap().east, 1.0);
+    }
+
+    #[test]
+    fn ignores_malformed_nmea_times() {
+        let mut state = ReceiverState::default();
+        let host = Utc::now();
+        for time in [
+            "1234inf",
+            "1234NaN",
+            "123499999999999",
+            "120061.00",
+            "126000.00",
+            "240000.00",
+        ] {
+            let body = format!("GPGGA,{time},4807.038,N,01131.000,E,1,08,0.9,545.4,M,46.9,M,,");
+            let sentence = NmeaSentence::parse(&nmea::encode(&body)).unwrap();
+            let message = GnssMessage::Nmea(NmeaMessage::decode(&sentence).unwrap());
+            state.apply(&message, host);
+            assert_eq!(state.fix.as_ref().unwrap().epoch, None, "{}", time);
+        }
+        let body = "GPGGA,235960.50,4807.038,N,01131.000,E,1,08,0.9,545.4,M,46.9,M,,";
+        let sentence = NmeaSentence::parse(&nmea::encode(body)).unwrap();
+        state.apply(
+            &GnssMessage::Nmea(NmeaMessage::decode(&sentence).unwrap()),
+            host,
+        );
+        // A leap second wraps to the start of the next day.
+        assert_eq!(state.fix.as_ref().unwrap().epoch, Some(500));
+    }
+
+    #[test]
+    fn tracks_rtk_status() {
+        let mut state = ReceiverState::default();
+        let host = Utc::now();
+        for body in [
+            "GPGGA,120000.00,4807.038,N,01131.000,E,1,08,0.9,545.4,M,46.9,M,,",
+            "GPGGA,120001.00,4807.038,N,01131.000,E,5,08,0.9,545.4,M,46.9,M,1.0,0001",
+            "GPGGA,120002.00,4807.038,N,01131.000,E,4,08,0.9,545.4,M,46.9,M,2.0,0001",
+        ] {
+            let sentence = NmeaSentence::parse(&nmea::encode(body)).unwrap();
+            let message = GnssMessage::Nmea(NmeaMessage::decode(&sentence).unwrap());
+            state.apply(&message, host);
+        }
+        assert_eq!(state.rtk, RtkStatus::Fixed);
+        assert_eq!(state.correction_age, Some(2.0));
+        assert_eq!(state.fix.as_ref().unwrap().epoch, Some(43_202_000));
+        assert_eq!(state.rtk_history.len(), 3);
+        let transitions: Vec<_> = state.rtk_transitions.iter().map(|t| t.to).collect();
+        assert_eq!(transitions, [RtkStatus::Float, RtkStatus::Fixed]);
+        for (quality, fix_type) in [
+            (6, FixType::DeadReckoning),
+            (7, FixType::ManualInput),
+            (8, FixType::Simulated),
+        ] {
+            let body =
+                format!("GPGGA,120003.00,4807.038,N,01131.000,E,{quality},00,,545.4,M,46.9,M,,");
+            let sentence = NmeaSentence::parse(&nmea::encode(&body)).unwrap();
+            state.apply(
+                &GnssMessage::Nmea(NmeaMessage::decode(&sentence).unwrap()),
+                host,
+            );
+            let fix = state.fix.as_ref().unwrap();
+            assert_eq!((fix.fix_type, state.rtk), (fix_type, RtkStatus::None));
+            assert!(!fix.fix_type.has_position());
+        }
+
+        let mut p = vec![0; 64];
+        p[0] = 1;
+        p[8..12].copy_from_slice(&300i32.to_le_bytes());
+        p[12..16].copy_from_slice(&(-400i32).to_le_bytes());
+        p[20..24].copy_from_slice(&500i32.to_le_bytes());
+        p[24..28].copy_from_slice(&12_345_678i32.to_le_bytes());
+        p[32] = 5;
+        p[60..64].copy_from_slice(&0x117u32.to_le_bytes());
+        let frame = UbxFrame::new(ubx::CLASS_NAV, ubx::ID_NAV_RELPOSNED, p);
+        state.apply(&GnssMessage::Ubx(UbxMessage::decode(&frame).unwrap()), host);
+        let relpos = state.relpos.as_ref().unwrap();
+        assert!((relpos.rel_pos[0] - 3.0005).abs() < 1e-9);
+        assert_eq!(relpos.baseline(), 5.0);
+        assert!((relpos.heading.unwrap() - 123.45678).abs() < 1e-9);
+        assert_eq!(relpos.carrier_solution(), 2);
+        assert!(relpos.heading_valid() && !relpos.is_moving());
+    }
+
+    #[test]
+    fn reads_sbas_status() {
+        let mut state = ReceiverState::default();
+        let host = Utc::now();
+        let mut apply = |body: &str| {
+            let sentence = NmeaSentence::parse(&nmea::encode(body)).unwrap();
+            let message = GnssMessage::Nmea(NmeaMessage::decode(&sentence).unwrap());
+            state.apply(&message, host);
+        };
+        apply("GPGGA,120000.00,4807.038,N,01131.000,E,2,08,0.9,545.4,M,46.9,M,,");
+        apply("GPGSV,1,1,02,05,40,100,44,49,30,170,40");
+        assert_eq!(state.sbas_providers(), [SbasProvider::Egnos]);
+        assert_eq!(state.sbas_aided(), Some(true));
+
+        // EGNOS on PRN 136 with corrections for GPS 5.
+        let mut p = vec![0; 12 + 12];
+        p[4] = 136;
+        p[5] = 1;
+        p[6] = 1;
+        p[7] = 0x07;
+        p[8] = 1;
+        p[9] = 2;
+        p[12] = 5;
+        p[14] = 3;
+        p[15] = 1;
+        p[16] = 0x06;
+        p[18..20].copy_from_slice(&(-123i16).to_le_bytes());
+        p[22..24].copy_from_slice(&250i16.to_le_bytes());
+        let frame = UbxFrame::new(ubx::CLASS_NAV, ubx::ID_NAV_SBAS, p);
+        state.apply(&GnssMessage::Ubx(UbxMessage::decode(&frame).unwrap()), host);
+        let sbas = state.nav_sbas.as_ref().unwrap();
+        assert_eq!((sbas.geo, sbas.integrity_used()), (136, Some(true)));
+        let sv = &sbas.satellites[0];
+        assert_eq!(sv.id, SatelliteId::new(Constellation::Gps, 5));
+        assert_eq!((sv.prc, sv.ic), (-1.23, 2.5));
+        assert_eq!(sv.udre(), crate::sbas::Udre::Bound(1.75));
+        assert_eq!(state.sbas_aided(), Some(true));
+
+        // NAV-SAT says which satellites were actually corrected.
+        let mut p = vec![0; 8 + 12];
+        p[5] = 1;
+        p[9] = 5;
+        p[16..20].copy_from_slice(&0x08u32.to_le_bytes());
+        let frame = UbxFrame::new(ubx::CLASS_NAV, ubx::ID_NAV_SAT, p);
+        state.apply(&GnssMessage::Ubx(UbxMessage::decode(&frame).unwrap()), host);
+        assert_eq!(state.sbas_aided(), Some(false));
+    }
+}
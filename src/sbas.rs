@@ -0,0 +1,191 @@
+use crate::gnss::{Constellation, SatelliteId};
+
+/// A satellite based augmentation system.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SbasProvider {
+    Waas,
+    Egnos,
+    Msas,
+    Gagan,
+    Sdcm,
+    Bdsbas,
+    Kass,
+    SouthPan,
+}
+
+impl SbasProvider {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SbasProvider::Waas => "WAAS",
+            SbasProvider::Egnos => "EGNOS",
+            SbasProvider::Msas => "MSAS",
+            SbasProvider::Gagan => "GAGAN",
+            SbasProvider::Sdcm => "SDCM",
+            SbasProvider::Bdsbas => "BDSBAS",
+            SbasProvider::Kass => "KASS",
+            SbasProvider::SouthPan => "SouthPAN",
+        }
+    }
+
+    pub fn region(&self) -> &'static str {
+        match self {
+            SbasProvider::Waas => "North America",
+            SbasProvider::Egnos => "Europe",
+            SbasProvider::Msas => "Japan",
+            SbasProvider::Gagan => "India",
+            SbasProvider::Sdcm => "Russia",
+            SbasProvider::Bdsbas => "China",
+            SbasProvider::Kass => "Korea",
+            SbasProvider::SouthPan => "Australia and New Zealand",
+        }
+    }
+
+    /// Maps the system field of UBX-NAV-SBAS.
+    pub fn from_ubx(sys: i8) -> Option<Self> {
+        Some(match sys {
+            0 => SbasProvider::Waas,
+            1 => SbasProvider::Egnos,
+            2 => SbasProvider::Msas,
+            3 => SbasProvider::Gagan,
+            4 => SbasProvider::Sdcm,
+            5 => SbasProvider::Bdsbas,
+            6 => SbasProvider::Kass,
+            _ => return None,
+        })
+    }
+
+    /// The operator of a geostationary SBAS satellite, from its PRN assignment.
+    pub fn from_prn(prn: u8) -> Option<Self> {
+        Some(match prn {
+            131 | 133 | 135 | 138 => SbasProvider::Waas,
+            120 | 121 | 123 | 124 | 126 | 136 => SbasProvider::Egnos,
+            129 | 137 => SbasProvider::Msas,
+            127 | 128 | 132 => SbasProvider::Gagan,
+            125 | 140 | 141 => SbasProvider::Sdcm,
+            130 | 143 | 144 => SbasProvider::Bdsbas,
+            134 => SbasProvider::Kass,
+            122 => SbasProvider::SouthPan,
+            _ => return None,
+        })
+    }
+
+    /// The providers of the SBAS satellites among `ids`, in order and without repeats.
+    pub fn of_satellites(ids: impl IntoIterator<Item = SatelliteId>) -> Vec<Self> {
+        let mut providers: Vec<Self> = ids
+            .into_iter()
+            .filter(|id| id.constellation == Constellation::Sbas)
+            .filter_map(|id| Self::from_prn(id.prn))
+            .collect();
+        providers.sort();
+        providers.dedup();
+        providers
+    }
+}
+
+/// Services an SBAS satellite provides, the service bits of UBX-NAV-SBAS.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SbasServices(pub u8);
+
+impl SbasServices {
+    pub fn ranging(&self) -> bool {
+        self.0 & 0x01 != 0
+    }
+
+    pub fn corrections(&self) -> bool {
+        self.0 & 0x02 != 0
+    }
+
+    pub fn integrity(&self) -> bool {
+        self.0 & 0x04 != 0
+    }
+
+    pub fn test_mode(&self) -> bool {
+        self.0 & 0x08 != 0
+    }
+
+    pub fn bad(&self) -> bool {
+        self.0 & 0x10 != 0
+    }
+
+    /// The services as a short list, e.g. "Ranging, Corrections".
+    pub fn summary(&self) -> String {
+        let names: Vec<&str> = [
+            (self.ranging(), "Ranging"),
+            (self.corrections(), "Corrections"),
+            (self.integrity(), "Integrity"),
+            (self.test_mode(), "Test mode"),
+            (self.bad(), "Bad"),
+        ]
+        .into_iter()
+        .filter_map(|(set, name)| set.then_some(name))
+        .collect();
+        match names.is_empty() {
+            true => "-".into(),
+            false => names.join(", "),
+        }
+    }
+}
+
+/// Integrity state of a satellite's corrections, from its user differential range error
+/// indicator (UDREI).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Udre {
+    /// 99.9% bound of the range error after correction, in metres.
+    Bound(f64),
+    NotMonitored,
+    DoNotUse,
+}
+
+impl Udre {
+    /// Maps a UDREI of RTCA DO-229.
+    pub fn from_indicator(udrei: u8) -> Self {
+        const BOUNDS: [f64; 14] = [
+            0.75, 1.0, 1.25, 1.75, 2.25, 3.0, 3.75, 4.5, 5.25, 6.0, 7.5, 15.0, 50.0, 150.0,
+        ];
+        match udrei {
+            0..=13 => Udre::Bound(BOUNDS[udrei as usize]),
+            14 => Udre::NotMonitored,
+            _ => Udre::DoNotUse,
+        }
+    }
+
+    pub fn usable(&self) -> bool {
+        matches!(self, Udre::Bound(_))
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Udre::Bound(metres) => format!("{:.2} m", metres),
+            Udre::NotMonitored => "Not monitored".into(),
+            Udre::DoNotUse => "Do not use".into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identifies_providers_and_integrity() {
+        let ids = [
+            SatelliteId::new(Constellation::Sbas, 136),
+            SatelliteId::new(Constellation::Gps, 131),
+            SatelliteId::new(Constellation::Sbas, 131),
+            SatelliteId::new(Constellation::Sbas, 123),
+            SatelliteId::new(Constellation::Sbas, 199),
+        ];
+        assert_eq!(
+            SbasProvider::of_satellites(ids),
+            [SbasProvider::Waas, SbasProvider::Egnos]
+        );
+        assert_eq!(SbasProvider::from_ubx(1), Some(SbasProvider::Egnos));
+        assert_eq!(SbasProvider::from_ubx(-1), None);
+
+        let services = SbasServices(0x07);
+        assert!(services.ranging() && services.corrections() && services.integrity());
+        assert_eq!(services.summary(), "Ranging, Corrections, Integrity");
+        assert_eq!(Udre::from_indicator(3), Udre::Bound(1.75));
+        assert!(!Udre::from_indicator(15).usable());
+    }
+}
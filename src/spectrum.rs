@@ -0,0 +1,129 @@
+use crate::ubx::SpanBlock;
+
+/// GNSS signal bands marked on the spectrum plot, as (label, centre frequency in Hz).
+pub const BAND_MARKERS: [(&str, f64); 9] = [
+    ("L1/E1/B1C", 1_575.42e6),
+    ("B1I", 1_561.098e6),
+    ("G1", 1_602.0e6),
+    ("L2", 1_227.60e6),
+    ("G2", 1_246.0e6),
+    ("E5b/B2I", 1_207.14e6),
+    ("L5/E5a", 1_176.45e6),
+    ("E6", 1_278.75e6),
+    ("B3I", 1_268.52e6),
+];
+
+/// The live, max-hold and average spectrum of one RF block.
+#[derive(Debug, Clone)]
+pub struct SpectrumTrace {
+    pub center_hz: f64,
+    pub span_hz: f64,
+    pub res_hz: f64,
+    pub pga_db: u8,
+    /// Power in dB per bin.
+    pub live: Vec<f64>,
+    pub max_hold: Vec<f64>,
+    pub average: Vec<f64>,
+    /// Number of sweeps in the average.
+    pub sweeps: u32,
+}
+
+impl SpectrumTrace {
+    pub fn new(block: &SpanBlock) -> Self {
+        let live = block.spectrum_db();
+        Self {
+            center_hz: block.center_hz as f64,
+            span_hz: block.span_hz as f64,
+            res_hz: block.res_hz as f64,
+            pga_db: block.pga_db,
+            max_hold: live.clone(),
+            average: live.clone(),
+            live,
+            sweeps: 1,
+        }
+    }
+
+    /// Adds a sweep, starting over if the receiver changed the frequency plan.
+    pub fn update(&mut self, block: &SpanBlock) {
+        if block.center_hz as f64 != self.center_hz || block.span_hz as f64 != self.span_hz {
+            *self = Self::new(block);
+            return;
+        }
+        self.pga_db = block.pga_db;
+        self.live = block.spectrum_db();
+        self.sweeps += 1;
+        let n = self.sweeps as f64;
+        for ((max, avg), live) in self
+            .max_hold
+            .iter_mut()
+            .zip(self.average.iter_mut())
+            .zip(&self.live)
+        {
+            *max = max.max(*live);
+            *avg += (live - *avg) / n;
+        }
+    }
+
+    /// Clears the max-hold and average traces.
+    pub fn reset(&mut self) {
+        self.max_hold = self.live.clone();
+        self.average = self.live.clone();
+        self.sweeps = 1;
+    }
+
+    /// Frequency in Hz of each bin.
+    pub fn frequencies(&self) -> Vec<f64> {
+        let n = self.live.len() as f64;
+        (0..self.live.len())
+            .map(|i| self.center_hz + self.span_hz * (i as f64 - n / 2.0) / n)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(levels: &[u8], center_hz: u32, span_hz: u32) -> SpanBlock {
+        SpanBlock {
+            spectrum: levels.to_vec(),
+            span_hz,
+            res_hz: span_hz / levels.len() as u32,
+            center_hz,
+            pga_db: 54,
+        }
+    }
+
+    #[test]
+    fn holds_maximum_and_average() {
+        let mut trace = SpectrumTrace::new(&block(&[40, 80], 1_575_000_000, 2_000_000));
+        trace.update(&block(&[80, 40], 1_575_000_000, 2_000_000));
+        trace.update(&block(&[60, 60], 1_575_000_000, 2_000_000));
+        assert_eq!(trace.sweeps, 3);
+        assert_eq!(trace.live, [15.0, 15.0]);
+        assert_eq!(trace.max_hold, [20.0, 20.0]);
+        assert_eq!(trace.average, [15.0, 15.0]);
+        assert_eq!(trace.frequencies(), [1_574_000_000.0, 1_575_000_000.0]);
+
+        trace.reset();
+        assert_eq!(
+            (trace.max_hold.clone(), trace.sweeps),
+            (vec![15.0, 15.0], 1)
+        );
+    }
+
+    #[test]
+    fn starts_over_when_the_frequency_plan_changes() {
+        let mut trace = SpectrumTrace::new(&block(&[80, 80], 1_575_000_000, 2_000_000));
+        trace.update(&block(&[40, 40], 1_575_000_000, 2_000_000));
+        assert_eq!(trace.sweeps, 2);
+
+        trace.update(&block(&[40, 40], 1_227_000_000, 2_000_000));
+        assert_eq!((trace.center_hz, trace.sweeps), (1_227_000_000.0, 1));
+        assert_eq!(trace.max_hold, [10.0, 10.0]);
+
+        trace.update(&block(&[48, 48], 1_227_000_000, 4_000_000));
+        assert_eq!((trace.span_hz, trace.sweeps), (4_000_000.0, 1));
+        assert_eq!(trace.average, [12.0, 12.0]);
+    }
+}
@@ -0,0 +1,448 @@
+use std::collections::BTreeMap;
+
+use crate::ephemeris::{NavigationData, EARTH_ROTATION, FREQ_B1I, FREQ_L1, SPEED_OF_LIGHT};
+use crate::geodesy;
+use crate::gnss::{Constellation, SatelliteId};
+use crate::time_scales::SECONDS_PER_WEEK;
+use crate::ubx::RxmRawx;
+
+/// Satellites below this elevation in degrees are not used.
+pub const ELEVATION_MASK: f64 = 10.0;
+/// Pseudorange error in metres at zenith, growing with 1 / sin(elevation).
+const ZENITH_SIGMA: f64 = 0.3;
+const MAX_ITERATIONS: usize = 10;
+
+/// A single frequency pseudorange.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Observation {
+    pub id: SatelliteId,
+    /// Metres.
+    pub pseudorange: f64,
+    pub cno: u8,
+}
+
+/// The GPS L1C/A, Galileo E1 and BeiDou B1I pseudoranges of a RXM-RAWX epoch, one per
+/// satellite.
+pub fn observations(raw: &RxmRawx) -> Vec<Observation> {
+    let mut observations: Vec<Observation> = Vec::new();
+    for m in raw.measurements.iter().filter(|m| m.pr_valid()) {
+        let primary = match m.id.constellation {
+            Constellation::Gps => m.sig_id == 0,
+            Constellation::Galileo | Constellation::BeiDou => m.sig_id <= 1,
+            _ => false,
+        };
+        if primary && !observations.iter().any(|o| o.id == m.id) {
+            observations.push(Observation {
+                id: m.id,
+                pseudorange: m.pseudorange,
+                cno: m.cno,
+            });
+        }
+    }
+    observations
+}
+
+/// Saastamoinen tropospheric delay in metres with a standard atmosphere and 70% humidity, for
+/// a receiver at `latitude` (degrees) and `height` (metres) seeing a satellite at `elevation`
+/// (degrees).
+pub fn saastamoinen(latitude: f64, height: f64, elevation: f64) -> f64 {
+    if !(-100.0..=10_000.0).contains(&height) || elevation <= 0.0 {
+        return 0.0;
+    }
+    let h = height.max(0.0);
+    let pressure = 1013.25 * (1.0 - 2.2557e-5 * h).powf(5.2568);
+    let temperature = 15.0 - 6.5e-3 * h + 273.16;
+    let vapour = 6.108 * 0.7 * ((17.15 * temperature - 4684.0) / (temperature - 38.45)).exp();
+    let cos_z = elevation.to_radians().sin();
+    let hydrostatic = 0.0022768 * pressure
+        / (1.0 - 0.00266 * (2.0 * latitude.to_radians()).cos() - 0.00028 * h / 1e3)
+        / cos_z;
+    let wet = 0.002277 * (1255.0 / temperature + 0.05) * vapour / cos_z;
+    hydrostatic + wet
+}
+
+/// A satellite used in a solution.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SppSatellite {
+    pub id: SatelliteId,
+    /// Degrees.
+    pub azimuth: f64,
+    pub elevation: f64,
+    /// Post-fit pseudorange residual in metres.
+    pub residual: f64,
+    /// Modelled ionospheric and tropospheric delays in metres.
+    pub iono: f64,
+    pub tropo: f64,
+    /// Standard deviation the pseudorange was weighted with, in metres.
+    pub sigma: f64,
+    /// Unit vector from the receiver to the satellite, ECEF.
+    pub line_of_sight: [f64; 3],
+}
+
+/// A weighted least-squares single point position.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SppSolution {
+    /// Receiver time of the epoch in seconds since the GPS epoch.
+    pub time: f64,
+    pub ecef: [f64; 3],
+    /// Degrees and metres above the WGS84 ellipsoid.
+    pub latitude: f64,
+    pub longitude: f64,
+    pub height: f64,
+    /// Receiver clock bias in metres against the time of each constellation used.
+    pub clock: BTreeMap<Constellation, f64>,
+    pub satellites: Vec<SppSatellite>,
+    pub iterations: usize,
+    /// Sum of the squared residuals divided by their variances.
+    pub chi_square: f64,
+    /// Measurements minus unknowns.
+    pub degrees_of_freedom: usize,
+    pub pdop: f64,
+}
+
+/// Inverts a small symmetric matrix by Gauss-Jordan elimination, `None` if it is singular.
+pub(crate) fn invert(mut m: Vec<Vec<f64>>) -> Option<Vec<Vec<f64>>> {
+    let n = m.len();
+    let mut inverse: Vec<Vec<f64>> = (0..n)
+        .map(|i| (0..n).map(|j| if i == j { 1.0 } else { 0.0 }).collect())
+        .collect();
+    for col in 0..n {
+        let pivot = (col..n).max_by(|&a, &b| m[a][col].abs().total_cmp(&m[b][col].abs()))?;
+        if m[pivot][col].abs() < 1e-12 {
+            return None;
+        }
+        m.swap(col, pivot);
+        inverse.swap(col, pivot);
+        let p = m[col][col];
+        for j in 0..n {
+            m[col][j] /= p;
+            inverse[col][j] /= p;
+        }
+        for row in (0..n).filter(|&r| r != col) {
+            let f = m[row][col];
+            for j in 0..n {
+                m[row][j] -= f * m[col][j];
+                inverse[row][j] -= f * inverse[col][j];
+            }
+        }
+    }
+    Some(inverse)
+}
+
+/// `(Hᵀ W H)⁻¹` of a design matrix with weights.
+pub(crate) fn normal_inverse(h: &[Vec<f64>], w: &[f64]) -> Option<Vec<Vec<f64>>> {
+    let k = h.first()?.len();
+    let n = (0..k)
+        .map(|a| {
+            (0..k)
+                .map(|b| h.iter().zip(w).map(|(row, w)| row[a] * w * row[b]).sum())
+                .collect()
+        })
+        .collect();
+    invert(n)
+}
+
+/// Solves for the receiver position and clocks from pseudoranges taken at receiver time `time`
+/// (seconds since the GPS epoch), starting from `initial` (ECEF) if known. Satellites in
+/// `exclude`, without a valid ephemeris or below the elevation mask are left out. `None` if
+/// there are too few satellites or the solution does not converge.
+pub fn solve(
+    observations: &[Observation],
+    time: f64,
+    nav: &NavigationData,
+    initial: Option<[f64; 3]>,
+    exclude: &[SatelliteId],
+) -> Option<SppSolution> {
+    // Satellite positions and clocks at transmission, which do not depend on the receiver.
+    let satellites: Vec<_> = observations
+        .iter()
+        .filter(|o| !exclude.contains(&o.id))
+        .filter_map(|o| {
+            let eph = nav.select(o.id, time)?;
+            let mut t = time - o.pseudorange / SPEED_OF_LIGHT;
+            t -= eph.clock(t);
+            let clock = eph.clock(t);
+            Some((o, eph.position(t), clock, eph.accuracy))
+        })
+        .collect();
+
+    let mut x = initial.unwrap_or([0.0; 3]);
+    let mut clock: BTreeMap<Constellation, f64> = BTreeMap::new();
+    for iteration in 1..=MAX_ITERATIONS {
+        let known = x.iter().map(|v| v * v).sum::<f64>().sqrt() > 6.0e6;
+        let (latitude, longitude, height) = geodesy::ecef_to_geodetic(x);
+        let tow = time.rem_euclid(SECONDS_PER_WEEK as f64);
+
+        let mut rows = Vec::new();
+        for &(obs, position, sat_clock, accuracy) in &satellites {
+            // Earth rotation during the signal travel time.
+            let travel = (0..3)
+                .map(|i| (position[i] - x[i]).powi(2))
+                .sum::<f64>()
+                .sqrt()
+                / SPEED_OF_LIGHT;
+            let (s, c) = (EARTH_ROTATION * travel).sin_cos();
+            let p = [
+                c * position[0] + s * position[1],
+                -s * position[0] + c * position[1],
+                position[2],
+            ];
+            let d = [p[0] - x[0], p[1] - x[1], p[2] - x[2]];
+            let range = (d[0] * d[0] + d[1] * d[1] + d[2] * d[2]).sqrt();
+            let los = d.map(|v| v / range);
+
+            let (mut azimuth, mut elevation, mut iono, mut tropo) = (0.0, 90.0, 0.0, 0.0);
+            if known {
+                let [e, n, u] = geodesy::ecef_to_enu(p, (latitude, longitude, height));
+                azimuth = e.atan2(n).to_degrees().rem_euclid(360.0);
+                elevation = u.atan2(e.hypot(n)).to_degrees();
+                if elevation < ELEVATION_MASK {
+                    continue;
+                }
+                iono = match (obs.id.constellation, nav.bds_klobuchar, nav.klobuchar) {
+                    (Constellation::BeiDou, Some(model), _) => {
+                        model.bds_delay(latitude, longitude, azimuth, elevation, tow)
+                    }
+                    // The GPS model scaled from L1 to B1I until BeiDou's own is decoded.
+                    (Constellation::BeiDou, None, Some(model)) => {
+                        (FREQ_L1 / FREQ_B1I).powi(2)
+                            * model.delay(latitude, longitude, azimuth, elevation, tow)
+                    }
+                    (_, _, Some(model)) => {
+                        model.delay(latitude, longitude, azimuth, elevation, tow)
+                    }
+                    _ => 0.0,
+                };
+                tropo = saastamoinen(latitude, height, elevation);
+            }
+            let sin_el = elevation.to_radians().sin();
+            let sigma =
+                (ZENITH_SIGMA.powi(2) * (1.0 + 1.0 / (sin_el * sin_el)) + accuracy.powi(2)).sqrt();
+            let bias = clock.get(&obs.id.constellation).copied().unwrap_or(0.0);
+            let predicted = range + bias - SPEED_OF_LIGHT * sat_clock + iono + tropo;
+            rows.push(SppSatellite {
+                id: obs.id,
+                azimuth,
+                elevation,
+                residual: obs.pseudorange - predicted,
+                iono,
+                tropo,
+                sigma,
+                line_of_sight: los,
+            });
+        }
+
+        let systems: Vec<Constellation> = {
+            let mut s: Vec<_> = rows.iter().map(|r| r.id.constellation).collect();
+            s.sort();
+            s.dedup();
+            s
+        };
+        let unknowns = 3 + systems.len();
+        if rows.len() < unknowns {
+            return None;
+        }
+        let design: Vec<Vec<f64>> = rows.iter().map(|r| design_row(r, &systems)).collect();
+        let weights: Vec<f64> = rows.iter().map(|r| 1.0 / (r.sigma * r.sigma)).collect();
+        let q = normal_inverse(&design, &weights)?;
+        let b: Vec<f64> = (0..unknowns)
+            .map(|a| {
+                design
+                    .iter()
+                    .zip(&weights)
+                    .zip(&rows)
+                    .map(|((row, w), r)| row[a] * w * r.residual)
+                    .sum()
+            })
+            .collect();
+        let dx: Vec<f64> = q
+            .iter()
+            .map(|row| row.iter().zip(&b).map(|(q, b)| q * b).sum())
+            .collect();
+        for i in 0..3 {
+            x[i] += dx[i];
+        }
+        let previous = std::mem::take(&mut clock);
+        for (i, system) in systems.iter().enumerate() {
+            clock.insert(
+                *system,
+                previous.get(system).copied().unwrap_or(0.0) + dx[3 + i],
+            );
+        }
+
+        let step = dx[..3].iter().map(|v| v * v).sum::<f64>().sqrt();
+        if step < 1e-4 && known {
+            // The residuals are of the previous estimate, which the last step barely moved.
+            for (r, row) in rows.iter_mut().zip(&design) {
+                r.residual -= row.iter().zip(&dx).map(|(h, d)| h * d).sum::<f64>();
+            }
+            let chi_square = rows.iter().map(|r| (r.residual / r.sigma).powi(2)).sum();
+            let dop = normal_inverse(&design, &vec![1.0; rows.len()])?;
+            let (latitude, longitude, height) = geodesy::ecef_to_geodetic(x);
+            return Some(SppSolution {
+                time,
+                ecef: x,
+                latitude,
+                longitude,
+                height,
+                clock,
+                degrees_of_freedom: rows.len() - unknowns,
+                satellites: rows,
+                iterations: iteration,
+                chi_square,
+                pdop: (dop[0][0] + dop[1][1] + dop[2][2]).sqrt(),
+            });
+        }
+    }
+    None
+}
+
+/// Row of the design matrix: the negated line of sight and a one for the satellite's clock.
+pub(crate) fn design_row(satellite: &SppSatellite, systems: &[Constellation]) -> Vec<f64> {
+    let mut row: Vec<f64> = satellite.line_of_sight.iter().map(|v| -v).collect();
+    row.extend(
+        systems
+            .iter()
+            .map(|s| (*s == satellite.id.constellation) as u8 as f64),
+    );
+    row
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::ephemeris::{Ephemeris, Klobuchar};
+
+    // Simulates pseudoranges from a constellation built around one broadcast orbit.
+    pub(crate) fn simulate(
+        receiver: [f64; 3],
+        time: f64,
+        clocks: &[(Constellation, f64)],
+    ) -> (NavigationData, Vec<Observation>) {
+        let template = Ephemeris {
+            id: SatelliteId::new(Constellation::Gps, 1),
+            toc: time - 600.0,
+            toe: time - 600.0,
+            af0: -1.5e-4,
+            af1: -1.1e-12,
+            af2: 0.0,
+            iode: 1,
+            iodc: 1,
+            sqrt_a: 5153.69,
+            e: 0.0056,
+            i0: 0.979,
+            omega0: 0.0,
+            omega: 1.096,
+            m0: 0.0,
+            delta_n: 4.0e-9,
+            omega_dot: -7.97e-9,
+            idot: 1.27e-10,
+            cuc: -6.0e-6,
+            cus: 6.4e-6,
+            crc: 250.8,
+            crs: -113.7,
+            cic: 5.0e-8,
+            cis: 7.5e-8,
+            health: 0,
+            tgd: -1.0e-8,
+            accuracy: 2.0,
+        };
+        let mut nav = NavigationData {
+            klobuchar: Some(Klobuchar {
+                alpha: [1.1e-8, 7.5e-9, -6.0e-8, -6.0e-8],
+                beta: [9.0e4, 4.9e4, -1.3e5, -3.3e5],
+            }),
+            ..Default::default()
+        };
+        let (latitude, longitude, height) = geodesy::ecef_to_geodetic(receiver);
+        let mut observations = Vec::new();
+        for &(constellation, bias) in clocks {
+            for plane in 0..6 {
+                for slot in 0..5 {
+                    let mut eph = template.clone();
+                    eph.id = SatelliteId::new(constellation, plane * 5 + slot + 1);
+                    eph.omega0 = plane as f64 * 1.047 + 0.3 * (constellation as u8) as f64;
+                    eph.m0 = slot as f64 * 1.256 + plane as f64 * 0.4;
+                    // True transmission time and signal travel time.
+                    let mut travel = 0.07;
+                    let (mut p, mut t) = ([0.0; 3], time);
+                    for _ in 0..5 {
+                        t = time - travel;
+                        let q = eph.position(t);
+                        let (s, c) = (EARTH_ROTATION * travel).sin_cos();
+                        p = [c * q[0] + s * q[1], -s * q[0] + c * q[1], q[2]];
+                        travel = (0..3)
+                            .map(|i| (p[i] - receiver[i]).powi(2))
+                            .sum::<f64>()
+                            .sqrt()
+                            / SPEED_OF_LIGHT;
+                    }
+                    let [e, n, u] = geodesy::ecef_to_enu(p, (latitude, longitude, height));
+                    let elevation = u.atan2(e.hypot(n)).to_degrees();
+                    if elevation < 15.0 {
+                        continue;
+                    }
+                    let azimuth = e.atan2(n).to_degrees().rem_euclid(360.0);
+                    let tow = (time + bias / SPEED_OF_LIGHT).rem_euclid(SECONDS_PER_WEEK as f64);
+                    let iono = nav
+                        .klobuchar
+                        .unwrap()
+                        .delay(latitude, longitude, azimuth, elevation, tow);
+                    observations.push(Observation {
+                        id: eph.id,
+                        pseudorange: SPEED_OF_LIGHT * (travel - eph.clock(t))
+                            + bias
+                            + iono
+                            + saastamoinen(latitude, height, elevation),
+                        cno: 45,
+                    });
+                    nav.insert(eph);
+                }
+            }
+        }
+        (nav, observations)
+    }
+
+    #[test]
+    fn solves_simulated_epoch() {
+        let receiver = geodesy::geodetic_to_ecef(52.0, 5.0, 50.0);
+        let time = 2297.0 * SECONDS_PER_WEEK as f64 + 200_000.0;
+        let clocks = [
+            (Constellation::Gps, 30_000.0),
+            (Constellation::Galileo, 30_010.0),
+        ];
+        let (nav, observations) = simulate(receiver, time, &clocks);
+        assert!(observations.len() >= 8, "{}", observations.len());
+
+        // The receiver time tag is off by the GPS clock bias.
+        let tagged = time + clocks[0].1 / SPEED_OF_LIGHT;
+        let solution = solve(&observations, tagged, &nav, None, &[]).unwrap();
+        let error = (0..3)
+            .map(|i| (solution.ecef[i] - receiver[i]).powi(2))
+            .sum::<f64>()
+            .sqrt();
+        assert!(error < 0.01, "{} m", error);
+        assert!((solution.clock[&Constellation::Galileo] - 30_010.0).abs() < 0.01);
+        assert!(solution.satellites.iter().all(|s| s.residual.abs() < 0.01));
+        assert!(solution
+            .satellites
+            .iter()
+            .all(|s| s.iono > 1.0 && s.tropo > 2.0));
+        assert_eq!(solution.degrees_of_freedom, solution.satellites.len() - 5);
+
+        // Leaving out satellites still solves, until there are too few.
+        let ids: Vec<_> = observations.iter().map(|o| o.id).collect();
+        assert!(solve(&observations, tagged, &nav, None, &ids[..2]).is_some());
+        assert!(solve(&observations, tagged, &nav, None, &ids[..ids.len() - 3]).is_none());
+    }
+
+    #[test]
+    fn tropospheric_delay() {
+        // About 2.4 m at zenith at sea level, roughly 1 / sin(elevation) lower down.
+        let zenith = saastamoinen(45.0, 0.0, 90.0);
+        assert!((2.3..2.6).contains(&zenith), "{}", zenith);
+        let low = saastamoinen(45.0, 0.0, 10.0);
+        assert!((low / zenith - 1.0 / 10f64.to_radians().sin()).abs() < 0.01);
+        assert_eq!(saastamoinen(45.0, 0.0, -1.0), 0.0);
+    }
+}
@@ -0,0 +1,145 @@
+/// Frequency stability statistics at one averaging time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StabilityPoint {
+    /// Averaging time in seconds.
+    pub tau: f64,
+    /// Overlapping Allan deviation (dimensionless).
+    pub adev: f64,
+    /// Time deviation in seconds.
+    pub tdev: f64,
+    /// Number of second differences averaged for the Allan deviation.
+    pub samples: usize,
+}
+
+/// Removes whole-millisecond steps from a clock bias series, as produced by receivers that
+/// keep their clock within ±0.5 ms of GNSS time.
+pub fn remove_millisecond_jumps(phase: &[f64]) -> Vec<f64> {
+    let mut correction = 0.0;
+    let mut out = Vec::with_capacity(phase.len());
+    for (i, x) in phase.iter().enumerate() {
+        if i > 0 {
+            let step = x - phase[i - 1];
+            if step.abs() > 0.5e-3 {
+                correction -= (step * 1e3).round() * 1e-3;
+            }
+        }
+        out.push(x + correction);
+    }
+    out
+}
+
+// Second differences of phase at an averaging factor m.
+fn second_differences(phase: &[f64], m: usize) -> Vec<f64> {
+    (0..phase.len() - 2 * m)
+        .map(|i| phase[i + 2 * m] - 2.0 * phase[i + m] + phase[i])
+        .collect()
+}
+
+/// Overlapping Allan deviation from phase samples (seconds) taken every `tau0` seconds.
+pub fn overlapping_adev(phase: &[f64], tau0: f64, m: usize) -> Option<f64> {
+    if m == 0 || phase.len() < 2 * m + 1 {
+        return None;
+    }
+    let tau = m as f64 * tau0;
+    let d = second_differences(phase, m);
+    let sum: f64 = d.iter().map(|v| v * v).sum();
+    Some((sum / (2.0 * tau * tau * d.len() as f64)).sqrt())
+}
+
+/// Modified Allan deviation from phase samples (seconds) taken every `tau0` seconds.
+pub fn modified_adev(phase: &[f64], tau0: f64, m: usize) -> Option<f64> {
+    if m == 0 || phase.len() < 3 * m {
+        return None;
+    }
+    let tau = m as f64 * tau0;
+    let d = second_differences(phase, m);
+
+    // Sliding sums of m consecutive second differences.
+    let mut window: f64 = d[..m].iter().sum();
+    let mut sum = window * window;
+    let count = phase.len() - 3 * m + 1;
+    for j in 1..count {
+        window += d[j + m - 1] - d[j - 1];
+        sum += window * window;
+    }
+    Some((sum / (2.0 * (m * m) as f64 * tau * tau * count as f64)).sqrt())
+}
+
+/// Time deviation from phase samples (seconds) taken every `tau0` seconds.
+pub fn tdev(phase: &[f64], tau0: f64, m: usize) -> Option<f64> {
+    let tau = m as f64 * tau0;
+    modified_adev(phase, tau0, m).map(|mdev| tau / 3f64.sqrt() * mdev)
+}
+
+/// ADEV and TDEV at octave-spaced averaging times, as far as the data allows.
+pub fn stability_curve(phase: &[f64], tau0: f64) -> Vec<StabilityPoint> {
+    let mut points = Vec::new();
+    let mut m = 1;
+    while phase.len() > 3 * m {
+        if let (Some(adev), Some(tdev)) = (overlapping_adev(phase, tau0, m), tdev(phase, tau0, m)) {
+            points.push(StabilityPoint {
+                tau: m as f64 * tau0,
+                adev,
+                tdev,
+                samples: phase.len() - 2 * m,
+            });
+        }
+        m *= 2;
+    }
+    points
+}
+
+/// Formats a stability curve as CSV with a header row.
+pub fn to_csv(points: &[StabilityPoint]) -> String {
+    let mut csv = String::from("tau_s,adev,tdev_s,samples\n");
+    for p in points {
+        csv.push_str(&format!(
+            "{},{:e},{:e},{}\n",
+            p.tau, p.adev, p.tdev, p.samples
+        ));
+    }
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn constant_frequency_offset_is_stable() {
+        let phase: Vec<f64> = (0..100).map(|i| 1e-9 * i as f64).collect();
+        for p in stability_curve(&phase, 1.0) {
+            assert!(p.adev < 1e-20);
+            assert!(p.tdev < 1e-20);
+        }
+    }
+
+    #[test]
+    fn linear_drift() {
+        // x(t) = D t^2 / 2 has ADEV = D tau / sqrt(2) at every tau.
+        let drift = 1e-10;
+        let phase: Vec<f64> = (0..1000)
+            .map(|i| 0.5 * drift * (i as f64).powi(2))
+            .collect();
+        for m in [1, 4, 32] {
+            let adev = overlapping_adev(&phase, 1.0, m).unwrap();
+            let expected = drift * m as f64 / 2f64.sqrt();
+            assert!((adev - expected).abs() / expected < 1e-6);
+        }
+    }
+
+    #[test]
+    fn millisecond_jumps() {
+        let phase = [0.0, 1e-9, 1e-3 + 2e-9, 1e-3 + 3e-9, 4e-9];
+        let fixed = remove_millisecond_jumps(&phase);
+        for (i, x) in fixed.iter().enumerate() {
+            assert!((x - i as f64 * 1e-9).abs() < 1e-15);
+        }
+    }
+
+    #[test]
+    fn not_enough_data() {
+        assert_eq!(overlapping_adev(&[0.0, 1.0], 1.0, 1), None);
+        assert!(stability_curve(&[0.0, 1.0, 2.0], 1.0).is_empty());
+    }
+}
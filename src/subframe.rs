@@ -0,0 +1,891 @@
+use std::collections::BTreeMap;
+use std::f64::consts::PI;
+
+use crate::ephemeris::{Almanac, Ephemeris, Klobuchar, NavigationData, NeQuick, UtcParameters};
+use crate::gnss::{Constellation, SatelliteId};
+use crate::time_scales::{BDT_WEEK_OFFSET, GPS_BDT_OFFSET, SECONDS_PER_DAY, SECONDS_PER_WEEK};
+use crate::ubx::RxmSfrbx;
+
+const WEEK: f64 = SECONDS_PER_WEEK as f64;
+const DAY: f64 = SECONDS_PER_DAY as f64;
+const BDT_OFFSET: f64 = GPS_BDT_OFFSET as f64;
+
+fn p2(n: i32) -> f64 {
+    2f64.powi(n)
+}
+
+/// A bit string, most significant bit first, as the navigation message fields are laid out.
+#[derive(Debug, Clone, Default)]
+struct Bits {
+    bytes: Vec<u8>,
+    len: usize,
+}
+
+impl Bits {
+    /// Appends the low `width` bits of `value`.
+    fn push(&mut self, value: u64, width: usize) {
+        for i in (0..width).rev() {
+            if self.len % 8 == 0 {
+                self.bytes.push(0);
+            }
+            if (value >> i) & 1 == 1 {
+                self.bytes[self.len / 8] |= 0x80 >> (self.len % 8);
+            }
+            self.len += 1;
+        }
+    }
+
+    fn u(&self, start: usize, width: usize) -> u64 {
+        (start..start + width).fold(0, |acc, i| {
+            acc << 1 | ((self.bytes[i / 8] >> (7 - i % 8)) & 1) as u64
+        })
+    }
+
+    fn s(&self, start: usize, width: usize) -> i64 {
+        let shift = 64 - width;
+        ((self.u(start, width) << shift) as i64) >> shift
+    }
+
+    fn unsigned(&self, start: usize, width: usize, scale: f64) -> f64 {
+        self.u(start, width) as f64 * scale
+    }
+
+    fn signed(&self, start: usize, width: usize, scale: f64) -> f64 {
+        self.s(start, width) as f64 * scale
+    }
+
+    /// A field split over two places, the most significant part first.
+    fn u2(&self, a: usize, a_width: usize, b: usize, b_width: usize) -> u64 {
+        self.u(a, a_width) << b_width | self.u(b, b_width)
+    }
+
+    fn unsigned2(&self, a: usize, a_width: usize, b: usize, b_width: usize, scale: f64) -> f64 {
+        self.u2(a, a_width, b, b_width) as f64 * scale
+    }
+
+    fn signed2(&self, a: usize, a_width: usize, b: usize, b_width: usize, scale: f64) -> f64 {
+        let shift = 64 - a_width - b_width;
+        (((self.u2(a, a_width, b, b_width) << shift) as i64) >> shift) as f64 * scale
+    }
+}
+
+/// Seconds since the GPS epoch of the time `sow` seconds into the week closest to
+/// `reference`.
+fn nearest(sow: f64, reference: f64) -> f64 {
+    let t = (reference / WEEK).floor() * WEEK + sow;
+    t - ((t - reference) / WEEK).round() * WEEK
+}
+
+/// The GPS week number whose low bits are `week`, closest to `reference`.
+fn nearest_week(week: u64, modulus: i64, reference: f64) -> i64 {
+    let current = (reference / WEEK).floor() as i64;
+    let below = current - (current - week as i64).rem_euclid(modulus);
+    match current - below > modulus / 2 {
+        true => below + modulus,
+        false => below,
+    }
+}
+
+/// Accuracy in metres of a GPS URA or BeiDou URAI index.
+fn ura(index: u64) -> f64 {
+    const URA: [f64; 15] = [
+        2.4, 3.4, 4.85, 6.85, 9.65, 13.65, 24.0, 48.0, 96.0, 192.0, 384.0, 768.0, 1536.0, 3072.0,
+        6144.0,
+    ];
+    URA.get(index as usize).copied().unwrap_or(6144.0)
+}
+
+/// Accuracy in metres of a Galileo SISA index; no accuracy prediction counts as the worst.
+fn sisa(index: u64) -> f64 {
+    let n = index as f64;
+    match index {
+        0..=49 => n * 0.01,
+        50..=74 => 0.5 + (n - 50.0) * 0.02,
+        75..=99 => 1.0 + (n - 75.0) * 0.04,
+        100..=125 => 2.0 + (n - 100.0) * 0.16,
+        _ => 6144.0,
+    }
+}
+
+/// Assembles navigation data from the UBX-RXM-SFRBX subframes of GPS LNAV, Galileo I/NAV and
+/// BeiDou D1 messages.
+///
+/// The receiver only passes on data words that passed their parity or CRC check, so they
+/// are not checked again. Ephemerides are only built from subframes of one issue of data.
+#[derive(Debug, Default)]
+pub struct SubframeDecoder {
+    /// Latest ephemeris subframes 1 to 3 of each GPS and BeiDou satellite.
+    subframes: BTreeMap<SatelliteId, [Option<Bits>; 3]>,
+    /// Latest I/NAV data word of each type of each Galileo satellite.
+    words: BTreeMap<SatelliteId, BTreeMap<u64, Bits>>,
+}
+
+impl SubframeDecoder {
+    /// Decodes one subframe or page into `nav`. `reference` is the current time in seconds
+    /// since the GPS epoch, which resolves the week of the times of week in the message.
+    pub fn decode(&mut self, frame: &RxmSfrbx, reference: f64, nav: &mut NavigationData) {
+        match frame.id.constellation {
+            // L1 C/A only; L2C and L5 carry CNAV.
+            Constellation::Gps if frame.sig_id == 0 => self.gps(frame, reference, nav),
+            Constellation::Galileo => self.galileo(frame, reference, nav),
+            Constellation::BeiDou => self.beidou(frame, reference, nav),
+            _ => {}
+        }
+    }
+
+    fn gps(&mut self, frame: &RxmSfrbx, reference: f64, nav: &mut NavigationData) {
+        if frame.words.len() < 10 {
+            return;
+        }
+        // 24 data bits per word, above the six parity bits.
+        let mut sf = Bits::default();
+        for word in &frame.words[..10] {
+            sf.push((*word as u64 >> 6) & 0xFF_FFFF, 24);
+        }
+        match sf.u(43, 3) {
+            id @ 1..=3 => {
+                let slots = self.subframes.entry(frame.id).or_default();
+                slots[id as usize - 1] = Some(sf);
+                if let [Some(sf1), Some(sf2), Some(sf3)] = slots {
+                    if let Some(ephemeris) = gps_ephemeris(frame.id, sf1, sf2, sf3, reference) {
+                        nav.insert(ephemeris);
+                        *slots = Default::default();
+                    }
+                }
+            }
+            4 | 5 => gps_page(&sf, reference, nav),
+            _ => {}
+        }
+    }
+
+    fn galileo(&mut self, frame: &RxmSfrbx, reference: f64, nav: &mut NavigationData) {
+        if frame.words.len() < 8 {
+            return;
+        }
+        // An even and an odd page of 120 bits each, neither of them an alert page.
+        let mut pages = Bits::default();
+        for word in &frame.words[..8] {
+            pages.push(*word as u64, 32);
+        }
+        if pages.u(0, 2) != 0 || pages.u(128, 2) != 2 {
+            return;
+        }
+        // The 128-bit data word: 112 bits of the even page and 16 of the odd one.
+        let mut word = Bits::default();
+        word.push(pages.u(2, 56), 56);
+        word.push(pages.u(58, 56), 56);
+        word.push(pages.u(130, 16), 16);
+        let kind = word.u(0, 6);
+
+        let words = self.words.entry(frame.id).or_default();
+        match kind {
+            5 => {
+                nav.nequick = Some(NeQuick {
+                    ai: [
+                        word.unsigned(6, 11, p2(-2)),
+                        word.signed(17, 11, p2(-8)),
+                        word.signed(28, 14, p2(-15)),
+                    ],
+                });
+            }
+            6 => {
+                let tot_week = nearest_week(word.u(78, 8), 256, reference);
+                let leap_week = nearest_week(word.u(86, 8), 256, reference);
+                nav.utc.insert(
+                    Constellation::Galileo,
+                    UtcParameters {
+                        a0: word.signed(6, 32, p2(-30)),
+                        a1: word.signed(38, 24, p2(-50)),
+                        tot: tot_week as f64 * WEEK + word.unsigned(70, 8, 3600.0),
+                        leap_seconds: word.s(62, 8) as i32,
+                        leap_seconds_future: word.s(97, 8) as i32,
+                        leap_time: leap_week as f64 * WEEK + word.unsigned(94, 3, DAY),
+                    },
+                );
+            }
+            _ => {}
+        }
+        words.insert(kind, word);
+        match kind {
+            1..=5 => {
+                if let Some(ephemeris) = galileo_ephemeris(frame.id, words, reference) {
+                    nav.insert(ephemeris);
+                    words.retain(|kind, _| *kind > 4);
+                }
+            }
+            7..=10 => {
+                for almanac in galileo_almanacs(words, reference) {
+                    nav.almanacs.insert(almanac.id, almanac);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn beidou(&mut self, frame: &RxmSfrbx, reference: f64, nav: &mut NavigationData) {
+        // Geostationary satellites broadcast D2 instead.
+        if frame.words.len() < 10 || !(6..=58).contains(&frame.id.prn) {
+            return;
+        }
+        // 30 bits per word, parity included, so fields sit at their ICD bit positions.
+        let mut sf = Bits::default();
+        for word in &frame.words[..10] {
+            sf.push(*word as u64 & 0x3FFF_FFFF, 30);
+        }
+        match sf.u(15, 3) {
+            id @ 1..=3 => {
+                if id == 1 {
+                    nav.bds_klobuchar = Some(Klobuchar {
+                        alpha: [
+                            sf.signed(126, 8, p2(-30)),
+                            sf.signed(134, 8, p2(-27)),
+                            sf.signed(150, 8, p2(-24)),
+                            sf.signed(158, 8, p2(-24)),
+                        ],
+                        beta: [
+                            sf.signed2(166, 6, 180, 2, p2(11)),
+                            sf.signed(182, 8, p2(14)),
+                            sf.signed(190, 8, p2(16)),
+                            sf.signed2(198, 4, 210, 4, p2(16)),
+                        ],
+                    });
+                }
+                let slots = self.subframes.entry(frame.id).or_default();
+                slots[id as usize - 1] = Some(sf);
+                if let [Some(sf1), Some(sf2), Some(sf3)] = slots {
+                    if let Some(ephemeris) = beidou_ephemeris(frame.id, sf1, sf2, sf3, reference) {
+                        nav.insert(ephemeris);
+                        *slots = Default::default();
+                    }
+                }
+            }
+            id @ 4..=5 => beidou_page(id, &sf, reference, nav),
+            _ => {}
+        }
+    }
+}
+
+/// IS-GPS-200 20.3.3.3 and 20.3.3.4: the clock and ephemeris of subframes 1 to 3, if the
+/// IODE of subframes 2 and 3 and the low bits of the IODC in subframe 1 agree.
+fn gps_ephemeris(
+    id: SatelliteId,
+    sf1: &Bits,
+    sf2: &Bits,
+    sf3: &Bits,
+    reference: f64,
+) -> Option<Ephemeris> {
+    let iodc = sf1.u2(70, 2, 168, 8) as u32;
+    let iode = sf2.u(48, 8) as u32;
+    if sf3.u(216, 8) as u32 != iode || iodc & 0xFF != iode {
+        return None;
+    }
+    Some(Ephemeris {
+        id,
+        toc: nearest(sf1.unsigned(176, 16, 16.0), reference),
+        toe: nearest(sf2.unsigned(216, 16, 16.0), reference),
+        af0: sf1.signed(216, 22, p2(-31)),
+        af1: sf1.signed(200, 16, p2(-43)),
+        af2: sf1.signed(192, 8, p2(-55)),
+        iode,
+        iodc,
+        sqrt_a: sf2.unsigned(184, 32, p2(-19)),
+        e: sf2.unsigned(136, 32, p2(-33)),
+        i0: sf3.signed(112, 32, p2(-31)) * PI,
+        omega0: sf3.signed(64, 32, p2(-31)) * PI,
+        omega: sf3.signed(160, 32, p2(-31)) * PI,
+        m0: sf2.signed(88, 32, p2(-31)) * PI,
+        delta_n: sf2.signed(72, 16, p2(-43)) * PI,
+        omega_dot: sf3.signed(192, 24, p2(-43)) * PI,
+        idot: sf3.signed(224, 14, p2(-43)) * PI,
+        cuc: sf2.signed(120, 16, p2(-29)),
+        cus: sf2.signed(168, 16, p2(-29)),
+        crc: sf3.signed(144, 16, p2(-5)),
+        crs: sf2.signed(56, 16, p2(-5)),
+        cic: sf3.signed(48, 16, p2(-29)),
+        cis: sf3.signed(96, 16, p2(-29)),
+        health: sf1.u(64, 6) as u32,
+        tgd: sf1.signed(160, 8, p2(-31)),
+        accuracy: ura(sf1.u(60, 4)),
+    })
+}
+
+/// IS-GPS-200 20.3.3.5: the almanac pages and the ionosphere and UTC page (page ID 56) of
+/// subframes 4 and 5.
+fn gps_page(sf: &Bits, reference: f64, nav: &mut NavigationData) {
+    if sf.u(48, 2) != 1 {
+        return;
+    }
+    match sf.u(50, 6) {
+        prn @ 1..=32 => {
+            let sqrt_a = sf.unsigned(120, 24, p2(-11));
+            if sqrt_a == 0.0 {
+                return;
+            }
+            let id = SatelliteId::new(Constellation::Gps, prn as u8);
+            let almanac = Almanac {
+                id,
+                toa: nearest(sf.unsigned(72, 8, 4096.0), reference),
+                sqrt_a,
+                e: sf.unsigned(56, 16, p2(-21)),
+                i0: (0.3 + sf.signed(80, 16, p2(-19))) * PI,
+                omega0: sf.signed(144, 24, p2(-23)) * PI,
+                omega: sf.signed(168, 24, p2(-23)) * PI,
+                m0: sf.signed(192, 24, p2(-23)) * PI,
+                omega_dot: sf.signed(96, 16, p2(-38)) * PI,
+                af0: sf.signed2(216, 8, 235, 3, p2(-20)),
+                af1: sf.signed(224, 11, p2(-38)),
+                health: sf.u(112, 8) as u32,
+            };
+            nav.almanacs.insert(id, almanac);
+        }
+        56 => {
+            nav.klobuchar = Some(Klobuchar {
+                alpha: [
+                    sf.signed(56, 8, p2(-30)),
+                    sf.signed(64, 8, p2(-27)),
+                    sf.signed(72, 8, p2(-24)),
+                    sf.signed(80, 8, p2(-24)),
+                ],
+                beta: [
+                    sf.signed(88, 8, p2(11)),
+                    sf.signed(96, 8, p2(14)),
+                    sf.signed(104, 8, p2(16)),
+                    sf.signed(112, 8, p2(16)),
+                ],
+            });
+            let tot_week = nearest_week(sf.u(184, 8), 256, reference);
+            let leap_week = nearest_week(sf.u(200, 8), 256, reference);
+            nav.utc.insert(
+                Constellation::Gps,
+                UtcParameters {
+                    a0: sf.signed(144, 32, p2(-30)),
+                    a1: sf.signed(120, 24, p2(-50)),
+                    tot: tot_week as f64 * WEEK + sf.unsigned(176, 8, 4096.0),
+                    leap_seconds: sf.s(192, 8) as i32,
+                    leap_seconds_future: sf.s(216, 8) as i32,
+                    leap_time: leap_week as f64 * WEEK + sf.unsigned(208, 8, DAY),
+                },
+            );
+        }
+        _ => {}
+    }
+}
+
+/// Galileo OS SIS ICD 4.3.5: the ephemeris of words 1 to 4 of one IODnav, with the health
+/// and group delay of word 5.
+fn galileo_ephemeris(
+    id: SatelliteId,
+    words: &BTreeMap<u64, Bits>,
+    reference: f64,
+) -> Option<Ephemeris> {
+    let [w1, w2, w3, w4, w5] = [1, 2, 3, 4, 5].map(|kind| words.get(&kind));
+    let (w1, w2, w3, w4, w5) = (w1?, w2?, w3?, w4?, w5?);
+    let iod = w1.u(6, 10) as u32;
+    if [w2, w3, w4].iter().any(|w| w.u(6, 10) as u32 != iod) || w4.u(16, 6) != id.prn as u64 {
+        return None;
+    }
+    // Laid out as in RINEX: E1-B DVS and HS, then E5b DVS and HS from bit 6.
+    let health = w5.u(72, 1) | w5.u(69, 2) << 1 | w5.u(71, 1) << 6 | w5.u(67, 2) << 7;
+    Some(Ephemeris {
+        id,
+        toc: nearest(w4.unsigned(54, 14, 60.0), reference),
+        toe: nearest(w1.unsigned(16, 14, 60.0), reference),
+        af0: w4.signed(68, 31, p2(-34)),
+        af1: w4.signed(99, 21, p2(-46)),
+        af2: w4.signed(120, 6, p2(-59)),
+        iode: iod,
+        iodc: iod,
+        sqrt_a: w1.unsigned(94, 32, p2(-19)),
+        e: w1.unsigned(62, 32, p2(-33)),
+        i0: w2.signed(48, 32, p2(-31)) * PI,
+        omega0: w2.signed(16, 32, p2(-31)) * PI,
+        omega: w2.signed(80, 32, p2(-31)) * PI,
+        m0: w1.signed(30, 32, p2(-31)) * PI,
+        delta_n: w3.signed(40, 16, p2(-43)) * PI,
+        omega_dot: w3.signed(16, 24, p2(-43)) * PI,
+        idot: w2.signed(112, 14, p2(-43)) * PI,
+        cuc: w3.signed(56, 16, p2(-29)),
+        cus: w3.signed(72, 16, p2(-29)),
+        crc: w3.signed(88, 16, p2(-5)),
+        crs: w3.signed(104, 16, p2(-5)),
+        cic: w4.signed(22, 16, p2(-29)),
+        cis: w4.signed(38, 16, p2(-29)),
+        health: health as u32,
+        tgd: w5.signed(57, 10, p2(-32)),
+        accuracy: sisa(w3.u(120, 8)),
+    })
+}
+
+/// A Galileo almanac from its raw fields: ΔsqrtA, e, ω, δi, Ω0, Ω̇, M0, af0 and af1, and the
+/// E1-B health.
+fn galileo_almanac(svid: u64, toa: f64, fields: [i64; 9], health: u64) -> Option<Almanac> {
+    if !(1..=36).contains(&svid) {
+        return None;
+    }
+    let [d_sqrt_a, e, omega, di, omega0, omega_dot, m0, af0, af1] = fields.map(|v| v as f64);
+    Some(Almanac {
+        id: SatelliteId::new(Constellation::Galileo, svid as u8),
+        toa,
+        sqrt_a: 29_600e3f64.sqrt() + d_sqrt_a * p2(-9),
+        e: e * p2(-16),
+        i0: 56f64.to_radians() + di * p2(-14) * PI,
+        omega0: omega0 * p2(-15) * PI,
+        omega: omega * p2(-15) * PI,
+        m0: m0 * p2(-15) * PI,
+        omega_dot: omega_dot * p2(-33) * PI,
+        af0: af0 * p2(-19),
+        af1: af1 * p2(-38),
+        health: health as u32,
+    })
+}
+
+/// Galileo OS SIS ICD 4.3.5: words 7 to 10 carry three almanacs, each spread over two
+/// consecutive words of the same IODa.
+fn galileo_almanacs(words: &BTreeMap<u64, Bits>, reference: f64) -> Vec<Almanac> {
+    let pair = |a: u64, b: u64| {
+        let (a, b) = (words.get(&a)?, words.get(&b)?);
+        (a.u(6, 4) == b.u(6, 4)).then_some((a, b))
+    };
+    let toa = |w: &Bits| nearest(w.unsigned(12, 10, 600.0), reference);
+    let mut almanacs = Vec::new();
+    if let Some((w7, w8)) = pair(7, 8) {
+        let fields = [
+            w7.s(28, 13),
+            w7.u(41, 11) as i64,
+            w7.s(52, 16),
+            w7.s(68, 11),
+            w7.s(79, 16),
+            w7.s(95, 11),
+            w7.s(106, 16),
+            w8.s(10, 16),
+            w8.s(26, 13),
+        ];
+        almanacs.extend(galileo_almanac(w7.u(22, 6), toa(w7), fields, w8.u(41, 2)));
+    }
+    if let Some((w8, w9)) = pair(8, 9) {
+        let fields = [
+            w8.s(49, 13),
+            w8.u(62, 11) as i64,
+            w8.s(73, 16),
+            w8.s(89, 11),
+            w8.s(100, 16),
+            w8.s(116, 11),
+            w9.s(22, 16),
+            w9.s(38, 16),
+            w9.s(54, 13),
+        ];
+        almanacs.extend(galileo_almanac(w8.u(43, 6), toa(w9), fields, w9.u(69, 2)));
+    }
+    if let Some((w9, w10)) = pair(9, 10) {
+        let fields = [
+            w9.s(77, 13),
+            w9.u(90, 11) as i64,
+            w9.s(101, 16),
+            w9.s(117, 11),
+            w10.s(10, 16),
+            w10.s(26, 11),
+            w10.s(37, 16),
+            w10.s(53, 16),
+            w10.s(69, 13),
+        ];
+        almanacs.extend(galileo_almanac(w9.u(71, 6), toa(w9), fields, w10.u(84, 2)));
+    }
+    almanacs
+}
+
+/// BDS-SIS-ICD-B1I 5.2.4: the clock and ephemeris of D1 subframes 1 to 3, if they are
+/// consecutive subframes of one frame.
+fn beidou_ephemeris(
+    id: SatelliteId,
+    sf1: &Bits,
+    sf2: &Bits,
+    sf3: &Bits,
+    reference: f64,
+) -> Option<Ephemeris> {
+    let sow = |sf: &Bits| sf.u2(18, 8, 30, 12);
+    if sow(sf2) != sow(sf1) + 6 || sow(sf3) != sow(sf1) + 12 {
+        return None;
+    }
+    let toe = (sf2.u(290, 2) << 15 | sf3.u2(42, 10, 60, 5)) as f64 * 8.0;
+    Some(Ephemeris {
+        id,
+        toc: nearest(sf1.unsigned2(73, 9, 90, 8, 8.0) + BDT_OFFSET, reference),
+        toe: nearest(toe + BDT_OFFSET, reference),
+        af0: sf1.signed2(225, 7, 240, 17, p2(-33)),
+        af1: sf1.signed2(257, 5, 270, 17, p2(-50)),
+        af2: sf1.signed(214, 11, p2(-66)),
+        iode: sf1.u(287, 5) as u32,
+        iodc: sf1.u(43, 5) as u32,
+        sqrt_a: sf2.unsigned2(250, 12, 270, 20, p2(-19)),
+        e: sf2.unsigned2(132, 10, 150, 22, p2(-33)),
+        i0: sf3.signed2(65, 17, 90, 15, p2(-31)) * PI,
+        omega0: sf3.signed2(211, 21, 240, 11, p2(-31)) * PI,
+        omega: sf3.signed2(251, 11, 270, 21, p2(-31)) * PI,
+        m0: sf2.signed2(92, 20, 120, 12, p2(-31)) * PI,
+        delta_n: sf2.signed2(42, 10, 60, 6, p2(-43)) * PI,
+        omega_dot: sf3.signed2(131, 11, 150, 13, p2(-43)) * PI,
+        idot: sf3.signed2(189, 13, 210, 1, p2(-43)) * PI,
+        cuc: sf2.signed2(66, 16, 90, 2, p2(-31)),
+        cus: sf2.signed(180, 18, p2(-31)),
+        crc: sf2.signed2(198, 4, 210, 14, p2(-6)),
+        crs: sf2.signed2(224, 8, 240, 10, p2(-6)),
+        cic: sf3.signed2(105, 7, 120, 11, p2(-31)),
+        cis: sf3.signed2(163, 9, 180, 9, p2(-31)),
+        health: sf1.u(42, 1) as u32,
+        tgd: sf1.signed(98, 10, 1e-10),
+        accuracy: ura(sf1.u(48, 4)),
+    })
+}
+
+/// BDS-SIS-ICD-B1I 5.2.4: the almanac pages of D1 subframes 4 and 5 and the UTC page,
+/// subframe 5 page 10.
+fn beidou_page(subframe: u64, sf: &Bits, reference: f64, nav: &mut NavigationData) {
+    let prn = match (subframe, sf.u(43, 7)) {
+        (4, page @ 1..=24) => page,
+        (5, page @ 1..=6) => page + 24,
+        (5, 10) => {
+            // BeiDou refers the drift to the start of each week of BDT.
+            let week_start = ((reference - BDT_OFFSET) / WEEK).floor() * WEEK + BDT_OFFSET;
+            let bdt_week = (sf.u(74, 8) + BDT_WEEK_OFFSET as u64) % 256;
+            let leap_week = nearest_week(bdt_week, 256, reference - BDT_OFFSET);
+            let day = sf.unsigned(162, 8, DAY) + DAY;
+            nav.utc.insert(
+                Constellation::BeiDou,
+                UtcParameters {
+                    a0: sf.signed2(90, 22, 120, 10, p2(-30)),
+                    a1: sf.signed2(130, 12, 150, 12, p2(-50)),
+                    tot: week_start,
+                    leap_seconds: sf.signed2(50, 2, 60, 6, 1.0) as i32,
+                    leap_seconds_future: sf.s(66, 8) as i32,
+                    leap_time: leap_week as f64 * WEEK + BDT_OFFSET + day,
+                },
+            );
+            return;
+        }
+        _ => return,
+    };
+    let sqrt_a = sf.unsigned2(50, 2, 60, 22, p2(-11));
+    if sqrt_a == 0.0 {
+        return;
+    }
+    let id = SatelliteId::new(Constellation::BeiDou, prn as u8);
+    // Inclinations are relative to 0.3 semicircles, except for geostationary orbits.
+    let inclination = if prn <= 5 { 0.0 } else { 0.3 };
+    let almanac = Almanac {
+        id,
+        toa: nearest(sf.unsigned(193, 8, 4096.0) + BDT_OFFSET, reference),
+        sqrt_a,
+        e: sf.unsigned(152, 17, p2(-21)),
+        i0: (inclination + sf.signed2(169, 3, 180, 13, p2(-19))) * PI,
+        omega0: sf.signed2(120, 22, 150, 2, p2(-23)) * PI,
+        omega: sf.signed2(226, 6, 240, 18, p2(-23)) * PI,
+        m0: sf.signed2(258, 4, 270, 20, p2(-23)) * PI,
+        omega_dot: sf.signed2(201, 1, 210, 16, p2(-38)) * PI,
+        af0: sf.signed(101, 11, p2(-20)),
+        af1: sf.signed(90, 11, p2(-38)),
+        health: 0,
+    };
+    nav.almanacs.insert(id, almanac);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ubx::{UbxFrame, UbxMessage, CLASS_RXM, ID_RXM_SFRBX};
+
+    /// Writes the low `width` bits of `value` at `start`.
+    fn put(bits: &mut Bits, start: usize, width: usize, value: i64) {
+        for i in 0..width {
+            let at = start + i;
+            let mask = 0x80 >> (at % 8);
+            match (value >> (width - 1 - i)) & 1 {
+                1 => bits.bytes[at / 8] |= mask,
+                _ => bits.bytes[at / 8] &= !mask,
+            }
+        }
+    }
+
+    fn zeros(len: usize) -> Bits {
+        Bits {
+            bytes: vec![0; len.div_ceil(8)],
+            len,
+        }
+    }
+
+    /// Puts `value / scale` rounded to an integer.
+    fn put_scaled(bits: &mut Bits, start: usize, width: usize, value: f64, scale: f64) {
+        put(bits, start, width, (value / scale).round() as i64);
+    }
+
+    /// The SFRBX frame of a GPS subframe: 24 data bits per word with zero parity.
+    fn gps_frame(prn: u8, sf: &Bits) -> RxmSfrbx {
+        RxmSfrbx {
+            id: SatelliteId::new(Constellation::Gps, prn),
+            sig_id: 0,
+            freq_id: 0,
+            channel: 0,
+            words: (0..10).map(|i| (sf.u(24 * i, 24) as u32) << 6).collect(),
+        }
+    }
+
+    #[test]
+    fn decodes_gps_lnav() {
+        let reference = 2297.0 * WEEK + 90_000.0;
+        let mut subframes: Vec<Bits> = (1..=3)
+            .map(|id| {
+                let mut sf = zeros(240);
+                put(&mut sf, 43, 3, id);
+                sf
+            })
+            .collect();
+        let [sf1, sf2, sf3] = &mut subframes[..] else {
+            unreachable!()
+        };
+        // IODC 0x131, whose low bits match the IODE 0x31.
+        put(sf1, 70, 2, 1);
+        put(sf1, 168, 8, 0x31);
+        put(sf1, 60, 4, 1);
+        put(sf1, 64, 6, 0);
+        put_scaled(sf1, 160, 8, -1.024e-8, p2(-31));
+        put(sf1, 176, 16, 93_600 / 16);
+        put_scaled(sf1, 216, 22, -1.5724e-4, p2(-31));
+        put(sf2, 48, 8, 0x31);
+        put(sf2, 216, 16, 93_600 / 16);
+        put_scaled(sf2, 184, 32, 5_153.696, p2(-19));
+        put_scaled(sf2, 136, 32, 5.64e-3, p2(-33));
+        put_scaled(sf2, 88, 32, 2.09 / PI, p2(-31));
+        put_scaled(sf3, 112, 32, 0.979 / PI, p2(-31));
+        put_scaled(sf3, 64, 32, -1.062 / PI, p2(-31));
+        put(sf3, 216, 8, 0x30);
+
+        let mut decoder = SubframeDecoder::default();
+        let mut nav = NavigationData::default();
+        let id = SatelliteId::new(Constellation::Gps, 5);
+        for sf in &subframes {
+            decoder.decode(&gps_frame(5, sf), reference, &mut nav);
+        }
+        // Subframe 3 of another issue.
+        assert_eq!(nav.satellites(), 0);
+        put(&mut subframes[2], 216, 8, 0x31);
+        decoder.decode(&gps_frame(5, &subframes[2]), reference, &mut nav);
+        let eph = nav.latest(id).unwrap();
+        assert_eq!((eph.iode, eph.iodc, eph.health), (0x31, 0x131, 0));
+        assert_eq!(eph.toe, 2297.0 * WEEK + 93_600.0);
+        assert_eq!(eph.accuracy, 3.4);
+        assert!((eph.sqrt_a - 5_153.696).abs() < 1e-5);
+        assert!((eph.e - 5.64e-3).abs() < 1e-9);
+        assert!((eph.i0 - 0.979).abs() < 1e-8);
+        assert!((eph.omega0 + 1.062).abs() < 1e-8);
+        assert!((eph.af0 + 1.5724e-4).abs() < 1e-9);
+        assert!((eph.tgd + 1.024e-8).abs() < 1e-9);
+
+        // Subframe 4 page 18, announcing a leap second at the end of day 7 of week 2298.
+        let mut page = zeros(240);
+        put(&mut page, 43, 3, 4);
+        put(&mut page, 48, 2, 1);
+        put(&mut page, 50, 6, 56);
+        put_scaled(&mut page, 56, 8, 1.1176e-8, p2(-30));
+        put_scaled(&mut page, 112, 8, -3.2768e5, p2(16));
+        put_scaled(&mut page, 144, 32, 1.862645e-9, p2(-30));
+        put(&mut page, 176, 8, 405_504 / 4096);
+        put(&mut page, 184, 8, 2297 % 256);
+        put(&mut page, 192, 8, 18);
+        put(&mut page, 200, 8, 2298 % 256);
+        put(&mut page, 208, 8, 7);
+        put(&mut page, 216, 8, 19);
+        decoder.decode(&gps_frame(5, &page), reference, &mut nav);
+        let klobuchar = nav.klobuchar.unwrap();
+        assert!((klobuchar.alpha[0] - 1.1176e-8).abs() < 1e-10);
+        assert_eq!(klobuchar.beta[3], -3.2768e5);
+        let utc = nav.utc[&Constellation::Gps];
+        assert_eq!(utc.tot, 2297.0 * WEEK + 405_504.0);
+        assert_eq!(utc.leap_time, 2299.0 * WEEK);
+        assert!((utc.offset(reference) - 18.0).abs() < 1e-8);
+        assert!((utc.offset(2299.0 * WEEK + 1.0) - 19.0).abs() < 1e-8);
+    }
+
+    #[test]
+    fn decodes_sfrbx_frames() {
+        let reference = 2297.0 * WEEK + 90_000.0;
+        let payload = |sf: &Bits| {
+            // GPS SV 9 on channel 3, ten words after the 8 byte header.
+            let mut p = vec![0, 9, 0, 0, 10, 3, 2, 0];
+            for word in gps_frame(9, sf).words {
+                p.extend_from_slice(&word.to_le_bytes());
+            }
+            p
+        };
+        let mut subframes: Vec<Bits> = (1..=3)
+            .map(|id| {
+                let mut sf = zeros(240);
+                put(&mut sf, 43, 3, id);
+                sf
+            })
+            .collect();
+        put(&mut subframes[0], 168, 8, 0x12);
+        put(&mut subframes[0], 176, 16, 93_600 / 16);
+        put(&mut subframes[1], 48, 8, 0x12);
+        put(&mut subframes[1], 216, 16, 93_600 / 16);
+        put_scaled(&mut subframes[1], 184, 32, 5_153.696, p2(-19));
+        put(&mut subframes[2], 216, 8, 0x12);
+
+        let decode = |p: Vec<u8>| UbxMessage::decode(&UbxFrame::new(CLASS_RXM, ID_RXM_SFRBX, p));
+        // numWords decides the length; a short last word is not read past the end.
+        let mut short = payload(&subframes[0]);
+        short.pop();
+        assert_eq!(decode(short), None);
+        assert_eq!(decode(vec![0; 7]), None);
+
+        let mut decoder = SubframeDecoder::default();
+        let mut nav = NavigationData::default();
+        for sf in &subframes {
+            let Some(UbxMessage::RxmSfrbx(frame)) = decode(payload(sf)) else {
+                panic!("RXM-SFRBX not decoded");
+            };
+            assert_eq!(frame.id, SatelliteId::new(Constellation::Gps, 9));
+            assert_eq!((frame.channel, frame.words.len()), (3, 10));
+            decoder.decode(&frame, reference, &mut nav);
+        }
+        let eph = nav.latest(SatelliteId::new(Constellation::Gps, 9)).unwrap();
+        assert_eq!(eph.iode, 0x12);
+        assert_eq!(eph.toe, 2297.0 * WEEK + 93_600.0);
+        assert!((eph.sqrt_a - 5_153.696).abs() < 1e-5);
+    }
+
+    #[test]
+    fn decodes_galileo_inav() {
+        let reference = 2297.0 * WEEK + 90_000.0;
+        let id = SatelliteId::new(Constellation::Galileo, 11);
+        let word = |kind: i64| {
+            let mut w = zeros(128);
+            put(&mut w, 0, 6, kind);
+            if kind <= 4 {
+                put(&mut w, 6, 10, 77);
+            }
+            w
+        };
+        let mut words: Vec<Bits> = (1..=5).map(word).collect();
+        put(&mut words[0], 16, 14, 93_600 / 60);
+        put_scaled(&mut words[0], 94, 32, 5_440.6, p2(-19));
+        put_scaled(&mut words[1], 48, 32, 0.97 / PI, p2(-31));
+        put(&mut words[2], 120, 8, 107);
+        put(&mut words[3], 16, 6, 11);
+        put(&mut words[3], 54, 14, 93_600 / 60);
+        put_scaled(&mut words[3], 68, 31, 3.2e-4, p2(-34));
+        put_scaled(&mut words[4], 57, 10, 2.3e-9, p2(-32));
+        // E5b signal health "out of service".
+        put(&mut words[4], 67, 2, 1);
+
+        // The pages of an SFRBX frame: even page bits 2..114, odd page from bit 130.
+        let frame = |w: &Bits| {
+            let mut pages = zeros(256);
+            put(&mut pages, 2, 56, w.u(0, 56) as i64);
+            put(&mut pages, 58, 56, w.u(56, 56) as i64);
+            put(&mut pages, 128, 1, 1);
+            put(&mut pages, 130, 16, w.u(112, 16) as i64);
+            RxmSfrbx {
+                id,
+                sig_id: 1,
+                freq_id: 0,
+                channel: 0,
+                words: (0..8).map(|i| pages.u(32 * i, 32) as u32).collect(),
+            }
+        };
+        let mut decoder = SubframeDecoder::default();
+        let mut nav = NavigationData::default();
+        for w in &words {
+            decoder.decode(&frame(w), reference, &mut nav);
+        }
+        let eph = nav.latest(id).unwrap();
+        assert_eq!((eph.iode, eph.toe), (77, 2297.0 * WEEK + 93_600.0));
+        assert_eq!(eph.health, 1 << 7);
+        assert_eq!(eph.accuracy, 2.0 + 7.0 * 0.16);
+        assert!((eph.sqrt_a - 5_440.6).abs() < 1e-5);
+        assert!((eph.i0 - 0.97).abs() < 1e-8);
+        assert!((eph.af0 - 3.2e-4).abs() < 1e-10);
+        assert!((eph.tgd - 2.3e-9).abs() < 1e-10);
+        assert!(nav.select(id, eph.toe).is_none());
+    }
+
+    /// Writes a field split over two places, the most significant part first.
+    fn put2(bits: &mut Bits, a: usize, a_width: usize, b: usize, b_width: usize, value: i64) {
+        put(bits, a, a_width, value >> b_width);
+        put(bits, b, b_width, value);
+    }
+
+    #[test]
+    fn decodes_beidou_d1() {
+        let reference = BDT_OFFSET + 900.0 * WEEK + 90_000.0;
+        let id = SatelliteId::new(Constellation::BeiDou, 21);
+        let sow = 91_200;
+        let mut subframes: Vec<Bits> = (1..=3)
+            .map(|n| {
+                let mut sf = zeros(300);
+                put(&mut sf, 15, 3, n);
+                put2(&mut sf, 18, 8, 30, 12, sow + 6 * (n - 1));
+                sf
+            })
+            .collect();
+        let [sf1, sf2, sf3] = &mut subframes[..] else {
+            unreachable!()
+        };
+        put(sf1, 43, 5, 3);
+        put(sf1, 287, 5, 3);
+        put2(sf1, 73, 9, 90, 8, 93_600 / 8);
+        put2(sf1, 225, 7, 240, 17, (-2.1e-4 / p2(-33)).round() as i64);
+        put(sf1, 98, 10, -23);
+        put_scaled(sf1, 126, 8, 1.49e-8, p2(-30));
+        put2(sf1, 166, 6, 180, 2, (1.2288e5 / p2(11)).round() as i64);
+        put2(sf1, 198, 4, 210, 4, (-1.966e5 / p2(16)).round() as i64);
+        // toe is split over subframes 2 and 3.
+        let toe = 93_600 / 8;
+        put(sf2, 290, 2, toe >> 15);
+        put2(sf3, 42, 10, 60, 5, toe & 0x7FFF);
+        put2(sf2, 250, 12, 270, 20, (5_282.63 / p2(-19)).round() as i64);
+        put2(sf2, 132, 10, 150, 22, (1.87e-3 / p2(-33)).round() as i64);
+        put2(sf3, 65, 17, 90, 15, (-0.961 / PI / p2(-31)).round() as i64);
+
+        let frame = |sf: &Bits| RxmSfrbx {
+            id,
+            sig_id: 0,
+            freq_id: 0,
+            channel: 0,
+            words: (0..10).map(|i| sf.u(30 * i, 30) as u32).collect(),
+        };
+        let mut decoder = SubframeDecoder::default();
+        let mut nav = NavigationData::default();
+        // Subframes 2 and 3 of the previous frame do not complete this one.
+        let mut stale = subframes[2].clone();
+        put2(&mut stale, 18, 8, 30, 12, sow - 18);
+        for sf in [&subframes[1], &stale, &subframes[0]] {
+            decoder.decode(&frame(sf), reference, &mut nav);
+        }
+        assert!(nav.latest(id).is_none());
+        decoder.decode(&frame(&subframes[2]), reference, &mut nav);
+
+        let eph = nav.latest(id).unwrap();
+        let toe = BDT_OFFSET + 900.0 * WEEK + 93_600.0;
+        assert_eq!((eph.toe, eph.toc), (toe, toe));
+        assert_eq!((eph.iode, eph.iodc, eph.accuracy), (3, 3, 2.4));
+        assert!((eph.sqrt_a - 5_282.63).abs() < 1e-5);
+        assert!((eph.e - 1.87e-3).abs() < 1e-9);
+        assert!((eph.i0 + 0.961).abs() < 1e-8);
+        assert!((eph.af0 + 2.1e-4).abs() < 1e-9);
+        assert!((eph.tgd + 2.3e-9).abs() < 1e-12);
+        let klobuchar = nav.bds_klobuchar.unwrap();
+        assert!((klobuchar.alpha[0] - 1.49e-8).abs() < 1e-9);
+        assert_eq!(klobuchar.beta[0], 1.2288e5);
+        assert_eq!(klobuchar.beta[3], -196_608.0);
+    }
+
+    #[test]
+    fn resolves_weeks() {
+        let reference = 2297.0 * WEEK + 600_000.0;
+        // A reference time early in the next week.
+        assert_eq!(nearest(3_600.0, reference), 2298.0 * WEEK + 3_600.0);
+        assert_eq!(nearest(590_000.0, reference), 2297.0 * WEEK + 590_000.0);
+        assert_eq!(nearest_week(2297 % 256, 256, reference), 2297);
+        assert_eq!(nearest_week(2300 % 256, 256, reference), 2300);
+        assert_eq!(nearest_week(2200 % 256, 256, reference), 2200);
+    }
+}
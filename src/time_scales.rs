@@ -0,0 +1,438 @@
+use std::fmt;
+
+pub const SECONDS_PER_DAY: i64 = 86_400;
+pub const SECONDS_PER_WEEK: i64 = 604_800;
+
+/// Unix timestamp of the GPS epoch, 1980-01-06 00:00:00 UTC.
+pub const GPS_EPOCH_UNIX: i64 = 315_964_800;
+/// TAI - GPS, fixed since the GPS epoch.
+pub const TAI_GPS_OFFSET: i64 = 19;
+/// GPS - BDT, fixed since the BeiDou epoch (2006-01-01 00:00:00 UTC).
+pub const GPS_BDT_OFFSET: i64 = 14;
+/// GPS week number of the first Galileo System Time week.
+pub const GST_WEEK_OFFSET: i64 = 1024;
+/// GPS week number of the first BeiDou Time week.
+pub const BDT_WEEK_OFFSET: i64 = 1356;
+/// GLONASS time runs on UTC(SU) + 3 h.
+pub const GLONASS_UTC_OFFSET: i64 = 3 * 3600;
+
+/// GPS - UTC in seconds, keyed by the Unix timestamp (UTC) from which it applies.
+const LEAP_SECONDS: [(i64, i32); 18] = [
+    (362_793_600, 1),    // 1981-07-01
+    (394_329_600, 2),    // 1982-07-01
+    (425_865_600, 3),    // 1983-07-01
+    (489_024_000, 4),    // 1985-07-01
+    (567_993_600, 5),    // 1988-01-01
+    (631_152_000, 6),    // 1990-01-01
+    (662_688_000, 7),    // 1991-01-01
+    (709_948_800, 8),    // 1992-07-01
+    (741_484_800, 9),    // 1993-07-01
+    (773_020_800, 10),   // 1994-07-01
+    (820_454_400, 11),   // 1996-01-01
+    (867_715_200, 12),   // 1997-07-01
+    (915_148_800, 13),   // 1999-01-01
+    (1_136_073_600, 14), // 2006-01-01
+    (1_230_768_000, 15), // 2009-01-01
+    (1_341_100_800, 16), // 2012-07-01
+    (1_435_708_800, 17), // 2015-07-01
+    (1_483_228_800, 18), // 2017-01-01
+];
+
+/// GPS - UTC from the built-in leap second table for a UTC instant given as a Unix timestamp.
+pub fn builtin_gps_utc_at_utc(unix_secs: i64) -> i32 {
+    LEAP_SECONDS
+        .iter()
+        .rev()
+        .find(|(start, _)| unix_secs >= *start)
+        .map_or(0, |(_, leap)| *leap)
+}
+
+/// GPS - UTC from the built-in leap second table for an instant given in seconds of GPS time.
+pub fn builtin_gps_utc_at_gps(gps_secs: i64) -> i32 {
+    LEAP_SECONDS
+        .iter()
+        .rev()
+        .find(|(start, leap)| gps_secs >= start - GPS_EPOCH_UNIX + *leap as i64)
+        .map_or(0, |(_, leap)| *leap)
+}
+
+/// Where the leap second count currently in use came from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LeapSecondSource {
+    BuiltIn,
+    Receiver(String),
+}
+
+impl fmt::Display for LeapSecondSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LeapSecondSource::BuiltIn => write!(f, "built-in table"),
+            LeapSecondSource::Receiver(detail) => write!(f, "receiver ({})", detail),
+        }
+    }
+}
+
+/// The GPS - UTC offset to apply and where it came from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LeapSeconds {
+    pub gps_utc: i32,
+    pub source: LeapSecondSource,
+}
+
+impl Default for LeapSeconds {
+    fn default() -> Self {
+        Self {
+            gps_utc: LEAP_SECONDS[LEAP_SECONDS.len() - 1].1,
+            source: LeapSecondSource::BuiltIn,
+        }
+    }
+}
+
+/// A week number and time of week pair.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WeekTow {
+    pub week: i64,
+    pub tow: f64,
+}
+
+/// A proleptic Gregorian calendar date and time of day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CalendarTime {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+    pub second: u32,
+    pub nanos: u32,
+}
+
+impl CalendarTime {
+    pub fn from_unix(secs: i64, nanos: u32) -> Self {
+        let days = secs.div_euclid(SECONDS_PER_DAY);
+        let sod = secs.rem_euclid(SECONDS_PER_DAY) as u32;
+        let (year, month, day) = civil_from_days(days);
+        Self {
+            year,
+            month,
+            day,
+            hour: sod / 3600,
+            minute: (sod % 3600) / 60,
+            second: sod % 60,
+            nanos,
+        }
+    }
+
+    pub fn to_unix(&self) -> i64 {
+        days_from_civil(self.year, self.month, self.day) * SECONDS_PER_DAY
+            + self.hour as i64 * 3600
+            + self.minute as i64 * 60
+            + self.second as i64
+    }
+}
+
+impl fmt::Display for CalendarTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02} {:02}:{:02}:{:02}.{:03}",
+            self.year,
+            self.month,
+            self.day,
+            self.hour,
+            self.minute,
+            self.second,
+            self.nanos / 1_000_000
+        )
+    }
+}
+
+/// GLONASS time expressed as four-year interval, day within the interval and time of day.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GlonassTime {
+    /// Four-year interval number, starting at 1 in 1996.
+    pub n4: i32,
+    /// Day number within the four-year interval, starting at 1.
+    pub nt: i64,
+    /// Seconds of the GLONASS day.
+    pub tod: f64,
+}
+
+/// An instant on the GPS time scale, held as whole seconds and nanoseconds since the GPS epoch.
+///
+/// Every other time scale is derived from this one; conversions to and from UTC need the
+/// GPS - UTC leap second count in effect at that instant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct GnssInstant {
+    secs: i64,
+    nanos: u32,
+}
+
+impl GnssInstant {
+    pub fn from_gps_secs(secs: i64, nanos: u32) -> Self {
+        Self { secs, nanos }.normalized()
+    }
+
+    pub fn from_gps_week_tow(week: i64, tow: f64) -> Self {
+        let whole = tow.floor();
+        Self::from_gps_secs(
+            week * SECONDS_PER_WEEK + whole as i64,
+            ((tow - whole) * 1e9).round() as u32,
+        )
+    }
+
+    pub fn from_utc_unix(unix_secs: i64, nanos: u32, gps_utc: i32) -> Self {
+        Self::from_gps_secs(unix_secs - GPS_EPOCH_UNIX + gps_utc as i64, nanos)
+    }
+
+    pub fn from_utc_calendar(time: &CalendarTime, gps_utc: i32) -> Self {
+        Self::from_utc_unix(time.to_unix(), time.nanos, gps_utc)
+    }
+
+    fn normalized(mut self) -> Self {
+        if self.nanos >= 1_000_000_000 {
+            self.secs += (self.nanos / 1_000_000_000) as i64;
+            self.nanos %= 1_000_000_000;
+        }
+        self
+    }
+
+    /// Whole seconds since the GPS epoch.
+    pub fn gps_secs(&self) -> i64 {
+        self.secs
+    }
+
+    pub fn as_gps_secs_f64(&self) -> f64 {
+        self.secs as f64 + self.nanos as f64 * 1e-9
+    }
+
+    /// Shifts the instant by a (possibly negative) number of seconds.
+    pub fn offset(&self, seconds: f64) -> Self {
+        let whole = seconds.floor();
+        let nanos = self.nanos as i64 + ((seconds - whole) * 1e9).round() as i64;
+        Self::from_gps_secs(self.secs + whole as i64, 0).add_nanos(nanos)
+    }
+
+    fn add_nanos(self, nanos: i64) -> Self {
+        Self::from_gps_secs(
+            self.secs + nanos.div_euclid(1_000_000_000),
+            nanos.rem_euclid(1_000_000_000) as u32,
+        )
+    }
+
+    /// Seconds elapsed from `earlier` to `self`.
+    pub fn seconds_since(&self, earlier: &GnssInstant) -> f64 {
+        (self.secs - earlier.secs) as f64 + (self.nanos as f64 - earlier.nanos as f64) * 1e-9
+    }
+
+    fn week_tow(secs: i64, nanos: u32) -> WeekTow {
+        WeekTow {
+            week: secs.div_euclid(SECONDS_PER_WEEK),
+            tow: secs.rem_euclid(SECONDS_PER_WEEK) as f64 + nanos as f64 * 1e-9,
+        }
+    }
+
+    /// GPS week (not rolled over) and time of week.
+    pub fn gps(&self) -> WeekTow {
+        Self::week_tow(self.secs, self.nanos)
+    }
+
+    /// Galileo System Time week and time of week. GST is steered to GPS time, only the week
+    /// numbering differs.
+    pub fn gst(&self) -> WeekTow {
+        Self::week_tow(self.secs - GST_WEEK_OFFSET * SECONDS_PER_WEEK, self.nanos)
+    }
+
+    /// BeiDou Time week and time of week.
+    pub fn bdt(&self) -> WeekTow {
+        Self::week_tow(
+            self.secs - GPS_BDT_OFFSET - BDT_WEEK_OFFSET * SECONDS_PER_WEEK,
+            self.nanos,
+        )
+    }
+
+    /// UTC as a Unix timestamp (seconds, nanoseconds).
+    pub fn utc_unix(&self, gps_utc: i32) -> (i64, u32) {
+        (self.secs + GPS_EPOCH_UNIX - gps_utc as i64, self.nanos)
+    }
+
+    pub fn utc(&self, gps_utc: i32) -> CalendarTime {
+        let (secs, nanos) = self.utc_unix(gps_utc);
+        CalendarTime::from_unix(secs, nanos)
+    }
+
+    /// TAI, laid out on the calendar the same way UTC is.
+    pub fn tai(&self) -> CalendarTime {
+        CalendarTime::from_unix(self.secs + GPS_EPOCH_UNIX + TAI_GPS_OFFSET, self.nanos)
+    }
+
+    /// GLONASS time, laid out on the calendar.
+    pub fn glonass_calendar(&self, gps_utc: i32) -> CalendarTime {
+        let (secs, nanos) = self.utc_unix(gps_utc);
+        CalendarTime::from_unix(secs + GLONASS_UTC_OFFSET, nanos)
+    }
+
+    pub fn glonass(&self, gps_utc: i32) -> GlonassTime {
+        let (secs, nanos) = self.utc_unix(gps_utc);
+        let glo = secs + GLONASS_UTC_OFFSET;
+        let days = glo.div_euclid(SECONDS_PER_DAY);
+        let (year, _, _) = civil_from_days(days);
+        let n4 = (year - 1996).div_euclid(4) + 1;
+        let interval_start = days_from_civil(1996 + 4 * (n4 - 1), 1, 1);
+        GlonassTime {
+            n4,
+            nt: days - interval_start + 1,
+            tod: glo.rem_euclid(SECONDS_PER_DAY) as f64 + nanos as f64 * 1e-9,
+        }
+    }
+}
+
+// Days since 1970-01-01 for a proleptic Gregorian date (H. Hinnant's algorithm).
+fn days_from_civil(year: i32, month: u32, day: u32) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year } as i64;
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let m = month as i64;
+    let doy = (153 * (if m > 2 { m - 3 } else { m + 9 }) + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i32, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = (yoe + era * 400) as i32 + i32::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(year: i32, month: u32, day: u32, hour: u32, minute: u32, second: u32) -> CalendarTime {
+        CalendarTime {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+            nanos: 0,
+        }
+    }
+
+    #[test]
+    fn calendar_round_trip() {
+        for unix in [0, 315_964_800, 951_782_400, 1_483_228_799, 4_102_444_800] {
+            assert_eq!(CalendarTime::from_unix(unix, 0).to_unix(), unix);
+        }
+        assert_eq!(
+            CalendarTime::from_unix(GPS_EPOCH_UNIX, 0),
+            utc(1980, 1, 6, 0, 0, 0)
+        );
+        // Leap day.
+        assert_eq!(
+            CalendarTime::from_unix(951_782_400, 0),
+            utc(2000, 2, 29, 0, 0, 0)
+        );
+    }
+
+    #[test]
+    fn leap_second_table() {
+        assert_eq!(builtin_gps_utc_at_utc(GPS_EPOCH_UNIX), 0);
+        assert_eq!(builtin_gps_utc_at_utc(1_483_228_799), 17);
+        assert_eq!(builtin_gps_utc_at_utc(1_483_228_800), 18);
+
+        // 2017-01-01 00:00:00 UTC is 00:00:18 GPS.
+        let gps = 1_483_228_800 - GPS_EPOCH_UNIX;
+        assert_eq!(builtin_gps_utc_at_gps(gps + 17), 17);
+        assert_eq!(builtin_gps_utc_at_gps(gps + 18), 18);
+    }
+
+    #[test]
+    fn gps_week_and_tow() {
+        let t = GnssInstant::from_utc_calendar(&utc(2017, 1, 1, 0, 0, 0), 18);
+        assert_eq!(
+            t.gps(),
+            WeekTow {
+                week: 1930,
+                tow: 18.0
+            }
+        );
+
+        // The second GPS week number rollover.
+        let t = GnssInstant::from_gps_week_tow(2048, 0.0);
+        assert_eq!(t.utc(18), utc(2019, 4, 6, 23, 59, 42));
+    }
+
+    #[test]
+    fn galileo_and_beidou_epochs() {
+        // GST week 0 started at 1999-08-22 00:00:00 GPS, 13 s before midnight UTC.
+        let t = GnssInstant::from_utc_calendar(&utc(1999, 8, 21, 23, 59, 47), 13);
+        assert_eq!(t.gst(), WeekTow { week: 0, tow: 0.0 });
+        assert_eq!(t.gps().week, 1024);
+
+        // BDT week 0 started at 2006-01-01 00:00:00 UTC.
+        let t = GnssInstant::from_utc_calendar(&utc(2006, 1, 1, 0, 0, 0), 14);
+        assert_eq!(t.bdt(), WeekTow { week: 0, tow: 0.0 });
+        assert_eq!(
+            t.gps(),
+            WeekTow {
+                week: 1356,
+                tow: 14.0
+            }
+        );
+    }
+
+    #[test]
+    fn tai_and_glonass() {
+        let t = GnssInstant::from_utc_calendar(&utc(2017, 1, 1, 0, 0, 0), 18);
+        assert_eq!(t.tai(), utc(2017, 1, 1, 0, 0, 37));
+        assert_eq!(t.glonass_calendar(18), utc(2017, 1, 1, 3, 0, 0));
+        assert_eq!(
+            t.glonass(18),
+            GlonassTime {
+                n4: 6,
+                nt: 367,
+                tod: 10_800.0
+            }
+        );
+    }
+
+    #[test]
+    fn sub_second_arithmetic() {
+        let t = GnssInstant::from_gps_week_tow(2000, 100.25);
+        assert_eq!(
+            t.gps(),
+            WeekTow {
+                week: 2000,
+                tow: 100.25
+            }
+        );
+
+        let later = t.offset(0.875);
+        assert_eq!(
+            later.gps(),
+            WeekTow {
+                week: 2000,
+                tow: 101.125
+            }
+        );
+        assert!((later.seconds_since(&t) - 0.875).abs() < 1e-9);
+
+        let earlier = t.offset(-100.5);
+        assert_eq!(
+            earlier.gps(),
+            WeekTow {
+                week: 1999,
+                tow: 604_799.75
+            }
+        );
+    }
+}
@@ -0,0 +1,442 @@
+use chrono::{DateTime, Utc};
+
+use crate::gnss::{FixType, RtkStatus};
+use crate::nmea;
+use crate::receiver::ReceiverState;
+use crate::ubx;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StartMode {
+    Cold,
+    Warm,
+    Hot,
+}
+
+impl StartMode {
+    pub const ALL: [StartMode; 3] = [StartMode::Cold, StartMode::Warm, StartMode::Hot];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            StartMode::Cold => "Cold",
+            StartMode::Warm => "Warm",
+            StartMode::Hot => "Hot",
+        }
+    }
+}
+
+/// How reset commands are sent to the receiver.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResetProtocol {
+    /// UBX-CFG-RST, controlled GNSS-only software reset.
+    Ubx,
+    /// MediaTek `$PMTK101/102/103` proprietary sentences.
+    Mtk,
+    /// SiRF `$PSRF101` proprietary sentence.
+    Sirf,
+}
+
+impl ResetProtocol {
+    pub const ALL: [ResetProtocol; 3] =
+        [ResetProtocol::Ubx, ResetProtocol::Mtk, ResetProtocol::Sirf];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ResetProtocol::Ubx => "UBX CFG-RST",
+            ResetProtocol::Mtk => "NMEA PMTK",
+            ResetProtocol::Sirf => "NMEA PSRF",
+        }
+    }
+
+    /// The bytes that reset the receiver into `mode`.
+    pub fn command(&self, mode: StartMode) -> Vec<u8> {
+        match self {
+            ResetProtocol::Ubx => {
+                let mask = match mode {
+                    StartMode::Cold => 0xFFFF,
+                    StartMode::Warm => 0x0001,
+                    StartMode::Hot => 0x0000,
+                };
+                ubx::cfg_rst(mask, 0x02).to_bytes()
+            }
+            ResetProtocol::Mtk => {
+                let body = match mode {
+                    StartMode::Cold => "PMTK103",
+                    StartMode::Warm => "PMTK102",
+                    StartMode::Hot => "PMTK101",
+                };
+                nmea::encode(body).into_bytes()
+            }
+            ResetProtocol::Sirf => {
+                let reset = match mode {
+                    StartMode::Cold => 4,
+                    StartMode::Warm => 2,
+                    StartMode::Hot => 1,
+                };
+                nmea::encode(&format!("PSRF101,0,0,0,0,0,0,12,{}", reset)).into_bytes()
+            }
+        }
+    }
+}
+
+/// Outcome of one reset, times in seconds since the reset command was sent.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TtffRun {
+    pub mode: StartMode,
+    pub started: DateTime<Utc>,
+    pub fix_2d: Option<f64>,
+    pub fix_3d: Option<f64>,
+    pub fix_rtk: Option<f64>,
+    pub timed_out: bool,
+}
+
+/// Minimum, median and maximum of a set of times.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Summary {
+    pub min: f64,
+    pub median: f64,
+    pub max: f64,
+    pub count: usize,
+}
+
+impl Summary {
+    pub fn new(values: &[f64]) -> Option<Self> {
+        if values.is_empty() {
+            return None;
+        }
+        let mut sorted = values.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        let n = sorted.len();
+        let median = match n % 2 {
+            0 => (sorted[n / 2 - 1] + sorted[n / 2]) / 2.0,
+            _ => sorted[n / 2],
+        };
+        Some(Self {
+            min: sorted[0],
+            median,
+            max: sorted[n - 1],
+            count: n,
+        })
+    }
+}
+
+/// Counts of `values` in bins of `width` seconds, as (bin start, count) for every bin from
+/// the one holding the smallest value to the one holding the largest.
+pub fn histogram(values: &[f64], width: f64) -> Vec<(f64, usize)> {
+    if values.is_empty() || width <= 0.0 {
+        return Vec::new();
+    }
+    let bin = |v: f64| (v / width).floor() as i64;
+    let first = values.iter().copied().map(bin).min().unwrap_or(0);
+    let last = values.iter().copied().map(bin).max().unwrap_or(0);
+    let mut counts = vec![0; (last - first + 1) as usize];
+    for v in values {
+        counts[(bin(*v) - first) as usize] += 1;
+    }
+    counts
+        .into_iter()
+        .enumerate()
+        .map(|(i, c)| ((first + i as i64) as f64 * width, c))
+        .collect()
+}
+
+/// Settings of a TTFF test.
+#[derive(Debug, Clone)]
+pub struct TtffConfig {
+    pub mode: StartMode,
+    pub protocol: ResetProtocol,
+    pub repeats: usize,
+    /// Seconds to wait for the fixes before giving up on a run.
+    pub timeout: f64,
+    /// Keep each run going until an RTK fixed solution, not just a 3D fix.
+    pub wait_for_rtk: bool,
+    /// Seconds after a reset during which fixes are ignored unless the receiver has already
+    /// reported losing its fix, so stale solutions sent before the reset are not counted.
+    pub settle: f64,
+    /// Pause in seconds between the end of one run and the next reset.
+    pub pause: f64,
+}
+
+impl Default for TtffConfig {
+    fn default() -> Self {
+        Self {
+            mode: StartMode::Cold,
+            protocol: ResetProtocol::Ubx,
+            repeats: 10,
+            timeout: 300.0,
+            wait_for_rtk: false,
+            settle: 1.0,
+            pause: 5.0,
+        }
+    }
+}
+
+enum Phase {
+    Idle,
+    /// Waiting to send the next reset.
+    Pending(DateTime<Utc>),
+    Running {
+        fix_lost: bool,
+    },
+}
+
+/// Repeatedly resets a receiver and times how long it takes to get a fix.
+pub struct TtffRunner {
+    pub config: TtffConfig,
+    pub runs: Vec<TtffRun>,
+    phase: Phase,
+    remaining: usize,
+}
+
+impl Default for TtffRunner {
+    fn default() -> Self {
+        Self {
+            config: TtffConfig::default(),
+            runs: Vec::new(),
+            phase: Phase::Idle,
+            remaining: 0,
+        }
+    }
+}
+
+impl TtffRunner {
+    pub fn is_running(&self) -> bool {
+        !matches!(self.phase, Phase::Idle)
+    }
+
+    /// Runs still to do, including the current one.
+    pub fn remaining(&self) -> usize {
+        self.remaining
+    }
+
+    /// The run in progress, if any.
+    pub fn current(&self) -> Option<&TtffRun> {
+        match self.phase {
+            Phase::Running { .. } => self.runs.last(),
+            _ => None,
+        }
+    }
+
+    pub fn start(&mut self, host: DateTime<Utc>) {
+        self.remaining = self.config.repeats.max(1);
+        self.phase = Phase::Pending(host);
+    }
+
+    /// Stops the test, marking an unfinished run as timed out.
+    pub fn stop(&mut self) {
+        if let Phase::Running { .. } = self.phase {
+            if let Some(run) = self.runs.last_mut() {
+                run.timed_out = true;
+            }
+        }
+        self.phase = Phase::Idle;
+        self.remaining = 0;
+    }
+
+    pub fn clear(&mut self) {
+        self.runs.clear();
+    }
+
+    /// Advances the test. Returns a reset command when one should be sent now.
+    pub fn poll(&mut self, host: DateTime<Utc>) -> Option<Vec<u8>> {
+        match self.phase {
+            Phase::Pending(at) if host >= at => {
+                self.runs.push(TtffRun {
+                    mode: self.config.mode,
+                    started: host,
+                    fix_2d: None,
+                    fix_3d: None,
+                    fix_rtk: None,
+                    timed_out: false,
+                });
+                self.phase = Phase::Running { fix_lost: false };
+                Some(self.config.protocol.command(self.config.mode))
+            }
+            Phase::Running { .. } => {
+                let run = self.runs.last_mut()?;
+                if seconds(run.started, host) > self.config.timeout {
+                    run.timed_out = true;
+                    self.finish_run(host);
+                }
+                None
+            }
+            _ => None,
+        }
+    }
+
+    /// Checks `state`, just updated with a new message, for fix progress.
+    pub fn observe(&mut self, state: &ReceiverState, host: DateTime<Utc>) {
+        let Phase::Running { fix_lost } = &mut self.phase else {
+            return;
+        };
+        let Some(run) = self.runs.last_mut() else {
+            return;
+        };
+        let elapsed = seconds(run.started, host);
+
+        let rtk = state.rtk == RtkStatus::Fixed;
+        let fix_type = state.fix.as_ref().map(|f| f.fix_type);
+        let has_position = fix_type.is_some_and(|f| f.has_position());
+        if !has_position {
+            *fix_lost = true;
+        }
+        if !*fix_lost && elapsed < self.config.settle {
+            return;
+        }
+
+        if has_position && run.fix_2d.is_none() {
+            run.fix_2d = Some(elapsed);
+        }
+        if fix_type == Some(FixType::Fix3D) && run.fix_3d.is_none() {
+            run.fix_3d = Some(elapsed);
+        }
+        if rtk && run.fix_rtk.is_none() {
+            run.fix_rtk = Some(elapsed);
+        }
+
+        let done = run.fix_3d.is_some() && (!self.config.wait_for_rtk || run.fix_rtk.is_some());
+        if done {
+            self.finish_run(host);
+        }
+    }
+
+    fn finish_run(&mut self, host: DateTime<Utc>) {
+        self.remaining = self.remaining.saturating_sub(1);
+        self.phase = match self.remaining {
+            0 => Phase::Idle,
+            _ => Phase::Pending(
+                host + chrono::Duration::milliseconds((self.config.pause * 1e3) as i64),
+            ),
+        };
+    }
+
+    /// Summaries of the 2D, 3D and RTK times over completed runs of `mode`.
+    pub fn summaries(&self, mode: StartMode) -> [Option<Summary>; 3] {
+        let times = |f: fn(&TtffRun) -> Option<f64>| -> Vec<f64> {
+            self.finished(mode).filter_map(f).collect()
+        };
+        [
+            Summary::new(&times(|r| r.fix_2d)),
+            Summary::new(&times(|r| r.fix_3d)),
+            Summary::new(&times(|r| r.fix_rtk)),
+        ]
+    }
+
+    /// Runs of `mode` that are no longer in progress.
+    pub fn finished(&self, mode: StartMode) -> impl Iterator<Item = &TtffRun> {
+        let running = self.current().is_some() as usize;
+        let end = self.runs.len().saturating_sub(running);
+        self.runs[..end].iter().filter(move |r| r.mode == mode)
+    }
+
+    /// Formats the results as CSV, with the receiver description in a comment line and a
+    /// summary per start mode at the end.
+    pub fn to_csv(&self, receiver: &str) -> String {
+        let mut csv = format!("# receiver: {}\n", receiver);
+        csv.push_str("run,mode,started_utc,ttff_2d_s,ttff_3d_s,ttff_rtk_s,timed_out\n");
+        let value = |v: Option<f64>| v.map_or(String::new(), |v| format!("{:.3}", v));
+        for (i, run) in self.runs.iter().enumerate() {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{}\n",
+                i + 1,
+                run.mode.name(),
+                run.started.format("%Y-%m-%dT%H:%M:%S%.3fZ"),
+                value(run.fix_2d),
+                value(run.fix_3d),
+                value(run.fix_rtk),
+                run.timed_out
+            ));
+        }
+
+        csv.push_str("\nmode,fix,count,min_s,median_s,max_s\n");
+        for mode in StartMode::ALL {
+            for (fix, summary) in ["2D", "3D", "RTK"].iter().zip(self.summaries(mode)) {
+                if let Some(s) = summary {
+                    csv.push_str(&format!(
+                        "{},{},{},{:.3},{:.3},{:.3}\n",
+                        mode.name(),
+                        fix,
+                        s.count,
+                        s.min,
+                        s.median,
+                        s.max
+                    ));
+                }
+            }
+        }
+        csv
+    }
+}
+
+fn seconds(earlier: DateTime<Utc>, later: DateTime<Utc>) -> f64 {
+    (later - earlier).num_microseconds().unwrap_or(0) as f64 * 1e-6
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nmea::{NmeaMessage, NmeaSentence};
+    use crate::receiver::GnssMessage;
+
+    #[test]
+    fn summary_and_histogram() {
+        let s = Summary::new(&[30.0, 25.0, 40.0, 27.0]).unwrap();
+        assert_eq!((s.min, s.median, s.max, s.count), (25.0, 28.5, 40.0, 4));
+        assert_eq!(Summary::new(&[]), None);
+        assert_eq!(
+            histogram(&[25.0, 27.0, 30.0, 40.0], 5.0),
+            vec![(25.0, 2), (30.0, 1), (35.0, 0), (40.0, 1)]
+        );
+    }
+
+    #[test]
+    fn reset_commands() {
+        assert_eq!(
+            ResetProtocol::Ubx.command(StartMode::Cold),
+            [0xB5, 0x62, 0x06, 0x04, 0x04, 0x00, 0xFF, 0xFF, 0x02, 0x00, 0x0E, 0x61]
+        );
+        assert_eq!(
+            ResetProtocol::Mtk.command(StartMode::Hot),
+            b"$PMTK101*32\r\n".to_vec()
+        );
+    }
+
+    #[test]
+    fn times_a_run() {
+        let mut runner = TtffRunner::default();
+        runner.config.repeats = 1;
+        let mut state = ReceiverState::default();
+        let t0 = Utc::now();
+        runner.start(t0);
+        assert!(runner.poll(t0).is_some());
+
+        let mut feed = |body: &str, after: f64| {
+            let sentence = NmeaSentence::parse(&nmea::encode(body)).unwrap();
+            let message = GnssMessage::Nmea(NmeaMessage::decode(&sentence).unwrap());
+            let host = t0 + chrono::Duration::milliseconds((after * 1e3) as i64);
+            state.apply(&message, host);
+            runner.observe(&state, host);
+        };
+        // A stale fix from before the reset, then the fix is lost and reacquired.
+        feed(
+            "GPGGA,120000.00,4807.038,N,01131.000,E,1,08,0.9,545.4,M,46.9,M,,",
+            0.1,
+        );
+        feed("GPGGA,120001.00,,,,,0,00,,,M,,M,,", 0.5);
+        feed(
+            "GPGGA,120031.00,4807.038,N,01131.000,E,1,05,1.9,,M,,M,,",
+            31.0,
+        );
+        feed(
+            "GPGGA,120033.00,4807.038,N,01131.000,E,1,06,1.2,545.4,M,46.9,M,,",
+            33.0,
+        );
+
+        assert!(!runner.is_running());
+        let run = &runner.runs[0];
+        assert_eq!(
+            (run.fix_2d, run.fix_3d, run.fix_rtk),
+            (Some(31.0), Some(33.0), None)
+        );
+        assert!(!run.timed_out);
+    }
+}
@@ -0,0 +1,1604 @@
+use crate::gnss::{Constellation, FixType, SatelliteId};
+use crate::nmea;
+use crate::sbas::{SbasProvider, SbasServices, Udre};
+
+pub const SYNC: [u8; 2] = [0xB5, 0x62];
+
+pub const CLASS_NAV: u8 = 0x01;
+pub const CLASS_RXM: u8 = 0x02;
+pub const CLASS_CFG: u8 = 0x06;
+pub const CLASS_MON: u8 = 0x0A;
+pub const CLASS_TIM: u8 = 0x0D;
+
+pub const ID_NAV_STATUS: u8 = 0x03;
+pub const ID_NAV_PVT: u8 = 0x07;
+pub const ID_NAV_TIMEGPS: u8 = 0x20;
+pub const ID_NAV_TIMEUTC: u8 = 0x21;
+pub const ID_NAV_CLOCK: u8 = 0x22;
+pub const ID_NAV_TIMELS: u8 = 0x26;
+pub const ID_NAV_SBAS: u8 = 0x32;
+pub const ID_NAV_SAT: u8 = 0x35;
+pub const ID_NAV_COV: u8 = 0x36;
+pub const ID_NAV_SVIN: u8 = 0x3B;
+pub const ID_NAV_RELPOSNED: u8 = 0x3C;
+
+pub const ID_RXM_SFRBX: u8 = 0x13;
+pub const ID_RXM_RAWX: u8 = 0x15;
+
+pub const ID_CFG_RST: u8 = 0x04;
+pub const ID_CFG_TMODE3: u8 = 0x71;
+
+pub const ID_MON_VER: u8 = 0x04;
+pub const ID_MON_HW: u8 = 0x09;
+pub const ID_MON_SPAN: u8 = 0x31;
+pub const ID_MON_HW3: u8 = 0x37;
+pub const ID_MON_RF: u8 = 0x38;
+
+pub const ID_TIM_TP: u8 = 0x01;
+pub const ID_TIM_TM2: u8 = 0x03;
+
+/// Builds a UBX-CFG-RST command. `nav_bbr_mask` selects the battery backed data to clear
+/// (0x0000 hot, 0x0001 warm, 0xFFFF cold start), `reset_mode` how to reset
+/// (0x00 hardware, 0x01 software, 0x02 GNSS only, 0x08 GNSS stop, 0x09 GNSS start).
+pub fn cfg_rst(nav_bbr_mask: u16, reset_mode: u8) -> UbxFrame {
+    let mut payload = nav_bbr_mask.to_le_bytes().to_vec();
+    payload.extend_from_slice(&[reset_mode, 0]);
+    UbxFrame::new(CLASS_CFG, ID_CFG_RST, payload)
+}
+
+/// UBX-CFG-TMODE3: time mode of timing and RTK base receivers.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CfgTmode3 {
+    /// 0 disabled, 1 survey-in, 2 fixed.
+    pub mode: u8,
+    /// Fixed position given as latitude/longitude/height instead of ECEF.
+    pub lla: bool,
+    /// ECEF X/Y/Z in cm, or latitude/longitude in 1e-7 degrees and height in cm.
+    pub position: [i32; 3],
+    /// High precision parts, 0.1 mm or 1e-9 degrees.
+    pub position_hp: [i8; 3],
+    /// Fixed position accuracy in 0.1 mm.
+    pub fixed_pos_acc: u32,
+    /// Survey-in minimum duration in seconds.
+    pub svin_min_dur: u32,
+    /// Survey-in position accuracy limit in 0.1 mm.
+    pub svin_acc_limit: u32,
+}
+
+impl CfgTmode3 {
+    pub fn to_frame(&self) -> UbxFrame {
+        let flags = self.mode as u16 | if self.lla { 0x100 } else { 0 };
+        let mut payload = vec![0, 0];
+        payload.extend_from_slice(&flags.to_le_bytes());
+        for v in self.position {
+            payload.extend_from_slice(&v.to_le_bytes());
+        }
+        payload.extend(self.position_hp.iter().map(|v| *v as u8));
+        payload.push(0);
+        payload.extend_from_slice(&self.fixed_pos_acc.to_le_bytes());
+        payload.extend_from_slice(&self.svin_min_dur.to_le_bytes());
+        payload.extend_from_slice(&self.svin_acc_limit.to_le_bytes());
+        payload.extend_from_slice(&[0; 8]);
+        UbxFrame::new(CLASS_CFG, ID_CFG_TMODE3, payload)
+    }
+}
+
+/// 8-bit Fletcher checksum over class, id, length and payload.
+pub fn checksum(bytes: &[u8]) -> [u8; 2] {
+    let (mut a, mut b) = (0u8, 0u8);
+    for byte in bytes {
+        a = a.wrapping_add(*byte);
+        b = b.wrapping_add(a);
+    }
+    [a, b]
+}
+
+/// A raw, checksum-verified UBX frame.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UbxFrame {
+    pub class: u8,
+    pub id: u8,
+    pub payload: Vec<u8>,
+}
+
+impl UbxFrame {
+    pub fn new(class: u8, id: u8, payload: Vec<u8>) -> Self {
+        Self { class, id, payload }
+    }
+
+    /// Serializes the frame including sync characters and checksum.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let len = self.payload.len() as u16;
+        let mut bytes = Vec::with_capacity(self.payload.len() + 8);
+        bytes.extend_from_slice(&SYNC);
+        bytes.extend_from_slice(&[self.class, self.id]);
+        bytes.extend_from_slice(&len.to_le_bytes());
+        bytes.extend_from_slice(&self.payload);
+        let ck = checksum(&bytes[2..]);
+        bytes.extend_from_slice(&ck);
+        bytes
+    }
+
+    /// Attempts to parse a frame from the start of `bytes`.
+    pub fn parse(bytes: &[u8]) -> FrameParse {
+        if bytes.len() < 2 {
+            return if bytes.is_empty() || bytes[0] == SYNC[0] {
+                FrameParse::Incomplete
+            } else {
+                FrameParse::Invalid
+            };
+        }
+        if bytes[..2] != SYNC {
+            return FrameParse::Invalid;
+        }
+        if bytes.len() < 6 {
+            return FrameParse::Incomplete;
+        }
+        let len = u16::from_le_bytes([bytes[4], bytes[5]]) as usize;
+        let total = len + 8;
+        if bytes.len() < total {
+            return FrameParse::Incomplete;
+        }
+        if checksum(&bytes[2..6 + len]) != bytes[6 + len..total] {
+            return FrameParse::Invalid;
+        }
+        FrameParse::Frame(
+            Self::new(bytes[2], bytes[3], bytes[6..6 + len].to_vec()),
+            total,
+        )
+    }
+}
+
+/// Outcome of [`UbxFrame::parse`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum FrameParse {
+    /// The bytes look like the start of a frame, more are needed.
+    Incomplete,
+    /// The bytes at the start are not a valid frame.
+    Invalid,
+    /// A frame and the number of bytes it occupied.
+    Frame(UbxFrame, usize),
+}
+
+// Little-endian field readers. Callers check the payload length first.
+fn u1(p: &[u8], at: usize) -> u8 {
+    p[at]
+}
+
+fn i1(p: &[u8], at: usize) -> i8 {
+    p[at] as i8
+}
+
+fn u2(p: &[u8], at: usize) -> u16 {
+    u16::from_le_bytes([p[at], p[at + 1]])
+}
+
+fn i2(p: &[u8], at: usize) -> i16 {
+    i16::from_le_bytes([p[at], p[at + 1]])
+}
+
+fn u4(p: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes([p[at], p[at + 1], p[at + 2], p[at + 3]])
+}
+
+fn i4(p: &[u8], at: usize) -> i32 {
+    i32::from_le_bytes([p[at], p[at + 1], p[at + 2], p[at + 3]])
+}
+
+fn r4(p: &[u8], at: usize) -> f32 {
+    f32::from_le_bytes([p[at], p[at + 1], p[at + 2], p[at + 3]])
+}
+
+fn r8(p: &[u8], at: usize) -> f64 {
+    f64::from_le_bytes(p[at..at + 8].try_into().unwrap())
+}
+
+/// UBX-NAV-TIMEGPS: GPS time solution.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NavTimeGps {
+    pub itow_ms: u32,
+    pub ftow_ns: i32,
+    pub week: i16,
+    pub leap_s: i8,
+    pub tow_valid: bool,
+    pub week_valid: bool,
+    pub leap_valid: bool,
+    pub t_acc_ns: u32,
+}
+
+impl NavTimeGps {
+    fn decode(p: &[u8]) -> Option<Self> {
+        if p.len() < 16 {
+            return None;
+        }
+        let valid = u1(p, 11);
+        Some(Self {
+            itow_ms: u4(p, 0),
+            ftow_ns: i4(p, 4),
+            week: i2(p, 8),
+            leap_s: i1(p, 10),
+            tow_valid: valid & 0x01 != 0,
+            week_valid: valid & 0x02 != 0,
+            leap_valid: valid & 0x04 != 0,
+            t_acc_ns: u4(p, 12),
+        })
+    }
+
+    /// Time of week in seconds, including the fractional part.
+    pub fn tow(&self) -> f64 {
+        self.itow_ms as f64 * 1e-3 + self.ftow_ns as f64 * 1e-9
+    }
+}
+
+/// UBX-NAV-TIMEUTC: UTC time solution.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NavTimeUtc {
+    pub itow_ms: u32,
+    pub t_acc_ns: u32,
+    pub nano: i32,
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub min: u8,
+    pub sec: u8,
+    pub utc_valid: bool,
+}
+
+impl NavTimeUtc {
+    fn decode(p: &[u8]) -> Option<Self> {
+        if p.len() < 20 {
+            return None;
+        }
+        Some(Self {
+            itow_ms: u4(p, 0),
+            t_acc_ns: u4(p, 4),
+            nano: i4(p, 8),
+            year: u2(p, 12),
+            month: u1(p, 14),
+            day: u1(p, 15),
+            hour: u1(p, 16),
+            min: u1(p, 17),
+            sec: u1(p, 18),
+            utc_valid: u1(p, 19) & 0x04 != 0,
+        })
+    }
+}
+
+/// UBX-NAV-TIMELS: leap second event information.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NavTimeLs {
+    pub src_of_curr_ls: u8,
+    pub curr_ls: i8,
+    pub ls_change: i8,
+    pub time_to_ls_event_s: i32,
+    pub curr_ls_valid: bool,
+    pub time_to_ls_event_valid: bool,
+}
+
+impl NavTimeLs {
+    fn decode(p: &[u8]) -> Option<Self> {
+        if p.len() < 24 {
+            return None;
+        }
+        let valid = u1(p, 23);
+        Some(Self {
+            src_of_curr_ls: u1(p, 8),
+            curr_ls: i1(p, 9),
+            ls_change: i1(p, 11),
+            time_to_ls_event_s: i4(p, 12),
+            curr_ls_valid: valid & 0x01 != 0,
+            time_to_ls_event_valid: valid & 0x02 != 0,
+        })
+    }
+
+    /// Human readable name of the `srcOfCurrLs` field.
+    pub fn source_name(&self) -> &'static str {
+        match self.src_of_curr_ls {
+            0 => "firmware default",
+            1 => "GPS/GLONASS difference",
+            2 => "GPS",
+            3 => "SBAS",
+            4 => "BeiDou",
+            5 => "Galileo",
+            6 => "aiding data",
+            7 => "configured",
+            _ => "unknown",
+        }
+    }
+}
+
+/// UBX-NAV-CLOCK: receiver clock solution.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NavClock {
+    pub itow_ms: u32,
+    pub clk_b_ns: i32,
+    pub clk_d_ns_s: i32,
+    pub t_acc_ns: u32,
+    pub f_acc_ps_s: u32,
+}
+
+impl NavClock {
+    fn decode(p: &[u8]) -> Option<Self> {
+        if p.len() < 20 {
+            return None;
+        }
+        Some(Self {
+            itow_ms: u4(p, 0),
+            clk_b_ns: i4(p, 4),
+            clk_d_ns_s: i4(p, 8),
+            t_acc_ns: u4(p, 12),
+            f_acc_ps_s: u4(p, 16),
+        })
+    }
+}
+
+/// UBX-TIM-TP: time of the next time pulse and its quantization error.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimTp {
+    pub tow_ms: u32,
+    /// Sub-millisecond part of the time of week, in 2^-32 ms.
+    pub tow_sub_ms: u32,
+    pub q_err_ps: i32,
+    pub week: u16,
+    pub flags: u8,
+    pub ref_info: u8,
+}
+
+impl TimTp {
+    fn decode(p: &[u8]) -> Option<Self> {
+        if p.len() < 16 {
+            return None;
+        }
+        Some(Self {
+            tow_ms: u4(p, 0),
+            tow_sub_ms: u4(p, 4),
+            q_err_ps: i4(p, 8),
+            week: u2(p, 12),
+            flags: u1(p, 14),
+            ref_info: u1(p, 15),
+        })
+    }
+
+    /// Time of week of the pulse in seconds.
+    pub fn tow(&self) -> f64 {
+        (self.tow_ms as f64 + self.tow_sub_ms as f64 / 4_294_967_296.0) * 1e-3
+    }
+
+    /// Whether the time base is UTC rather than GNSS time.
+    pub fn utc_base(&self) -> bool {
+        self.flags & 0x01 != 0
+    }
+
+    /// Whether the quantization error is reported as invalid.
+    pub fn q_err_invalid(&self) -> bool {
+        self.flags & 0x10 != 0
+    }
+}
+
+/// UBX-TIM-TM2: time mark on an external input.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimTm2 {
+    pub channel: u8,
+    pub flags: u8,
+    pub count: u16,
+    pub wn_r: u16,
+    pub wn_f: u16,
+    pub tow_ms_r: u32,
+    pub tow_sub_ms_r: u32,
+    pub tow_ms_f: u32,
+    pub tow_sub_ms_f: u32,
+    pub acc_est_ns: u32,
+}
+
+impl TimTm2 {
+    fn decode(p: &[u8]) -> Option<Self> {
+        if p.len() < 28 {
+            return None;
+        }
+        Some(Self {
+            channel: u1(p, 0),
+            flags: u1(p, 1),
+            count: u2(p, 2),
+            wn_r: u2(p, 4),
+            wn_f: u2(p, 6),
+            tow_ms_r: u4(p, 8),
+            tow_sub_ms_r: u4(p, 12),
+            tow_ms_f: u4(p, 16),
+            tow_sub_ms_f: u4(p, 20),
+            acc_est_ns: u4(p, 24),
+        })
+    }
+
+    /// Rising edge time of week in seconds.
+    pub fn tow_rising(&self) -> f64 {
+        self.tow_ms_r as f64 * 1e-3 + self.tow_sub_ms_r as f64 * 1e-9
+    }
+
+    /// Falling edge time of week in seconds.
+    pub fn tow_falling(&self) -> f64 {
+        self.tow_ms_f as f64 * 1e-3 + self.tow_sub_ms_f as f64 * 1e-9
+    }
+
+    pub fn new_rising_edge(&self) -> bool {
+        self.flags & 0x80 != 0
+    }
+
+    pub fn new_falling_edge(&self) -> bool {
+        self.flags & 0x04 != 0
+    }
+
+    pub fn time_valid(&self) -> bool {
+        self.flags & 0x40 != 0
+    }
+}
+
+/// UBX-NAV-PVT: navigation position velocity time solution.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NavPvt {
+    pub itow_ms: u32,
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub min: u8,
+    pub sec: u8,
+    pub valid: u8,
+    pub t_acc_ns: u32,
+    pub nano: i32,
+    pub fix_type: FixType,
+    pub flags: u8,
+    pub num_sv: u8,
+    /// Degrees.
+    pub lon: f64,
+    /// Degrees.
+    pub lat: f64,
+    /// Height above ellipsoid in metres.
+    pub height: f64,
+    /// Height above mean sea level in metres.
+    pub h_msl: f64,
+    /// Metres.
+    pub h_acc: f64,
+    /// Metres.
+    pub v_acc: f64,
+    /// North, east and down velocity in m/s.
+    pub vel_ned: [f64; 3],
+    /// Ground speed in m/s.
+    pub g_speed: f64,
+    /// Heading of motion in degrees.
+    pub head_mot: f64,
+    /// Speed accuracy in m/s.
+    pub s_acc: f64,
+    /// Heading accuracy in degrees.
+    pub head_acc: f64,
+    pub flags3: u16,
+    /// Heading of vehicle in degrees, valid when `head_veh_valid`.
+    pub head_veh: f64,
+}
+
+impl NavPvt {
+    fn decode(p: &[u8]) -> Option<Self> {
+        if p.len() < 92 {
+            return None;
+        }
+        Some(Self {
+            itow_ms: u4(p, 0),
+            year: u2(p, 4),
+            month: u1(p, 6),
+            day: u1(p, 7),
+            hour: u1(p, 8),
+            min: u1(p, 9),
+            sec: u1(p, 10),
+            valid: u1(p, 11),
+            t_acc_ns: u4(p, 12),
+            nano: i4(p, 16),
+            fix_type: fix_type(u1(p, 20)),
+            flags: u1(p, 21),
+            num_sv: u1(p, 23),
+            lon: i4(p, 24) as f64 * 1e-7,
+            lat: i4(p, 28) as f64 * 1e-7,
+            height: i4(p, 32) as f64 * 1e-3,
+            h_msl: i4(p, 36) as f64 * 1e-3,
+            h_acc: u4(p, 40) as f64 * 1e-3,
+            v_acc: u4(p, 44) as f64 * 1e-3,
+            vel_ned: [
+                i4(p, 48) as f64 * 1e-3,
+                i4(p, 52) as f64 * 1e-3,
+                i4(p, 56) as f64 * 1e-3,
+            ],
+            g_speed: i4(p, 60) as f64 * 1e-3,
+            head_mot: i4(p, 64) as f64 * 1e-5,
+            s_acc: u4(p, 68) as f64 * 1e-3,
+            head_acc: u4(p, 72) as f64 * 1e-5,
+            flags3: u2(p, 78),
+            head_veh: i4(p, 84) as f64 * 1e-5,
+        })
+    }
+
+    /// Whether the UTC date and time of day are both valid.
+    pub fn time_valid(&self) -> bool {
+        self.valid & 0x03 == 0x03
+    }
+
+    pub fn gnss_fix_ok(&self) -> bool {
+        self.flags & 0x01 != 0
+    }
+
+    /// Carrier phase solution: 0 none, 1 float, 2 fixed.
+    pub fn carrier_solution(&self) -> u8 {
+        (self.flags >> 6) & 0x03
+    }
+
+    pub fn diff_soln(&self) -> bool {
+        self.flags & 0x02 != 0
+    }
+
+    pub fn head_veh_valid(&self) -> bool {
+        self.flags & 0x20 != 0
+    }
+
+    /// Upper bound of the age of the last differential correction in seconds, `None` when
+    /// not available (older firmware reports no age at all).
+    pub fn correction_age(&self) -> Option<f64> {
+        const BOUNDS: [f64; 12] = [
+            1.0,
+            2.0,
+            5.0,
+            10.0,
+            15.0,
+            20.0,
+            30.0,
+            45.0,
+            60.0,
+            90.0,
+            120.0,
+            f64::INFINITY,
+        ];
+        match (self.flags3 >> 1) & 0x0F {
+            0 => None,
+            n => BOUNDS.get(n as usize - 1).copied(),
+        }
+    }
+}
+
+fn fix_type(raw: u8) -> FixType {
+    match raw {
+        1 => FixType::DeadReckoning,
+        2 => FixType::Fix2D,
+        3 => FixType::Fix3D,
+        4 => FixType::GnssDeadReckoning,
+        5 => FixType::TimeOnly,
+        _ => FixType::NoFix,
+    }
+}
+
+/// Spoofing detection state reported in UBX-NAV-STATUS.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpoofingState {
+    Unknown,
+    None,
+    Indicated,
+    Multiple,
+}
+
+impl SpoofingState {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SpoofingState::Unknown => "Unknown / deactivated",
+            SpoofingState::None => "No spoofing indicated",
+            SpoofingState::Indicated => "Spoofing indicated",
+            SpoofingState::Multiple => "Multiple spoofing indications",
+        }
+    }
+}
+
+/// UBX-NAV-RELPOSNED: position relative to the reference station or moving base.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NavRelPosNed {
+    pub ref_station_id: u16,
+    pub itow_ms: u32,
+    /// North, east, down in metres.
+    pub rel_pos: [f64; 3],
+    /// Metres, only reported by version 1 of the message.
+    pub length: Option<f64>,
+    /// Degrees, only reported by version 1 of the message.
+    pub heading: Option<f64>,
+    /// North, east, down accuracy in metres.
+    pub acc: [f64; 3],
+    pub acc_length: Option<f64>,
+    pub acc_heading: Option<f64>,
+    pub flags: u32,
+}
+
+impl NavRelPosNed {
+    fn decode(p: &[u8]) -> Option<Self> {
+        if p.is_empty() {
+            return None;
+        }
+        let axis = |at: usize, hp: usize| i4(p, at) as f64 * 1e-2 + i1(p, hp) as f64 * 1e-4;
+        match u1(p, 0) {
+            // u-blox M8: no length or heading.
+            0 if p.len() >= 40 => Some(Self {
+                ref_station_id: u2(p, 2),
+                itow_ms: u4(p, 4),
+                rel_pos: [axis(8, 20), axis(12, 21), axis(16, 22)],
+                length: None,
+                heading: None,
+                acc: [
+                    u4(p, 24) as f64 * 1e-4,
+                    u4(p, 28) as f64 * 1e-4,
+                    u4(p, 32) as f64 * 1e-4,
+                ],
+                acc_length: None,
+                acc_heading: None,
+                flags: u4(p, 36),
+            }),
+            1 if p.len() >= 64 => Some(Self {
+                ref_station_id: u2(p, 2),
+                itow_ms: u4(p, 4),
+                rel_pos: [axis(8, 32), axis(12, 33), axis(16, 34)],
+                length: Some(axis(20, 35)),
+                heading: Some(i4(p, 24) as f64 * 1e-5),
+                acc: [
+                    u4(p, 36) as f64 * 1e-4,
+                    u4(p, 40) as f64 * 1e-4,
+                    u4(p, 44) as f64 * 1e-4,
+                ],
+                acc_length: Some(u4(p, 48) as f64 * 1e-4),
+                acc_heading: Some(u4(p, 52) as f64 * 1e-5),
+                flags: u4(p, 60),
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn gnss_fix_ok(&self) -> bool {
+        self.flags & 0x01 != 0
+    }
+
+    pub fn diff_soln(&self) -> bool {
+        self.flags & 0x02 != 0
+    }
+
+    pub fn rel_pos_valid(&self) -> bool {
+        self.flags & 0x04 != 0
+    }
+
+    /// Carrier phase solution: 0 none, 1 float, 2 fixed.
+    pub fn carrier_solution(&self) -> u8 {
+        ((self.flags >> 3) & 0x03) as u8
+    }
+
+    /// Whether the reference is a moving base.
+    pub fn is_moving(&self) -> bool {
+        self.flags & 0x20 != 0
+    }
+
+    pub fn ref_pos_missing(&self) -> bool {
+        self.flags & 0x40 != 0
+    }
+
+    pub fn ref_obs_missing(&self) -> bool {
+        self.flags & 0x80 != 0
+    }
+
+    pub fn heading_valid(&self) -> bool {
+        self.flags & 0x100 != 0
+    }
+
+    /// Baseline length, computed from the components when the message does not carry it.
+    pub fn baseline(&self) -> f64 {
+        self.length.unwrap_or_else(|| {
+            let [n, e, d] = self.rel_pos;
+            (n * n + e * e + d * d).sqrt()
+        })
+    }
+}
+
+/// UBX-NAV-COV: covariance of the position and velocity solution.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NavCov {
+    pub itow_ms: u32,
+    pub pos_cov_valid: bool,
+    pub vel_cov_valid: bool,
+    /// Position covariance in m², upper triangle of the north/east/down matrix:
+    /// NN, NE, ND, EE, ED, DD.
+    pub pos_cov: [f64; 6],
+    /// Velocity covariance in m²/s², in the same order.
+    pub vel_cov: [f64; 6],
+}
+
+impl NavCov {
+    fn decode(p: &[u8]) -> Option<Self> {
+        if p.len() < 64 {
+            return None;
+        }
+        Some(Self {
+            itow_ms: u4(p, 0),
+            pos_cov_valid: u1(p, 5) != 0,
+            vel_cov_valid: u1(p, 6) != 0,
+            pos_cov: std::array::from_fn(|i| r4(p, 16 + 4 * i) as f64),
+            vel_cov: std::array::from_fn(|i| r4(p, 40 + 4 * i) as f64),
+        })
+    }
+}
+
+/// UBX-NAV-SVIN: survey-in progress.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NavSvin {
+    pub itow_ms: u32,
+    /// Seconds since the survey-in started.
+    pub duration_s: u32,
+    /// Mean ECEF position in metres.
+    pub mean: [f64; 3],
+    /// Accuracy of the mean position in metres.
+    pub mean_acc: f64,
+    pub observations: u32,
+    pub valid: bool,
+    pub active: bool,
+}
+
+impl NavSvin {
+    fn decode(p: &[u8]) -> Option<Self> {
+        if p.len() < 40 {
+            return None;
+        }
+        let axis = |i: usize| i4(p, 12 + 4 * i) as f64 * 1e-2 + i1(p, 24 + i) as f64 * 1e-4;
+        Some(Self {
+            itow_ms: u4(p, 4),
+            duration_s: u4(p, 8),
+            mean: [axis(0), axis(1), axis(2)],
+            mean_acc: u4(p, 28) as f64 * 1e-4,
+            observations: u4(p, 32),
+            valid: u1(p, 36) != 0,
+            active: u1(p, 37) != 0,
+        })
+    }
+}
+
+/// UBX-NAV-STATUS: receiver navigation status.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NavStatus {
+    pub itow_ms: u32,
+    pub fix_type: FixType,
+    pub flags: u8,
+    pub fix_stat: u8,
+    pub flags2: u8,
+    pub ttff_ms: u32,
+    pub msss_ms: u32,
+}
+
+impl NavStatus {
+    fn decode(p: &[u8]) -> Option<Self> {
+        if p.len() < 16 {
+            return None;
+        }
+        Some(Self {
+            itow_ms: u4(p, 0),
+            fix_type: fix_type(u1(p, 4)),
+            flags: u1(p, 5),
+            fix_stat: u1(p, 6),
+            flags2: u1(p, 7),
+            ttff_ms: u4(p, 8),
+            msss_ms: u4(p, 12),
+        })
+    }
+
+    pub fn spoofing(&self) -> SpoofingState {
+        match (self.flags2 >> 3) & 0x03 {
+            1 => SpoofingState::None,
+            2 => SpoofingState::Indicated,
+            3 => SpoofingState::Multiple,
+            _ => SpoofingState::Unknown,
+        }
+    }
+}
+
+/// One satellite from UBX-NAV-SAT.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NavSatInfo {
+    pub id: SatelliteId,
+    pub cno: u8,
+    /// Degrees, -91 when unknown.
+    pub elev: i8,
+    /// Degrees, 0 to 360.
+    pub azim: i16,
+    /// Pseudorange residual in metres.
+    pub pr_res: f64,
+    pub flags: u32,
+}
+
+impl NavSatInfo {
+    pub fn used(&self) -> bool {
+        self.flags & 0x08 != 0
+    }
+
+    /// Signal health: 0 unknown, 1 healthy, 2 unhealthy.
+    pub fn health(&self) -> u8 {
+        ((self.flags >> 4) & 0x03) as u8
+    }
+
+    /// Whether SBAS corrections were applied to this satellite's measurements.
+    pub fn sbas_corrections_used(&self) -> bool {
+        self.flags & 0x1_0000 != 0
+    }
+}
+
+/// UBX-NAV-SAT: satellite information.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NavSat {
+    pub itow_ms: u32,
+    pub satellites: Vec<NavSatInfo>,
+}
+
+impl NavSat {
+    fn decode(p: &[u8]) -> Option<Self> {
+        if p.len() < 8 {
+            return None;
+        }
+        let num_svs = u1(p, 5) as usize;
+        if p.len() < 8 + 12 * num_svs {
+            return None;
+        }
+        let satellites = (0..num_svs)
+            .map(|i| {
+                let o = 8 + 12 * i;
+                NavSatInfo {
+                    id: SatelliteId::new(Constellation::from_ubx(u1(p, o)), u1(p, o + 1)),
+                    cno: u1(p, o + 2),
+                    elev: i1(p, o + 3),
+                    azim: i2(p, o + 4),
+                    pr_res: i2(p, o + 6) as f64 * 0.1,
+                    flags: u4(p, o + 8),
+                }
+            })
+            .collect();
+        Some(Self {
+            itow_ms: u4(p, 0),
+            satellites,
+        })
+    }
+}
+
+/// One satellite's SBAS data from UBX-NAV-SBAS.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NavSbasSv {
+    pub id: SatelliteId,
+    /// User differential range error indicator.
+    pub udre: u8,
+    pub provider: Option<SbasProvider>,
+    pub services: SbasServices,
+    /// Pseudorange and ionospheric corrections in metres.
+    pub prc: f64,
+    pub ic: f64,
+}
+
+impl NavSbasSv {
+    pub fn udre(&self) -> Udre {
+        Udre::from_indicator(self.udre)
+    }
+}
+
+/// UBX-NAV-SBAS: SBAS status and the corrections of each satellite.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NavSbas {
+    pub itow_ms: u32,
+    /// PRN of the geostationary satellite in use, 0 for none.
+    pub geo: u8,
+    /// 0 disabled, 1 enabled with integrity, 3 enabled in test mode.
+    pub mode: u8,
+    pub provider: Option<SbasProvider>,
+    pub services: SbasServices,
+    pub status_flags: u8,
+    pub satellites: Vec<NavSbasSv>,
+}
+
+impl NavSbas {
+    fn decode(p: &[u8]) -> Option<Self> {
+        if p.len() < 12 {
+            return None;
+        }
+        let count = u1(p, 8) as usize;
+        if p.len() < 12 + 12 * count {
+            return None;
+        }
+        let satellites = (0..count)
+            .map(|i| {
+                let o = 12 + 12 * i;
+                NavSbasSv {
+                    id: nmea::satellite_id("GN", u1(p, o) as u32),
+                    udre: u1(p, o + 2),
+                    provider: SbasProvider::from_ubx(i1(p, o + 3)),
+                    services: SbasServices(u1(p, o + 4)),
+                    prc: i2(p, o + 6) as f64 * 0.01,
+                    ic: i2(p, o + 10) as f64 * 0.01,
+                }
+            })
+            .collect();
+        Some(Self {
+            itow_ms: u4(p, 0),
+            geo: u1(p, 4),
+            mode: u1(p, 5),
+            provider: SbasProvider::from_ubx(i1(p, 6)),
+            services: SbasServices(u1(p, 7)),
+            status_flags: u1(p, 9),
+            satellites,
+        })
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.mode != 0
+    }
+
+    pub fn test_mode(&self) -> bool {
+        self.mode == 3
+    }
+
+    /// Whether the receiver uses the SBAS integrity information; `None` when it does not say.
+    pub fn integrity_used(&self) -> Option<bool> {
+        match self.status_flags & 0x03 {
+            1 => Some(false),
+            2 => Some(true),
+            _ => None,
+        }
+    }
+}
+
+/// Jamming state of an RF block, from UBX-MON-RF.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JammingState {
+    Unknown,
+    Ok,
+    Warning,
+    Critical,
+}
+
+impl JammingState {
+    pub fn name(&self) -> &'static str {
+        match self {
+            JammingState::Unknown => "Unknown",
+            JammingState::Ok => "OK",
+            JammingState::Warning => "Warning",
+            JammingState::Critical => "Critical",
+        }
+    }
+}
+
+/// Name of an antenna supervisor state (`antStatus`) in UBX-MON-RF and UBX-MON-HW.
+pub fn antenna_status_name(status: u8) -> &'static str {
+    match status {
+        0 => "Initializing",
+        1 => "Unknown",
+        2 => "OK",
+        3 => "Short",
+        4 => "Open",
+        _ => "Invalid",
+    }
+}
+
+/// Name of an antenna power state (`antPower`) in UBX-MON-RF and UBX-MON-HW.
+pub fn antenna_power_name(power: u8) -> &'static str {
+    match power {
+        0 => "Off",
+        1 => "On",
+        2 => "Unknown",
+        _ => "Invalid",
+    }
+}
+
+/// One RF block from UBX-MON-RF.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RfBlock {
+    pub block_id: u8,
+    pub jamming: JammingState,
+    pub ant_status: u8,
+    pub ant_power: u8,
+    pub post_status: u32,
+    pub noise_per_ms: u16,
+    /// Automatic gain control count, 0 to 8191.
+    pub agc_cnt: u16,
+    /// Continuous wave jamming indicator, 0 (none) to 255 (strong).
+    pub jam_ind: u8,
+    pub ofs_i: i8,
+    pub mag_i: u8,
+    pub ofs_q: i8,
+    pub mag_q: u8,
+}
+
+impl RfBlock {
+    /// AGC as a percentage of its full range.
+    pub fn agc_percent(&self) -> f64 {
+        self.agc_cnt as f64 / 8191.0 * 100.0
+    }
+}
+
+/// UBX-MON-RF: RF information.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonRf {
+    pub blocks: Vec<RfBlock>,
+}
+
+impl MonRf {
+    fn decode(p: &[u8]) -> Option<Self> {
+        if p.len() < 4 {
+            return None;
+        }
+        let n_blocks = u1(p, 1) as usize;
+        if p.len() < 4 + 24 * n_blocks {
+            return None;
+        }
+        let blocks = (0..n_blocks)
+            .map(|i| {
+                let o = 4 + 24 * i;
+                RfBlock {
+                    block_id: u1(p, o),
+                    jamming: match u1(p, o + 1) & 0x03 {
+                        1 => JammingState::Ok,
+                        2 => JammingState::Warning,
+                        3 => JammingState::Critical,
+                        _ => JammingState::Unknown,
+                    },
+                    ant_status: u1(p, o + 2),
+                    ant_power: u1(p, o + 3),
+                    post_status: u4(p, o + 4),
+                    noise_per_ms: u2(p, o + 12),
+                    agc_cnt: u2(p, o + 14),
+                    jam_ind: u1(p, o + 16),
+                    ofs_i: i1(p, o + 17),
+                    mag_i: u1(p, o + 18),
+                    ofs_q: i1(p, o + 19),
+                    mag_q: u1(p, o + 20),
+                }
+            })
+            .collect();
+        Some(Self { blocks })
+    }
+}
+
+// Reads a NUL-padded fixed length string.
+fn fixed_str(p: &[u8]) -> String {
+    let end = p.iter().position(|b| *b == 0).unwrap_or(p.len());
+    String::from_utf8_lossy(&p[..end]).trim().to_owned()
+}
+
+/// UBX-MON-VER: receiver and software version.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonVer {
+    pub sw_version: String,
+    pub hw_version: String,
+    /// Extended version strings, e.g. `PROTVER=18.00` or `GPS;GLO;GAL;BDS`.
+    pub extensions: Vec<String>,
+}
+
+impl MonVer {
+    fn decode(p: &[u8]) -> Option<Self> {
+        if p.len() < 40 {
+            return None;
+        }
+        Some(Self {
+            sw_version: fixed_str(&p[0..30]),
+            hw_version: fixed_str(&p[30..40]),
+            extensions: p[40..].chunks_exact(30).map(fixed_str).collect(),
+        })
+    }
+}
+
+/// UBX-MON-HW: hardware status.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonHw {
+    pub noise_per_ms: u16,
+    pub agc_cnt: u16,
+    pub a_status: u8,
+    pub a_power: u8,
+    pub flags: u8,
+    pub jam_ind: u8,
+}
+
+impl MonHw {
+    fn decode(p: &[u8]) -> Option<Self> {
+        if p.len() < 60 {
+            return None;
+        }
+        Some(Self {
+            noise_per_ms: u2(p, 16),
+            agc_cnt: u2(p, 18),
+            a_status: u1(p, 20),
+            a_power: u1(p, 21),
+            flags: u1(p, 22),
+            jam_ind: u1(p, 45),
+        })
+    }
+
+    pub fn rtc_calibrated(&self) -> bool {
+        self.flags & 0x01 != 0
+    }
+
+    pub fn safe_boot(&self) -> bool {
+        self.flags & 0x02 != 0
+    }
+
+    pub fn xtal_absent(&self) -> bool {
+        self.flags & 0x10 != 0
+    }
+}
+
+/// UBX-MON-HW3: I/O pin status.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonHw3 {
+    pub flags: u8,
+    pub hw_version: String,
+    pub n_pins: u8,
+}
+
+impl MonHw3 {
+    fn decode(p: &[u8]) -> Option<Self> {
+        if p.len() < 22 {
+            return None;
+        }
+        Some(Self {
+            flags: u1(p, 2),
+            hw_version: fixed_str(&p[3..13]),
+            n_pins: u1(p, 1),
+        })
+    }
+
+    pub fn rtc_calibrated(&self) -> bool {
+        self.flags & 0x01 != 0
+    }
+
+    pub fn safe_boot(&self) -> bool {
+        self.flags & 0x02 != 0
+    }
+
+    pub fn xtal_absent(&self) -> bool {
+        self.flags & 0x04 != 0
+    }
+}
+
+/// One RF block from UBX-MON-SPAN.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpanBlock {
+    /// Spectrum in units of 2^-2 dB.
+    pub spectrum: Vec<u8>,
+    pub span_hz: u32,
+    pub res_hz: u32,
+    pub center_hz: u32,
+    pub pga_db: u8,
+}
+
+impl SpanBlock {
+    pub fn spectrum_db(&self) -> Vec<f64> {
+        self.spectrum.iter().map(|v| *v as f64 * 0.25).collect()
+    }
+}
+
+/// UBX-MON-SPAN: signal characteristics (spectrum analyzer).
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonSpan {
+    pub blocks: Vec<SpanBlock>,
+}
+
+impl MonSpan {
+    fn decode(p: &[u8]) -> Option<Self> {
+        if p.len() < 4 {
+            return None;
+        }
+        let n_blocks = u1(p, 1) as usize;
+        if p.len() < 4 + 272 * n_blocks {
+            return None;
+        }
+        let blocks = (0..n_blocks)
+            .map(|i| {
+                let o = 4 + 272 * i;
+                SpanBlock {
+                    spectrum: p[o..o + 256].to_vec(),
+                    span_hz: u4(p, o + 256),
+                    res_hz: u4(p, o + 260),
+                    center_hz: u4(p, o + 264),
+                    pga_db: u1(p, o + 268),
+                }
+            })
+            .collect();
+        Some(Self { blocks })
+    }
+}
+
+/// One signal measurement of UBX-RXM-RAWX.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RawMeasurement {
+    pub id: SatelliteId,
+    /// Signal within the constellation (`sigId`), e.g. 0 for GPS L1C/A or BeiDou B1I D1.
+    pub sig_id: u8,
+    /// GLONASS frequency slot plus 7.
+    pub freq_id: u8,
+    /// Pseudorange in metres.
+    pub pseudorange: f64,
+    /// Carrier phase in cycles.
+    pub carrier_phase: f64,
+    /// Doppler in Hz, positive for approaching satellites.
+    pub doppler: f64,
+    /// Carrier phase lock time in milliseconds.
+    pub lock_time_ms: u16,
+    pub cno: u8,
+    /// Standard deviation estimates of the pseudorange (m), carrier phase (cycles) and
+    /// Doppler (Hz).
+    pub pr_stdev: f64,
+    pub cp_stdev: f64,
+    pub do_stdev: f64,
+    pub trk_stat: u8,
+}
+
+impl RawMeasurement {
+    pub fn pr_valid(&self) -> bool {
+        self.trk_stat & 0x01 != 0
+    }
+
+    pub fn cp_valid(&self) -> bool {
+        self.trk_stat & 0x02 != 0
+    }
+
+    /// Whether the half cycle ambiguity of the carrier phase is resolved.
+    pub fn half_cycle_resolved(&self) -> bool {
+        self.trk_stat & 0x04 != 0
+    }
+}
+
+/// UBX-RXM-RAWX: raw pseudorange, carrier phase and Doppler measurements.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RxmRawx {
+    /// Receiver time of week of the measurements in seconds, GPS time scale.
+    pub rcv_tow: f64,
+    pub week: u16,
+    pub leap_s: i8,
+    pub rec_stat: u8,
+    pub measurements: Vec<RawMeasurement>,
+}
+
+impl RxmRawx {
+    fn decode(p: &[u8]) -> Option<Self> {
+        if p.len() < 16 {
+            return None;
+        }
+        let count = u1(p, 11) as usize;
+        if p.len() < 16 + 32 * count {
+            return None;
+        }
+        let measurements = (0..count)
+            .map(|i| {
+                let o = 16 + 32 * i;
+                RawMeasurement {
+                    id: SatelliteId::new(Constellation::from_ubx(u1(p, o + 20)), u1(p, o + 21)),
+                    sig_id: u1(p, o + 22),
+                    freq_id: u1(p, o + 23),
+                    pseudorange: r8(p, o),
+                    carrier_phase: r8(p, o + 8),
+                    doppler: r4(p, o + 16) as f64,
+                    lock_time_ms: u2(p, o + 24),
+                    cno: u1(p, o + 26),
+                    pr_stdev: 0.01 * (1u32 << (u1(p, o + 27) & 0x0F)) as f64,
+                    cp_stdev: 0.004 * (u1(p, o + 28) & 0x0F) as f64,
+                    do_stdev: 0.002 * (1u32 << (u1(p, o + 29) & 0x0F)) as f64,
+                    trk_stat: u1(p, o + 30),
+                }
+            })
+            .collect();
+        Some(Self {
+            rcv_tow: r8(p, 0),
+            week: u2(p, 8),
+            leap_s: i1(p, 10),
+            rec_stat: u1(p, 12),
+            measurements,
+        })
+    }
+
+    /// Whether the receiver has determined the leap second count.
+    pub fn leap_valid(&self) -> bool {
+        self.rec_stat & 0x01 != 0
+    }
+}
+
+/// UBX-RXM-SFRBX: one subframe or page of a satellite's navigation message, as the data
+/// words the receiver decoded.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RxmSfrbx {
+    pub id: SatelliteId,
+    /// Signal the data came from (`sigId`); zero on receivers that predate it.
+    pub sig_id: u8,
+    pub freq_id: u8,
+    pub channel: u8,
+    pub words: Vec<u32>,
+}
+
+impl RxmSfrbx {
+    fn decode(p: &[u8]) -> Option<Self> {
+        if p.len() < 8 {
+            return None;
+        }
+        let count = u1(p, 4) as usize;
+        if p.len() < 8 + 4 * count {
+            return None;
+        }
+        Some(Self {
+            id: SatelliteId::new(Constellation::from_ubx(u1(p, 0)), u1(p, 1)),
+            sig_id: u1(p, 2),
+            freq_id: u1(p, 3),
+            channel: u1(p, 5),
+            words: (0..count).map(|i| u4(p, 8 + 4 * i)).collect(),
+        })
+    }
+}
+
+/// The UBX messages this application understands.
+#[derive(Debug, Clone, PartialEq)]
+pub enum UbxMessage {
+    NavTimeGps(NavTimeGps),
+    NavTimeUtc(NavTimeUtc),
+    NavTimeLs(NavTimeLs),
+    NavClock(NavClock),
+    TimTp(TimTp),
+    TimTm2(TimTm2),
+    NavPvt(NavPvt),
+    NavStatus(NavStatus),
+    NavSbas(NavSbas),
+    NavSat(NavSat),
+    NavSvin(NavSvin),
+    NavRelPosNed(NavRelPosNed),
+    NavCov(NavCov),
+    RxmRawx(RxmRawx),
+    RxmSfrbx(RxmSfrbx),
+    MonRf(MonRf),
+    MonSpan(MonSpan),
+    MonVer(MonVer),
+    MonHw(MonHw),
+    MonHw3(MonHw3),
+}
+
+impl UbxMessage {
+    /// Decodes a frame into a known message, `None` for unsupported or malformed frames.
+    pub fn decode(frame: &UbxFrame) -> Option<Self> {
+        let p = &frame.payload;
+        match (frame.class, frame.id) {
+            (CLASS_NAV, ID_NAV_TIMEGPS) => NavTimeGps::decode(p).map(UbxMessage::NavTimeGps),
+            (CLASS_NAV, ID_NAV_TIMEUTC) => NavTimeUtc::decode(p).map(UbxMessage::NavTimeUtc),
+            (CLASS_NAV, ID_NAV_TIMELS) => NavTimeLs::decode(p).map(UbxMessage::NavTimeLs),
+            (CLASS_NAV, ID_NAV_CLOCK) => NavClock::decode(p).map(UbxMessage::NavClock),
+            (CLASS_NAV, ID_NAV_PVT) => NavPvt::decode(p).map(UbxMessage::NavPvt),
+            (CLASS_NAV, ID_NAV_STATUS) => NavStatus::decode(p).map(UbxMessage::NavStatus),
+            (CLASS_NAV, ID_NAV_SAT) => NavSat::decode(p).map(UbxMessage::NavSat),
+            (CLASS_NAV, ID_NAV_SBAS) => NavSbas::decode(p).map(UbxMessage::NavSbas),
+            (CLASS_NAV, ID_NAV_SVIN) => NavSvin::decode(p).map(UbxMessage::NavSvin),
+            (CLASS_NAV, ID_NAV_RELPOSNED) => NavRelPosNed::decode(p).map(UbxMessage::NavRelPosNed),
+            (CLASS_NAV, ID_NAV_COV) => NavCov::decode(p).map(UbxMessage::NavCov),
+            (CLASS_RXM, ID_RXM_RAWX) => RxmRawx::decode(p).map(UbxMessage::RxmRawx),
+            (CLASS_RXM, ID_RXM_SFRBX) => RxmSfrbx::decode(p).map(UbxMessage::RxmSfrbx),
+            (CLASS_MON, ID_MON_RF) => MonRf::decode(p).map(UbxMessage::MonRf),
+            (CLASS_MON, ID_MON_SPAN) => MonSpan::decode(p).map(UbxMessage::MonSpan),
+            (CLASS_MON, ID_MON_VER) => MonVer::decode(p).map(UbxMessage::MonVer),
+            (CLASS_MON, ID_MON_HW) => MonHw::decode(p).map(UbxMessage::MonHw),
+            (CLASS_MON, ID_MON_HW3) => MonHw3::decode(p).map(UbxMessage::MonHw3),
+            (CLASS_TIM, ID_TIM_TP) => TimTp::decode(p).map(UbxMessage::TimTp),
+            (CLASS_TIM, ID_TIM_TM2) => TimTm2::decode(p).map(UbxMessage::TimTm2),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_relposned_versions() {
+        let decode = |payload: Vec<u8>| {
+            UbxMessage::decode(&UbxFrame::new(CLASS_NAV, ID_NAV_RELPOSNED, payload))
+        };
+        // A poll echo and truncated frames are rejected, not read past their end.
+        assert_eq!(decode(Vec::new()), None);
+        assert_eq!(decode(vec![1; 20]), None);
+        assert_eq!(decode(vec![0; 39]), None);
+
+        let mut p = vec![0; 64];
+        p[0] = 1;
+        p[8..12].copy_from_slice(&150i32.to_le_bytes());
+        p[32] = 25;
+        p[24..28].copy_from_slice(&9_000_000i32.to_le_bytes());
+        p[60..64].copy_from_slice(&0x07u32.to_le_bytes());
+        let Some(UbxMessage::NavRelPosNed(rel)) = decode(p) else {
+            panic!("RELPOSNED not decoded");
+        };
+        assert!((rel.rel_pos[0] - 1.5025).abs() < 1e-9);
+        assert!((rel.heading.unwrap() - 90.0).abs() < 1e-9);
+        assert!(rel.gnss_fix_ok() && rel.diff_soln() && rel.rel_pos_valid());
+    }
+
+    #[test]
+    fn decodes_tim_tp() {
+        let decode =
+            |payload: Vec<u8>| UbxMessage::decode(&UbxFrame::new(CLASS_TIM, ID_TIM_TP, payload));
+        assert_eq!(decode(vec![0; 15]), None);
+
+        let mut p = vec![0; 16];
+        p[0..4].copy_from_slice(&345_600_000u32.to_le_bytes());
+        p[4..8].copy_from_slice(&0x8000_0000u32.to_le_bytes());
+        p[8..12].copy_from_slice(&(-1250i32).to_le_bytes());
+        p[12..14].copy_from_slice(&2300u16.to_le_bytes());
+        p[14] = 0x01;
+        let Some(UbxMessage::TimTp(tp)) = decode(p.clone()) else {
+            panic!("TIM-TP not decoded");
+        };
+        assert_eq!(tp.week, 2300);
+        assert_eq!(tp.q_err_ps, -1250);
+        assert!((tp.tow() - 345_600.000_5).abs() < 1e-9);
+        assert!(tp.utc_base() && !tp.q_err_invalid());
+
+        p[14] = 0x10;
+        let Some(UbxMessage::TimTp(tp)) = decode(p) else {
+            panic!("TIM-TP not decoded");
+        };
+        assert!(!tp.utc_base() && tp.q_err_invalid());
+    }
+
+    #[test]
+    fn decodes_tim_tm2() {
+        let decode =
+            |payload: Vec<u8>| UbxMessage::decode(&UbxFrame::new(CLASS_TIM, ID_TIM_TM2, payload));
+        assert_eq!(decode(vec![0; 27]), None);
+
+        let mut p = vec![0; 28];
+        p[0] = 1;
+        p[1] = 0x80 | 0x40;
+        p[2..4].copy_from_slice(&7u16.to_le_bytes());
+        p[4..6].copy_from_slice(&2300u16.to_le_bytes());
+        p[6..8].copy_from_slice(&2299u16.to_le_bytes());
+        p[8..12].copy_from_slice(&1_000u32.to_le_bytes());
+        p[12..16].copy_from_slice(&250u32.to_le_bytes());
+        p[16..20].copy_from_slice(&900u32.to_le_bytes());
+        p[20..24].copy_from_slice(&500_000u32.to_le_bytes());
+        p[24..28].copy_from_slice(&20u32.to_le_bytes());
+        let Some(UbxMessage::TimTm2(tm)) = decode(p.clone()) else {
+            panic!("TIM-TM2 not decoded");
+        };
+        assert_eq!((tm.channel, tm.count, tm.wn_r, tm.wn_f), (1, 7, 2300, 2299));
+        assert_eq!(tm.acc_est_ns, 20);
+        assert!((tm.tow_rising() - 1.000_000_25).abs() < 1e-12);
+        assert!((tm.tow_falling() - 0.9005).abs() < 1e-12);
+        assert!(tm.new_rising_edge() && !tm.new_falling_edge() && tm.time_valid());
+
+        p[1] = 0x04;
+        let Some(UbxMessage::TimTm2(tm)) = decode(p) else {
+            panic!("TIM-TM2 not decoded");
+        };
+        assert!(!tm.new_rising_edge() && tm.new_falling_edge() && !tm.time_valid());
+    }
+
+    #[test]
+    fn decodes_nav_clock() {
+        let frame = |itow_ms: u32, bias_ns: i32| {
+            let mut p = vec![0; 20];
+            p[0..4].copy_from_slice(&itow_ms.to_le_bytes());
+            p[4..8].copy_from_slice(&bias_ns.to_le_bytes());
+            p[8..12].copy_from_slice(&(-35i32).to_le_bytes());
+            p[12..16].copy_from_slice(&12u32.to_le_bytes());
+            p[16..20].copy_from_slice(&450u32.to_le_bytes());
+            UbxFrame::new(CLASS_NAV, ID_NAV_CLOCK, p)
+        };
+        assert_eq!(
+            UbxMessage::decode(&UbxFrame::new(CLASS_NAV, ID_NAV_CLOCK, vec![0; 19])),
+            None
+        );
+        let Some(UbxMessage::NavClock(clock)) = UbxMessage::decode(&frame(1000, -2_500_000)) else {
+            panic!("NAV-CLOCK not decoded");
+        };
+        assert_eq!(
+            (clock.itow_ms, clock.clk_b_ns, clock.clk_d_ns_s),
+            (1000, -2_500_000, -35)
+        );
+        assert_eq!((clock.t_acc_ns, clock.f_acc_ps_s), (12, 450));
+
+        // The clock bias is what the stability analysis runs on.
+        let mut state = crate::receiver::ReceiverState::default();
+        let host = chrono::Utc::now();
+        for (itow_ms, bias_ns) in [(1000, 100), (2000, 135), (3000, 170)] {
+            let message = UbxMessage::decode(&frame(itow_ms, bias_ns)).unwrap();
+            state.apply(&crate::receiver::GnssMessage::Ubx(message), host);
+        }
+        let (phase, tau0) = state.clock_phase().unwrap();
+        assert_eq!(tau0, 1.0);
+        assert_eq!(phase.len(), 3);
+        assert!((phase[2] - 170e-9).abs() < 1e-18);
+    }
+
+    #[test]
+    fn decodes_mon_span_blocks() {
+        let block = |level: u8, center_hz: u32, pga_db: u8| {
+            let mut b = vec![level; 256];
+            b[255] = 200;
+            b.extend_from_slice(&128_000_000u32.to_le_bytes());
+            b.extend_from_slice(&500_000u32.to_le_bytes());
+            b.extend_from_slice(&center_hz.to_le_bytes());
+            b.extend_from_slice(&[pga_db, 0, 0, 0]);
+            b
+        };
+        let mut p = vec![0, 2, 0, 0];
+        p.extend(block(100, 1_583_400_000, 54));
+        p.extend(block(80, 1_228_000_000, 38));
+        let decode = |payload: &[u8]| {
+            UbxMessage::decode(&UbxFrame::new(CLASS_MON, ID_MON_SPAN, payload.to_vec()))
+        };
+        // The block count decides the length; a short second block is not read past the end.
+        assert_eq!(decode(&p[..4 + 272 + 271]), None);
+        assert_eq!(decode(&p[..3]), None);
+
+        let Some(UbxMessage::MonSpan(span)) = decode(&p) else {
+            panic!("MON-SPAN not decoded");
+        };
+        assert_eq!(span.blocks.len(), 2);
+        let [l1, l2] = [&span.blocks[0], &span.blocks[1]];
+        assert_eq!(l1.spectrum.len(), 256);
+        assert_eq!((l1.span_hz, l1.res_hz), (128_000_000, 500_000));
+        assert_eq!((l1.center_hz, l1.pga_db), (1_583_400_000, 54));
+        assert_eq!((l2.center_hz, l2.pga_db), (1_228_000_000, 38));
+        let db = l2.spectrum_db();
+        assert_eq!((db[0], db[255]), (20.0, 50.0));
+    }
+
+    #[test]
+    fn decodes_rxm_rawx() {
+        let measurement = |gnss_id: u8, sv_id: u8, pseudorange: f64, std_dev: [u8; 3]| {
+            let mut m = vec![0; 32];
+            m[0..8].copy_from_slice(&pseudorange.to_le_bytes());
+            m[8..16].copy_from_slice(&(-1.25e8f64).to_le_bytes());
+            m[16..20].copy_from_slice(&(-812.5f32).to_le_bytes());
+            m[20] = gnss_id;
+            m[21] = sv_id;
+            m[22] = 1;
+            m[23] = 7;
+            m[24..26].copy_from_slice(&64_500u16.to_le_bytes());
+            m[26] = 42;
+            m[27..30].copy_from_slice(&std_dev);
+            m[30] = 0x07;
+            m
+        };
+        let mut p = vec![0; 16];
+        p[0..8].copy_from_slice(&345_600.5f64.to_le_bytes());
+        p[8..10].copy_from_slice(&2300u16.to_le_bytes());
+        p[10] = 18;
+        p[11] = 2;
+        p[12] = 0x01;
+        p.extend(measurement(0, 5, 21_000_123.25, [3, 5, 4]));
+        // Only the low nibbles count.
+        p.extend(measurement(2, 11, 24_500_000.5, [0xF0, 0xF0, 0xF0]));
+        let decode = |payload: &[u8]| {
+            UbxMessage::decode(&UbxFrame::new(CLASS_RXM, ID_RXM_RAWX, payload.to_vec()))
+        };
+        // numMeas decides the length; a short last measurement is not read past the end.
+        assert_eq!(decode(&p[..p.len() - 1]), None);
+        assert_eq!(decode(&p[..15]), None);
+
+        let Some(UbxMessage::RxmRawx(raw)) = decode(&p) else {
+            panic!("RXM-RAWX not decoded");
+        };
+        assert_eq!((raw.rcv_tow, raw.week, raw.leap_s), (345_600.5, 2300, 18));
+        assert!(raw.leap_valid());
+        assert_eq!(raw.measurements.len(), 2);
+        let gps = &raw.measurements[0];
+        assert_eq!(gps.id, SatelliteId::new(Constellation::Gps, 5));
+        assert_eq!((gps.sig_id, gps.freq_id), (1, 7));
+        assert_eq!(gps.pseudorange, 21_000_123.25);
+        assert_eq!(gps.carrier_phase, -1.25e8);
+        assert_eq!(gps.doppler, -812.5);
+        assert_eq!((gps.lock_time_ms, gps.cno), (64_500, 42));
+        assert!((gps.pr_stdev - 0.08).abs() < 1e-12);
+        assert!((gps.cp_stdev - 0.02).abs() < 1e-12);
+        assert!((gps.do_stdev - 0.032).abs() < 1e-12);
+        assert!(gps.pr_valid() && gps.cp_valid());
+
+        let galileo = &raw.measurements[1];
+        assert_eq!(galileo.id, SatelliteId::new(Constellation::Galileo, 11));
+        assert_eq!(galileo.pseudorange, 24_500_000.5);
+        assert_eq!(
+            (galileo.pr_stdev, galileo.cp_stdev, galileo.do_stdev),
+            (0.01, 0.0, 0.002)
+        );
+    }
+}
@@ -0,0 +1,250 @@
+use crate::ephemeris::NavigationData;
+use crate::geodesy;
+use crate::gnss::{Satellite, SatelliteId};
+
+/// Size of the sky cells of the obstruction map and other azimuth/elevation grids, in
+/// degrees.
+pub const AZIMUTH_STEP: f64 = 10.0;
+pub const ELEVATION_STEP: f64 = 5.0;
+const AZIMUTH_CELLS: usize = (360.0 / AZIMUTH_STEP) as usize;
+const ELEVATION_CELLS: usize = (90.0 / ELEVATION_STEP) as usize;
+pub const SKY_CELLS: usize = AZIMUTH_CELLS * ELEVATION_CELLS;
+
+/// Index of the sky cell an azimuth and elevation in degrees fall in.
+pub fn sky_cell(azimuth: f64, elevation: f64) -> usize {
+    let a = ((azimuth.rem_euclid(360.0) / AZIMUTH_STEP) as usize).min(AZIMUTH_CELLS - 1);
+    let e = ((elevation.max(0.0) / ELEVATION_STEP) as usize).min(ELEVATION_CELLS - 1);
+    e * AZIMUTH_CELLS + a
+}
+
+/// Lower azimuth and elevation bounds of a sky cell.
+pub fn sky_cell_origin(index: usize) -> (f64, f64) {
+    (
+        (index % AZIMUTH_CELLS) as f64 * AZIMUTH_STEP,
+        (index / AZIMUTH_CELLS) as f64 * ELEVATION_STEP,
+    )
+}
+
+/// Distance in metres the receiver may move before the obstruction map is for another site.
+pub const SITE_RADIUS: f64 = 50.0;
+/// Seconds from its reference time an almanac is still used for prediction.
+const ALMANAC_AGE: f64 = 7.0 * 86_400.0;
+
+/// Orbit data a predicted position was computed from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrbitSource {
+    Ephemeris,
+    Almanac,
+}
+
+/// A satellite predicted above the horizon, and whether the receiver tracks it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Visibility {
+    pub id: SatelliteId,
+    /// Degrees, clockwise from north.
+    pub azimuth: f64,
+    pub elevation: f64,
+    pub source: OrbitSource,
+    pub tracked: bool,
+}
+
+/// Azimuth and elevation in degrees of the ECEF `position` seen from `receiver` (latitude,
+/// longitude, height).
+pub fn look_angles(position: [f64; 3], receiver: (f64, f64, f64)) -> (f64, f64) {
+    let [e, n, u] = geodesy::ecef_to_enu(position, receiver);
+    (
+        e.atan2(n).to_degrees().rem_euclid(360.0),
+        u.atan2(e.hypot(n)).to_degrees(),
+    )
+}
+
+/// The satellites above the horizon at `time` (seconds since the GPS epoch) seen from
+/// `receiver`, from an ephemeris valid then or else a recent almanac, with whether they are
+/// among the tracked `satellites`.
+pub fn predict(
+    nav: &NavigationData,
+    satellites: &[Satellite],
+    receiver: (f64, f64, f64),
+    time: f64,
+) -> Vec<Visibility> {
+    let mut ids: Vec<SatelliteId> = nav.ephemerides.keys().copied().collect();
+    ids.extend(
+        nav.almanacs
+            .keys()
+            .filter(|id| !nav.ephemerides.contains_key(id)),
+    );
+    ids.sort();
+
+    ids.into_iter()
+        .filter_map(|id| {
+            let ephemeris = nav.ephemerides.get(&id).and_then(|list| {
+                list.iter()
+                    .filter(|e| e.valid_at(time))
+                    .min_by(|a, b| (a.toe - time).abs().total_cmp(&(b.toe - time).abs()))
+            });
+            let (position, source) = match ephemeris {
+                Some(eph) => (eph.position(time), OrbitSource::Ephemeris),
+                None => {
+                    let almanac = nav
+                        .almanacs
+                        .get(&id)
+                        .filter(|a| (time - a.toa).abs() < ALMANAC_AGE)?;
+                    (almanac.position(time), OrbitSource::Almanac)
+                }
+            };
+            let (azimuth, elevation) = look_angles(position, receiver);
+            (elevation >= 0.0).then(|| Visibility {
+                id,
+                azimuth,
+                elevation,
+                source,
+                tracked: satellites.iter().any(|s| s.id == id && s.cno > 0),
+            })
+        })
+        .collect()
+}
+
+/// One cell of the obstruction map.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SkyCell {
+    /// Lower azimuth and elevation bounds in degrees.
+    pub azimuth: f64,
+    pub elevation: f64,
+    /// Number of predictions in the cell and how many of them were tracked.
+    pub predicted: u32,
+    pub tracked: u32,
+}
+
+impl SkyCell {
+    pub fn tracked_fraction(&self) -> f64 {
+        self.tracked as f64 / self.predicted as f64
+    }
+}
+
+/// How often satellites predicted in each part of the sky were actually tracked at a site;
+/// cells where they mostly were not are obstructed.
+#[derive(Debug, Clone)]
+pub struct ObstructionMap {
+    /// ECEF position the map was started at.
+    pub site: Option<[f64; 3]>,
+    predicted: Vec<u32>,
+    tracked: Vec<u32>,
+    pub epochs: u32,
+}
+
+impl Default for ObstructionMap {
+    fn default() -> Self {
+        Self {
+            site: None,
+            predicted: vec![0; SKY_CELLS],
+            tracked: vec![0; SKY_CELLS],
+            epochs: 0,
+        }
+    }
+}
+
+impl ObstructionMap {
+    /// Adds one epoch of predictions made at `receiver` (ECEF), starting over if the receiver
+    /// has moved to another site.
+    pub fn add(&mut self, receiver: [f64; 3], visibility: &[Visibility]) {
+        let moved = self.site.is_some_and(|site| {
+            (0..3)
+                .map(|i| (site[i] - receiver[i]).powi(2))
+                .sum::<f64>()
+                .sqrt()
+                > SITE_RADIUS
+        });
+        if moved || self.site.is_none() {
+            *self = Self {
+                site: Some(receiver),
+                ..Self::default()
+            };
+        }
+        for v in visibility {
+            let i = sky_cell(v.azimuth, v.elevation);
+            self.predicted[i] += 1;
+            if v.tracked {
+                self.tracked[i] += 1;
+            }
+        }
+        self.epochs += 1;
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// The cells with at least one prediction.
+    pub fn cells(&self) -> Vec<SkyCell> {
+        (0..self.predicted.len())
+            .filter(|&i| self.predicted[i] > 0)
+            .map(|i| {
+                let (azimuth, elevation) = sky_cell_origin(i);
+                SkyCell {
+                    azimuth,
+                    elevation,
+                    predicted: self.predicted[i],
+                    tracked: self.tracked[i],
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gnss::Constellation;
+    use crate::time_scales::SECONDS_PER_WEEK;
+
+    #[test]
+    fn predicts_and_maps_untracked_satellites() {
+        let receiver = geodesy::geodetic_to_ecef(52.0, 5.0, 50.0);
+        let site = (52.0, 5.0, 50.0);
+        let time = 2297.0 * SECONDS_PER_WEEK as f64 + 200_000.0;
+        let (nav, observations) =
+            crate::spp::tests::simulate(receiver, time, &[(Constellation::Gps, 0.0)]);
+        let mut satellites: Vec<Satellite> = observations
+            .iter()
+            .map(|o| Satellite {
+                id: o.id,
+                cno: 45,
+                elevation: None,
+                azimuth: None,
+                used: true,
+            })
+            .collect();
+        let blocked = satellites[0].id;
+        satellites[0].cno = 0;
+
+        let visibility = predict(&nav, &satellites, site, time);
+        assert!(visibility.len() >= observations.len());
+        assert!(visibility
+            .iter()
+            .all(|v| v.source == OrbitSource::Ephemeris));
+        let missing: Vec<_> = visibility
+            .iter()
+            .filter(|v| !v.tracked && v.elevation > 15.0)
+            .collect();
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].id, blocked);
+
+        let mut map = ObstructionMap::default();
+        map.add(receiver, &visibility);
+        map.add(receiver, &visibility);
+        let cell = map
+            .cells()
+            .into_iter()
+            .find(|c| {
+                (c.azimuth..c.azimuth + AZIMUTH_STEP).contains(&missing[0].azimuth)
+                    && (c.elevation..c.elevation + ELEVATION_STEP).contains(&missing[0].elevation)
+            })
+            .unwrap();
+        assert_eq!((cell.predicted - cell.tracked, map.epochs), (2, 2));
+
+        // Another site starts a new map.
+        let elsewhere = geodesy::geodetic_to_ecef(52.001, 5.0, 50.0);
+        map.add(elsewhere, &visibility);
+        assert_eq!(map.epochs, 1);
+    }
+}
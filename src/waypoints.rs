@@ -0,0 +1,427 @@
+use std::collections::VecDeque;
+use std::io::{self, ErrorKind};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::geodesy::{self, Geodesic};
+
+/// Crossings kept in the log, oldest dropped first.
+pub const MAX_CROSSINGS: usize = 1_000;
+/// Least distance (m) past a fence boundary that counts as crossing it, so a position
+/// wandering along the boundary does not raise an alarm every epoch. Positions less
+/// accurate than this must be their accuracy past it.
+pub const MIN_FENCE_MARGIN: f64 = 3.0;
+
+/// A named position to navigate to.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Waypoint {
+    pub name: String,
+    /// Degrees, positive north.
+    pub latitude: f64,
+    /// Degrees, positive east.
+    pub longitude: f64,
+    /// Height above the WGS84 ellipsoid in metres.
+    pub height: Option<f64>,
+}
+
+/// Replaces the predefined XML entities.
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Value of `name="..."` (or single quoted) in an XML start tag.
+fn attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let mut rest = tag;
+    while let Some(at) = rest.find(name) {
+        let after = rest[at + name.len()..].trim_start();
+        let preceded = rest[..at].ends_with(char::is_whitespace);
+        if let (true, Some(value)) = (preceded, after.strip_prefix('=')) {
+            let value = value.trim_start();
+            let quote = value.chars().next().filter(|c| matches!(c, '"' | '\''))?;
+            let value = &value[quote.len_utf8()..];
+            return value.find(quote).map(|end| &value[..end]);
+        }
+        rest = &rest[at + name.len()..];
+    }
+    None
+}
+
+/// Text of the first `<name>...</name>` element.
+fn element<'a>(body: &'a str, name: &str) -> Option<&'a str> {
+    let start = body.find(&format!("<{}>", name))? + name.len() + 2;
+    let end = body[start..].find(&format!("</{}>", name))?;
+    Some(body[start..start + end].trim())
+}
+
+/// Reads the waypoints (`wpt`) and route points (`rtept`) of a GPX file. Unnamed points are
+/// numbered.
+pub fn parse_gpx(text: &str) -> io::Result<Vec<Waypoint>> {
+    let invalid = |message: &str| io::Error::new(ErrorKind::InvalidData, message.to_owned());
+    if !text.contains("<gpx") {
+        return Err(invalid("not a GPX file"));
+    }
+
+    let mut waypoints = Vec::new();
+    for tag in ["wpt", "rtept"] {
+        let open = format!("<{}", tag);
+        let close = format!("</{}>", tag);
+        let mut rest = text;
+        while let Some(at) = rest.find(&open) {
+            rest = &rest[at + open.len()..];
+            if !rest.starts_with(char::is_whitespace) {
+                continue;
+            }
+            let tag_end = rest.find('>').ok_or_else(|| invalid("unterminated tag"))?;
+            let start_tag = &rest[..tag_end];
+            let body = match start_tag.ends_with('/') {
+                true => "",
+                false => {
+                    let body = &rest[tag_end..];
+                    &body[..body.find(&close).unwrap_or(body.len())]
+                }
+            };
+            let coordinate = |name: &str| {
+                attribute(start_tag, name)
+                    .and_then(|v| v.trim().parse::<f64>().ok())
+                    .ok_or_else(|| invalid(&format!("{} without a valid {}", tag, name)))
+            };
+            waypoints.push(Waypoint {
+                name: element(body, "name")
+                    .map(unescape)
+                    .unwrap_or_else(|| format!("WPT{:03}", waypoints.len() + 1)),
+                latitude: coordinate("lat")?,
+                longitude: coordinate("lon")?,
+                height: element(body, "ele").and_then(|v| v.parse().ok()),
+            });
+        }
+    }
+    Ok(waypoints)
+}
+
+/// The area a geofence encloses.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum FenceShape {
+    Circle {
+        latitude: f64,
+        longitude: f64,
+        /// Metres along the ellipsoid.
+        radius: f64,
+    },
+    /// Vertices as latitude/longitude in degrees, implicitly closed. Edges are straight in
+    /// latitude and longitude, which is close enough for fences of a few kilometres.
+    Polygon(Vec<(f64, f64)>),
+}
+
+impl FenceShape {
+    pub fn contains(&self, latitude: f64, longitude: f64) -> bool {
+        match self {
+            FenceShape::Circle {
+                latitude: lat,
+                longitude: lon,
+                radius,
+            } => geodesy::geodesic_inverse(*lat, *lon, latitude, longitude)
+                .is_some_and(|g| g.distance <= *radius),
+            FenceShape::Polygon(vertices) => {
+                // Even-odd rule: count edges crossed by a ray going east.
+                let mut inside = false;
+                for (i, &(lat1, lon1)) in vertices.iter().enumerate() {
+                    let (lat2, lon2) = vertices[(i + 1) % vertices.len()];
+                    if (lat1 > latitude) != (lat2 > latitude) {
+                        let crossing = lon1 + (latitude - lat1) / (lat2 - lat1) * (lon2 - lon1);
+                        if longitude < crossing {
+                            inside = !inside;
+                        }
+                    }
+                }
+                inside
+            }
+        }
+    }
+
+    /// Distance in metres from a position to the boundary, negative inside.
+    pub fn boundary_distance(&self, latitude: f64, longitude: f64) -> f64 {
+        match self {
+            FenceShape::Circle {
+                latitude: lat,
+                longitude: lon,
+                radius,
+            } => geodesy::geodesic_inverse(*lat, *lon, latitude, longitude)
+                .map_or(f64::INFINITY, |g| g.distance - radius),
+            FenceShape::Polygon(vertices) => {
+                // Nearest edge, with the vertices in local east/north metres.
+                let local = |&(lat, lon): &(f64, f64)| {
+                    let ecef = geodesy::geodetic_to_ecef(lat, lon, 0.0);
+                    let [e, n, _] = geodesy::ecef_to_enu(ecef, (latitude, longitude, 0.0));
+                    (e, n)
+                };
+                let points: Vec<(f64, f64)> = vertices.iter().map(local).collect();
+                let distance = (0..points.len())
+                    .map(|i| {
+                        let (a, b) = (points[i], points[(i + 1) % points.len()]);
+                        let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+                        let length = dx * dx + dy * dy;
+                        let t = match length > 0.0 {
+                            true => (-(a.0 * dx + a.1 * dy) / length).clamp(0.0, 1.0),
+                            false => 0.0,
+                        };
+                        (a.0 + t * dx).hypot(a.1 + t * dy)
+                    })
+                    .fold(f64::INFINITY, f64::min);
+                match self.contains(latitude, longitude) {
+                    true => -distance,
+                    false => distance,
+                }
+            }
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            FenceShape::Circle { radius, .. } => format!("Circle, {:.0} m radius", radius),
+            FenceShape::Polygon(vertices) => format!("Polygon, {} vertices", vertices.len()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Geofence {
+    pub name: String,
+    pub shape: FenceShape,
+    pub alarm_on_entry: bool,
+    pub alarm_on_exit: bool,
+    /// Whether the last position was inside; `None` until the first position.
+    #[serde(skip)]
+    pub inside: Option<bool>,
+}
+
+impl Geofence {
+    pub fn new(name: &str, shape: FenceShape) -> Self {
+        Self {
+            name: name.to_owned(),
+            shape,
+            alarm_on_entry: true,
+            alarm_on_exit: true,
+            inside: None,
+        }
+    }
+}
+
+/// A position crossing a geofence boundary.
+#[derive(Debug, Clone)]
+pub struct FenceCrossing {
+    pub time: DateTime<Utc>,
+    pub fence: String,
+    pub entered: bool,
+}
+
+impl FenceCrossing {
+    pub fn message(&self) -> String {
+        match self.entered {
+            true => format!("Entered geofence {}", self.fence),
+            false => format!("Left geofence {}", self.fence),
+        }
+    }
+}
+
+/// Waypoints, the navigation target and geofences, kept across sessions.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Navigator {
+    pub waypoints: Vec<Waypoint>,
+    /// Index into `waypoints` of the waypoint distance and bearing are shown to.
+    pub target: Option<usize>,
+    pub geofences: Vec<Geofence>,
+    /// Boundary crossings this session, oldest first.
+    #[serde(skip)]
+    pub crossings: VecDeque<FenceCrossing>,
+}
+
+impl Navigator {
+    pub fn remove_waypoint(&mut self, index: usize) {
+        self.waypoints.remove(index);
+        self.target = match self.target {
+            Some(t) if t == index => None,
+            Some(t) if t > index => Some(t - 1),
+            t => t,
+        };
+    }
+
+    /// Geodesic from a position to the target waypoint.
+    pub fn to_target(&self, latitude: f64, longitude: f64) -> Option<(&Waypoint, Geodesic)> {
+        let target = self.waypoints.get(self.target?)?;
+        let geodesic =
+            geodesy::geodesic_inverse(latitude, longitude, target.latitude, target.longitude)?;
+        Some((target, geodesic))
+    }
+
+    /// Tests a new position against every geofence, logging boundary crossings. Returns the
+    /// crossings that have an alarm enabled. The first position after start-up only sets the
+    /// initial state. A fence only counts as crossed once the position is `h_acc`, or at
+    /// least `MIN_FENCE_MARGIN`, past its boundary.
+    pub fn update(
+        &mut self,
+        latitude: f64,
+        longitude: f64,
+        h_acc: Option<f64>,
+        host: DateTime<Utc>,
+    ) -> Vec<FenceCrossing> {
+        let margin = h_acc.unwrap_or(0.0).max(MIN_FENCE_MARGIN);
+        let mut alarms = Vec::new();
+        for fence in &mut self.geofences {
+            let distance = fence.shape.boundary_distance(latitude, longitude);
+            let inside = match fence.inside {
+                None => {
+                    fence.inside = Some(distance <= 0.0);
+                    continue;
+                }
+                Some(true) if distance > margin => false,
+                Some(false) if distance < -margin => true,
+                Some(_) => continue,
+            };
+            fence.inside = Some(inside);
+            let crossing = FenceCrossing {
+                time: host,
+                fence: fence.name.clone(),
+                entered: inside,
+            };
+            if (inside && fence.alarm_on_entry) || (!inside && fence.alarm_on_exit) {
+                alarms.push(crossing.clone());
+            }
+            if self.crossings.len() == MAX_CROSSINGS {
+                self.crossings.pop_front();
+            }
+            self.crossings.push_back(crossing);
+        }
+        alarms
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_gpx_points() {
+        let gpx = r#"<?xml version="1.0"?>
+<gpx version="1.1" creator="test">
+  <wpt lat="52.1" lon='4.5'><ele>12.5</ele><name>Mast &amp; dish</name></wpt>
+  <wpt lon="5.0" lat="-1.25"/>
+  <rte><rtept lat="1" lon="2"><name>Turn</name></rtept></rte>
+</gpx>"#;
+        let points = parse_gpx(gpx).unwrap();
+        assert_eq!(points.len(), 3);
+        assert_eq!(points[0].name, "Mast & dish");
+        assert_eq!(points[0].height, Some(12.5));
+        assert_eq!((points[1].latitude, points[1].longitude), (-1.25, 5.0));
+        assert_eq!(points[1].name, "WPT002");
+        assert_eq!(points[2].name, "Turn");
+        assert!(parse_gpx("<gpx><wpt lat=\"x\" lon=\"1\"/></gpx>").is_err());
+        assert!(parse_gpx("lat,lon").is_err());
+    }
+
+    #[test]
+    fn rejects_malformed_gpx() {
+        assert!(parse_gpx("<gpx><wpt lat=\"1\" lon=\"2\"").is_err());
+        assert!(parse_gpx("<gpx><wpt lat=«1» lon=\"2\"/></gpx>").is_err());
+        assert!(parse_gpx("<gpx><wpt lat=é1é lon=\"2\"/></gpx>").is_err());
+        assert!(parse_gpx("<gpx><wpt </wpt> lat=\"1\" lon=\"2\"></gpx>").is_err());
+        let points = parse_gpx("<gpx><wpt lat=\"1\" lon=\"2\"></wpt></gpx>").unwrap();
+        assert_eq!(points[0].name, "WPT001");
+    }
+
+    #[test]
+    fn raises_fence_crossings() {
+        let square = vec![(52.0, 4.0), (52.0, 4.1), (52.1, 4.1), (52.1, 4.0)];
+        let mut nav = Navigator::default();
+        nav.geofences
+            .push(Geofence::new("Field", FenceShape::Polygon(square)));
+        let mut circle = Geofence::new(
+            "Yard",
+            FenceShape::Circle {
+                latitude: 52.05,
+                longitude: 4.05,
+                radius: 100.0,
+            },
+        );
+        circle.alarm_on_exit = false;
+        nav.geofences.push(circle);
+        let host = Utc::now();
+
+        // Starting outside both raises nothing.
+        assert!(nav.update(51.9, 4.05, None, host).is_empty());
+        let alarms = nav.update(52.01, 4.05, None, host);
+        assert_eq!(alarms.len(), 1);
+        assert!(alarms[0].entered && alarms[0].fence == "Field");
+        // About 70 m from the centre.
+        assert_eq!(nav.update(52.0505, 4.0505, None, host).len(), 1);
+        // Leaving the circle is logged but does not alarm.
+        assert!(nav.update(52.06, 4.05, None, host).is_empty());
+        assert_eq!(nav.crossings.len(), 3);
+        assert!(!nav.crossings[2].entered);
+    }
+
+    #[test]
+    fn ignores_jitter_on_the_boundary() {
+        // Metres north of the equator, close enough for a few hundred metres.
+        let north = |metres: f64| metres / 110_574.0;
+        let square = vec![(0.0, -0.01), (0.0, 0.01), (-0.01, 0.01), (-0.01, -0.01)];
+        let mut nav = Navigator::default();
+        nav.geofences
+            .push(Geofence::new("Edge", FenceShape::Polygon(square)));
+        nav.geofences.push(Geofence::new(
+            "Ring",
+            FenceShape::Circle {
+                latitude: 0.0,
+                longitude: 0.0,
+                radius: 100.0,
+            },
+        ));
+        let host = Utc::now();
+
+        // Inside both, then a metre either side of the square's edge every epoch.
+        assert!(nav.update(north(-1.0), 0.0, Some(2.0), host).is_empty());
+        for i in 0..100 {
+            let metres = if i % 2 == 0 { 1.0 } else { -1.0 };
+            assert!(nav.update(north(metres), 0.0, Some(2.0), host).is_empty());
+        }
+        assert!(nav.crossings.is_empty());
+        // Clearly out, but not by the 5 m accuracy, then by it.
+        assert!(nav.update(north(4.0), 0.0, Some(5.0), host).is_empty());
+        let alarms = nav.update(north(6.0), 0.0, Some(5.0), host);
+        assert_eq!(alarms.len(), 1);
+        assert!(!alarms[0].entered && alarms[0].fence == "Edge");
+
+        // Crossing the ring back and forth by far more than the margin is logged each time,
+        // keeping only the latest crossings.
+        for i in 0..MAX_CROSSINGS + 10 {
+            let metres = if i % 2 == 0 { 150.0 } else { 50.0 };
+            nav.update(north(metres), 0.0, None, host);
+        }
+        assert_eq!(nav.crossings.len(), MAX_CROSSINGS);
+        assert_eq!(nav.crossings.back().unwrap().fence, "Ring");
+    }
+
+    #[test]
+    fn keeps_target_when_removing() {
+        let point = |name: &str| Waypoint {
+            name: name.into(),
+            latitude: 0.0,
+            longitude: 0.0,
+            height: None,
+        };
+        let mut nav = Navigator {
+            waypoints: vec![point("a"), point("b"), point("c")],
+            target: Some(2),
+            ..Default::default()
+        };
+        nav.remove_waypoint(0);
+        assert_eq!(nav.target, Some(1));
+        nav.remove_waypoint(1);
+        assert_eq!(nav.target, None);
+    }
+}
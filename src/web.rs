@@ -9,12 +9,11 @@ pub async fn start(canvas: web_sys::HtmlCanvasElement) -> Result<(), JsValue> {
     // Redirect `log` message to `console.log` and friends:
     eframe::WebLogger::init(log::LevelFilter::Debug).ok();
 
-    let app = crate::GenCamGUI::default();
     eframe::WebRunner::new()
         .start(
             "gui_canvas",
             Default::default(),
-            Box::new(|_cc| Ok(Box::new(app))),
+            Box::new(|cc| Ok(Box::new(crate::GenCamGUI::new(cc)))),
         )
         .await?;
     Ok(())